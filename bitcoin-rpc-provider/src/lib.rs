@@ -12,11 +12,13 @@ use bitcoin::{
     consensus::Decodable, network::constants::Network, PrivateKey, Script, Transaction, Txid,
 };
 use bitcoin::{Address, OutPoint, TxOut};
+use bitcoincore_rpc::jsonrpc::serde_json::Value;
 use bitcoincore_rpc::{Auth, Client, RpcApi};
 use bitcoincore_rpc_json::AddressType;
 use dlc_manager::error::Error as ManagerError;
-use dlc_manager::{Blockchain, Utxo, Wallet};
+use dlc_manager::{AddressType as DlcAddressType, Blockchain, Utxo, Wallet};
 use rust_bitcoin_coin_selection::select_coins;
+use std::str::FromStr;
 
 pub struct BitcoinCoreProvider {
     pub client: Client,
@@ -28,6 +30,7 @@ pub enum Error {
     NotEnoughCoins,
     BitcoinError,
     InvalidState,
+    UnsupportedScript(Script),
 }
 
 impl From<bitcoincore_rpc::Error> for Error {
@@ -51,6 +54,7 @@ impl std::fmt::Display for Error {
             }
             Error::BitcoinError => write!(f, "Bitcoin related error"),
             Error::InvalidState => write!(f, "Unexpected state was encountered"),
+            Error::UnsupportedScript(s) => write!(f, "Unsupported script pubkey {}", s),
         }
     }
 }
@@ -101,6 +105,66 @@ fn rpc_err_to_manager_err(e: bitcoincore_rpc::Error) -> ManagerError {
     Error::RpcError(e).into()
 }
 
+/// Witness of a P2WPKH spend: item count, signature and compressed public key.
+const P2WPKH_WITNESS_LEN: usize = 107;
+/// Witness of a P2TR key path spend: item count and a signature with an
+/// explicit sighash type.
+const P2TR_KEY_SPEND_WITNESS_LEN: usize = 1 + 1 + 65;
+
+fn is_p2tr(script_pubkey: &Script) -> bool {
+    let bytes = script_pubkey.as_bytes();
+    bytes.len() == 34 && bytes[0] == 0x51 && bytes[1] == 0x20
+}
+
+/// Returns the maximum length of the witness required to spend an output with
+/// the given script pubkey (and redeem script for P2SH outputs).
+fn get_max_witness_len(script_pubkey: &Script, redeem_script: &Script) -> Result<usize, Error> {
+    if script_pubkey.is_v0_p2wpkh() || (script_pubkey.is_p2sh() && redeem_script.is_v0_p2wpkh()) {
+        Ok(P2WPKH_WITNESS_LEN)
+    } else if is_p2tr(script_pubkey) {
+        Ok(P2TR_KEY_SPEND_WITNESS_LEN)
+    } else {
+        Err(Error::UnsupportedScript(script_pubkey.clone()))
+    }
+}
+
+impl BitcoinCoreProvider {
+    fn list_utxos(&self) -> Result<Vec<Utxo>, ManagerError> {
+        let utxo_res = self
+            .client
+            .list_unspent(None, None, None, None, None)
+            .map_err(rpc_err_to_manager_err)?;
+        let utxos = utxo_res
+            .iter()
+            .map(|x| {
+                let redeem_script = x.redeem_script.as_ref().unwrap_or(&Script::new()).clone();
+                Ok(Utxo {
+                    tx_out: TxOut {
+                        value: x.amount.as_sat(),
+                        script_pubkey: x.script_pub_key.clone(),
+                    },
+                    outpoint: OutPoint {
+                        txid: x.txid.clone(),
+                        vout: x.vout,
+                    },
+                    address: x.address.as_ref().ok_or(Error::InvalidState)?.clone(),
+                    max_witness_len: get_max_witness_len(&x.script_pub_key, &redeem_script)?,
+                    redeem_script,
+                })
+            })
+            .collect::<Result<Vec<Utxo>, Error>>()?;
+        Ok(utxos)
+    }
+
+    fn lock_utxos(&self, utxos: &[Utxo]) -> Result<(), ManagerError> {
+        let outputs: Vec<_> = utxos.iter().map(|x| x.outpoint.clone()).collect();
+        self.client
+            .lock_unspent(&outputs)
+            .map_err(rpc_err_to_manager_err)?;
+        Ok(())
+    }
+}
+
 impl Wallet for BitcoinCoreProvider {
     fn get_new_address(&self) -> Result<Address, ManagerError> {
         self.client
@@ -108,6 +172,23 @@ impl Wallet for BitcoinCoreProvider {
             .map_err(rpc_err_to_manager_err)
     }
 
+    fn get_new_address_of_type(
+        &self,
+        address_type: DlcAddressType,
+    ) -> Result<Address, ManagerError> {
+        match address_type {
+            DlcAddressType::P2wpkh => self.get_new_address(),
+            DlcAddressType::P2tr => {
+                // The address type enum of the rpc client predates bech32m.
+                let address: String = self
+                    .client
+                    .call("getnewaddress", &[Value::from(""), Value::from("bech32m")])
+                    .map_err(rpc_err_to_manager_err)?;
+                Ok(Address::from_str(&address).or(Err(Error::BitcoinError))?)
+            }
+        }
+    }
+
     fn get_new_secret_key(&self) -> Result<SecretKey, ManagerError> {
         let sk = SecretKey::new(&mut thread_rng());
         self.client
@@ -149,38 +230,74 @@ impl Wallet for BitcoinCoreProvider {
         _fee_rate: Option<u64>,
         lock_utxos: bool,
     ) -> Result<Vec<Utxo>, ManagerError> {
-        let utxo_res = self
-            .client
-            .list_unspent(None, None, None, None, None)
-            .map_err(rpc_err_to_manager_err)?;
-        let mut utxo_pool: Vec<UtxoWrap> = utxo_res
-            .iter()
-            .map(|x| {
-                Ok(UtxoWrap(Utxo {
-                    tx_out: TxOut {
-                        value: x.amount.as_sat(),
-                        script_pubkey: x.script_pub_key.clone(),
-                    },
-                    outpoint: OutPoint {
-                        txid: x.txid.clone(),
-                        vout: x.vout,
-                    },
-                    address: x.address.as_ref().ok_or(Error::InvalidState)?.clone(),
-                    redeem_script: x.redeem_script.as_ref().unwrap_or(&Script::new()).clone(),
-                }))
-            })
-            .collect::<Result<Vec<UtxoWrap>, Error>>()?;
+        let mut utxo_pool: Vec<UtxoWrap> = self.list_utxos()?.into_iter().map(UtxoWrap).collect();
         // TODO(tibo): properly compute the cost of change
         let selection = select_coins(amount, 20, &mut utxo_pool).ok_or(Error::NotEnoughCoins)?;
+        let selection: Vec<Utxo> = selection.into_iter().map(|x| x.0).collect();
 
         if lock_utxos {
-            let outputs: Vec<_> = selection.iter().map(|x| x.0.outpoint.clone()).collect();
-            self.client
-                .lock_unspent(&outputs)
-                .map_err(rpc_err_to_manager_err)?;
+            self.lock_utxos(&selection)?;
         }
 
-        Ok(selection.into_iter().map(|x| x.0).collect())
+        Ok(selection)
+    }
+
+    fn get_utxos_for_outpoints(
+        &self,
+        outpoints: &[OutPoint],
+        lock_utxos: bool,
+    ) -> Result<Vec<Utxo>, ManagerError> {
+        let utxos = self.list_utxos()?;
+        let selection = outpoints
+            .iter()
+            .map(|outpoint| {
+                utxos
+                    .iter()
+                    .find(|x| &x.outpoint == outpoint)
+                    .cloned()
+                    .ok_or_else(|| {
+                        ManagerError::InvalidParameters(format!(
+                            "Unknown or spent outpoint {}",
+                            outpoint
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<Utxo>, ManagerError>>()?;
+
+        if lock_utxos {
+            self.lock_utxos(&selection)?;
+        }
+
+        Ok(selection)
+    }
+
+    /// Returns all the UTXOs locked in the bitcoind wallet, including the ones
+    /// locked by other applications using the same wallet.
+    fn get_reserved_utxos(&self) -> Result<Vec<OutPoint>, ManagerError> {
+        // The rpc client does not expose `listlockunspent`.
+        let locked: Vec<Value> = self
+            .client
+            .call("listlockunspent", &[])
+            .map_err(rpc_err_to_manager_err)?;
+        let outpoints = locked
+            .iter()
+            .map(|x| {
+                let txid = x["txid"].as_str().ok_or(Error::InvalidState)?;
+                let vout = x["vout"].as_u64().ok_or(Error::InvalidState)?;
+                Ok(OutPoint {
+                    txid: Txid::from_str(txid).or(Err(Error::BitcoinError))?,
+                    vout: vout as u32,
+                })
+            })
+            .collect::<Result<Vec<OutPoint>, Error>>()?;
+        Ok(outpoints)
+    }
+
+    fn unreserve_utxos(&self, outpoints: &[OutPoint]) -> Result<(), ManagerError> {
+        self.client
+            .unlock_unspent(outpoints)
+            .map_err(rpc_err_to_manager_err)?;
+        Ok(())
     }
 
     fn import_address(&self, address: &Address) -> Result<(), ManagerError> {
@@ -251,4 +368,12 @@ impl Blockchain for BitcoinCoreProvider {
 
         Ok(network)
     }
+
+    fn is_output_spent(&self, outpoint: &OutPoint) -> Result<bool, ManagerError> {
+        let tx_out = self
+            .client
+            .get_tx_out(&outpoint.txid, outpoint.vout, Some(true))
+            .map_err(rpc_err_to_manager_err)?;
+        Ok(tx_out.is_none())
+    }
 }
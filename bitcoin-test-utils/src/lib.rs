@@ -64,3 +64,81 @@ pub fn tx_from_string(tx_str: &str) -> Transaction {
     let tx_hex = str_to_hex(tx_str);
     Transaction::consensus_decode(&tx_hex[..]).unwrap()
 }
+
+/// Number of bytes displayed on each side of the first differing byte when
+/// describing a serialization mismatch.
+const MISMATCH_CONTEXT_SIZE: usize = 8;
+
+fn to_hex_string(bytes: &[u8]) -> String {
+    let mut res = String::new();
+    for x in bytes {
+        write!(&mut res, "{:02x}", x).unwrap();
+    }
+    res
+}
+
+/// Returns the offset of the first differing byte between the expected and
+/// actual buffers, or the length of the shortest one if it is a prefix of the
+/// other. Returns `None` if both buffers are equal.
+pub fn first_mismatch_offset(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    if expected == actual {
+        return None;
+    }
+
+    Some(
+        expected
+            .iter()
+            .zip(actual.iter())
+            .position(|(e, a)| e != a)
+            .unwrap_or_else(|| std::cmp::min(expected.len(), actual.len())),
+    )
+}
+
+/// Returns a description of the first mismatch between the expected and
+/// actual buffers, including the offset and a hex window around it, or `None`
+/// if both buffers are equal.
+pub fn describe_mismatch(expected: &[u8], actual: &[u8]) -> Option<String> {
+    let offset = first_mismatch_offset(expected, actual)?;
+    let window = |buf: &[u8]| {
+        let start = offset.saturating_sub(MISMATCH_CONTEXT_SIZE);
+        let end = std::cmp::min(buf.len(), offset + MISMATCH_CONTEXT_SIZE + 1);
+        to_hex_string(&buf[std::cmp::min(start, end)..end])
+    };
+    Some(format!(
+        "Serialization mismatch at byte offset {} (expected length {}, actual length {})\n\
+         expected: {}\n  actual: {}",
+        offset,
+        expected.len(),
+        actual.len(),
+        window(expected),
+        window(actual)
+    ))
+}
+
+/// Panics with a description of the first mismatch if the buffers differ.
+pub fn assert_bytes_eq(expected: &[u8], actual: &[u8]) {
+    if let Some(description) = describe_mismatch(expected, actual) {
+        panic!("{}", description);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_byte_mismatch_offset_is_reported() {
+        let expected: Vec<u8> = (0..64).collect();
+        let mut actual = expected.clone();
+        actual[42] = 0xff;
+
+        assert_eq!(None, first_mismatch_offset(&expected, &expected));
+        assert_eq!(Some(42), first_mismatch_offset(&expected, &actual));
+        assert_eq!(Some(32), first_mismatch_offset(&expected, &expected[..32]));
+
+        let description = describe_mismatch(&expected, &actual).unwrap();
+        assert!(description.contains("byte offset 42"));
+        assert!(description.contains(&to_hex_string(&expected[34..51])));
+        assert!(description.contains(&to_hex_string(&actual[34..51])));
+    }
+}
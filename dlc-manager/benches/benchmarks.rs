@@ -1,12 +1,14 @@
 use bitcoin::hashes::Hash;
 use bitcoin::OutPoint;
 use bitcoin::Script;
+use bitcoin::SigHashType;
 use bitcoin::WPubkeyHash;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use dlc::create_dlc_transactions;
 use dlc::DlcTransactions;
 use dlc::PartyParams;
 use dlc::Payout;
+use dlc::SigHashParams;
 use dlc::TxInputInfo;
 use dlc_manager::contract::contract_info::ContractInfo;
 use dlc_manager::contract::numerical_descriptor::DifferenceParams;
@@ -211,7 +213,11 @@ fn offer_seckey() -> SecretKey {
 pub fn sign_bench(c: &mut Criterion) {
     let contract_info = create_contract_info();
     let dlc_transactions = create_transactions(&contract_info.get_payouts(200000000));
-    let fund_output_value = dlc_transactions.get_fund_output().value;
+    let fund_output = SigHashParams {
+        script_pubkey: &dlc_transactions.funding_script_pubkey,
+        value: dlc_transactions.get_fund_output().value,
+        sig_hash_type: SigHashType::All,
+    };
 
     let seckey = accept_seckey();
     c.bench_function("sign", |b| {
@@ -222,8 +228,7 @@ pub fn sign_bench(c: &mut Criterion) {
                         &SECP256K1,
                         TOTAL_COLLATERAL,
                         &seckey,
-                        &dlc_transactions.funding_script_pubkey,
-                        fund_output_value,
+                        &fund_output,
                         &dlc_transactions.cets,
                         0,
                     )
@@ -237,7 +242,11 @@ pub fn sign_bench(c: &mut Criterion) {
 pub fn verify_bench(c: &mut Criterion) {
     let contract_info = create_contract_info();
     let dlc_transactions = create_transactions(&contract_info.get_payouts(200000000));
-    let fund_output_value = dlc_transactions.get_fund_output().value;
+    let fund_output = SigHashParams {
+        script_pubkey: &dlc_transactions.funding_script_pubkey,
+        value: dlc_transactions.get_fund_output().value,
+        sig_hash_type: SigHashType::All,
+    };
 
     let seckey = accept_seckey();
     let pubkey = secp256k1_zkp::PublicKey::from_secret_key(SECP256K1, &seckey);
@@ -246,8 +255,7 @@ pub fn verify_bench(c: &mut Criterion) {
             &SECP256K1,
             TOTAL_COLLATERAL,
             &seckey,
-            &dlc_transactions.funding_script_pubkey,
-            fund_output_value,
+            &fund_output,
             &dlc_transactions.cets,
             0,
         )
@@ -260,8 +268,7 @@ pub fn verify_bench(c: &mut Criterion) {
                     .verify_adaptor_info(
                         &SECP256K1,
                         &pubkey,
-                        &dlc_transactions.funding_script_pubkey,
-                        fund_output_value,
+                        &fund_output,
                         &dlc_transactions.cets,
                         adaptor_signatures,
                         0,
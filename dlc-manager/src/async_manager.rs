@@ -0,0 +1,104 @@
+//! #AsyncManager an adapter exposing the [`Manager`] through async functions,
+//! for use within a `tokio` runtime.
+
+use super::{Blockchain, Oracle, Storage, Time, Wallet};
+use crate::contract::contract_input::ContractInput;
+use crate::error::Error;
+use crate::manager::{Manager, ManagerAction};
+use crate::ContractId;
+use dlc_messages::{AcceptDlc, Message as DlcMessage, OfferDlc};
+use secp256k1_zkp::PublicKey;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+/// A [`Manager`] shared between the calls made through an [`AsyncManager`].
+pub type SharedManager<W, B, S, O, T> = Arc<Mutex<Manager<W, B, S, O, T>>>;
+
+/// Wraps a [`Manager`] so that its functions, which block on the calls made
+/// to the wallet and blockchain providers, run on the blocking thread pool of
+/// the `tokio` runtime instead of stalling its event loop. Calls are
+/// serialized using an internal mutex.
+pub struct AsyncManager<W: Deref, B: Deref, S: DerefMut, O: Deref, T: Deref>
+where
+    W::Target: Wallet,
+    B::Target: Blockchain,
+    S::Target: Storage,
+    O::Target: Oracle,
+    T::Target: Time,
+{
+    manager: SharedManager<W, B, S, O, T>,
+}
+
+impl<W: Deref, B: Deref, S: DerefMut, O: Deref, T: Deref> AsyncManager<W, B, S, O, T>
+where
+    W::Target: Wallet,
+    B::Target: Blockchain,
+    S::Target: Storage,
+    O::Target: Oracle,
+    T::Target: Time,
+    Manager<W, B, S, O, T>: Send + 'static,
+{
+    /// Create a new AsyncManager wrapping the given manager.
+    pub fn new(manager: Manager<W, B, S, O, T>) -> Self {
+        AsyncManager {
+            manager: Arc::new(Mutex::new(manager)),
+        }
+    }
+
+    /// Returns the wrapped manager, to call the functions that are not exposed
+    /// by the adapter. Locking it blocks the current thread while a call made
+    /// through the adapter is in progress.
+    pub fn get_manager(&self) -> SharedManager<W, B, S, O, T> {
+        Arc::clone(&self.manager)
+    }
+
+    /// See [`Manager::on_dlc_message`].
+    pub async fn on_dlc_message(
+        &self,
+        msg: DlcMessage,
+        counter_party: PublicKey,
+    ) -> Result<Option<DlcMessage>, Error> {
+        self.run(move |manager| manager.on_dlc_message(&msg, counter_party))
+            .await
+    }
+
+    /// See [`Manager::send_offer`].
+    pub async fn send_offer(
+        &self,
+        contract: ContractInput,
+        counter_party: PublicKey,
+    ) -> Result<OfferDlc, Error> {
+        self.run(move |manager| manager.send_offer(&contract, counter_party))
+            .await
+    }
+
+    /// See [`Manager::accept_contract_offer`].
+    pub async fn accept_contract_offer(
+        &self,
+        contract_id: ContractId,
+    ) -> Result<(ContractId, PublicKey, AcceptDlc), Error> {
+        self.run(move |manager| manager.accept_contract_offer(&contract_id))
+            .await
+    }
+
+    /// See [`Manager::periodic_check`].
+    pub async fn periodic_check(&self) -> Result<Vec<ManagerAction>, Error> {
+        self.run(|manager| manager.periodic_check()).await
+    }
+
+    async fn run<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Manager<W, B, S, O, T>) -> Result<R, Error> + Send + 'static,
+        R: Send + 'static,
+    {
+        let manager = Arc::clone(&self.manager);
+        tokio::task::spawn_blocking(move || {
+            let mut manager = manager
+                .lock()
+                .map_err(|_| Error::Other("Manager lock poisoned.".to_string()))?;
+            f(&mut manager)
+        })
+        .await
+        .map_err(|e| Error::Other(e.to_string()))?
+    }
+}
@@ -2,7 +2,7 @@
 
 use super::offered_contract::OfferedContract;
 use super::{AdaptorInfo, FundingInputInfo};
-use dlc::{DlcTransactions, PartyParams};
+use dlc::{DlcTransactions, PartyParams, SigHashParams};
 use secp256k1_zkp::EcdsaAdaptorSignature;
 use secp256k1_zkp::Signature;
 
@@ -18,8 +18,8 @@ pub struct AcceptedContract {
     /// The adaptor information for the contract storing information about
     /// the relation between adaptor signatures and outcomes.
     pub adaptor_infos: Vec<AdaptorInfo>,
-    /// The adaptor signatures of the accepting party. Note that the accepting
-    /// party does not keep them thus an option is used.
+    /// The adaptor signatures of the accepting party. An option is used as
+    /// contracts accepted by earlier versions did not keep their own.
     pub adaptor_signatures: Option<Vec<EcdsaAdaptorSignature>>,
     /// The signature for the refund transaction from the accepting party.
     pub accept_refund_signature: Signature,
@@ -56,6 +56,16 @@ impl AcceptedContract {
         contract_id
     }
 
+    /// Returns the parameters of the fund output used to sign and verify the
+    /// CET signatures of the contract.
+    pub fn get_cet_sig_hash_params(&self) -> SigHashParams<'_> {
+        SigHashParams {
+            script_pubkey: &self.dlc_transactions.funding_script_pubkey,
+            value: self.dlc_transactions.get_fund_output().value,
+            sig_hash_type: self.offered_contract.get_cet_sig_hash_type(),
+        }
+    }
+
     /// Utility function to get the contract id as a string.
     pub fn get_contract_id_string(&self) -> String {
         let mut string_id = String::with_capacity(32 * 2 + 2);
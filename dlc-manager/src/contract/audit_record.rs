@@ -0,0 +1,201 @@
+//! #AuditRecord
+
+use super::{Contract, ContractState, Party};
+use crate::error::Error;
+use crate::ContractId;
+use bitcoin::OutPoint;
+use secp256k1_zkp::schnorrsig::PublicKey as SchnorrPublicKey;
+use secp256k1_zkp::{EcdsaAdaptorSignature, Message, PublicKey, Secp256k1, Signature};
+
+/// A CET adaptor signature contained in an [`AuditRecord`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditSignature {
+    /// The index in [`AuditRecord::cet_sighashes`] of the sighash of the CET
+    /// that the signature applies to.
+    pub cet_index: usize,
+    /// The adaptor point used to encrypt the signature.
+    pub adaptor_point: PublicKey,
+    /// The adaptor signature.
+    pub adaptor_signature: EcdsaAdaptorSignature,
+}
+
+/// Contains the minimal information required to re-verify the signatures of a
+/// signed contract, without keeping the transactions and payout information
+/// of the contract. See [`verify_audit_record`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditRecord {
+    /// The id of the contract.
+    pub contract_id: ContractId,
+    /// The outpoint of the fund output of the contract.
+    pub fund_outpoint: OutPoint,
+    /// The funding public key of the offering party.
+    pub offer_fund_pubkey: PublicKey,
+    /// The funding public key of the accepting party.
+    pub accept_fund_pubkey: PublicKey,
+    /// The public keys of the oracles of the contract, in the order of their
+    /// announcements.
+    pub oracle_public_keys: Vec<SchnorrPublicKey>,
+    /// The ids of the events announced by the oracles of the contract.
+    pub oracle_event_ids: Vec<String>,
+    /// The sighash of the refund transaction.
+    pub refund_sighash: Message,
+    /// The refund signature of the offering party.
+    pub offer_refund_signature: Signature,
+    /// The refund signature of the accepting party.
+    pub accept_refund_signature: Signature,
+    /// The sighashes of the CETs of the contract.
+    pub cet_sighashes: Vec<Message>,
+    /// The party that produced the adaptor signatures of the record, which
+    /// are the ones received from the counter party.
+    pub adaptor_signer: Party,
+    /// The CET adaptor signatures of the `adaptor_signer`.
+    pub adaptor_signatures: Vec<AuditSignature>,
+}
+
+impl Contract {
+    /// Returns an [`AuditRecord`] for the contract. Returns an error for
+    /// contracts that were not signed, and for contracts whose oracle
+    /// announcements were pruned, see
+    /// [`crate::manager::Manager::prune_expired_announcements`].
+    pub fn to_audit_record(&self) -> Result<AuditRecord, Error> {
+        let signed_contract = match self {
+            Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => s,
+            Contract::Closed(c) => &c.signed_contract,
+            Contract::CollaborativelyClosed(c) => &c.signed_contract,
+            _ => {
+                return Err(Error::InvalidState {
+                    expected: vec![
+                        ContractState::Signed,
+                        ContractState::Confirmed,
+                        ContractState::Closed,
+                        ContractState::Refunded,
+                        ContractState::CollaborativelyClosed,
+                    ],
+                    found: self.get_state(),
+                })
+            }
+        };
+        let accepted_contract = &signed_contract.accepted_contract;
+        let offered_contract = &accepted_contract.offered_contract;
+        let dlc_transactions = &accepted_contract.dlc_transactions;
+        if offered_contract
+            .contract_info
+            .iter()
+            .any(|x| x.oracle_announcements.is_empty())
+        {
+            return Err(Error::Other(
+                "The oracle announcements of the contract were pruned.".to_string(),
+            ));
+        }
+
+        // Both parties keep their own adaptor signatures, the ones audited are
+        // those received from the counter party.
+        let (adaptor_signer, adaptor_sigs) = if offered_contract.is_offer_party {
+            (Party::Accept, &accepted_contract.adaptor_signatures)
+        } else {
+            (Party::Offer, &signed_contract.adaptor_signatures)
+        };
+        let adaptor_sigs = adaptor_sigs.as_ref().ok_or_else(|| {
+            Error::Other("Signed contract without adaptor signatures.".to_string())
+        })?;
+
+        let secp = Secp256k1::new();
+        let mut adaptor_points = Vec::new();
+        for (contract_info, adaptor_info) in offered_contract
+            .contract_info
+            .iter()
+            .zip(accepted_contract.adaptor_infos.iter())
+        {
+            adaptor_points.extend(contract_info.get_adaptor_points(&secp, adaptor_info)?);
+        }
+        if adaptor_points.len() != adaptor_sigs.len() {
+            return Err(Error::Other(
+                "Number of adaptor signatures does not match the contract.".to_string(),
+            ));
+        }
+
+        let announcements = offered_contract
+            .contract_info
+            .iter()
+            .flat_map(|x| x.oracle_announcements.iter());
+
+        Ok(AuditRecord {
+            contract_id: accepted_contract.get_contract_id(),
+            fund_outpoint: OutPoint {
+                txid: dlc_transactions.fund.txid(),
+                vout: dlc_transactions.get_fund_output_index() as u32,
+            },
+            offer_fund_pubkey: offered_contract.offer_params.fund_pubkey,
+            accept_fund_pubkey: accepted_contract.accept_params.fund_pubkey,
+            oracle_public_keys: announcements.clone().map(|x| x.oracle_public_key).collect(),
+            oracle_event_ids: announcements
+                .map(|x| x.oracle_event.event_id.clone())
+                .collect(),
+            refund_sighash: self.refund_sighash()?,
+            offer_refund_signature: signed_contract.offer_refund_signature,
+            accept_refund_signature: accepted_contract.accept_refund_signature,
+            cet_sighashes: self.cet_sighashes()?,
+            adaptor_signer,
+            adaptor_signatures: adaptor_points
+                .into_iter()
+                .zip(adaptor_sigs.iter())
+                .map(
+                    |((cet_index, adaptor_point), adaptor_signature)| AuditSignature {
+                        cet_index,
+                        adaptor_point,
+                        adaptor_signature: *adaptor_signature,
+                    },
+                )
+                .collect(),
+        })
+    }
+}
+
+/// Verifies the refund signatures and the CET adaptor signatures contained in
+/// the given audit record against the funding public keys that it contains.
+pub fn verify_audit_record(record: &AuditRecord) -> Result<(), Error> {
+    let secp = Secp256k1::verification_only();
+
+    for (party, signature, pubkey) in [
+        (
+            Party::Offer,
+            &record.offer_refund_signature,
+            &record.offer_fund_pubkey,
+        ),
+        (
+            Party::Accept,
+            &record.accept_refund_signature,
+            &record.accept_fund_pubkey,
+        ),
+    ] {
+        secp.verify(&record.refund_sighash, signature, pubkey)
+            .map_err(|_| {
+                Error::InvalidParameters(format!(
+                    "Invalid refund signature of the {:?} party.",
+                    party
+                ))
+            })?;
+    }
+
+    let signer_pubkey = match record.adaptor_signer {
+        Party::Offer => &record.offer_fund_pubkey,
+        Party::Accept => &record.accept_fund_pubkey,
+    };
+    for (i, signature) in record.adaptor_signatures.iter().enumerate() {
+        let sighash = record
+            .cet_sighashes
+            .get(signature.cet_index)
+            .ok_or_else(|| {
+                Error::InvalidParameters(format!(
+                    "Adaptor signature {} refers to unknown CET {}.",
+                    i, signature.cet_index
+                ))
+            })?;
+        signature
+            .adaptor_signature
+            .verify(&secp, sighash, signer_pubkey, &signature.adaptor_point)
+            .map_err(|_| Error::InvalidParameters(format!("Invalid adaptor signature {}.", i)))?;
+    }
+
+    Ok(())
+}
@@ -3,13 +3,18 @@
 use super::utils::get_majority_combination;
 use super::AdaptorInfo;
 use super::ContractDescriptor;
-use bitcoin::{Script, Transaction};
-use dlc::{OracleInfo, Payout};
+use bitcoin::Transaction;
+use dlc::{OracleInfo, Payout, SigHashParams};
 use dlc_messages::oracle_msgs::OracleAnnouncement;
 use dlc_trie::combination_iterator::CombinationIterator;
 use dlc_trie::{DlcTrie, RangeInfo};
 use secp256k1_zkp::{All, EcdsaAdaptorSignature, PublicKey, Secp256k1, SecretKey};
 
+/// The index and number of signatures of each oracle whose attestation is
+/// used to close a contract, together with the information on the matching
+/// CET.
+type OutcomeRangeInfo = (Vec<(usize, usize)>, RangeInfo);
+
 /// Contains information about the contract conditions and oracles used.
 #[derive(Clone, Debug)]
 #[cfg_attr(
@@ -49,8 +54,7 @@ impl ContractInfo {
         secp: &Secp256k1<All>,
         adaptor_info: &AdaptorInfo,
         fund_privkey: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
     ) -> Result<Vec<EcdsaAdaptorSignature>, dlc::Error> {
         let oracle_infos = self.get_oracle_infos();
@@ -62,27 +66,16 @@ impl ContractInfo {
                     self.threshold,
                     cets,
                     fund_privkey,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                 ),
                 _ => unreachable!(),
             },
-            AdaptorInfo::Numerical(trie) => trie.sign(
-                secp,
-                fund_privkey,
-                funding_script_pubkey,
-                fund_output_value,
-                cets,
-                &oracle_infos,
-            ),
-            AdaptorInfo::NumericalWithDifference(trie) => trie.sign(
-                secp,
-                fund_privkey,
-                funding_script_pubkey,
-                fund_output_value,
-                cets,
-                &oracle_infos,
-            ),
+            AdaptorInfo::Numerical(trie) => {
+                trie.sign(secp, fund_privkey, fund_output, cets, &oracle_infos)
+            }
+            AdaptorInfo::NumericalWithDifference(trie) => {
+                trie.sign(secp, fund_privkey, fund_output, cets, &oracle_infos)
+            }
         }
     }
 
@@ -93,8 +86,7 @@ impl ContractInfo {
         secp: &Secp256k1<All>,
         total_collateral: u64,
         fund_pubkey: &PublicKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_sig_start: usize,
@@ -106,8 +98,7 @@ impl ContractInfo {
                 &oracle_infos,
                 self.threshold,
                 fund_pubkey,
-                funding_script_pubkey,
-                fund_output_value,
+                fund_output,
                 cets,
                 adaptor_sigs,
                 adaptor_sig_start,
@@ -116,8 +107,7 @@ impl ContractInfo {
                 secp,
                 total_collateral,
                 fund_pubkey,
-                funding_script_pubkey,
-                fund_output_value,
+                fund_output,
                 self.threshold,
                 &oracle_infos,
                 cets,
@@ -128,12 +118,58 @@ impl ContractInfo {
     }
 
     /// Tries to find a match in the given adaptor info for the given outcomes.
+    /// The outcomes attested to by the oracles using their first nonces
+    /// independently of the event outcome (e.g. the sign of numerical
+    /// outcomes) are expected to be included, and are accounted for in the
+    /// number of signatures returned for each oracle. Outcomes with unexpected
+    /// prefix values are ignored.
     pub fn get_range_info_for_outcome(
         &self,
         adaptor_info: &AdaptorInfo,
         outcomes: &[(usize, &Vec<String>)],
         adaptor_sig_start: usize,
-    ) -> Result<Option<(Vec<(usize, usize)>, RangeInfo)>, crate::error::Error> {
+    ) -> Result<Option<OutcomeRangeInfo>, crate::error::Error> {
+        let prefixes: Vec<Vec<String>> = self
+            .oracle_announcements
+            .iter()
+            .map(|x| x.oracle_event.event_descriptor.get_prefix_outcomes())
+            .collect();
+        let stripped_outcomes: Vec<(usize, Vec<String>)> = outcomes
+            .iter()
+            .filter_map(|(i, outcome)| {
+                let prefix = prefixes.get(*i)?;
+                if outcome.len() < prefix.len() || outcome[..prefix.len()] != prefix[..] {
+                    return None;
+                }
+                Some((*i, outcome[prefix.len()..].to_vec()))
+            })
+            .collect();
+        let stripped_outcomes: Vec<(usize, &Vec<String>)> =
+            stripped_outcomes.iter().map(|(i, x)| (*i, x)).collect();
+
+        let res = self.get_range_info_for_stripped_outcome(
+            adaptor_info,
+            &stripped_outcomes,
+            adaptor_sig_start,
+        )?;
+
+        Ok(res.map(|(sig_infos, range_info)| {
+            (
+                sig_infos
+                    .into_iter()
+                    .map(|(i, nb_sigs)| (i, nb_sigs + prefixes[i].len()))
+                    .collect(),
+                range_info,
+            )
+        }))
+    }
+
+    fn get_range_info_for_stripped_outcome(
+        &self,
+        adaptor_info: &AdaptorInfo,
+        outcomes: &[(usize, &Vec<String>)],
+        adaptor_sig_start: usize,
+    ) -> Result<Option<OutcomeRangeInfo>, crate::error::Error> {
         let get_digits_outcome = |input: &[String]| -> Result<Vec<usize>, crate::error::Error> {
             input
                 .iter()
@@ -160,10 +196,9 @@ impl ContractInfo {
                 let (s_outcomes, actual_combination) = get_majority_combination(outcomes)?;
                 let digits_outcome = get_digits_outcome(&s_outcomes)?;
 
-                let res = n
-                    .digit_trie
-                    .look_up(&digits_outcome)
-                    .ok_or(crate::error::Error::InvalidState)?;
+                let res = n.digit_trie.look_up(&digits_outcome).ok_or_else(|| {
+                    crate::error::Error::Other("Outcome not found in the trie.".to_string())
+                })?;
 
                 let sufficient_combination: Vec<_> = actual_combination
                     .into_iter()
@@ -172,7 +207,9 @@ impl ContractInfo {
                 let position =
                     CombinationIterator::new(self.oracle_announcements.len(), self.threshold)
                         .get_index_for_combination(&sufficient_combination)
-                        .ok_or(crate::error::Error::InvalidState)?;
+                        .ok_or_else(|| {
+                            crate::error::Error::Other("Invalid oracle combination.".to_string())
+                        })?;
                 Ok(Some((
                     sufficient_combination
                         .iter()
@@ -190,7 +227,9 @@ impl ContractInfo {
                             .map(|(x, path)| Ok((*x, get_digits_outcome(path)?)))
                             .collect::<Result<Vec<(usize, Vec<usize>)>, crate::error::Error>>()?,
                     )
-                    .ok_or(crate::error::Error::InvalidState)?;
+                    .ok_or_else(|| {
+                        crate::error::Error::Other("Outcome not found in the trie.".to_string())
+                    })?;
                 Ok(Some((
                     res.path.iter().map(|(x, y)| (*x, y.len())).collect(),
                     res.value.clone(),
@@ -199,14 +238,43 @@ impl ContractInfo {
         }
     }
 
+    /// Returns whether all the given attested outcomes are outside of the range
+    /// of outcomes covered by the payout function of a numerical contract, in
+    /// which case no CET can be used to close the contract.
+    pub fn is_outcome_out_of_range(&self, outcomes: &[(usize, &Vec<String>)]) -> bool {
+        let descriptor = match &self.contract_descriptor {
+            ContractDescriptor::Numerical(n) => n,
+            ContractDescriptor::Enum(_) => return false,
+        };
+        let (min_outcome, max_outcome) = descriptor.payout_function.get_outcome_range();
+        !outcomes.is_empty()
+            && outcomes.iter().all(|(i, outcome)| {
+                let prefix = match self.oracle_announcements.get(*i) {
+                    Some(a) => a.oracle_event.event_descriptor.get_prefix_outcomes(),
+                    None => return false,
+                };
+                // For signed events, negative values are never covered.
+                if outcome.len() < prefix.len() || outcome[..prefix.len()] != prefix[..] {
+                    return true;
+                }
+                let value = outcome[prefix.len()..].iter().try_fold(0u64, |acc, digit| {
+                    acc.checked_mul(descriptor.info.base as u64)?
+                        .checked_add(digit.parse::<u64>().ok()?)
+                });
+                match value {
+                    Some(value) => value < min_outcome || value > max_outcome,
+                    None => false,
+                }
+            })
+    }
+
     /// Verifies the given adaptor signatures are valid with respect to the given
     /// adaptor info.
     pub fn verify_adaptor_info(
         &self,
         secp: &Secp256k1<All>,
         fund_pubkey: &PublicKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_sig_start: usize,
@@ -219,8 +287,7 @@ impl ContractInfo {
                 &oracle_infos,
                 self.threshold,
                 fund_pubkey,
-                funding_script_pubkey,
-                fund_output_value,
+                fund_output,
                 cets,
                 adaptor_sigs,
                 adaptor_sig_start,
@@ -230,8 +297,7 @@ impl ContractInfo {
                 AdaptorInfo::Numerical(trie) => trie.verify(
                     secp,
                     fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                     adaptor_sigs,
                     cets,
                     &oracle_infos,
@@ -239,8 +305,7 @@ impl ContractInfo {
                 AdaptorInfo::NumericalWithDifference(trie) => trie.verify(
                     secp,
                     fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                     adaptor_sigs,
                     cets,
                     &oracle_infos,
@@ -249,17 +314,54 @@ impl ContractInfo {
         }
     }
 
+    /// Returns the index of the CET and the adaptor point of each adaptor
+    /// signature of the contract, in the order of the adaptor signatures.
+    pub fn get_adaptor_points(
+        &self,
+        secp: &Secp256k1<All>,
+        adaptor_info: &AdaptorInfo,
+    ) -> Result<Vec<(usize, PublicKey)>, dlc::Error> {
+        let oracle_infos = self.get_oracle_infos();
+        match adaptor_info {
+            AdaptorInfo::Enum => match &self.contract_descriptor {
+                ContractDescriptor::Enum(e) => {
+                    let mut points = Vec::new();
+                    let mut callback =
+                        |adaptor_point: &PublicKey, cet_index: usize| -> Result<(), dlc::Error> {
+                            points.push((cet_index, *adaptor_point));
+                            Ok(())
+                        };
+                    e.iter_outcomes(secp, &oracle_infos, self.threshold, &mut callback)?;
+                    Ok(points)
+                }
+                _ => unreachable!(),
+            },
+            AdaptorInfo::Numerical(trie) => get_trie_adaptor_points(trie, secp, &oracle_infos),
+            AdaptorInfo::NumericalWithDifference(trie) => {
+                get_trie_adaptor_points(trie, secp, &oracle_infos)
+            }
+        }
+    }
+
     /// Generate the adaptor info and adaptor signatures for the contract.
     pub fn get_adaptor_info(
         &self,
         secp: &Secp256k1<All>,
         total_collateral: u64,
         fund_priv_key: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
         adaptor_index_start: usize,
     ) -> Result<(AdaptorInfo, Vec<EcdsaAdaptorSignature>), dlc::Error> {
+        debug_assert!(
+            cets_match_payouts(
+                cets,
+                &self
+                    .contract_descriptor
+                    .canonical_cet_order(total_collateral)
+            ),
+            "CETs are not ordered as required by the specification."
+        );
         let oracle_infos = self.get_oracle_infos();
         match &self.contract_descriptor {
             ContractDescriptor::Enum(e) => Ok(e.get_adaptor_info(
@@ -267,16 +369,14 @@ impl ContractInfo {
                 &oracle_infos,
                 self.threshold,
                 fund_priv_key,
-                funding_script_pubkey,
-                fund_output_value,
+                fund_output,
                 cets,
             )?),
             ContractDescriptor::Numerical(n) => Ok(n.get_adaptor_info(
                 secp,
                 total_collateral,
                 fund_priv_key,
-                funding_script_pubkey,
-                fund_output_value,
+                fund_output,
                 self.threshold,
                 &oracle_infos,
                 cets,
@@ -285,3 +385,43 @@ impl ContractInfo {
         }
     }
 }
+
+/// Checks that each CET pays the payout at the same position, ignoring the
+/// outputs that were discarded as dust.
+fn cets_match_payouts(cets: &[Transaction], payouts: &[Payout]) -> bool {
+    cets.len() == payouts.len()
+        && cets.iter().zip(payouts).all(|(cet, payout)| {
+            let mut expected: Vec<u64> = [payout.offer, payout.accept]
+                .iter()
+                .copied()
+                .filter(|x| *x >= dlc::DUST_LIMIT)
+                .collect();
+            let mut actual: Vec<u64> = cet.output.iter().map(|x| x.value).collect();
+            expected.sort_unstable();
+            actual.sort_unstable();
+            expected == actual
+        })
+}
+
+fn get_trie_adaptor_points<T: DlcTrie>(
+    trie: &T,
+    secp: &Secp256k1<All>,
+    oracle_infos: &[OracleInfo],
+) -> Result<Vec<(usize, PublicKey)>, dlc::Error> {
+    let mut points = Vec::new();
+    let mut callback =
+        |adaptor_point: &PublicKey, range_info: &RangeInfo| -> Result<(), dlc::Error> {
+            points.push((
+                range_info.adaptor_index,
+                range_info.cet_index,
+                *adaptor_point,
+            ));
+            Ok(())
+        };
+    trie.iter(secp, oracle_infos, &mut callback)?;
+    points.sort_by_key(|x| x.0);
+    Ok(points
+        .into_iter()
+        .map(|(_, cet_index, point)| (cet_index, point))
+        .collect())
+}
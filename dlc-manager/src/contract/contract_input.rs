@@ -1,6 +1,8 @@
 //! #ContractInput
 
 use super::ContractDescriptor;
+use crate::error::Error;
+use dlc_messages::oracle_msgs::OracleAnnouncement;
 use secp256k1_zkp::schnorrsig::PublicKey as SchnorrPublicKey;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -55,3 +57,61 @@ pub struct ContractInput {
     /// on multiple contracts).
     pub contract_infos: Vec<ContractInputInfo>,
 }
+
+/// The conditions of a contract, independently of the oracle event it is
+/// based on, making it possible to offer the same contract for successive
+/// events.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct ContractTemplate {
+    /// The collateral for the offering party.
+    pub offer_collateral: u64,
+    /// The collateral for the accepting party.
+    pub accept_collateral: u64,
+    /// The fee rate used to construct the transactions.
+    pub fee_rate: u64,
+    /// The contract conditions.
+    pub contract_descriptor: ContractDescriptor,
+    /// The public keys of the oracles used for every event.
+    pub oracle_public_keys: Vec<SchnorrPublicKey>,
+    /// The number of oracles that need to provide attestations satisfying the
+    /// contract conditions to be able to close the contract.
+    pub threshold: u16,
+}
+
+impl ContractTemplate {
+    /// Returns the input for a contract following the template, based on the
+    /// event of the given announcement and maturing at the event maturity. The
+    /// oracles of the template are expected to announce the event with the
+    /// same id, so an error is returned if the announcement is from another
+    /// oracle.
+    pub fn instantiate(&self, announcement: &OracleAnnouncement) -> Result<ContractInput, Error> {
+        if !self
+            .oracle_public_keys
+            .contains(&announcement.oracle_public_key)
+        {
+            return Err(Error::InvalidParameters(
+                "Announcement is not from an oracle of the template.".to_string(),
+            ));
+        }
+
+        Ok(ContractInput {
+            offer_collateral: self.offer_collateral,
+            accept_collateral: self.accept_collateral,
+            maturity_time: announcement.oracle_event.event_maturity_epoch,
+            fee_rate: self.fee_rate,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor: self.contract_descriptor.clone(),
+                oracles: OracleInput {
+                    public_keys: self.oracle_public_keys.clone(),
+                    event_id: announcement.oracle_event.event_id.clone(),
+                    threshold: self.threshold,
+                },
+            }],
+        })
+    }
+}
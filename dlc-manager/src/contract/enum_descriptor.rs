@@ -2,16 +2,39 @@
 
 use super::utils::get_majority_combination;
 use super::AdaptorInfo;
-use bitcoin::{Script, Transaction};
+use bitcoin::Transaction;
 use dlc::OracleInfo;
-use dlc::{EnumerationPayout, Payout};
+use dlc::{EnumerationPayout, Payout, SigHashParams};
 use dlc_trie::{combination_iterator::CombinationIterator, RangeInfo};
+use secp256k1_zkp::bitcoin_hashes::hex::FromHex;
+use secp256k1_zkp::bitcoin_hashes::{sha256, Hash};
 use secp256k1_zkp::{
     All, EcdsaAdaptorSignature, Message, PublicKey, Secp256k1, SecretKey, Verification,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Describes what the oracle signs to attest to the outcome of an enumerated
+/// event, and thus the messages over which the CET adaptor points are computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum EnumOutcomeCommitment {
+    /// The oracle signs the outcome string.
+    #[default]
+    String,
+    /// The oracle signs the decimal representation of the index of the outcome
+    /// in the set of outcomes.
+    Index,
+    /// The outcomes of the descriptor are the hex encoded SHA256 hashes of the
+    /// outcome strings signed by the oracle, so that the outcome strings are
+    /// not revealed when offering the contract.
+    Hash,
+}
+
 /// A descriptor for a contract whose outcomes are represented as an enumeration.
 #[derive(Clone, Debug)]
 #[cfg_attr(
@@ -22,9 +45,66 @@ use serde::{Deserialize, Serialize};
 pub struct EnumDescriptor {
     /// The set of outcomes.
     pub outcome_payouts: Vec<EnumerationPayout>,
+    /// What the oracle signs for each outcome.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub outcome_commitment: EnumOutcomeCommitment,
 }
 
 impl EnumDescriptor {
+    /// Returns the value that the oracle attests to when the outcome at the
+    /// given index occurs, or `None` if the descriptor only commits to the hash
+    /// of that value.
+    pub fn get_oracle_outcome(&self, index: usize) -> Option<String> {
+        match self.outcome_commitment {
+            EnumOutcomeCommitment::String => Some(self.outcome_payouts[index].outcome.clone()),
+            EnumOutcomeCommitment::Index => Some(index.to_string()),
+            EnumOutcomeCommitment::Hash => None,
+        }
+    }
+
+    /// Returns the index of the outcome that the oracle attests to by signing
+    /// the given value, if any.
+    pub fn get_outcome_index(&self, attested: &str) -> Option<usize> {
+        match self.outcome_commitment {
+            EnumOutcomeCommitment::Hash => {
+                let hash = sha256::Hash::hash(attested.as_bytes());
+                self.outcome_payouts
+                    .iter()
+                    .position(|x| sha256::Hash::from_hex(&x.outcome).ok() == Some(hash))
+            }
+            _ => (0..self.outcome_payouts.len())
+                .position(|i| self.get_oracle_outcome(i).as_deref() == Some(attested)),
+        }
+    }
+
+    /// Returns the value that the oracle attests to when the given outcome
+    /// occurs, or `None` if it is not part of the descriptor. The outcomes of
+    /// descriptors committing to outcome hashes are given as the preimages of
+    /// these hashes.
+    pub fn get_attested_value(&self, outcome: &str) -> Option<String> {
+        match self.outcome_commitment {
+            EnumOutcomeCommitment::Hash => {
+                self.get_outcome_index(outcome).map(|_| outcome.to_string())
+            }
+            _ => self
+                .outcome_payouts
+                .iter()
+                .position(|x| x.outcome == outcome)
+                .and_then(|i| self.get_oracle_outcome(i)),
+        }
+    }
+
+    /// Returns the message signed by the oracle when the outcome at the given
+    /// index occurs.
+    fn get_outcome_message(&self, index: usize) -> Result<Message, dlc::Error> {
+        let hash = match self.get_oracle_outcome(index) {
+            Some(outcome) => sha256::Hash::hash(outcome.as_bytes()),
+            None => sha256::Hash::from_hex(&self.outcome_payouts[index].outcome)
+                .map_err(|_| dlc::Error::InvalidArgument)?,
+        };
+        Ok(Message::from_slice(&hash[..])?)
+    }
+
     /// Returns the set of payouts.
     pub fn get_payouts(&self) -> Vec<Payout> {
         self.outcome_payouts
@@ -59,14 +139,12 @@ impl EnumDescriptor {
 
         actual_combination.truncate(threshold);
 
-        let pos = self
-            .outcome_payouts
-            .iter()
-            .position(|x| x.outcome == outcome)
-            .ok_or(crate::error::Error::InvalidParameters(format!(
-                "Outcome {} not found in the set of possible outcomes",
-                outcome
-            )))?;
+        let pos =
+            self.get_outcome_index(&outcome)
+                .ok_or(crate::error::Error::InvalidParameters(format!(
+                    "Outcome {} not found in the set of possible outcomes",
+                    outcome
+                )))?;
 
         let combinator = CombinationIterator::new(nb_oracles, threshold);
         let mut comb_pos = 0;
@@ -97,8 +175,7 @@ impl EnumDescriptor {
         oracle_infos: &[OracleInfo],
         threshold: usize,
         fund_pubkey: &PublicKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_sig_start: usize,
@@ -108,14 +185,13 @@ impl EnumDescriptor {
             |adaptor_point: &PublicKey, cet_index: usize| -> Result<(), dlc::Error> {
                 let sig = adaptor_sigs[adaptor_sig_index];
                 adaptor_sig_index += 1;
-                dlc::verify_cet_adaptor_sig_from_point(
+                dlc::verify_cet_adaptor_sig_from_point_with_sig_hash_type(
                     secp,
                     &sig,
                     &cets[cet_index],
                     &adaptor_point,
                     fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                 )?;
                 Ok(())
             };
@@ -132,8 +208,7 @@ impl EnumDescriptor {
         oracle_infos: &[OracleInfo],
         threshold: usize,
         fund_pubkey: &PublicKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
         adaptor_sigs: &[EcdsaAdaptorSignature],
         adaptor_sig_start: usize,
@@ -143,8 +218,7 @@ impl EnumDescriptor {
             oracle_infos,
             threshold,
             fund_pubkey,
-            funding_script_pubkey,
-            fund_output_value,
+            fund_output,
             cets,
             adaptor_sigs,
             adaptor_sig_start,
@@ -160,8 +234,7 @@ impl EnumDescriptor {
         oracle_infos: &[OracleInfo],
         threshold: usize,
         fund_privkey: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
     ) -> Result<(AdaptorInfo, Vec<EcdsaAdaptorSignature>), dlc::Error> {
         let adaptor_sigs = self.get_adaptor_signatures(
@@ -170,8 +243,7 @@ impl EnumDescriptor {
             threshold,
             cets,
             fund_privkey,
-            funding_script_pubkey,
-            fund_output_value,
+            fund_output,
         )?;
 
         Ok((AdaptorInfo::Enum, adaptor_sigs))
@@ -185,19 +257,17 @@ impl EnumDescriptor {
         threshold: usize,
         cets: &[Transaction],
         fund_privkey: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
     ) -> Result<Vec<EcdsaAdaptorSignature>, dlc::Error> {
         let mut adaptor_sigs = Vec::new();
         let mut callback =
             |adaptor_point: &PublicKey, cet_index: usize| -> Result<(), dlc::Error> {
-                let sig = dlc::create_cet_adaptor_sig_from_point(
+                let sig = dlc::create_cet_adaptor_sig_from_point_with_sig_hash_type(
                     secp,
                     &cets[cet_index],
                     &adaptor_point,
                     fund_privkey,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                 )?;
                 adaptor_sigs.push(sig);
                 Ok(())
@@ -208,7 +278,7 @@ impl EnumDescriptor {
         Ok(adaptor_sigs)
     }
 
-    fn iter_outcomes<C: Verification, F>(
+    pub(crate) fn iter_outcomes<C: Verification, F>(
         &self,
         secp: &Secp256k1<C>,
         oracle_infos: &[OracleInfo],
@@ -218,16 +288,12 @@ impl EnumDescriptor {
     where
         F: FnMut(&PublicKey, usize) -> Result<(), dlc::Error>,
     {
-        let messages: Vec<Vec<Vec<Message>>> = self
-            .outcome_payouts
-            .iter()
-            .map(|x| {
-                let message = vec![Message::from_hashed_data::<
-                    secp256k1_zkp::bitcoin_hashes::sha256::Hash,
-                >(x.outcome.as_bytes())];
-                std::iter::repeat(message).take(threshold).collect()
+        let messages: Vec<Vec<Vec<Message>>> = (0..self.outcome_payouts.len())
+            .map(|i| {
+                let message = vec![self.get_outcome_message(i)?];
+                Ok(std::iter::repeat(message).take(threshold).collect())
             })
-            .collect();
+            .collect::<Result<_, dlc::Error>>()?;
         let combination_iter = CombinationIterator::new(oracle_infos.len(), threshold);
         let combinations: Vec<Vec<usize>> = combination_iter.collect();
 
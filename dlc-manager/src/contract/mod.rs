@@ -1,15 +1,20 @@
 //! Module containing structures and functions related to contracts.
 
+use crate::error::Error;
 use crate::ContractId;
-use bitcoin::Address;
+use bitcoin::{Address, Transaction, TxIn, TxOut};
+use dlc::Payout;
 use dlc_messages::{oracle_msgs::OracleAttestation, AcceptDlc, FundingInput, SignDlc};
 use dlc_trie::multi_oracle_trie::MultiOracleTrie;
 use dlc_trie::multi_oracle_trie_with_diff::MultiOracleTrieWithDiff;
+use secp256k1_zkp::schnorrsig::PublicKey as SchnorrPublicKey;
+use secp256k1_zkp::{EcdsaAdaptorSignature, Message};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use signed_contract::SignedContract;
 
 pub mod accepted_contract;
+pub mod audit_record;
 pub mod contract_info;
 pub mod contract_input;
 pub mod enum_descriptor;
@@ -19,6 +24,13 @@ pub mod ser;
 pub mod signed_contract;
 pub(crate) mod utils;
 
+/// The estimated number of bytes used in memory by a CET, which has a single
+/// input and at most two outputs, together with its adaptor signature.
+pub const CET_MEMORY_FOOTPRINT: usize = std::mem::size_of::<Transaction>()
+    + std::mem::size_of::<TxIn>()
+    + 2 * (std::mem::size_of::<TxOut>() + 34)
+    + std::mem::size_of::<EcdsaAdaptorSignature>();
+
 #[derive(Clone)]
 /// Enum representing the possible states of a DLC.
 pub enum Contract {
@@ -38,6 +50,11 @@ pub enum Contract {
     FailedAccept(FailedAcceptContract),
     /// A contract that failed when verifying information from a sign message.
     FailedSign(FailedSignContract),
+    /// A contract whose offer was declined by the party that received it.
+    Rejected(offered_contract::OfferedContract),
+    /// A contract that was closed by broadcasting a transaction signed by both
+    /// parties, paying out an amount they agreed upon.
+    CollaborativelyClosed(CollaborativelyClosedContract),
 }
 
 impl std::fmt::Debug for Contract {
@@ -51,17 +68,60 @@ impl std::fmt::Debug for Contract {
             Contract::Refunded(_) => "refunded",
             Contract::FailedAccept(_) => "failed accept",
             Contract::FailedSign(_) => "failed sign",
+            Contract::Rejected(_) => "rejected",
+            Contract::CollaborativelyClosed(_) => "collaboratively closed",
         };
         f.debug_struct("Contract").field("state", &state).finish()
     }
 }
 
+/// The state of a contract, without the associated data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContractState {
+    /// See [`Contract::Offered`].
+    Offered,
+    /// See [`Contract::Accepted`].
+    Accepted,
+    /// See [`Contract::Signed`].
+    Signed,
+    /// See [`Contract::Confirmed`].
+    Confirmed,
+    /// See [`Contract::Closed`].
+    Closed,
+    /// See [`Contract::Refunded`].
+    Refunded,
+    /// See [`Contract::FailedAccept`].
+    FailedAccept,
+    /// See [`Contract::FailedSign`].
+    FailedSign,
+    /// See [`Contract::Rejected`].
+    Rejected,
+    /// See [`Contract::CollaborativelyClosed`].
+    CollaborativelyClosed,
+}
+
 impl Contract {
-    /// Get the id of a contract. Returns the temporary contract id for offered
-    /// and failed accept contracts.
+    /// Returns the state of the contract.
+    pub fn get_state(&self) -> ContractState {
+        match self {
+            Contract::Offered(_) => ContractState::Offered,
+            Contract::Accepted(_) => ContractState::Accepted,
+            Contract::Signed(_) => ContractState::Signed,
+            Contract::Confirmed(_) => ContractState::Confirmed,
+            Contract::Closed(_) => ContractState::Closed,
+            Contract::Refunded(_) => ContractState::Refunded,
+            Contract::FailedAccept(_) => ContractState::FailedAccept,
+            Contract::FailedSign(_) => ContractState::FailedSign,
+            Contract::Rejected(_) => ContractState::Rejected,
+            Contract::CollaborativelyClosed(_) => ContractState::CollaborativelyClosed,
+        }
+    }
+
+    /// Get the id of a contract. Returns the temporary contract id for offered,
+    /// rejected and failed accept contracts.
     pub fn get_id(&self) -> ContractId {
         match self {
-            Contract::Offered(o) => o.id,
+            Contract::Offered(o) | Contract::Rejected(o) => o.id,
             Contract::Accepted(o) => o.get_contract_id(),
             Contract::Signed(o) | Contract::Confirmed(o) | Contract::Refunded(o) => {
                 o.accepted_contract.get_contract_id()
@@ -69,13 +129,16 @@ impl Contract {
             Contract::FailedAccept(c) => c.offered_contract.id,
             Contract::FailedSign(c) => c.accepted_contract.get_contract_id(),
             Contract::Closed(c) => c.signed_contract.accepted_contract.get_contract_id(),
+            Contract::CollaborativelyClosed(c) => {
+                c.signed_contract.accepted_contract.get_contract_id()
+            }
         }
     }
 
     /// Returns the temporary contract id of a contract.
     pub fn get_temporary_id(&self) -> ContractId {
         match self {
-            Contract::Offered(o) => o.id,
+            Contract::Offered(o) | Contract::Rejected(o) => o.id,
             Contract::Accepted(o) => o.offered_contract.id,
             Contract::Signed(o) | Contract::Confirmed(o) | Contract::Refunded(o) => {
                 o.accepted_contract.offered_contract.id
@@ -83,8 +146,259 @@ impl Contract {
             Contract::FailedAccept(c) => c.offered_contract.id,
             Contract::FailedSign(c) => c.accepted_contract.offered_contract.id,
             Contract::Closed(c) => c.signed_contract.accepted_contract.offered_contract.id,
+            Contract::CollaborativelyClosed(c) => {
+                c.signed_contract.accepted_contract.offered_contract.id
+            }
+        }
+    }
+
+    /// Returns the offered contract from which the contract originates.
+    pub fn get_offered_contract(&self) -> &offered_contract::OfferedContract {
+        match self {
+            Contract::Offered(o) | Contract::Rejected(o) => o,
+            Contract::Accepted(o) => &o.offered_contract,
+            Contract::Signed(o) | Contract::Confirmed(o) | Contract::Refunded(o) => {
+                &o.accepted_contract.offered_contract
+            }
+            Contract::FailedAccept(c) => &c.offered_contract,
+            Contract::FailedSign(c) => &c.accepted_contract.offered_contract,
+            Contract::Closed(c) => &c.signed_contract.accepted_contract.offered_contract,
+            Contract::CollaborativelyClosed(c) => {
+                &c.signed_contract.accepted_contract.offered_contract
+            }
+        }
+    }
+
+    /// Returns the nonces of the oracle announcements used by the contract,
+    /// ordered by contract info and announcement, which are the ones the CET
+    /// adaptor signatures are encrypted against.
+    pub fn required_nonces(&self) -> Vec<SchnorrPublicKey> {
+        self.get_offered_contract()
+            .contract_info
+            .iter()
+            .flat_map(|x| x.oracle_announcements.iter())
+            .flat_map(|x| x.oracle_event.oracle_nonces.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the amounts respectively paid to the offer and accept parties
+    /// if the contract is refunded. As the refund fee is provisioned in the fund
+    /// output by each party, these amounts are equal to the parties' collaterals.
+    pub fn refund_payouts(&self) -> Result<(u64, u64), Error> {
+        let accepted_contract = match self {
+            Contract::Offered(_) | Contract::FailedAccept(_) | Contract::Rejected(_) => {
+                let o = self.get_offered_contract();
+                let offer_collateral = o.offer_params.collateral;
+                return Ok((offer_collateral, o.total_collateral - offer_collateral));
+            }
+            Contract::Accepted(a) => a,
+            Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => {
+                &s.accepted_contract
+            }
+            Contract::FailedSign(c) => &c.accepted_contract,
+            Contract::Closed(c) => &c.signed_contract.accepted_contract,
+            Contract::CollaborativelyClosed(c) => &c.signed_contract.accepted_contract,
+        };
+
+        let refund = &accepted_contract.dlc_transactions.refund;
+        match refund.output.as_slice() {
+            [offer, accept, ..] => Ok((offer.value, accept.value)),
+            _ => Err(Error::Other(
+                "Refund transaction is missing an output.".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the sighash of the refund transaction of the contract, which is
+    /// the message signed by the refund signatures of both parties. Returns an
+    /// error for contracts that were not accepted, as their refund transaction
+    /// is not yet known.
+    pub fn refund_sighash(&self) -> Result<Message, Error> {
+        let dlc_transactions = &self.get_accepted_contract_or_err()?.dlc_transactions;
+
+        Ok(dlc::util::get_sig_hash_msg(
+            &dlc_transactions.refund,
+            0,
+            &dlc_transactions.funding_script_pubkey,
+            dlc_transactions.get_fund_output().value,
+        ))
+    }
+
+    /// Returns how the value of the fund output of the contract is composed,
+    /// which is the sum of the collaterals and of the fees provisioned by the
+    /// parties for the CET or refund transaction. Returns an error for
+    /// contracts that were not accepted, as their fund transaction is not yet
+    /// known.
+    pub fn funding_breakdown(&self) -> Result<FundingBreakdown, Error> {
+        let accepted_contract = self.get_accepted_contract_or_err()?;
+
+        let offer_collateral = accepted_contract.offered_contract.offer_params.collateral;
+        let accept_collateral = accepted_contract.accept_params.collateral;
+        let funding_output_value = accepted_contract.dlc_transactions.get_fund_output().value;
+
+        Ok(FundingBreakdown {
+            offer_collateral,
+            accept_collateral,
+            funding_output_value,
+        })
+    }
+
+    /// Returns the amount that the given party receives if the CET at the
+    /// given index is broadcast. The CET fee is paid out of the closing fee
+    /// that both parties provisioned in the fund output (see
+    /// [`Contract::funding_breakdown`]), so this is the value of the CET output
+    /// of the party, or zero if its payout was below the dust limit and was
+    /// discarded from the CET.
+    pub fn net_payout_after_close(&self, cet_index: usize, party: Party) -> Result<u64, Error> {
+        let accepted_contract = self.get_accepted_contract_or_err()?;
+        let cet = accepted_contract
+            .dlc_transactions
+            .cets
+            .get(cet_index)
+            .ok_or_else(|| Error::InvalidParameters("Invalid CET index.".to_string()))?;
+        let payout_script_pubkey = match party {
+            Party::Offer => {
+                &accepted_contract
+                    .offered_contract
+                    .offer_params
+                    .payout_script_pubkey
+            }
+            Party::Accept => &accepted_contract.accept_params.payout_script_pubkey,
+        };
+
+        Ok(cet
+            .output
+            .iter()
+            .filter(|x| &x.script_pubkey == payout_script_pubkey)
+            .map(|x| x.value)
+            .sum())
+    }
+
+    /// Checks that the given funding transaction returns to each party the
+    /// change they are owed, that is the value of their funding inputs minus
+    /// their collateral and their share of the fund and CET (or refund)
+    /// transaction fees, as split by the fee contribution of the offer.
+    /// Change below the dust limit is expected to have been discarded.
+    /// Returns an error for contracts that were not accepted, as the change of
+    /// the accept party is not yet known. The CET fees are assumed to use the
+    /// contract fee rate.
+    pub fn verify_change_outputs(&self, funding_tx: &Transaction) -> Result<(), Error> {
+        let accepted_contract = self.get_accepted_contract_or_err()?;
+        let offered_contract = &accepted_contract.offered_contract;
+        let fee_rate = offered_contract.fee_rate_per_vb;
+        let ((offer_change, _, _), (accept_change, _, _)) = dlc::get_change_outputs_and_fees(
+            &offered_contract.offer_params,
+            &accepted_contract.accept_params,
+            fee_rate,
+            fee_rate,
+            offered_contract.fee_contribution,
+        )?;
+
+        for (party, expected_change) in
+            [(Party::Offer, offer_change), (Party::Accept, accept_change)]
+        {
+            let expected = if expected_change.value < dlc::DUST_LIMIT {
+                0
+            } else {
+                expected_change.value
+            };
+            let actual: u64 = funding_tx
+                .output
+                .iter()
+                .filter(|x| x.script_pubkey == expected_change.script_pubkey)
+                .map(|x| x.value)
+                .sum();
+            if actual != expected {
+                return Err(Error::InvalidParameters(format!(
+                    "Change output of the {:?} party is {} sats instead of {}.",
+                    party, actual, expected
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an estimate of the number of bytes used in memory by the CETs of
+    /// a contract with the given descriptor and total collateral, computed as
+    /// the number of CETs times [`CET_MEMORY_FOOTPRINT`]. Useful to decide
+    /// whether to accept a large numerical contract.
+    pub fn estimate_memory_footprint(
+        descriptor: &ContractDescriptor,
+        total_collateral: u64,
+    ) -> usize {
+        descriptor.canonical_cet_order(total_collateral).len() * CET_MEMORY_FOOTPRINT
+    }
+
+    /// Returns the sighashes of the CETs of the contract, in the order of the
+    /// CETs, which are the messages that the CET adaptor signatures encrypt a
+    /// signature of. Returns an error for contracts that were not accepted, as
+    /// their CETs are not yet known. The sighashes are computed using the CET
+    /// sighash type of the contract.
+    pub fn cet_sighashes(&self) -> Result<Vec<Message>, Error> {
+        let accepted_contract = self.get_accepted_contract_or_err()?;
+        let fund_output = accepted_contract.get_cet_sig_hash_params();
+
+        Ok(accepted_contract
+            .dlc_transactions
+            .cets
+            .iter()
+            .map(|cet| fund_output.get_sig_hash_msg(cet, 0))
+            .collect())
+    }
+
+    pub(crate) fn get_accepted_contract(&self) -> Option<&accepted_contract::AcceptedContract> {
+        match self {
+            Contract::Offered(_) | Contract::FailedAccept(_) | Contract::Rejected(_) => None,
+            Contract::Accepted(a) => Some(a),
+            Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => {
+                Some(&s.accepted_contract)
+            }
+            Contract::FailedSign(c) => Some(&c.accepted_contract),
+            Contract::Closed(c) => Some(&c.signed_contract.accepted_contract),
+            Contract::CollaborativelyClosed(c) => Some(&c.signed_contract.accepted_contract),
         }
     }
+
+    /// Same as `get_accepted_contract` but returns an [`Error::InvalidState`]
+    /// error for contracts that were not accepted.
+    pub(crate) fn get_accepted_contract_or_err(
+        &self,
+    ) -> Result<&accepted_contract::AcceptedContract, Error> {
+        self.get_accepted_contract()
+            .ok_or_else(|| Error::InvalidState {
+                expected: vec![
+                    ContractState::Accepted,
+                    ContractState::Signed,
+                    ContractState::Confirmed,
+                    ContractState::Closed,
+                    ContractState::Refunded,
+                    ContractState::FailedSign,
+                    ContractState::CollaborativelyClosed,
+                ],
+                found: self.get_state(),
+            })
+    }
+}
+
+/// One of the two parties of a contract.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Party {
+    /// The party that offered the contract.
+    Offer,
+    /// The party that accepted the contract.
+    Accept,
+}
+
+/// The composition of the value of the fund output of a contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FundingBreakdown {
+    /// The collateral of the offer party.
+    pub offer_collateral: u64,
+    /// The collateral of the accept party.
+    pub accept_collateral: u64,
+    /// The value of the fund output.
+    pub funding_output_value: u64,
 }
 
 /// Information about a funding input.
@@ -134,6 +448,16 @@ pub struct ClosedContract {
     pub cet_index: usize,
 }
 
+/// Information about a contract that was closed by broadcasting a transaction
+/// spending the fund output that was signed by both parties.
+#[derive(Clone)]
+pub struct CollaborativelyClosedContract {
+    /// The signed contract that was closed.
+    pub signed_contract: SignedContract,
+    /// The fully signed close transaction that was broadcast.
+    pub close_tx: Transaction,
+}
+
 /// Information about the adaptor signatures and the CET for which they are
 /// valid.
 #[derive(Clone)]
@@ -169,4 +493,330 @@ impl ContractDescriptor {
             ContractDescriptor::Numerical(n) => n.difference_params.clone(),
         }
     }
+
+    /// Returns the payouts of the CETs of the contract in the order mandated by
+    /// the specification, which is the order in which adaptor signatures are
+    /// exchanged. For enumeration outcome DLC, this is the order in which the
+    /// outcomes appear in the descriptor, while for numerical outcome DLC it is
+    /// the increasing order of the outcome ranges.
+    pub fn canonical_cet_order(&self, total_collateral: u64) -> Vec<Payout> {
+        match self {
+            ContractDescriptor::Enum(e) => e.get_payouts(),
+            ContractDescriptor::Numerical(n) => {
+                let mut range_payouts = n.get_range_payouts(total_collateral);
+                range_payouts.sort_by_key(|x| x.start);
+                range_payouts.into_iter().map(|x| x.payout).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::ser::Serializable;
+    use bitcoin::hashes::hex::FromHex;
+    use bitcoin::{Address, Transaction};
+    use std::str::FromStr;
+
+    fn get_payout_value(cet: &Transaction, address: &str) -> u64 {
+        let script_pubkey = Address::from_str(address).unwrap().script_pubkey();
+        cet.output
+            .iter()
+            .find(|x| x.script_pubkey == script_pubkey)
+            .map_or(0, |x| x.value)
+    }
+
+    fn without_dust(value: u64) -> u64 {
+        if value < dlc::DUST_LIMIT {
+            0
+        } else {
+            value
+        }
+    }
+
+    #[test]
+    fn enum_cet_order_matches_dlcspecs_test_vectors_test() {
+        let vectors: serde_json::Value = serde_json::from_str(include_str!(
+            "../../../dlc-messages/src/test_inputs/dlc_test.json"
+        ))
+        .unwrap();
+
+        for vector in vectors.as_array().unwrap() {
+            let inputs = &vector["inputs"];
+            let offer_params = &inputs["offerParams"];
+            let accept_params = &inputs["acceptParams"];
+            let total_collateral = offer_params["collateral"].as_u64().unwrap()
+                + accept_params["collateral"].as_u64().unwrap();
+            let descriptor = ContractDescriptor::Enum(enum_descriptor::EnumDescriptor {
+                outcome_payouts: inputs["params"]["contractInfo"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|x| {
+                        let offer = x["localPayout"].as_u64().unwrap();
+                        dlc::EnumerationPayout {
+                            outcome: x["outcome"].as_str().unwrap().to_owned(),
+                            payout: Payout {
+                                offer,
+                                accept: total_collateral - offer,
+                            },
+                        }
+                    })
+                    .collect(),
+                outcome_commitment: enum_descriptor::EnumOutcomeCommitment::String,
+            });
+            let cets: Vec<Transaction> = vector["unsignedTxs"]["cets"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|x| {
+                    bitcoin::consensus::deserialize(
+                        &Vec::<u8>::from_hex(x.as_str().unwrap()).unwrap(),
+                    )
+                    .unwrap()
+                })
+                .collect();
+
+            let payouts = descriptor.canonical_cet_order(total_collateral);
+
+            assert_eq!(cets.len(), payouts.len());
+            for (cet, payout) in cets.iter().zip(payouts.iter()) {
+                assert_eq!(
+                    without_dust(payout.offer),
+                    get_payout_value(cet, offer_params["payoutAddress"].as_str().unwrap())
+                );
+                assert_eq!(
+                    without_dust(payout.accept),
+                    get_payout_value(cet, accept_params["payoutAddress"].as_str().unwrap())
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "use-serde")]
+    #[test]
+    fn numerical_cet_order_matches_dlcspecs_test_vector_test() {
+        use dlc_messages::contract_msgs::{
+            ContractDescriptor as SerContractDescriptor, ContractInfo as SerContractInfo,
+        };
+
+        let offer: dlc_messages::OfferDlc = serde_json::from_str(include_str!(
+            "../../../dlc-messages/src/test_inputs/offer_msg.json"
+        ))
+        .unwrap();
+        let single = match &offer.contract_info {
+            SerContractInfo::SingleContractInfo(single) => single,
+            _ => panic!("Expected a single contract info."),
+        };
+        let function = match &single.contract_info.contract_descriptor {
+            SerContractDescriptor::NumericOutcomeContractDescriptor(n) => &n.payout_function,
+            _ => panic!("Expected a numerical contract descriptor."),
+        };
+        let offered =
+            offered_contract::OfferedContract::try_from_offer_dlc(&offer, offer.funding_pubkey)
+                .unwrap();
+        let total_collateral = single.total_collateral;
+        let descriptor = &offered.contract_info[0].contract_descriptor;
+
+        // The vector is a single linear piece with a rounding modulus of one, so
+        // each outcome gets its own CET, ordered by increasing outcome.
+        let left = &function.payout_function_pieces[0].left_end_point;
+        let right = &function.last_endpoint;
+        let expected: Vec<_> = (left.event_outcome..=right.event_outcome)
+            .map(|outcome| {
+                let offer = left.outcome_payout
+                    + ((right.outcome_payout - left.outcome_payout) as f64
+                        * (outcome - left.event_outcome) as f64
+                        / (right.event_outcome - left.event_outcome) as f64)
+                        .round() as u64;
+                Payout {
+                    offer,
+                    accept: total_collateral - offer,
+                }
+            })
+            .collect();
+
+        assert_eq!(expected, descriptor.canonical_cet_order(total_collateral));
+    }
+
+    #[test]
+    fn refund_payouts_return_collaterals_test() {
+        let mut cursor = std::io::Cursor::new(include_bytes!("../../test_files/Signed"));
+        let signed = signed_contract::SignedContract::deserialize(&mut cursor).unwrap();
+        let accepted = &signed.accepted_contract;
+        let offered = &accepted.offered_contract;
+        let (_, _, offer_refund_fee) = offered
+            .offer_params
+            .get_change_output_and_fees(offered.fee_rate_per_vb)
+            .unwrap();
+        let (_, _, accept_refund_fee) = accepted
+            .accept_params
+            .get_change_output_and_fees(offered.fee_rate_per_vb)
+            .unwrap();
+
+        let (offer_payout, accept_payout) =
+            Contract::Signed(signed.clone()).refund_payouts().unwrap();
+
+        assert_eq!(offered.offer_params.collateral, offer_payout);
+        assert_eq!(accepted.accept_params.collateral, accept_payout);
+        assert_eq!(
+            accepted.dlc_transactions.get_fund_output().value,
+            offer_payout + accept_payout + offer_refund_fee + accept_refund_fee
+        );
+        assert_eq!(
+            (offer_payout, accept_payout),
+            Contract::Offered(offered.clone()).refund_payouts().unwrap()
+        );
+    }
+
+    #[test]
+    fn funding_breakdown_sums_to_fund_output_value_test() {
+        let mut cursor = std::io::Cursor::new(include_bytes!("../../test_files/Signed"));
+        let signed = signed_contract::SignedContract::deserialize(&mut cursor).unwrap();
+        let accepted = &signed.accepted_contract;
+
+        let breakdown = Contract::Signed(signed.clone())
+            .funding_breakdown()
+            .unwrap();
+
+        assert_eq!(
+            accepted.offered_contract.offer_params.collateral,
+            breakdown.offer_collateral
+        );
+        assert_eq!(
+            accepted.accept_params.collateral,
+            breakdown.accept_collateral
+        );
+        assert_eq!(
+            accepted.dlc_transactions.get_fund_output().value,
+            breakdown.funding_output_value
+        );
+        let (_, _, offer_closing_fee) = accepted
+            .offered_contract
+            .offer_params
+            .get_change_output_and_fees(accepted.offered_contract.fee_rate_per_vb)
+            .unwrap();
+        let (_, _, accept_closing_fee) = accepted
+            .accept_params
+            .get_change_output_and_fees(accepted.offered_contract.fee_rate_per_vb)
+            .unwrap();
+        assert_eq!(
+            breakdown.funding_output_value,
+            breakdown.offer_collateral
+                + breakdown.accept_collateral
+                + offer_closing_fee
+                + accept_closing_fee
+        );
+        assert!(Contract::Offered(accepted.offered_contract.clone())
+            .funding_breakdown()
+            .is_err());
+    }
+
+    #[test]
+    fn net_payout_after_close_matches_cet_outputs_test() {
+        let mut cursor = std::io::Cursor::new(include_bytes!("../../test_files/Signed"));
+        let signed = signed_contract::SignedContract::deserialize(&mut cursor).unwrap();
+        let accepted = &signed.accepted_contract;
+        let contract = Contract::Signed(signed.clone());
+        let cet_index = accepted.dlc_transactions.cets.len() / 2;
+        let cet = &accepted.dlc_transactions.cets[cet_index];
+
+        let get_output_value = |script_pubkey| {
+            cet.output
+                .iter()
+                .find(|x| &x.script_pubkey == script_pubkey)
+                .map_or(0, |x| x.value)
+        };
+        let offer_net_payout = contract
+            .net_payout_after_close(cet_index, Party::Offer)
+            .unwrap();
+        let accept_net_payout = contract
+            .net_payout_after_close(cet_index, Party::Accept)
+            .unwrap();
+
+        assert_eq!(
+            get_output_value(&accepted.offered_contract.offer_params.payout_script_pubkey),
+            offer_net_payout
+        );
+        assert_eq!(
+            get_output_value(&accepted.accept_params.payout_script_pubkey),
+            accept_net_payout
+        );
+        assert!(
+            offer_net_payout + accept_net_payout
+                <= contract.funding_breakdown().unwrap().funding_output_value
+        );
+        assert!(contract
+            .net_payout_after_close(accepted.dlc_transactions.cets.len(), Party::Offer)
+            .is_err());
+    }
+
+    #[test]
+    fn verify_change_outputs_test() {
+        let mut cursor = std::io::Cursor::new(include_bytes!("../../test_files/Signed"));
+        let signed = signed_contract::SignedContract::deserialize(&mut cursor).unwrap();
+        let accepted = &signed.accepted_contract;
+        let contract = Contract::Signed(signed.clone());
+        let fund = &accepted.dlc_transactions.fund;
+        let change_script_pubkeys = [
+            &accepted.offered_contract.offer_params.change_script_pubkey,
+            &accepted.accept_params.change_script_pubkey,
+        ];
+        assert_eq!(
+            2,
+            fund.output
+                .iter()
+                .filter(|x| change_script_pubkeys.contains(&&x.script_pubkey))
+                .count()
+        );
+
+        contract
+            .verify_change_outputs(fund)
+            .expect("Change outputs should be valid");
+
+        for change_script_pubkey in change_script_pubkeys {
+            let mut tampered = fund.clone();
+            tampered
+                .output
+                .iter_mut()
+                .find(|x| &x.script_pubkey == change_script_pubkey)
+                .unwrap()
+                .value -= 1;
+            assert!(matches!(
+                contract.verify_change_outputs(&tampered),
+                Err(Error::InvalidParameters(_))
+            ));
+        }
+        assert!(Contract::Offered(accepted.offered_contract.clone())
+            .verify_change_outputs(fund)
+            .is_err());
+    }
+
+    #[test]
+    fn fee_contribution_serialization_is_backward_compatible_test() {
+        let serialized = include_bytes!("../../test_files/Signed");
+        let mut cursor = std::io::Cursor::new(serialized);
+        let mut signed = signed_contract::SignedContract::deserialize(&mut cursor).unwrap();
+        assert_eq!(
+            dlc::FeeContribution::Equal,
+            signed.accepted_contract.offered_contract.fee_contribution
+        );
+        assert_eq!(serialized[..], signed.serialize().unwrap()[..]);
+
+        signed.accepted_contract.offered_contract.fee_contribution =
+            dlc::FeeContribution::Ratio(1, 3);
+        let reserialized = signed.serialize().unwrap();
+        assert_ne!(serialized[..], reserialized[..]);
+        let mut cursor = std::io::Cursor::new(&reserialized);
+        let deserialized = signed_contract::SignedContract::deserialize(&mut cursor).unwrap();
+        assert_eq!(
+            dlc::FeeContribution::Ratio(1, 3),
+            deserialized
+                .accepted_contract
+                .offered_contract
+                .fee_contribution
+        );
+        assert_eq!(reserialized, deserialized.serialize().unwrap());
+    }
 }
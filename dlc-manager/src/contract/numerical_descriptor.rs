@@ -2,8 +2,8 @@
 
 use super::AdaptorInfo;
 use crate::payout_curve::{PayoutFunction, RoundingIntervals};
-use bitcoin::{Script, Transaction};
-use dlc::{OracleInfo, Payout, RangePayout};
+use bitcoin::Transaction;
+use dlc::{OracleInfo, Payout, RangePayout, SigHashParams};
 use dlc_trie::multi_oracle_trie::MultiOracleTrie;
 use dlc_trie::multi_oracle_trie_with_diff::MultiOracleTrieWithDiff;
 use dlc_trie::DlcTrie;
@@ -75,6 +75,14 @@ impl NumericalDescriptor {
             .to_range_payouts(total_collateral, &self.rounding_intervals)
     }
 
+    /// Returns the largest outcome value that the oracles can attest to given
+    /// the base and number of digits of the event.
+    pub fn get_max_outcome(&self) -> u64 {
+        (self.info.base as u64)
+            .checked_pow(self.info.nb_digits as u32)
+            .map_or(u64::MAX, |x| x - 1)
+    }
+
     /// Returns the set of payouts for the descriptor generated from the payout
     /// function.
     pub fn get_payouts(&self, total_collateral: u64) -> Vec<Payout> {
@@ -90,8 +98,7 @@ impl NumericalDescriptor {
         secp: &Secp256k1<All>,
         total_collateral: u64,
         fund_pubkey: &PublicKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         threshold: usize,
         oracle_infos: &[OracleInfo],
         cets: &[Transaction],
@@ -111,8 +118,7 @@ impl NumericalDescriptor {
                 let index = multi_trie.generate_verify(
                     secp,
                     fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                     &self.get_range_payouts(total_collateral),
                     cets,
                     oracle_infos,
@@ -131,8 +137,7 @@ impl NumericalDescriptor {
                 let index = trie.generate_verify(
                     secp,
                     fund_pubkey,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                     &self.get_range_payouts(total_collateral),
                     cets,
                     oracle_infos,
@@ -150,8 +155,7 @@ impl NumericalDescriptor {
         secp: &Secp256k1<All>,
         total_collateral: u64,
         fund_priv_key: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         threshold: usize,
         oracle_infos: &[OracleInfo],
         cets: &[Transaction],
@@ -170,8 +174,7 @@ impl NumericalDescriptor {
                 let adaptor_pairs = multi_trie.generate_sign(
                     secp,
                     fund_priv_key,
-                    funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                     &self.get_range_payouts(total_collateral),
                     cets,
                     oracle_infos,
@@ -193,8 +196,7 @@ impl NumericalDescriptor {
                 let sigs = trie.generate_sign(
                     secp,
                     &fund_priv_key,
-                    &funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                     &self.get_range_payouts(total_collateral),
                     cets,
                     oracle_infos,
@@ -2,7 +2,8 @@
 
 use super::contract_info::ContractInfo;
 use super::FundingInputInfo;
-use dlc::PartyParams;
+use bitcoin::SigHashType;
+use dlc::{CetCsvDelays, FeeContribution, PartyParams};
 use secp256k1_zkp::PublicKey;
 
 /// Contains information about a contract that was offered.
@@ -36,4 +37,26 @@ pub struct OfferedContract {
     pub contract_maturity_bound: u32,
     /// The time at which the contract becomes refundable.
     pub contract_timeout: u32,
+    /// How the fees of the transactions are split between the parties.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fee_contribution: FeeContribution,
+    /// The sighash type of the CET signatures of both parties, `ALL` if not
+    /// set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cet_sig_hash_type: Option<SigHashType>,
+    /// The relative delays after which the parties can claim their CET
+    /// outputs, which are not timelocked if not set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cet_csv_delays: Option<CetCsvDelays>,
+    /// The factor by which the fee rate of the contract is multiplied to get
+    /// the fee rate of the CETs and refund transaction, `1` if not set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cet_fee_reserve_multiplier: Option<u64>,
+}
+
+impl OfferedContract {
+    /// Returns the sighash type of the CET signatures of both parties.
+    pub fn get_cet_sig_hash_type(&self) -> SigHashType {
+        self.cet_sig_hash_type.unwrap_or(SigHashType::All)
+    }
 }
@@ -2,8 +2,9 @@
 //! to be converted to byte arrays.
 
 use crate::contract::accepted_contract::AcceptedContract;
+use crate::contract::audit_record::{AuditRecord, AuditSignature};
 use crate::contract::contract_info::ContractInfo;
-use crate::contract::enum_descriptor::EnumDescriptor;
+use crate::contract::enum_descriptor::{EnumDescriptor, EnumOutcomeCommitment};
 use crate::contract::numerical_descriptor::{
     DifferenceParams, NumericalDescriptor, NumericalEventInfo,
 };
@@ -11,16 +12,19 @@ use crate::contract::offered_contract::OfferedContract;
 use crate::contract::signed_contract::SignedContract;
 use crate::contract::AdaptorInfo;
 use crate::contract::{
-    ClosedContract, ContractDescriptor, FailedAcceptContract, FailedSignContract, FundingInputInfo,
+    ClosedContract, CollaborativelyClosedContract, ContractDescriptor, FailedAcceptContract,
+    FailedSignContract, FundingInputInfo, Party,
 };
 use crate::payout_curve::{
     HyperbolaPayoutCurvePiece, PayoutFunction, PayoutFunctionPiece, PayoutPoint,
     PolynomialPayoutCurvePiece, RoundingInterval, RoundingIntervals,
 };
-use dlc::DlcTransactions;
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, SigHashType, Txid};
+use dlc::{CetCsvDelays, DlcTransactions, FeeContribution};
 use dlc_messages::ser_impls::{
-    read_ecdsa_adaptor_signatures, read_option_cb, read_usize, read_vec_cb,
-    write_ecdsa_adaptor_signatures, write_option_cb, write_usize, write_vec_cb,
+    read_ecdsa_adaptor_signatures, read_option_cb, read_usize, read_vec, read_vec_cb,
+    write_ecdsa_adaptor_signatures, write_option_cb, write_usize, write_vec, write_vec_cb,
 };
 use dlc_trie::digit_trie::{DigitNodeData, DigitTrieDump};
 use dlc_trie::multi_oracle_trie::{MultiOracleTrie, MultiOracleTrieDump};
@@ -29,6 +33,7 @@ use dlc_trie::multi_trie::{MultiTrieDump, MultiTrieNodeData, TrieNodeInfo};
 use dlc_trie::RangeInfo;
 use lightning::ln::msgs::DecodeError;
 use lightning::util::ser::{Readable, Writeable, Writer};
+use secp256k1_zkp::Message;
 use std::io::Read;
 
 /// Trait used to de/serialize an object to/from a vector of bytes.
@@ -81,27 +86,187 @@ impl_dlc_writeable!(HyperbolaPayoutCurvePiece, {
     (c, float),
     (d, float)
 });
-impl_dlc_writeable_enum!(ContractDescriptor, (0, Enum), (1, Numerical);;);
 impl_dlc_writeable!(ContractInfo, { (contract_descriptor, writeable), (oracle_announcements, vec), (threshold, usize)});
 impl_dlc_writeable!(FundingInputInfo, { (funding_input, writeable), (address, {option_cb, dlc_messages::ser_impls::write_address, dlc_messages::ser_impls::read_address}) });
-impl_dlc_writeable!(EnumDescriptor, {
-    (
-        outcome_payouts,
-        {vec_cb, dlc_messages::ser_impls::enum_payout::write, dlc_messages::ser_impls::enum_payout::read}
-    )
+// The outcome commitment of enumerated descriptors is encoded in the type of
+// the contract descriptor so that descriptors committing to outcome strings keep
+// the same serialization as before the commitment was introduced.
+impl Writeable for ContractDescriptor {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ::std::io::Error> {
+        match self {
+            ContractDescriptor::Enum(e) => {
+                let id: u8 = match e.outcome_commitment {
+                    EnumOutcomeCommitment::String => 0,
+                    EnumOutcomeCommitment::Index => 2,
+                    EnumOutcomeCommitment::Hash => 3,
+                };
+                id.write(writer)?;
+                e.write(writer)
+            }
+            ContractDescriptor::Numerical(n) => {
+                1u8.write(writer)?;
+                n.write(writer)
+            }
+        }
+    }
+}
+
+impl Readable for ContractDescriptor {
+    fn read<R: Read>(reader: &mut R) -> Result<ContractDescriptor, DecodeError> {
+        let id: u8 = Readable::read(reader)?;
+        match id {
+            0 => Ok(ContractDescriptor::Enum(Readable::read(reader)?)),
+            1 => Ok(ContractDescriptor::Numerical(Readable::read(reader)?)),
+            2 => {
+                let mut descriptor: EnumDescriptor = Readable::read(reader)?;
+                descriptor.outcome_commitment = EnumOutcomeCommitment::Index;
+                Ok(ContractDescriptor::Enum(descriptor))
+            }
+            3 => {
+                let mut descriptor: EnumDescriptor = Readable::read(reader)?;
+                descriptor.outcome_commitment = EnumOutcomeCommitment::Hash;
+                Ok(ContractDescriptor::Enum(descriptor))
+            }
+            _ => Err(DecodeError::UnknownRequiredFeature),
+        }
+    }
+}
+
+impl Writeable for EnumDescriptor {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ::std::io::Error> {
+        write_vec_cb(
+            &self.outcome_payouts,
+            writer,
+            &dlc_messages::ser_impls::enum_payout::write,
+        )
+    }
+}
+
+impl Readable for EnumDescriptor {
+    fn read<R: Read>(reader: &mut R) -> Result<EnumDescriptor, DecodeError> {
+        Ok(EnumDescriptor {
+            outcome_payouts: read_vec_cb(reader, &dlc_messages::ser_impls::enum_payout::read)?,
+            outcome_commitment: EnumOutcomeCommitment::String,
+        })
+    }
+}
+
+// The CET CSV delays, CET sighash type, CET fee reserve multiplier and fee
+// contribution are only written when not set to their default value, in which
+// case they are flagged in the byte holding `is_offer_party`, so that contracts
+// using the default values keep the same serialization as before these fields
+// were introduced.
+impl Writeable for OfferedContract {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ::std::io::Error> {
+        let has_fee_contribution = self.fee_contribution != FeeContribution::Equal;
+        self.id.write(writer)?;
+        let flags = self.is_offer_party as u8
+            | ((self.cet_csv_delays.is_some() as u8) << 1)
+            | ((self.cet_sig_hash_type.is_some() as u8) << 2)
+            | ((self.cet_fee_reserve_multiplier.is_some() as u8) << 3)
+            | ((has_fee_contribution as u8) << 4);
+        flags.write(writer)?;
+        if let Some(csv_delays) = &self.cet_csv_delays {
+            csv_delays.offer.write(writer)?;
+            csv_delays.accept.write(writer)?;
+        }
+        if let Some(sig_hash_type) = self.cet_sig_hash_type {
+            sig_hash_type.as_u32().write(writer)?;
+        }
+        if let Some(multiplier) = self.cet_fee_reserve_multiplier {
+            multiplier.write(writer)?;
+        }
+        if has_fee_contribution {
+            dlc_messages::ser_impls::write_fee_contribution(&self.fee_contribution, writer)?;
+        }
+        write_vec(&self.contract_info, writer)?;
+        dlc_messages::ser_impls::party_params::write(&self.offer_params, writer)?;
+        self.total_collateral.write(writer)?;
+        write_vec(&self.funding_inputs_info, writer)?;
+        self.fund_output_serial_id.write(writer)?;
+        self.fee_rate_per_vb.write(writer)?;
+        self.contract_maturity_bound.write(writer)?;
+        self.contract_timeout.write(writer)?;
+        self.counter_party.write(writer)
+    }
+}
+
+impl Readable for OfferedContract {
+    fn read<R: Read>(reader: &mut R) -> Result<OfferedContract, DecodeError> {
+        let id = Readable::read(reader)?;
+        let flags: u8 = Readable::read(reader)?;
+        if flags > 31 {
+            return Err(DecodeError::UnknownRequiredFeature);
+        }
+        let cet_csv_delays = if flags & 2 != 0 {
+            Some(CetCsvDelays {
+                offer: Readable::read(reader)?,
+                accept: Readable::read(reader)?,
+            })
+        } else {
+            None
+        };
+        let cet_sig_hash_type = if flags & 4 != 0 {
+            let sig_hash_type: u32 = Readable::read(reader)?;
+            Some(
+                SigHashType::from_u32_standard(sig_hash_type)
+                    .map_err(|_| DecodeError::InvalidValue)?,
+            )
+        } else {
+            None
+        };
+        let cet_fee_reserve_multiplier = if flags & 8 != 0 {
+            Some(Readable::read(reader)?)
+        } else {
+            None
+        };
+        let fee_contribution = if flags & 16 != 0 {
+            match dlc_messages::ser_impls::read_fee_contribution(reader)? {
+                FeeContribution::Equal => return Err(DecodeError::InvalidValue),
+                f => f,
+            }
+        } else {
+            FeeContribution::Equal
+        };
+        Ok(OfferedContract {
+            id,
+            is_offer_party: flags & 1 != 0,
+            contract_info: read_vec(reader)?,
+            offer_params: dlc_messages::ser_impls::party_params::read(reader)?,
+            total_collateral: Readable::read(reader)?,
+            funding_inputs_info: read_vec(reader)?,
+            fund_output_serial_id: Readable::read(reader)?,
+            fee_rate_per_vb: Readable::read(reader)?,
+            contract_maturity_bound: Readable::read(reader)?,
+            contract_timeout: Readable::read(reader)?,
+            counter_party: Readable::read(reader)?,
+            fee_contribution,
+            cet_sig_hash_type,
+            cet_csv_delays,
+            cet_fee_reserve_multiplier,
+        })
+    }
+}
+
+impl_dlc_writeable_enum!(Party,;; (0, Offer), (1, Accept));
+impl_dlc_writeable!(AuditSignature, {
+    (cet_index, usize),
+    (adaptor_point, writeable),
+    (adaptor_signature, { cb_writeable, dlc_messages::ser_impls::write_ecdsa_adaptor_signature, dlc_messages::ser_impls::read_ecdsa_adaptor_signature })
 });
-impl_dlc_writeable!(OfferedContract, {
-    (id, writeable),
-    (is_offer_party, writeable),
-    (contract_info, vec),
-    (offer_params, { cb_writeable, dlc_messages::ser_impls::party_params::write, dlc_messages::ser_impls::party_params::read }),
-    (total_collateral, writeable),
-    (funding_inputs_info, vec),
-    (fund_output_serial_id, writeable),
-    (fee_rate_per_vb, writeable),
-    (contract_maturity_bound, writeable),
-    (contract_timeout, writeable),
-    (counter_party, writeable)
+impl_dlc_writeable!(AuditRecord, {
+    (contract_id, writeable),
+    (fund_outpoint, { cb_writeable, write_outpoint, read_outpoint }),
+    (offer_fund_pubkey, writeable),
+    (accept_fund_pubkey, writeable),
+    (oracle_public_keys, { cb_writeable, dlc_messages::ser_impls::write_schnorr_pubkeys, dlc_messages::ser_impls::read_schnorr_pubkeys }),
+    (oracle_event_ids, { cb_writeable, dlc_messages::ser_impls::write_strings, dlc_messages::ser_impls::read_strings }),
+    (refund_sighash, { cb_writeable, write_message, read_message }),
+    (offer_refund_signature, writeable),
+    (accept_refund_signature, writeable),
+    (cet_sighashes, { vec_cb, write_message, read_message }),
+    (adaptor_signer, writeable),
+    (adaptor_signatures, vec)
 });
 impl_dlc_writeable_external!(RangeInfo, range_info, { (cet_index, usize), (adaptor_index, usize)});
 impl_dlc_writeable_enum!(AdaptorInfo,; (0, Numerical, write_multi_oracle_trie, read_multi_oracle_trie), (1, NumericalWithDifference, write_multi_oracle_trie_with_diff, read_multi_oracle_trie_with_diff); (2, Enum));
@@ -132,6 +297,10 @@ impl_dlc_writeable!(ClosedContract, {
     (attestations, vec),
     (cet_index, usize)
 });
+impl_dlc_writeable!(CollaborativelyClosedContract, {
+    (signed_contract, writeable),
+    (close_tx, writeable)
+});
 impl_dlc_writeable!(FailedAcceptContract, {(offered_contract, writeable), (accept_message, writeable), (error_message, string)});
 impl_dlc_writeable!(FailedSignContract, {(accepted_contract, writeable), (sign_message, writeable), (error_message, string)});
 
@@ -266,3 +435,24 @@ fn read_multi_oracle_trie_with_diff<R: Read>(
     let dump = multi_oracle_trie_with_diff_dump::read(reader)?;
     Ok(MultiOracleTrieWithDiff::from_dump(dump))
 }
+
+fn write_outpoint<W: Writer>(outpoint: &OutPoint, w: &mut W) -> Result<(), ::std::io::Error> {
+    outpoint.txid.into_inner().write(w)?;
+    outpoint.vout.write(w)
+}
+
+fn read_outpoint<R: Read>(reader: &mut R) -> Result<OutPoint, DecodeError> {
+    Ok(OutPoint {
+        txid: Txid::from_inner(Readable::read(reader)?),
+        vout: Readable::read(reader)?,
+    })
+}
+
+fn write_message<W: Writer>(message: &Message, w: &mut W) -> Result<(), ::std::io::Error> {
+    w.write_all(&message[..])
+}
+
+fn read_message<R: Read>(reader: &mut R) -> Result<Message, DecodeError> {
+    let buf: [u8; 32] = Readable::read(reader)?;
+    Message::from_slice(&buf).map_err(|_| DecodeError::InvalidValue)
+}
@@ -10,7 +10,8 @@ use secp256k1_zkp::Signature;
 pub struct SignedContract {
     /// The accepted contract that was signed.
     pub accepted_contract: AcceptedContract,
-    /// The adaptor signatures of the offering party (None if offering party).
+    /// The adaptor signatures of the offering party (None for contracts
+    /// offered by earlier versions, which did not keep their own).
     pub adaptor_signatures: Option<Vec<EcdsaAdaptorSignature>>,
     /// The refund signature of the offering party.
     pub offer_refund_signature: Signature,
@@ -1,7 +1,7 @@
 use crate::contract::{
     accepted_contract::AcceptedContract,
     contract_info::ContractInfo,
-    enum_descriptor::EnumDescriptor,
+    enum_descriptor::{EnumDescriptor, EnumOutcomeCommitment},
     numerical_descriptor::{DifferenceParams, NumericalDescriptor, NumericalEventInfo},
     offered_contract::OfferedContract,
     signed_contract::SignedContract,
@@ -91,6 +91,10 @@ impl From<&OfferedContract> for OfferDlc {
             contract_timeout: offered_contract.contract_timeout,
             fee_rate_per_vb: offered_contract.fee_rate_per_vb,
             fund_output_serial_id: offered_contract.fund_output_serial_id,
+            fee_contribution: offered_contract.fee_contribution,
+            cet_sig_hash_type: offered_contract.cet_sig_hash_type,
+            cet_csv_delays: offered_contract.cet_csv_delays,
+            cet_fee_reserve_multiplier: offered_contract.cet_fee_reserve_multiplier,
         }
     }
 }
@@ -114,7 +118,7 @@ pub fn get_tx_input_infos(
                 txid: tx.txid(),
                 vout,
             },
-            max_witness_len: 107,
+            max_witness_len: fund_input.max_witness_len as usize,
             redeem_script: fund_input.redeem_script.clone(),
             serial_id: fund_input.input_serial_id,
         });
@@ -153,6 +157,10 @@ impl OfferedContract {
             funding_inputs_info: offer_dlc.funding_inputs.iter().map(|x| x.into()).collect(),
             total_collateral: offer_dlc.contract_info.get_total_collateral(),
             counter_party,
+            fee_contribution: offer_dlc.fee_contribution,
+            cet_sig_hash_type: offer_dlc.cet_sig_hash_type,
+            cet_csv_delays: offer_dlc.cet_csv_delays,
+            cet_fee_reserve_multiplier: offer_dlc.cet_fee_reserve_multiplier,
         })
     }
 }
@@ -171,7 +179,18 @@ fn get_contract_info_and_announcements(offer_dlc: &OfferDlc) -> Result<Vec<Contr
     for contract_info in inner_contract_infos {
         let (descriptor, oracle_announcements, threshold) = match contract_info.contract_descriptor
         {
-            SerContractDescriptor::EnumeratedContractDescriptor(enumerated) => {
+            SerContractDescriptor::EnumeratedContractDescriptor(ref enumerated)
+            | SerContractDescriptor::IndexedEnumeratedContractDescriptor(ref enumerated)
+            | SerContractDescriptor::HashedEnumeratedContractDescriptor(ref enumerated) => {
+                let outcome_commitment = match contract_info.contract_descriptor {
+                    SerContractDescriptor::IndexedEnumeratedContractDescriptor(_) => {
+                        EnumOutcomeCommitment::Index
+                    }
+                    SerContractDescriptor::HashedEnumeratedContractDescriptor(_) => {
+                        EnumOutcomeCommitment::Hash
+                    }
+                    _ => EnumOutcomeCommitment::String,
+                };
                 let outcome_payouts = enumerated
                     .payouts
                     .iter()
@@ -183,7 +202,10 @@ fn get_contract_info_and_announcements(offer_dlc: &OfferDlc) -> Result<Vec<Contr
                         },
                     })
                     .collect();
-                let descriptor = ContractDescriptor::Enum(EnumDescriptor { outcome_payouts });
+                let descriptor = ContractDescriptor::Enum(EnumDescriptor {
+                    outcome_payouts,
+                    outcome_commitment,
+                });
                 let mut threshold = 1;
                 let announcements = match contract_info.oracle_info {
                     SerOracleInfo::Single(single) => vec![single.oracle_announcement],
@@ -337,9 +359,17 @@ impl From<&NumericalDescriptor> for NumericOutcomeContractDescriptor {
 impl From<&ContractDescriptor> for SerContractDescriptor {
     fn from(descriptor: &ContractDescriptor) -> SerContractDescriptor {
         match descriptor {
-            ContractDescriptor::Enum(e) => {
-                SerContractDescriptor::EnumeratedContractDescriptor(e.into())
-            }
+            ContractDescriptor::Enum(e) => match e.outcome_commitment {
+                EnumOutcomeCommitment::String => {
+                    SerContractDescriptor::EnumeratedContractDescriptor(e.into())
+                }
+                EnumOutcomeCommitment::Index => {
+                    SerContractDescriptor::IndexedEnumeratedContractDescriptor(e.into())
+                }
+                EnumOutcomeCommitment::Hash => {
+                    SerContractDescriptor::HashedEnumeratedContractDescriptor(e.into())
+                }
+            },
             ContractDescriptor::Numerical(n) => {
                 SerContractDescriptor::NumericOutcomeContractDescriptor(n.into())
             }
@@ -1,4 +1,5 @@
 //! #Error
+use crate::contract::ContractState;
 use std::fmt;
 
 /// An error code.
@@ -11,10 +12,28 @@ pub enum Error {
     IOError(std::io::Error),
     /// Some invalid parameters were provided.
     InvalidParameters(String),
-    /// An invalid state was encounter, likely to indicate a bug.
-    InvalidState,
+    /// The contract is not in a state in which the requested operation can be
+    /// performed.
+    InvalidState {
+        /// The states in which the operation can be performed.
+        expected: Vec<ContractState>,
+        /// The state in which the contract was found.
+        found: ContractState,
+    },
+    /// The funds available are not enough to cover the collateral and fees.
+    InsufficientFunds {
+        /// The amount required, in satoshis.
+        required: u64,
+        /// The amount available, in satoshis.
+        available: u64,
+    },
+    /// The oracles or their attestations do not match the ones expected by
+    /// the contract.
+    OracleMismatch,
+    /// A signature provided by the counter party is invalid.
+    CounterpartySignatureInvalid,
     /// An error occurred in the wallet component.
-    WalletError(Box<dyn std::error::Error>),
+    WalletError(Box<dyn std::error::Error + Send + Sync>),
     /// An error occurred in the blockchain component.
     BlockchainError,
     /// The storage component encountered an error.
@@ -23,6 +42,44 @@ pub enum Error {
     OracleError(String),
     /// An error occurred in the DLC library.
     DlcError(dlc::Error),
+    /// An offer was received with a temporary contract id that is already used
+    /// by another contract.
+    DuplicateTemporaryContractId,
+    /// The requested operation is not supported by the component.
+    Unsupported,
+    /// The share of the total collateral provided by the offer party is
+    /// outside of the bounds set in the manager configuration.
+    CollateralRatioOutOfBounds,
+    /// The contract id computed locally differs from the one computed by the
+    /// counter party.
+    ContractIdMismatch,
+    /// The message was not processed as a previous message of the same batch
+    /// from the same counter party could not be processed.
+    SkippedMessage,
+    /// The memory estimated to be used by the CETs of an offered contract
+    /// exceeds the budget set in the manager configuration.
+    MemoryBudgetExceeded,
+    /// The data was produced using a format version that is not supported.
+    UnsupportedVersion,
+    /// The contract was paused and cannot be processed until it is resumed.
+    ContractPaused,
+    /// No contract with the given id was found.
+    UnknownContract,
+    /// Fewer oracles than required by the contract have attested.
+    MissingAttestations,
+    /// The outcome is not covered by the payout function of the contract, so
+    /// that it can only be closed through its refund transaction.
+    OutcomeNotCovered,
+    /// The refund transaction of the contract is not valid yet.
+    RefundNotYetValid {
+        /// The time or height from which the refund transaction is valid.
+        lock_time: u32,
+    },
+    /// The output proven unspent by the blockchain provider differs from the
+    /// fund output of the contract.
+    UtxoProofMismatch,
+    /// An unexpected error, likely to indicate a bug.
+    Other(String),
 }
 
 impl fmt::Display for Error {
@@ -30,13 +87,50 @@ impl fmt::Display for Error {
         match *self {
             Error::Conversion(ref e) => write!(f, "Conversion error {}", e),
             Error::IOError(ref e) => write!(f, "IO error {}", e),
-            Error::InvalidState => write!(f, "Invalid state"),
+            Error::InvalidState {
+                ref expected,
+                found,
+            } => write!(
+                f,
+                "Invalid state, expected one of {:?} but found {:?}",
+                expected, found
+            ),
+            Error::InsufficientFunds {
+                required,
+                available,
+            } => write!(
+                f,
+                "Insufficient funds, {} sats required but {} available",
+                required, available
+            ),
+            Error::OracleMismatch => write!(f, "Oracle mismatch"),
+            Error::CounterpartySignatureInvalid => write!(f, "Invalid counter party signature"),
             Error::InvalidParameters(ref s) => write!(f, "Invalid parameters were provided: {}", s),
             Error::WalletError(ref e) => write!(f, "Wallet error {}", e),
             Error::BlockchainError => write!(f, "Blockchain error"),
             Error::StorageError(ref s) => write!(f, "Storage error {}", s),
             Error::DlcError(ref e) => write!(f, "Dlc error {}", e),
             Error::OracleError(ref s) => write!(f, "Oracle error {}", s),
+            Error::DuplicateTemporaryContractId => write!(f, "Duplicate temporary contract id"),
+            Error::Unsupported => write!(f, "Unsupported operation"),
+            Error::CollateralRatioOutOfBounds => {
+                write!(f, "Offer party collateral ratio out of bounds")
+            }
+            Error::ContractIdMismatch => write!(f, "Contract id mismatch"),
+            Error::SkippedMessage => {
+                write!(f, "Message skipped after a previous message failed")
+            }
+            Error::MemoryBudgetExceeded => write!(f, "Contract exceeds memory budget"),
+            Error::UnsupportedVersion => write!(f, "Unsupported format version"),
+            Error::ContractPaused => write!(f, "Contract is paused"),
+            Error::UnknownContract => write!(f, "Unknown contract id"),
+            Error::MissingAttestations => write!(f, "Not enough attestations"),
+            Error::OutcomeNotCovered => write!(f, "Outcome is not covered by the contract"),
+            Error::RefundNotYetValid { lock_time } => {
+                write!(f, "Refund transaction is not valid before {}", lock_time)
+            }
+            Error::UtxoProofMismatch => write!(f, "UTXO proof does not match the fund output"),
+            Error::Other(ref s) => write!(f, "Unexpected error {}", s),
         }
     }
 }
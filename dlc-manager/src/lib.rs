@@ -24,6 +24,8 @@ extern crate lightning;
 extern crate log;
 extern crate secp256k1_zkp;
 
+#[cfg(feature = "tokio")]
+pub mod async_manager;
 pub mod contract;
 mod conversion_utils;
 pub mod error;
@@ -33,7 +35,7 @@ mod utils;
 
 use bitcoin::{Address, OutPoint, Script, Transaction, TxOut, Txid};
 use contract::{offered_contract::OfferedContract, signed_contract::SignedContract, Contract};
-use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
+use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation, OracleKeyDelegation};
 use error::Error;
 use secp256k1_zkp::schnorrsig::PublicKey as SchnorrPublicKey;
 use secp256k1_zkp::{PublicKey, SecretKey};
@@ -59,11 +61,30 @@ impl Time for SystemTimeProvider {
     }
 }
 
+/// The type of addresses that a wallet can generate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AddressType {
+    /// Pay to witness public key hash (bech32) address.
+    #[default]
+    P2wpkh,
+    /// Pay to taproot (bech32m) address.
+    P2tr,
+}
+
 /// Wallet trait to provide functionalities related to generating, storing and
 /// managing bitcoin addresses and UTXOs.
 pub trait Wallet {
     /// Returns a new (unused) address.
     fn get_new_address(&self) -> Result<Address, Error>;
+    /// Returns a new (unused) address of the given type. Wallets only
+    /// generating P2WPKH addresses from [`Wallet::get_new_address`] can rely
+    /// on the default implementation.
+    fn get_new_address_of_type(&self, address_type: AddressType) -> Result<Address, Error> {
+        match address_type {
+            AddressType::P2wpkh => self.get_new_address(),
+            AddressType::P2tr => Err(Error::Unsupported),
+        }
+    }
     /// Generate a new secret key and store it in the wallet so that it can later
     /// be retrieved.
     fn get_new_secret_key(&self) -> Result<SecretKey, Error>;
@@ -78,6 +99,24 @@ pub trait Wallet {
         fee_rate: Option<u64>,
         lock_utxos: bool,
     ) -> Result<Vec<Utxo>, Error>;
+    /// Get the wallet UTXOs corresponding to the given outpoints, returning an
+    /// error if any of them is unknown or spent.
+    fn get_utxos_for_outpoints(
+        &self,
+        outpoints: &[OutPoint],
+        lock_utxos: bool,
+    ) -> Result<Vec<Utxo>, Error>;
+    /// Returns the outpoints of the UTXOs that are currently reserved, i.e.
+    /// that were locked when selected and are not spent yet. Wallets that do
+    /// not expose their reservations return [`Error::Unsupported`].
+    fn get_reserved_utxos(&self) -> Result<Vec<OutPoint>, Error> {
+        Err(Error::Unsupported)
+    }
+    /// Releases the reservation of the given UTXOs, making them available for
+    /// selection again.
+    fn unreserve_utxos(&self, _outpoints: &[OutPoint]) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
     /// Import the provided address.
     fn import_address(&self, address: &Address) -> Result<(), Error>;
     /// Get the transaction with given id.
@@ -86,12 +125,77 @@ pub trait Wallet {
     fn get_transaction_confirmations(&self, tx_id: &Txid) -> Result<u32, Error>;
 }
 
+/// Evidence that a transaction output is part of the UTXO set, as provided by
+/// a light client (e.g. using utreexo or compact block filters).
+#[derive(Clone, Debug)]
+pub struct UtxoProof {
+    /// The output that the proof commits to.
+    pub tx_out: TxOut,
+    /// The number of confirmations of the transaction including the output.
+    pub confirmations: u32,
+    /// The provider specific proof data.
+    pub proof: Vec<u8>,
+}
+
 /// Blockchain trait provides access to the bitcoin blockchain.
 pub trait Blockchain {
     /// Broadcast the given transaction to the bitcoin network.
     fn send_transaction(&self, transaction: &Transaction) -> Result<(), Error>;
     /// Returns the network currently used (mainnet, testnet or regtest).
     fn get_network(&self) -> Result<bitcoin::network::constants::Network, Error>;
+    /// Returns a verified proof that the given outpoint is unspent. Providers
+    /// that cannot produce such proofs return [`Error::Unsupported`], in which
+    /// case the wallet is used to look up transaction confirmations.
+    fn get_utxo_proof(&self, _outpoint: &OutPoint) -> Result<UtxoProof, Error> {
+        Err(Error::Unsupported)
+    }
+    /// Returns whether the given output of a known transaction was spent by a
+    /// confirmed or unconfirmed transaction. Providers that cannot tell return
+    /// [`Error::Unsupported`].
+    fn is_output_spent(&self, _outpoint: &OutPoint) -> Result<bool, Error> {
+        Err(Error::Unsupported)
+    }
+}
+
+/// The kinds of data that the [`manager::Manager`] keeps about contracts
+/// besides the contracts themselves. The value of each variant is part of the
+/// storage format and must not change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContractDataKind {
+    /// The height of the block including the fund transaction, as provided
+    /// through [`manager::Manager::notify_funding_confirmed`].
+    FundingConfirmationHeight = 1,
+    /// The time at which the fund transaction was broadcast, until it gets
+    /// confirmed.
+    FundingBroadcastTime = 2,
+    /// A CET broadcast by the manager, until it gets buried deep enough in the
+    /// chain to be safe from reorgs.
+    BroadcastCet = 3,
+    /// A wallet UTXO reserved by the manager to fund a contract, until
+    /// [`manager::Manager::reclaim_orphan_reservations`] finds that it is not
+    /// reserved anymore. It is stored under the SHA256 hash of the outpoint
+    /// instead of a contract id.
+    ReservedUtxo = 4,
+    /// A CET signed in [`manager::BroadcastMode::Manual`], until its broadcast
+    /// is reported through [`manager::Manager::notify_cet_broadcast`].
+    PendingCet = 5,
+    /// A collaborative close offer sent through
+    /// [`manager::Manager::offer_collaborative_close`], until it is accepted.
+    SentCloseOffer = 6,
+    /// A collaborative close offer received from the counter party, until it
+    /// is accepted through [`manager::Manager::accept_collaborative_close`].
+    ReceivedCloseOffer = 7,
+    /// A sign message received before the accept message it responds to was
+    /// stored, see [`manager::ManagerConfig::buffer_early_sign_messages`],
+    /// until it is applied or expires. It is stored under the SHA256 hash of
+    /// the public key of the sender and the contract id instead of a contract
+    /// id.
+    EarlySignMessage = 8,
+    /// An oracle attestation validated by the manager, until no signed or
+    /// confirmed contract refers to its event anymore. It is stored under the
+    /// SHA256 hash of the oracle public key and event id instead of a
+    /// contract id.
+    ValidatedAttestation = 9,
 }
 
 /// Storage trait provides functionalities to store and retrieve DLCs.
@@ -112,6 +216,62 @@ pub trait Storage {
     fn get_signed_contracts(&self) -> Result<Vec<SignedContract>, Error>;
     /// Returns the set of confirmed contracts.
     fn get_confirmed_contracts(&self) -> Result<Vec<SignedContract>, Error>;
+    /// Stores the given serialized data of the given kind for the contract
+    /// with given id, replacing any previous one. Providers that do not
+    /// support it return [`Error::Unsupported`], in which case the data is
+    /// only kept in memory by the manager.
+    fn upsert_contract_data(
+        &mut self,
+        _kind: ContractDataKind,
+        _id: &ContractId,
+        _data: &[u8],
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+    /// Deletes the data of the given kind for the contract with given id if
+    /// any.
+    fn delete_contract_data(
+        &mut self,
+        _kind: ContractDataKind,
+        _id: &ContractId,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Returns the data of the given kind of every contract for which some
+    /// was stored.
+    fn get_contract_data(
+        &self,
+        _kind: ContractDataKind,
+    ) -> Result<Vec<(ContractId, Vec<u8>)>, Error> {
+        Ok(Vec::new())
+    }
+    /// Returns once all previous writes are durable. Providers that persist
+    /// writes synchronously can rely on the default implementation.
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Starts a batch of writes, that are all applied at once by
+    /// [`Storage::commit_batch`]. Writes of the batch are visible to reads
+    /// made before it is committed. Providers that write directly can rely on
+    /// the default implementation.
+    fn begin_batch(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Atomically applies the writes made since [`Storage::begin_batch`] and
+    /// returns once they are durable. The default implementation flushes the
+    /// provider.
+    fn commit_batch(&mut self) -> Result<(), Error> {
+        self.flush()
+    }
+    /// Sets whether the contract with given id is paused. Providers that do
+    /// not support pausing contracts return [`Error::Unsupported`].
+    fn set_contract_paused(&mut self, _id: &ContractId, _paused: bool) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+    /// Returns the ids of the paused contracts.
+    fn get_paused_contracts(&self) -> Result<Vec<ContractId>, Error> {
+        Ok(Vec::new())
+    }
 }
 
 /// Oracle trait provides access to oracle information.
@@ -122,6 +282,13 @@ pub trait Oracle {
     fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, Error>;
     /// Returns the attestation for the event with the given id if found.
     fn get_attestation(&self, event_id: &str) -> Result<OracleAttestation, Error>;
+    /// Returns the delegation from the key returned by
+    /// [`Oracle::get_public_key`] to the key signing the announcements and
+    /// attestations of the oracle, for oracles not signing them with their
+    /// public key directly.
+    fn get_key_delegation(&self) -> Option<OracleKeyDelegation> {
+        None
+    }
 }
 
 /// Represents a UTXO.
@@ -135,4 +302,7 @@ pub struct Utxo {
     pub address: Address,
     /// The redeem script for the referenced output.
     pub redeem_script: Script,
+    /// The maximum length of the witness required to spend the referenced
+    /// output, used to compute the fee of the funding transaction.
+    pub max_witness_len: usize,
 }
@@ -1,38 +1,631 @@
 //! #Manager a component to create and update DLCs.
 
-use super::{Blockchain, Oracle, Storage, Time, Wallet};
+use super::{AddressType, Blockchain, ContractDataKind, Oracle, Storage, Time, Utxo, Wallet};
+use crate::contract::ser::Serializable;
 use crate::contract::{
     accepted_contract::AcceptedContract, contract_info::ContractInfo,
     contract_input::ContractInput, contract_input::ContractInputInfo, contract_input::OracleInput,
-    offered_contract::OfferedContract, signed_contract::SignedContract, AdaptorInfo,
-    ClosedContract, Contract, FailedAcceptContract, FailedSignContract, FundingInputInfo,
+    numerical_descriptor::NumericalDescriptor, offered_contract::OfferedContract,
+    signed_contract::SignedContract, AdaptorInfo, ClosedContract, CollaborativelyClosedContract,
+    Contract, ContractDescriptor, ContractState, FailedAcceptContract, FailedSignContract,
+    FundingInputInfo,
 };
 use crate::conversion_utils::get_tx_input_infos;
 use crate::error::Error;
 use crate::ContractId;
 use bitcoin::{
     consensus::{Decodable, Encodable},
-    Address, Transaction,
+    Address, OutPoint, Script, SigHashType, Transaction, TxIn, TxOut, Txid,
+};
+use dlc::{
+    DlcTransactions, DlcTransactionsParams, FeeContribution, PartyParams, Payout, SigHashParams,
+    TxInputInfo,
+};
+use dlc_messages::oracle_msgs::{
+    DelegatedOracleAnnouncement, EventDescriptor, OracleAnnouncement, OracleAttestation,
+    OracleKeyDelegation,
 };
-use dlc::{DlcTransactions, PartyParams, TxInputInfo};
-use dlc_messages::oracle_msgs::{OracleAnnouncement, OracleAttestation};
 use dlc_messages::{
-    AcceptDlc, FundingInput, FundingSignature, FundingSignatures, Message as DlcMessage, OfferDlc,
-    SignDlc, WitnessElement,
+    AcceptDlc, CollaborativeCloseAccept, CollaborativeCloseOffer, FundingInput, FundingSignature,
+    FundingSignatures, Message as DlcMessage, NegotiationFields, OfferDlc, OfferReject,
+    OracleNegotiationFields, SignDlc, WitnessElement,
 };
+use dlc_trie::digit_decomposition::decompose_value;
+use lightning::ln::msgs::DecodeError;
+use lightning::util::ser::{Readable, Writeable, Writer};
 use log::{error, warn};
+use secp256k1_zkp::bitcoin_hashes::{sha256, Hash, HashEngine};
 use secp256k1_zkp::rand::{thread_rng, RngCore};
 use secp256k1_zkp::schnorrsig::{PublicKey as SchnorrPublicKey, Signature as SchnorrSignature};
 use secp256k1_zkp::EcdsaAdaptorSignature;
-use secp256k1_zkp::{All, PublicKey, Secp256k1, SecretKey};
-use std::collections::HashMap;
+use secp256k1_zkp::{All, Message, PublicKey, Secp256k1, SecretKey, Signature, Verification};
+use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
 use std::string::ToString;
+use std::time::Duration;
+
+pub use dlc::CetCsvDelays;
 
 /// The number of confirmations required before moving the the confirmed state.
 pub const NB_CONFIRMATIONS: u32 = 6;
 /// The delay to set the refund value to.
 pub const REFUND_DELAY: u32 = 86400 * 7;
+/// The maximum number of sign messages buffered for a single counter party,
+/// see [`ManagerConfig::buffer_early_sign_messages`].
+pub const MAX_EARLY_SIGN_MESSAGES_PER_COUNTER_PARTY: usize = 10;
+/// The maximum number of sign messages buffered for all counter parties, see
+/// [`ManagerConfig::buffer_early_sign_messages`].
+pub const MAX_EARLY_SIGN_MESSAGES: usize = 1000;
+/// The number of seconds after which a buffered sign message that could not
+/// be applied is dropped, see [`ManagerConfig::buffer_early_sign_messages`].
+pub const EARLY_SIGN_MESSAGE_TIMEOUT: u64 = 3600;
+/// Number of bytes by which an ECDSA signature can exceed the length assumed
+/// by the usual maximum witness sizes, when its R value is not low.
+const MAX_SIG_LEN_MARGIN: usize = 2;
+/// The version of the format used by [`Manager::export_state`]. Version 3
+/// has the same layout as version 2 but may include contracts with a fee
+/// contribution, CET sighash type, CET CSV delays or CET fee reserve
+/// multiplier, which previous versions of the library cannot read.
+pub const STATE_FORMAT_VERSION: u8 = 3;
+/// The kinds of contract data included in the state exported by
+/// [`Manager::export_state`].
+const CONTRACT_DATA_KINDS: [ContractDataKind; 9] = [
+    ContractDataKind::FundingConfirmationHeight,
+    ContractDataKind::FundingBroadcastTime,
+    ContractDataKind::BroadcastCet,
+    ContractDataKind::PendingCet,
+    ContractDataKind::SentCloseOffer,
+    ContractDataKind::ReceivedCloseOffer,
+    ContractDataKind::EarlySignMessage,
+    ContractDataKind::ValidatedAttestation,
+    ContractDataKind::ReservedUtxo,
+];
+
+/// Configuration parameters of a [`Manager`].
+#[derive(Clone, Debug, Default)]
+pub struct ManagerConfig {
+    /// The time to wait after the refund transaction becomes valid before
+    /// broadcasting it, leaving a chance to close the contract using a late
+    /// oracle attestation.
+    pub refund_grace_period: Duration,
+    /// The type of the addresses generated to receive CET and refund payouts.
+    pub payout_address_type: AddressType,
+    /// If set, the CET outputs of each party of offered contracts can only be
+    /// claimed using its fund public key once the party's delay has elapsed
+    /// since the CET confirmation, see [`dlc::create_csv_gated_cets`]. The
+    /// refund transaction is not affected. As the wallet does not watch these
+    /// outputs, they must be swept using the witness script returned by
+    /// [`dlc::make_csv_payout_script`], and cannot be used as funding inputs.
+    /// [`Contract::net_payout_after_close`] does not recognize them. The
+    /// delays are part of the offer message, and contracts use the delays of
+    /// their offer regardless of the value set by the accepting party. Offers
+    /// with delays not passing [`CetCsvDelays::validate`] are rejected.
+    pub cet_csv_delays: Option<CetCsvDelays>,
+    /// If set, [`Manager::periodic_check`] removes the oracle announcements of
+    /// the contracts that were closed, refunded, rejected or failed once their
+    /// events matured this long ago, see
+    /// [`Manager::prune_expired_announcements`].
+    pub announcement_expiry: Option<Duration>,
+    /// If set, received offers are only accepted if the share of the total
+    /// collateral provided by the offer party is within these bounds.
+    pub offer_collateral_ratio_bounds: Option<CollateralRatioBounds>,
+    /// How CETs are broadcast when closing contracts.
+    pub cet_broadcast_mode: BroadcastMode,
+    /// How outcomes outside of the range covered by the payout function of
+    /// offered numerical contracts are resolved.
+    pub out_of_range_policy: OutOfRangePolicy,
+    /// If set, contracts for which fewer oracles than required attested by
+    /// this long after their maturity are refunded as soon as their refund
+    /// transaction becomes valid, without waiting for the refund grace period.
+    /// The deadline must end before the grace period after the refund
+    /// locktime does for it to have any effect, so offering or accepting
+    /// contracts for which it does not is rejected.
+    pub attestation_deadline: Option<Duration>,
+    /// If set, received offers are only accepted if the memory used by their
+    /// CETs, as estimated by summing [`Contract::estimate_memory_footprint`]
+    /// over their contract infos, is at most this number of bytes.
+    pub max_contract_memory: Option<usize>,
+    /// The sighash type of the CET signatures offered to the counter party,
+    /// `ALL` if not set. Only `ALL` and `ALL|ANYONECANPAY` are accepted, see
+    /// [`dlc::validate_cet_sig_hash_type`], and sending offers fails with any
+    /// other value. The type is part of the offer message, and contracts use
+    /// the type of their offer regardless of the value set by the accepting
+    /// party. Offers with any other type are rejected.
+    pub cet_sig_hash_type: Option<SigHashType>,
+    /// The sighash type of the signatures of the own funding inputs, and of
+    /// the input of CPFP child transactions, `ALL` if not set. As any other
+    /// type would let the fund transaction be modified, changing its txid and
+    /// invalidating the CETs and refund transaction, only `ALL` is accepted,
+    /// see [`dlc::validate_funding_sig_hash_type`].
+    pub funding_sig_hash_type: Option<SigHashType>,
+    /// If set, sign messages received for a contract that is not known yet,
+    /// which happens when they get processed before the accept message they
+    /// respond to is stored, are kept instead of being rejected, and applied
+    /// once the contract gets accepted by the sender of the message. They are
+    /// kept in the storage provider if it supports
+    /// [`Storage::upsert_contract_data`], and in memory only otherwise. At
+    /// most [`MAX_EARLY_SIGN_MESSAGES_PER_COUNTER_PARTY`] messages are kept
+    /// per counter party, and [`MAX_EARLY_SIGN_MESSAGES`] in total, each of
+    /// them for [`EARLY_SIGN_MESSAGE_TIMEOUT`] seconds.
+    pub buffer_early_sign_messages: bool,
+    /// If set, the fee rate of the CETs and refund transaction of offered
+    /// contracts is the fee rate of the contract multiplied by this factor,
+    /// so that they remain broadcastable if fees rise before they are used.
+    /// The fund transaction still uses the contract fee rate. The multiplier
+    /// is part of the offer message, and contracts use the multiplier of their
+    /// offer regardless of the value set by the accepting party. Offers with a
+    /// zero multiplier, or one overflowing the fee rate, are rejected.
+    pub cet_fee_reserve_multiplier: Option<u64>,
+}
+
+/// Defines how a numerical contract is resolved when the oracles attest to a
+/// value that is not covered by its payout function. The policy is applied
+/// when offering contracts, the accepting party agreeing to it by accepting
+/// the offer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfRangePolicy {
+    /// The payout function is extended to cover all the non-negative values
+    /// that can be attested, out of range values getting the payout of the
+    /// closest covered value. As negative values cannot be covered, offering
+    /// contracts on signed events is rejected under this policy.
+    Clamp,
+    /// No CET is created for out of range values, including the negative
+    /// values of signed events, and the contract gets closed through its
+    /// refund transaction once its locktime has passed.
+    #[default]
+    Refund,
+}
+
+/// Defines who is responsible for broadcasting CETs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BroadcastMode {
+    /// The manager broadcasts CETs through its blockchain provider.
+    #[default]
+    Automatic,
+    /// Signed CETs are handed over through [`ManagerEvent::CetReadyForBroadcast`]
+    /// for an external service to broadcast them, which must then be reported
+    /// using [`Manager::notify_cet_broadcast`].
+    Manual,
+}
+
+/// Overrides of the transaction parameters derived from a [`ContractInput`]
+/// when offering a contract through [`Manager::send_offer_with_options`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OfferOptions {
+    /// The fee rate used to construct the transactions, in satoshis per
+    /// virtual byte. Defaults to the fee rate of the contract input.
+    pub fee_rate_sat_per_vb: Option<u64>,
+    /// The locktime of the CETs. Defaults to the maturity time of the contract
+    /// input.
+    pub cet_locktime: Option<u32>,
+    /// The locktime of the refund transaction. Defaults to [`REFUND_DELAY`]
+    /// after the maturity time of the contract input.
+    pub refund_locktime: Option<u32>,
+    /// How the fees of the transactions are split between the parties.
+    /// Defaults to [`FeeContribution::Equal`].
+    pub fee_contribution: FeeContribution,
+}
+
+/// Inclusive bounds on the ratio of the offer party collateral over the total
+/// collateral of a contract, e.g. a `min` of 0.1 requires the offer party to
+/// provide at least 10% of the total collateral.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollateralRatioBounds {
+    /// The minimum ratio.
+    pub min: f64,
+    /// The maximum ratio.
+    pub max: f64,
+}
+
+impl CollateralRatioBounds {
+    /// Returns whether the given collaterals are within the bounds.
+    pub fn contains(&self, offer_collateral: u64, total_collateral: u64) -> bool {
+        if total_collateral == 0 {
+            return false;
+        }
+        let ratio = offer_collateral as f64 / total_collateral as f64;
+        self.min <= ratio && ratio <= self.max
+    }
+}
+
+/// Events generated by the [`Manager`] that might require an action from the
+/// user.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ManagerEvent {
+    /// The event of a contract has matured but the attestation of the oracle
+    /// could not be retrieved.
+    AttestationMissing {
+        /// The id of the contract waiting for the attestation.
+        contract_id: ContractId,
+        /// The id of the event for which the attestation is missing.
+        event_id: String,
+        /// The public key of the oracle expected to provide the attestation.
+        oracle: SchnorrPublicKey,
+    },
+    /// A CET was signed in [`BroadcastMode::Manual`] and is waiting to be
+    /// broadcast externally.
+    CetReadyForBroadcast {
+        /// The id of the contract being closed.
+        contract_id: ContractId,
+        /// The fully signed CET.
+        cet: Transaction,
+    },
+}
+
+/// An event outcome, as it would be attested by oracles.
+#[derive(Clone, Debug)]
+pub enum Outcome {
+    /// The outcome of an enumeration event.
+    Enum(String),
+    /// The value of the outcome of a numerical event.
+    Numerical(usize),
+}
+
+/// The result of simulating the closing of a contract with a given outcome.
+#[derive(Clone, Debug)]
+pub struct SimulatedClose {
+    /// The CET that would be broadcast, without its witness.
+    pub cet: Transaction,
+    /// The amount that the offer party would receive.
+    pub offer_payout: u64,
+    /// The amount that the accept party would receive.
+    pub accept_payout: u64,
+}
+/// A function called with the oracle public key and event id of each
+/// attestation validated by the manager.
+type AttestationValidatedHook = Box<dyn Fn(&SchnorrPublicKey, &str) + Send + Sync>;
+
+/// The attestation status of one of the oracles of a contract, as returned by
+/// [`Manager::get_contract_oracle_status`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OracleStatus {
+    /// The public key of the oracle.
+    pub oracle_public_key: SchnorrPublicKey,
+    /// The id of the event announced by the oracle.
+    pub event_id: String,
+    /// The outcomes attested by the oracle, or `None` if no attestation could
+    /// be fetched.
+    pub outcomes: Option<Vec<String>>,
+}
+
+impl OracleStatus {
+    /// Returns whether an attestation was fetched from the oracle.
+    pub fn is_attested(&self) -> bool {
+        self.outcomes.is_some()
+    }
+}
+
+/// The CETs of a contract together with the signatures exchanged to set it
+/// up, as returned by [`Manager::get_contract_signatures`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractSignatures {
+    /// The CETs of the contract, in the order of the adaptor signatures.
+    pub cets: Vec<Transaction>,
+    /// The adaptor signatures of the local party for each CET.
+    pub local_adaptor_signatures: Vec<EcdsaAdaptorSignature>,
+    /// The adaptor signatures of the counter party for each CET, if they were
+    /// received.
+    pub counter_party_adaptor_signatures: Option<Vec<EcdsaAdaptorSignature>>,
+    /// The signature of the refund transaction of the local party.
+    pub local_refund_signature: Signature,
+    /// The signature of the refund transaction of the counter party, if it was
+    /// received.
+    pub counter_party_refund_signature: Option<Signature>,
+}
+
+/// An action taken by the [`Manager`] during a [`Manager::periodic_check`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ManagerAction {
+    /// The fund transaction of the contract reached [`NB_CONFIRMATIONS`]
+    /// confirmations.
+    ContractConfirmed(ContractId),
+    /// The contract was closed using the CET matching the oracle attestations.
+    ClosedByOutcome {
+        /// The id of the closed contract.
+        contract_id: ContractId,
+        /// The id of the CET used to close the contract.
+        txid: Txid,
+    },
+    /// The refund locktime of the contract expired and its refund transaction
+    /// was broadcast, or was found already broadcast.
+    RefundBroadcast(ContractId),
+}
+
+/// Why the [`Manager`] monitors the confirmations of a transaction, as
+/// returned by [`Manager::watched_transactions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchReason {
+    /// The fund transaction of a signed contract, until it reaches
+    /// [`NB_CONFIRMATIONS`] confirmations.
+    Funding,
+    /// A CET broadcast by the manager, until it reaches [`NB_CONFIRMATIONS`]
+    /// confirmations.
+    Cet,
+    /// The refund transaction of a confirmed contract, whose confirmation is
+    /// checked once its lock time passes.
+    Refund,
+}
+
+/// The contract state changes applied by [`Manager::reconcile_with_chain`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReconcileReport {
+    /// The contracts whose fund transaction was found to be confirmed.
+    pub confirmed: Vec<ContractId>,
+    /// The contracts for which a CET was found to be confirmed.
+    pub closed: Vec<ContractId>,
+    /// The contracts for which the refund transaction was found to be confirmed.
+    pub refunded: Vec<ContractId>,
+}
+
+/// The outcome of a [`Manager::merge_from`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MergeReport {
+    /// The contracts that were imported.
+    pub imported: Vec<ContractId>,
+    /// The contracts that were not imported because a contract with the same
+    /// id or temporary id was already stored.
+    pub collisions: Vec<ContractId>,
+}
+
+/// Selects the contracts returned by [`Manager::list_contracts`].
+#[derive(Clone, Debug, Default)]
+pub struct ContractStateFilter {
+    /// The states of the contracts to return, any state if empty.
+    pub states: Vec<ContractState>,
+    /// If set, only the contracts with this counter party are returned.
+    pub counter_party: Option<PublicKey>,
+    /// The number of matching contracts to skip.
+    pub offset: usize,
+    /// The maximum number of contracts to return, if any.
+    pub limit: Option<usize>,
+}
+
+/// Summary information about a contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractSummary {
+    /// The id of the contract, or its temporary id if it was not accepted.
+    pub id: ContractId,
+    /// The current state of the contract.
+    pub state: ContractState,
+    /// The public key of the counter party.
+    pub counter_party: PublicKey,
+    /// Whether the local party is the one that offered the contract.
+    pub is_offer_party: bool,
+    /// The collateral of the offer party.
+    pub offer_collateral: u64,
+    /// The collateral of the accept party.
+    pub accept_collateral: u64,
+}
+
+/// Proves control over one of the keys of the funding output of a contract.
+#[derive(Clone, Debug)]
+pub struct ReserveProofEntry {
+    /// The funding output of the contract.
+    pub outpoint: OutPoint,
+    /// The value of the funding output.
+    pub value: u64,
+    /// The 2-of-2 multisig script that the funding output pays to.
+    pub funding_script: Script,
+    /// The funding public key of the local party.
+    pub fund_pubkey: PublicKey,
+    /// A signature by `fund_pubkey` over the challenge and the outpoint.
+    pub signature: Signature,
+}
+
+/// A proof that the local party controls funds locked in DLCs, generated by
+/// [`Manager::prove_reserves`].
+#[derive(Clone, Debug)]
+pub struct ReserveProof {
+    /// One entry per active contract.
+    pub entries: Vec<ReserveProofEntry>,
+}
+
+impl ReserveProof {
+    /// Returns the total value of the funding outputs included in the proof.
+    pub fn total_value(&self) -> u64 {
+        self.entries.iter().map(|x| x.value).sum()
+    }
+
+    /// Verifies that each entry is signed over the given challenge by one of
+    /// the keys of its funding script. Note that the verifier should also check
+    /// on chain that each outpoint is unspent, has the given value and pays to
+    /// the P2WSH of the given funding script.
+    pub fn verify<C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+        challenge: &[u8],
+    ) -> Result<(), Error> {
+        for entry in &self.entries {
+            let serialized_pubkey = entry.fund_pubkey.serialize();
+            if !entry
+                .funding_script
+                .as_bytes()
+                .windows(serialized_pubkey.len())
+                .any(|x| x == serialized_pubkey)
+            {
+                return Err(Error::InvalidParameters(format!(
+                    "Public key is not part of the funding script of {}",
+                    entry.outpoint
+                )));
+            }
+            let msg = get_reserve_proof_message(challenge, &entry.outpoint);
+            secp.verify(&msg, &entry.signature, &entry.fund_pubkey)
+                .map_err(|_| Error::CounterpartySignatureInvalid)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns an identifier of the state of the given contract along with its
+/// serialization.
+fn serialize_contract(contract: &Contract) -> Result<(u8, Vec<u8>), Error> {
+    Ok(match contract {
+        Contract::Offered(o) => (0u8, o.serialize()?),
+        Contract::Accepted(a) => (1, a.serialize()?),
+        Contract::Signed(s) => (2, s.serialize()?),
+        Contract::Confirmed(s) => (3, s.serialize()?),
+        Contract::Closed(c) => (4, c.serialize()?),
+        Contract::Refunded(s) => (5, s.serialize()?),
+        Contract::FailedAccept(c) => (6, c.serialize()?),
+        Contract::FailedSign(c) => (7, c.serialize()?),
+        Contract::Rejected(o) => (8, o.serialize()?),
+        Contract::CollaborativelyClosed(c) => (9, c.serialize()?),
+    })
+}
+
+/// Inverse of [`serialize_contract`].
+fn deserialize_contract(state: u8, data: &[u8]) -> Result<Contract, Error> {
+    fn read<T: Serializable>(data: &[u8]) -> Result<T, Error> {
+        T::deserialize(&mut ::std::io::Cursor::new(data))
+            .map_err(|_| Error::InvalidParameters("Could not deserialize contract.".to_string()))
+    }
+
+    let contract = match state {
+        0 => Contract::Offered(read(data)?),
+        1 => Contract::Accepted(read(data)?),
+        2 => Contract::Signed(read(data)?),
+        3 => Contract::Confirmed(read(data)?),
+        4 => Contract::Closed(read(data)?),
+        5 => Contract::Refunded(read(data)?),
+        6 => Contract::FailedAccept(read(data)?),
+        7 => Contract::FailedSign(read(data)?),
+        8 => Contract::Rejected(read(data)?),
+        9 => Contract::CollaborativelyClosed(read(data)?),
+        _ => {
+            return Err(Error::InvalidParameters(
+                "Unknown contract state.".to_string(),
+            ))
+        }
+    };
+    Ok(contract)
+}
+
+/// Maps an error returned while verifying signatures of the counter party,
+/// where secp256k1 errors indicate an invalid signature.
+fn signature_verification_error(e: dlc::Error) -> Error {
+    match e {
+        dlc::Error::Secp256k1(_) => Error::CounterpartySignatureInvalid,
+        e => e.into(),
+    }
+}
+
+/// Checks that the given sighash type can be used for CET signatures, see
+/// [`dlc::validate_cet_sig_hash_type`].
+fn validate_cet_sig_hash_type(sig_hash_type: SigHashType) -> Result<(), Error> {
+    dlc::validate_cet_sig_hash_type(sig_hash_type).map_err(|_| {
+        Error::InvalidParameters(format!(
+            "Sighash type {} cannot be used for CET signatures.",
+            sig_hash_type
+        ))
+    })
+}
+
+fn get_reserve_proof_message(challenge: &[u8], outpoint: &OutPoint) -> Message {
+    let mut data = challenge.to_vec();
+    data.extend_from_slice(&outpoint.txid[..]);
+    data.extend_from_slice(&outpoint.vout.to_le_bytes());
+    Message::from_hashed_data::<sha256::Hash>(&data)
+}
+
+/// Restricts the oracles of the given contract to the one with the given public
+/// key, which must be part of the announcements of every contract info, each of
+/// them requiring a single oracle attestation.
+fn select_oracle(
+    offered_contract: &mut OfferedContract,
+    oracle_public_key: &SchnorrPublicKey,
+) -> Result<(), Error> {
+    for contract_info in offered_contract.contract_info.iter_mut() {
+        if contract_info.threshold != 1 {
+            return Err(Error::InvalidParameters(
+                "An oracle can only be selected for contracts with a threshold of one.".to_string(),
+            ));
+        }
+        let announcement = contract_info
+            .oracle_announcements
+            .iter()
+            .find(|x| &x.oracle_public_key == oracle_public_key)
+            .ok_or(Error::OracleMismatch)?
+            .clone();
+        contract_info.oracle_announcements = vec![announcement];
+    }
+
+    Ok(())
+}
+
+/// A CET broadcast by the manager that is not yet buried deep enough in the
+/// chain to be safe from reorgs.
+struct BroadcastCet {
+    cet: Transaction,
+    seen_in_chain: bool,
+}
+
+impl_dlc_writeable!(BroadcastCet, { (cet, writeable), (seen_in_chain, writeable) });
+
+/// A CET signed in [`BroadcastMode::Manual`] that is waiting for its broadcast
+/// to be reported, along with the contract to store once it is.
+struct PendingCet {
+    cet: Transaction,
+    closed_contract: ClosedContract,
+}
+
+impl_dlc_writeable!(PendingCet, { (cet, writeable), (closed_contract, writeable) });
+
+/// A sign message received before the accept message it responds to was
+/// stored, see [`ManagerConfig::buffer_early_sign_messages`].
+struct EarlySignMessage {
+    counter_party: PublicKey,
+    sign_message: SignDlc,
+    received_time: u64,
+}
+
+impl_dlc_writeable!(EarlySignMessage, {
+    (counter_party, writeable),
+    (sign_message, writeable),
+    (received_time, writeable)
+});
+
+/// An attestation validated by the manager, along with the id of the event it
+/// attests to.
+struct CachedAttestation {
+    event_id: String,
+    attestation: OracleAttestation,
+}
+
+impl_dlc_writeable!(CachedAttestation, { (event_id, string), (attestation, writeable) });
+
+/// Returns the key under which the attestation of the given oracle for the
+/// given event is stored, see [`ContractDataKind::ValidatedAttestation`].
+fn get_attestation_key(oracle_public_key: &SchnorrPublicKey, event_id: &str) -> ContractId {
+    let mut engine = sha256::Hash::engine();
+    engine.input(&oracle_public_key.serialize());
+    engine.input(event_id.as_bytes());
+    sha256::Hash::from_engine(engine).into_inner()
+}
+
+/// Serializes the values of the given map as the contract data of the given
+/// kind, adding them to `res`.
+fn serialize_contract_data<V: Serializable>(
+    kind: ContractDataKind,
+    values: &HashMap<ContractId, V>,
+    res: &mut Vec<(ContractDataKind, ContractId, Vec<u8>)>,
+) -> Result<(), Error> {
+    for (id, value) in values {
+        res.push((kind, *id, value.serialize()?));
+    }
+    Ok(())
+}
+
+/// The outputs used to fund the collateral of a party.
+enum FundingSource<'a> {
+    /// UTXOs selected by the wallet.
+    Wallet,
+    /// The given wallet UTXOs.
+    Outpoints(&'a [OutPoint]),
+    /// The payout outputs of the party in the settlement transaction of the
+    /// closed contract with given id.
+    Renewal(&'a ContractId),
+}
 
 /// Used to create and update DLCs.
 pub struct Manager<W: Deref, B: Deref, S: DerefMut, O: Deref, T: Deref>
@@ -49,6 +642,164 @@ where
     store: S,
     secp: Secp256k1<All>,
     time: T,
+    config: ManagerConfig,
+    pending_events: Vec<ManagerEvent>,
+    missing_attestations: HashSet<(ContractId, SchnorrPublicKey, String)>,
+    attestations: HashMap<(SchnorrPublicKey, String), OracleAttestation>,
+    funding_confirmation_heights: HashMap<ContractId, u32>,
+    funding_broadcast_times: HashMap<ContractId, u64>,
+    attestation_validated_hook: Option<AttestationValidatedHook>,
+    broadcast_cets: HashMap<ContractId, BroadcastCet>,
+    reserved_utxos: HashMap<ContractId, OutPoint>,
+    pending_cets: HashMap<ContractId, PendingCet>,
+    sent_close_offers: HashMap<ContractId, CollaborativeCloseOffer>,
+    received_close_offers: HashMap<ContractId, CollaborativeCloseOffer>,
+    early_sign_messages: HashMap<(PublicKey, ContractId), EarlySignMessage>,
+}
+
+/// Reads the data of the given kind that was stored for contracts. Entries that
+/// cannot be read are logged and skipped so that the manager can still start.
+fn load_contract_data<S: Deref, V: Serializable>(
+    store: &S,
+    kind: ContractDataKind,
+) -> HashMap<ContractId, V>
+where
+    S::Target: Storage,
+{
+    let entries = match store.get_contract_data(kind) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("Error reading {:?} contract data: {}", kind, e);
+            return HashMap::new();
+        }
+    };
+    entries
+        .into_iter()
+        .filter_map(|(contract_id, data)| {
+            match V::deserialize(&mut ::std::io::Cursor::new(&data)) {
+                Ok(value) => Some((contract_id, value)),
+                Err(e) => {
+                    error!("Error deserializing {:?} contract data: {:?}", kind, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the key under which the sign message sent by the given counter
+/// party for the contract with given id is stored, see
+/// [`ContractDataKind::EarlySignMessage`].
+fn get_early_sign_message_key(counter_party: &PublicKey, contract_id: &ContractId) -> ContractId {
+    let mut engine = sha256::Hash::engine();
+    engine.input(&counter_party.serialize());
+    engine.input(contract_id);
+    sha256::Hash::from_engine(engine).into_inner()
+}
+
+/// Returns the key under which the given UTXO reserved by the manager is
+/// stored, see [`ContractDataKind::ReservedUtxo`].
+fn get_reserved_utxo_key(outpoint: &OutPoint) -> ContractId {
+    let mut engine = sha256::Hash::engine();
+    engine.input(&outpoint.txid);
+    engine.input(&outpoint.vout.to_be_bytes());
+    sha256::Hash::from_engine(engine).into_inner()
+}
+
+/// Creates the CETs of the given offered contract spending the given input for
+/// the given payouts, gating their outputs as set by its CSV delays.
+fn create_cets(
+    cet_input: &TxIn,
+    offered_contract: &OfferedContract,
+    accept_params: &PartyParams,
+    payouts: &[Payout],
+    lock_time: u32,
+) -> Vec<Transaction> {
+    let offer_params = &offered_contract.offer_params;
+    match &offered_contract.cet_csv_delays {
+        Some(csv_delays) => dlc::create_csv_gated_cets(
+            cet_input,
+            offer_params,
+            accept_params,
+            csv_delays,
+            payouts,
+            lock_time,
+        ),
+        None => dlc::create_cets(
+            cet_input,
+            &offer_params.payout_script_pubkey,
+            offer_params.payout_serial_id,
+            &accept_params.payout_script_pubkey,
+            accept_params.payout_serial_id,
+            payouts,
+            lock_time,
+        ),
+    }
+}
+
+/// Returns the script pubkeys of the CET outputs of the offer and accept
+/// parties of the given offered contract, gated as set by its CSV delays.
+fn get_cet_payout_script_pubkeys(
+    offered_contract: &OfferedContract,
+    accept_params: &PartyParams,
+) -> (Script, Script) {
+    let offer_params = &offered_contract.offer_params;
+    match &offered_contract.cet_csv_delays {
+        Some(csv_delays) => csv_delays.get_payout_script_pubkeys(offer_params, accept_params),
+        None => (
+            offer_params.payout_script_pubkey.clone(),
+            accept_params.payout_script_pubkey.clone(),
+        ),
+    }
+}
+
+/// Returns the fee rate of the CETs and refund transaction of contracts using
+/// the given fee rate and fee reserve multiplier, see
+/// [`ManagerConfig::cet_fee_reserve_multiplier`].
+fn get_cet_fee_rate(fee_rate: u64, cet_fee_reserve_multiplier: Option<u64>) -> Result<u64, Error> {
+    let multiplier = cet_fee_reserve_multiplier.unwrap_or(1);
+    fee_rate
+        .checked_mul(multiplier)
+        .filter(|_| multiplier > 0)
+        .ok_or_else(|| {
+            Error::InvalidParameters(format!(
+                "CET fee reserve multiplier {} cannot be applied to fee rate {}.",
+                multiplier, fee_rate
+            ))
+        })
+}
+
+/// Returns the parameters of the DLC transactions of the given offered
+/// contract.
+fn get_dlc_transactions_params(
+    offered_contract: &OfferedContract,
+) -> Result<DlcTransactionsParams, Error> {
+    Ok(DlcTransactionsParams {
+        refund_lock_time: offered_contract.contract_timeout,
+        fee_rate_per_vb: offered_contract.fee_rate_per_vb,
+        cet_fee_rate_per_vb: get_cet_fee_rate(
+            offered_contract.fee_rate_per_vb,
+            offered_contract.cet_fee_reserve_multiplier,
+        )?,
+        fee_contribution: offered_contract.fee_contribution,
+        fund_lock_time: 0,
+        cet_lock_time: offered_contract.contract_maturity_bound,
+        fund_output_serial_id: offered_contract.fund_output_serial_id,
+    })
+}
+
+/// Checks that the given CET CSV delays can be used, see
+/// [`CetCsvDelays::validate`].
+fn validate_cet_csv_delays(csv_delays: &Option<CetCsvDelays>) -> Result<(), Error> {
+    match csv_delays {
+        Some(delays) => delays.validate().map_err(|_| {
+            Error::InvalidParameters(format!(
+                "CET CSV delays {} and {} must be between 1 and 65535 blocks.",
+                delays.offer, delays.accept
+            ))
+        }),
+        None => Ok(()),
+    }
 }
 
 impl<W: Deref, B: Deref, S: DerefMut, O: Deref, T: Deref> Manager<W, B, S, O, T>
@@ -67,6 +818,65 @@ where
         oracles: HashMap<SchnorrPublicKey, O>,
         time: T,
     ) -> Self {
+        Self::new_with_config(
+            wallet,
+            blockchain,
+            store,
+            oracles,
+            time,
+            ManagerConfig::default(),
+        )
+    }
+
+    /// Create a new Manager struct using the given configuration.
+    pub fn new_with_config(
+        wallet: W,
+        blockchain: B,
+        store: S,
+        oracles: HashMap<SchnorrPublicKey, O>,
+        time: T,
+        config: ManagerConfig,
+    ) -> Self {
+        let funding_confirmation_heights =
+            load_contract_data(&store, ContractDataKind::FundingConfirmationHeight);
+        let funding_broadcast_times =
+            load_contract_data(&store, ContractDataKind::FundingBroadcastTime);
+        let broadcast_cets = load_contract_data(&store, ContractDataKind::BroadcastCet);
+        let reserved_utxos = load_contract_data(&store, ContractDataKind::ReservedUtxo);
+        let pending_cets: HashMap<ContractId, PendingCet> =
+            load_contract_data(&store, ContractDataKind::PendingCet);
+        let sent_close_offers = load_contract_data(&store, ContractDataKind::SentCloseOffer);
+        let received_close_offers =
+            load_contract_data(&store, ContractDataKind::ReceivedCloseOffer);
+        let buffered_sign_messages: HashMap<ContractId, EarlySignMessage> =
+            load_contract_data(&store, ContractDataKind::EarlySignMessage);
+        let early_sign_messages = buffered_sign_messages
+            .into_values()
+            .map(|buffered| {
+                let key = (buffered.counter_party, buffered.sign_message.contract_id);
+                (key, buffered)
+            })
+            .collect();
+        let cached_attestations: HashMap<ContractId, CachedAttestation> =
+            load_contract_data(&store, ContractDataKind::ValidatedAttestation);
+        let attestations = cached_attestations
+            .into_values()
+            .map(|cached| {
+                let key = (cached.attestation.oracle_public_key, cached.event_id);
+                (key, cached.attestation)
+            })
+            .collect();
+        // Hand over the CETs again in case the events were not processed
+        // before the manager stopped.
+        let pending_events = pending_cets
+            .iter()
+            .map(
+                |(contract_id, pending)| ManagerEvent::CetReadyForBroadcast {
+                    contract_id: *contract_id,
+                    cet: pending.cet.clone(),
+                },
+            )
+            .collect();
         Manager {
             secp: secp256k1_zkp::Secp256k1::new(),
             wallet,
@@ -74,20 +884,307 @@ where
             store,
             oracles,
             time,
+            config,
+            pending_events,
+            missing_attestations: HashSet::new(),
+            attestations,
+            funding_confirmation_heights,
+            funding_broadcast_times,
+            broadcast_cets,
+            reserved_utxos,
+            pending_cets,
+            sent_close_offers,
+            received_close_offers,
+            attestation_validated_hook: None,
+            early_sign_messages,
         }
     }
 
+    /// Stops the manager, returning once the state it wrote to the storage
+    /// provider is durable. Note that the data kept only in memory when the
+    /// storage provider does not support [`Storage::upsert_contract_data`] is
+    /// dropped.
+    pub fn shutdown(mut self) -> Result<(), Error> {
+        self.store.flush()
+    }
+
     /// Get the store from the Manager to access contracts.
     pub fn get_store(&self) -> &S {
         &self.store
     }
 
+    /// Sets a function called with the oracle public key and event id of
+    /// every attestation validated by the manager. Validated attestations are
+    /// cached until all the contracts referencing their event are closed, so
+    /// the function is called once per event.
+    pub fn set_attestation_validated_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&SchnorrPublicKey, &str) + Send + Sync + 'static,
+    {
+        self.attestation_validated_hook = Some(Box::new(hook));
+    }
+
+    /// Adds the given oracle, replacing and returning any oracle previously
+    /// registered with the same public key. Contracts referring to the oracle
+    /// will use the new instance to retrieve attestations.
+    pub fn add_oracle(&mut self, oracle: O) -> Option<O> {
+        self.oracles.insert(oracle.get_public_key(), oracle)
+    }
+
+    /// Removes and returns the oracle with the given public key if any. Note
+    /// that active contracts relying on this oracle will not be able to
+    /// retrieve its attestations until it is added back.
+    pub fn remove_oracle(&mut self, public_key: &SchnorrPublicKey) -> Option<O> {
+        self.oracles.remove(public_key)
+    }
+
+    /// Returns the public keys of the registered oracles.
+    pub fn list_oracles(&self) -> Vec<SchnorrPublicKey> {
+        self.oracles.keys().cloned().collect()
+    }
+
+    /// Returns the ids of the contracts, in any state, for which at least one
+    /// of the oracle announcements refers to the event with the given id.
+    ///
+    /// The contracts are read from the storage provider, so an error is
+    /// returned if it fails rather than an empty or partial list that could
+    /// not be told apart from the absence of matching contracts.
+    pub fn contracts_for_event(&self, event_id: &str) -> Result<Vec<ContractId>, Error> {
+        Ok(self
+            .store
+            .get_contracts()?
+            .iter()
+            .filter(|c| {
+                c.get_offered_contract().contract_info.iter().any(|info| {
+                    info.oracle_announcements
+                        .iter()
+                        .any(|a| a.oracle_event.event_id == event_id)
+                })
+            })
+            .map(|c| c.get_id())
+            .collect())
+    }
+
+    /// Returns a summary of the stored contracts matching the given filter,
+    /// ordered by id so that successive pages are consistent.
+    pub fn list_contracts(
+        &self,
+        filter: ContractStateFilter,
+    ) -> Result<Vec<ContractSummary>, Error> {
+        let mut summaries: Vec<ContractSummary> = self
+            .store
+            .get_contracts()?
+            .iter()
+            .filter(|c| filter.states.is_empty() || filter.states.contains(&c.get_state()))
+            .filter(|c| {
+                filter
+                    .counter_party
+                    .is_none_or(|x| c.get_offered_contract().counter_party == x)
+            })
+            .map(|c| {
+                let offered_contract = c.get_offered_contract();
+                let offer_collateral = offered_contract.offer_params.collateral;
+                ContractSummary {
+                    id: c.get_id(),
+                    state: c.get_state(),
+                    counter_party: offered_contract.counter_party,
+                    is_offer_party: offered_contract.is_offer_party,
+                    offer_collateral,
+                    accept_collateral: offered_contract.total_collateral - offer_collateral,
+                }
+            })
+            .collect();
+        summaries.sort_by_key(|x| x.id);
+
+        Ok(summaries
+            .into_iter()
+            .skip(filter.offset)
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// Returns the sum of the collateral contributed by the local party to the
+    /// contracts that are not closed, refunded or failed. Received offers that
+    /// were not accepted yet are not included.
+    ///
+    /// As the contracts are read from the storage provider, a failure to read
+    /// them is returned as an error instead of being reported as a total
+    /// that would understate the locked collateral.
+    pub fn total_locked_collateral(&self) -> Result<u64, Error> {
+        let get_own_collateral = |accepted: &AcceptedContract| {
+            if accepted.offered_contract.is_offer_party {
+                accepted.offered_contract.offer_params.collateral
+            } else {
+                accepted.accept_params.collateral
+            }
+        };
+
+        Ok(self
+            .store
+            .get_contracts()?
+            .iter()
+            .map(|c| match c {
+                Contract::Offered(o) if o.is_offer_party => o.offer_params.collateral,
+                Contract::Accepted(a) => get_own_collateral(a),
+                Contract::Signed(s) | Contract::Confirmed(s) => {
+                    get_own_collateral(&s.accepted_contract)
+                }
+                _ => 0,
+            })
+            .sum())
+    }
+
+    /// Releases the wallet reservations made by the manager for UTXOs that are
+    /// not used as funding input by any contract that is pending or was
+    /// funded, for example because the process stopped before an offer was
+    /// stored, or because the contract failed. Returns the number of released
+    /// UTXOs.
+    ///
+    /// Only the UTXOs that the manager reserved itself are considered, so
+    /// that the reservations made by other applications sharing the wallet,
+    /// as can be the case with a bitcoind wallet, are left untouched. The
+    /// records of UTXOs that the wallet does not report as reserved anymore,
+    /// because they were spent or released, are dropped.
+    pub fn reclaim_orphan_reservations(&mut self) -> Result<usize, Error> {
+        let mut funding_inputs = Vec::new();
+        for contract in self.store.get_contracts()? {
+            if let Contract::FailedAccept(_) | Contract::FailedSign(_) | Contract::Rejected(_) =
+                contract
+            {
+                continue;
+            }
+            funding_inputs.extend(
+                contract
+                    .get_offered_contract()
+                    .funding_inputs_info
+                    .iter()
+                    .map(|x| x.funding_input.clone()),
+            );
+            if let Some(accepted) = contract.get_accepted_contract() {
+                funding_inputs.extend(
+                    accepted
+                        .funding_inputs
+                        .iter()
+                        .map(|x| x.funding_input.clone()),
+                );
+            }
+        }
+
+        let (input_infos, _) = get_tx_input_infos(&funding_inputs)?;
+        let used: HashSet<OutPoint> = input_infos.iter().map(|x| x.outpoint).collect();
+        let reserved: HashSet<OutPoint> = self.wallet.get_reserved_utxos()?.into_iter().collect();
+        let orphans: Vec<OutPoint> = self
+            .reserved_utxos
+            .values()
+            .filter(|x| reserved.contains(x) && !used.contains(x))
+            .cloned()
+            .collect();
+
+        if !orphans.is_empty() {
+            self.wallet.unreserve_utxos(&orphans)?;
+        }
+
+        let released: Vec<ContractId> = self
+            .reserved_utxos
+            .iter()
+            .filter(|(_, x)| !reserved.contains(x) || orphans.contains(x))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in released {
+            self.store
+                .delete_contract_data(ContractDataKind::ReservedUtxo, &key)?;
+            self.reserved_utxos.remove(&key);
+        }
+
+        Ok(orphans.len())
+    }
+
+    /// Imports the contracts of the given storage provider, for example when
+    /// consolidating two nodes. Contracts whose id or temporary id is already
+    /// used by a stored contract are left untouched and reported as
+    /// collisions. The wallet of the other node is not imported, so the keys
+    /// required to close imported contracts must be made available separately.
+    pub fn merge_from(&mut self, other_storage: &S) -> Result<MergeReport, Error> {
+        let mut report = MergeReport::default();
+        for contract in other_storage.get_contracts()? {
+            let id = contract.get_id();
+            let temporary_id = contract.get_temporary_id();
+            if self.store.get_contract(&id)?.is_some()
+                || self.store.get_contract(&temporary_id)?.is_some()
+            {
+                report.collisions.push(id);
+                continue;
+            }
+
+            self.store
+                .create_contract(contract.get_offered_contract())?;
+            if !matches!(contract, Contract::Offered(_)) {
+                self.store.update_contract(&contract)?;
+                if id != temporary_id {
+                    self.store.delete_contract(&temporary_id)?;
+                }
+            }
+            report.imported.push(id);
+        }
+
+        Ok(report)
+    }
+
+    /// Generates a [`ReserveProof`] for the funding outputs of the signed and
+    /// confirmed contracts, by signing the given challenge together with each
+    /// outpoint using the local funding key of the contract.
+    pub fn prove_reserves(&self, challenge: &[u8]) -> Result<ReserveProof, Error> {
+        let mut entries = Vec::new();
+        for contract in self.store.get_contracts()? {
+            let signed = match contract {
+                Contract::Signed(s) | Contract::Confirmed(s) => s,
+                _ => continue,
+            };
+            let accepted = &signed.accepted_contract;
+            let fund_pubkey = if accepted.offered_contract.is_offer_party {
+                accepted.offered_contract.offer_params.fund_pubkey
+            } else {
+                accepted.accept_params.fund_pubkey
+            };
+            let fund_privkey = self.wallet.get_secret_key_for_pubkey(&fund_pubkey)?;
+            let dlc_transactions = &accepted.dlc_transactions;
+            let outpoint = OutPoint {
+                txid: dlc_transactions.fund.txid(),
+                vout: dlc_transactions.get_fund_output_index() as u32,
+            };
+            let msg = get_reserve_proof_message(challenge, &outpoint);
+            entries.push(ReserveProofEntry {
+                outpoint,
+                value: dlc_transactions.get_fund_output().value,
+                funding_script: dlc_transactions.funding_script_pubkey.clone(),
+                fund_pubkey,
+                signature: self.secp.sign(&msg, &fund_privkey),
+            });
+        }
+
+        Ok(ReserveProof { entries })
+    }
+
     /// Function called to pass a DlcMessage to the Manager.
     pub fn on_dlc_message(
         &mut self,
         msg: &DlcMessage,
         counter_party: PublicKey,
     ) -> Result<Option<DlcMessage>, Error> {
+        let contract_id = match msg {
+            DlcMessage::Offer(_) => None,
+            DlcMessage::Accept(a) => Some(a.temporary_contract_id),
+            DlcMessage::Sign(s) => Some(s.contract_id),
+            DlcMessage::Reject(r) => Some(r.temporary_contract_id),
+            DlcMessage::CollaborativeCloseOffer(c) => Some(c.contract_id),
+            DlcMessage::CollaborativeCloseAccept(c) => Some(c.contract_id),
+        };
+        if let Some(contract_id) = contract_id {
+            if self.get_paused_contracts()?.contains(&contract_id) {
+                return Err(Error::ContractPaused);
+            }
+        }
+
         match msg {
             DlcMessage::Offer(o) => {
                 self.on_offer_message(o, counter_party)?;
@@ -95,45 +1192,148 @@ where
             }
             DlcMessage::Accept(a) => Ok(Some(self.on_accept_message(a)?)),
             DlcMessage::Sign(s) => {
-                self.on_sign_message(s)?;
+                if self.config.buffer_early_sign_messages
+                    && self
+                        .get_contract_for_sign_message(s, &counter_party)?
+                        .is_none()
+                {
+                    self.buffer_early_sign_message(s, &counter_party)?;
+                    return Ok(None);
+                }
+                self.on_sign_message(s, &counter_party)?;
+                Ok(None)
+            }
+            DlcMessage::Reject(r) => {
+                self.on_reject_message(r)?;
+                Ok(None)
+            }
+            DlcMessage::CollaborativeCloseOffer(c) => {
+                self.on_collaborative_close_offer(c)?;
+                Ok(None)
+            }
+            DlcMessage::CollaborativeCloseAccept(c) => {
+                self.on_collaborative_close_accept(c)?;
                 Ok(None)
             }
         }
     }
 
-    fn get_party_params(
+    /// Processes the given messages in order, returning the result of each of
+    /// them. When processing a message fails, the following messages from the
+    /// same counter party are skipped with [`Error::SkippedMessage`], as they
+    /// likely depend on it. Messages from other counter parties are still
+    /// processed. The writes to the storage provider are made in a single
+    /// batch, and an error is returned if it cannot be committed.
+    pub fn on_dlc_messages(
+        &mut self,
+        messages: &[(DlcMessage, PublicKey)],
+    ) -> Result<Vec<Result<Option<DlcMessage>, Error>>, Error> {
+        let mut failed_peers = HashSet::new();
+        let mut results = Vec::with_capacity(messages.len());
+        self.store.begin_batch()?;
+        for (msg, counter_party) in messages {
+            if failed_peers.contains(counter_party) {
+                results.push(Err(Error::SkippedMessage));
+                continue;
+            }
+            let result = self.on_dlc_message(msg, *counter_party);
+            if result.is_err() {
+                failed_peers.insert(*counter_party);
+            }
+            results.push(result);
+        }
+
+        self.store.commit_batch()?;
+        Ok(results)
+    }
+
+    /// Returns an approximation of the share of the fees of the DLC
+    /// transactions paid by the given party, used to select funding inputs.
+    fn get_approximate_fee_share(
         &self,
+        fee_rate: u64,
+        cet_fee_reserve_multiplier: Option<u64>,
+        fee_contribution: FeeContribution,
+        is_offer_party: bool,
+    ) -> Result<u64, Error> {
+        let half_common_fee = crate::utils::get_half_common_fee(get_cet_fee_rate(
+            fee_rate,
+            cet_fee_reserve_multiplier,
+        )?);
+        let (offer_share, accept_share) =
+            fee_contribution.split_fee(half_common_fee, half_common_fee)?;
+        Ok(if is_offer_party {
+            offer_share
+        } else {
+            accept_share
+        })
+    }
+
+    fn get_party_params(
+        &mut self,
         own_collateral: u64,
+        own_fee_share: u64,
         fee_rate: u64,
+        funding_source: FundingSource,
     ) -> Result<(PartyParams, SecretKey, Vec<FundingInputInfo>), Error> {
         let mut rng = thread_rng();
 
         let funding_privkey = self.wallet.get_new_secret_key()?;
         let funding_pubkey = PublicKey::from_secret_key(&self.secp, &funding_privkey);
 
-        let payout_addr = self.wallet.get_new_address()?;
+        let payout_addr = self
+            .wallet
+            .get_new_address_of_type(self.config.payout_address_type)?;
         let payout_spk = payout_addr.script_pubkey();
         let payout_serial_id = rng.next_u64();
         let change_addr = self.wallet.get_new_address()?;
         let change_spk = change_addr.script_pubkey();
         let change_serial_id = rng.next_u64();
 
-        let appr_required_amount = own_collateral + crate::utils::get_half_common_fee(fee_rate);
-        let utxos = self
-            .wallet
-            .get_utxos_for_amount(appr_required_amount, Some(fee_rate), true)?;
+        let appr_required_amount = own_collateral + own_fee_share;
+        let utxos = match funding_source {
+            FundingSource::Outpoints(outpoints) => {
+                let utxos = self.wallet.get_utxos_for_outpoints(outpoints, false)?;
+                let total: u64 = utxos.iter().map(|x| x.tx_out.value).sum();
+                if total < appr_required_amount {
+                    return Err(Error::InsufficientFunds {
+                        required: appr_required_amount,
+                        available: total,
+                    });
+                }
+                let utxos = self.wallet.get_utxos_for_outpoints(outpoints, true)?;
+                self.record_reserved_utxos(&utxos)?;
+                self.with_prev_txs(utxos)?
+            }
+            FundingSource::Wallet => {
+                let utxos =
+                    self.wallet
+                        .get_utxos_for_amount(appr_required_amount, Some(fee_rate), true)?;
+                self.record_reserved_utxos(&utxos)?;
+                self.with_prev_txs(utxos)?
+            }
+            FundingSource::Renewal(closed_contract_id) => {
+                let utxos = self.get_settlement_utxos(closed_contract_id)?;
+                let total: u64 = utxos.iter().map(|x| x.0.tx_out.value).sum();
+                if total < appr_required_amount {
+                    return Err(Error::InsufficientFunds {
+                        required: appr_required_amount,
+                        available: total,
+                    });
+                }
+                utxos
+            }
+        };
 
         let mut funding_inputs_info: Vec<FundingInputInfo> = Vec::new();
         let mut funding_tx_info: Vec<TxInputInfo> = Vec::new();
         let mut total_input = 0;
-        for utxo in utxos {
-            let prev_tx = self.wallet.get_transaction(&utxo.outpoint.txid)?;
+        for (utxo, prev_tx) in utxos {
             let mut writer = Vec::new();
             prev_tx.consensus_encode(&mut writer)?;
             let prev_tx_vout = utxo.outpoint.vout;
             let sequence = 0xffffffff;
-            // TODO(tibo): this assumes P2WPKH with low R
-            let max_witness_len = 107;
+            let max_witness_len = utxo.max_witness_len as u16;
             let funding_input = FundingInput {
                 input_serial_id: rng.next_u64(),
                 prev_tx: writer,
@@ -164,19 +1364,162 @@ where
 
         Ok((party_params, funding_privkey, funding_inputs_info))
     }
-    fn get_oracle_announcements(
-        &self,
-        oracle_inputs: &OracleInput,
-    ) -> Result<Vec<OracleAnnouncement>, Error> {
-        let mut announcements = Vec::new();
-        for pubkey in &oracle_inputs.public_keys {
-            let oracle = self.oracles.get(pubkey).ok_or(Error::InvalidParameters(
-                "Unknown oracle public key".to_string(),
-            ))?;
-            announcements.push(oracle.get_announcement(&oracle_inputs.event_id)?.clone());
-        }
 
-        Ok(announcements)
+    /// Records the given UTXOs as reserved by the manager, so that
+    /// [`Manager::reclaim_orphan_reservations`] can release them if no
+    /// contract ends up using them.
+    fn record_reserved_utxos(&mut self, utxos: &[Utxo]) -> Result<(), Error> {
+        for utxo in utxos {
+            let key = get_reserved_utxo_key(&utxo.outpoint);
+            self.upsert_contract_data(ContractDataKind::ReservedUtxo, &key, &utxo.outpoint)?;
+            self.reserved_utxos.insert(key, utxo.outpoint);
+        }
+        Ok(())
+    }
+
+    fn with_prev_txs(&self, utxos: Vec<Utxo>) -> Result<Vec<(Utxo, Transaction)>, Error> {
+        utxos
+            .into_iter()
+            .map(|utxo| {
+                let prev_tx = self.wallet.get_transaction(&utxo.outpoint.txid)?;
+                Ok((utxo, prev_tx))
+            })
+            .collect()
+    }
+
+    /// Returns the outputs paying to this party in the settlement transaction
+    /// of the closed contract with given id, along with the settlement
+    /// transaction. The transaction is not required to be confirmed.
+    fn get_settlement_utxos(
+        &self,
+        closed_contract_id: &ContractId,
+    ) -> Result<Vec<(Utxo, Transaction)>, Error> {
+        let (signed_contract, settlement_tx) = match self.store.get_contract(closed_contract_id)? {
+            Some(Contract::Closed(c)) => {
+                // CSV gated CET outputs cannot be spent as funding inputs.
+                if c.signed_contract
+                    .accepted_contract
+                    .offered_contract
+                    .cet_csv_delays
+                    .is_some()
+                {
+                    return Err(Error::Unsupported);
+                }
+                let cet = c
+                    .signed_contract
+                    .accepted_contract
+                    .dlc_transactions
+                    .cets
+                    .get(c.cet_index)
+                    .cloned()
+                    .ok_or_else(|| Error::Other("Invalid CET index.".to_string()))?;
+                (c.signed_contract, cet)
+            }
+            Some(Contract::CollaborativelyClosed(c)) => (c.signed_contract, c.close_tx),
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Closed, ContractState::CollaborativelyClosed],
+                    found: c.get_state(),
+                })
+            }
+        };
+        let accepted_contract = &signed_contract.accepted_contract;
+        let payout_script_pubkey = if accepted_contract.offered_contract.is_offer_party {
+            &accepted_contract
+                .offered_contract
+                .offer_params
+                .payout_script_pubkey
+        } else {
+            &accepted_contract.accept_params.payout_script_pubkey
+        };
+        // Funding inputs can only be signed as P2WPKH.
+        if !payout_script_pubkey.is_v0_p2wpkh() {
+            return Err(Error::Unsupported);
+        }
+        let address = Address::from_script(payout_script_pubkey, self.blockchain.get_network()?)
+            .ok_or_else(|| Error::Other("Invalid payout script pubkey.".to_string()))?;
+        let txid = settlement_tx.txid();
+
+        let utxos: Vec<(Utxo, Transaction)> = settlement_tx
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| &x.script_pubkey == payout_script_pubkey)
+            .map(|(vout, x)| {
+                (
+                    Utxo {
+                        tx_out: x.clone(),
+                        outpoint: OutPoint {
+                            txid,
+                            vout: vout as u32,
+                        },
+                        address: address.clone(),
+                        redeem_script: Script::new(),
+                        max_witness_len: dlc::P2WPKH_WITNESS_SIZE,
+                    },
+                    settlement_tx.clone(),
+                )
+            })
+            .collect();
+
+        if utxos.is_empty() {
+            return Err(Error::InvalidParameters(
+                "The settlement transaction has no payout for this party.".to_string(),
+            ));
+        }
+
+        Ok(utxos)
+    }
+    fn get_oracle_announcements(
+        &self,
+        oracle_inputs: &OracleInput,
+    ) -> Result<Vec<OracleAnnouncement>, Error> {
+        let mut announcements = Vec::new();
+        for pubkey in &oracle_inputs.public_keys {
+            let oracle = self.oracles.get(pubkey).ok_or(Error::InvalidParameters(
+                "Unknown oracle public key".to_string(),
+            ))?;
+            let announcement = oracle.get_announcement(&oracle_inputs.event_id)?;
+            if &announcement.oracle_public_key != pubkey {
+                self.validate_key_delegation(pubkey, oracle.get_key_delegation(), &announcement)?;
+            }
+            announcements.push(announcement);
+        }
+
+        Ok(announcements)
+    }
+
+    /// Checks that the given announcement was signed by a key that the given
+    /// oracle master key delegated to.
+    fn validate_key_delegation(
+        &self,
+        master_public_key: &SchnorrPublicKey,
+        delegation: Option<OracleKeyDelegation>,
+        announcement: &OracleAnnouncement,
+    ) -> Result<(), Error> {
+        let delegation = delegation
+            .filter(|x| &x.master_public_key == master_public_key)
+            .ok_or(Error::OracleMismatch)?;
+        DelegatedOracleAnnouncement {
+            delegation,
+            oracle_announcement: announcement.clone(),
+        }
+        .validate(&self.secp)
+        .map_err(|_| Error::OracleMismatch)
+    }
+
+    /// Returns the oracle that signed the given announcement, either using its
+    /// public key or a key it delegated to.
+    fn get_announcement_oracle(&self, announcement: &OracleAnnouncement) -> Option<&O> {
+        if let Some(oracle) = self.oracles.get(&announcement.oracle_public_key) {
+            return Some(oracle);
+        }
+        self.oracles.iter().find_map(|(public_key, oracle)| {
+            self.validate_key_delegation(public_key, oracle.get_key_delegation(), announcement)
+                .ok()
+                .map(|_| oracle)
+        })
     }
 
     fn contract_view_info_to_contract_info(
@@ -184,8 +1527,31 @@ where
         contract_view_info: &ContractInputInfo,
     ) -> Result<ContractInfo, Error> {
         let oracle_announcements = self.get_oracle_announcements(&contract_view_info.oracles)?;
+        let contract_descriptor = match &contract_view_info.contract_descriptor {
+            ContractDescriptor::Numerical(n)
+                if self.config.out_of_range_policy == OutOfRangePolicy::Clamp =>
+            {
+                // CETs only cover non-negative values, negative ones can only
+                // be resolved through the refund transaction.
+                if oracle_announcements.iter().any(|x| {
+                    matches!(
+                        &x.oracle_event.event_descriptor,
+                        EventDescriptor::DigitDecompositionEvent(d) if d.is_signed
+                    )
+                }) {
+                    return Err(Error::InvalidParameters(
+                        "Out of range outcomes of signed events cannot be clamped.".to_string(),
+                    ));
+                }
+                ContractDescriptor::Numerical(NumericalDescriptor {
+                    payout_function: n.payout_function.clamp_to_range(0, n.get_max_outcome()),
+                    ..n.clone()
+                })
+            }
+            d => d.clone(),
+        };
         Ok(ContractInfo {
-            contract_descriptor: contract_view_info.contract_descriptor.clone(),
+            contract_descriptor,
             oracle_announcements,
             threshold: contract_view_info.oracles.threshold as usize,
         })
@@ -198,9 +1564,87 @@ where
         contract: &ContractInput,
         counter_party: PublicKey,
     ) -> Result<OfferDlc, Error> {
+        self.send_offer_with_options(contract, counter_party, OfferOptions::default())
+    }
+
+    /// Same as [`Manager::send_offer`], using the fee rate and locktimes set
+    /// in the given options instead of the ones derived from the contract
+    /// input. Returns an error if the CET locktime is not lower than the refund
+    /// locktime.
+    pub fn send_offer_with_options(
+        &mut self,
+        contract: &ContractInput,
+        counter_party: PublicKey,
+        opts: OfferOptions,
+    ) -> Result<OfferDlc, Error> {
+        self.send_offer_internal(contract, counter_party, opts, FundingSource::Wallet)
+    }
+
+    /// Offers a new contract to the counter party of the closed contract with
+    /// given id, funded with the outputs paying to this party in the
+    /// transaction that settled it, which do not need to be confirmed. This
+    /// lets the parties roll over into a new contract without going through
+    /// the wallet. The counter party can do the same with its own payout
+    /// using [`Manager::accept_renewal_offer`]. Only P2WPKH payout addresses
+    /// are supported.
+    pub fn renew_offer(
+        &mut self,
+        closed_contract_id: &ContractId,
+        new_contract_input: &ContractInput,
+    ) -> Result<OfferDlc, Error> {
+        let counter_party = self
+            .store
+            .get_contract(closed_contract_id)?
+            .ok_or(Error::UnknownContract)?
+            .get_offered_contract()
+            .counter_party;
+        self.send_offer_internal(
+            new_contract_input,
+            counter_party,
+            OfferOptions::default(),
+            FundingSource::Renewal(closed_contract_id),
+        )
+    }
+
+    fn send_offer_internal(
+        &mut self,
+        contract: &ContractInput,
+        counter_party: PublicKey,
+        opts: OfferOptions,
+        funding_source: FundingSource,
+    ) -> Result<OfferDlc, Error> {
+        validate_cet_csv_delays(&self.config.cet_csv_delays)?;
+        let fee_rate = opts.fee_rate_sat_per_vb.unwrap_or(contract.fee_rate);
+        let cet_locktime = opts.cet_locktime.unwrap_or(contract.maturity_time);
+        let refund_locktime = opts
+            .refund_locktime
+            .unwrap_or(contract.maturity_time + REFUND_DELAY);
+        if cet_locktime >= refund_locktime {
+            return Err(Error::InvalidParameters(
+                "CET locktime must be lower than refund locktime.".to_string(),
+            ));
+        }
+        self.validate_attestation_deadline(cet_locktime, refund_locktime)?;
+        let cet_sig_hash_type = self.get_offered_cet_sig_hash_type()?;
+        self.get_funding_sig_hash_type()?;
+
+        opts.fee_contribution
+            .validate()
+            .map_err(|_| Error::InvalidParameters("Invalid fee contribution.".to_string()))?;
         let total_collateral = contract.offer_collateral + contract.accept_collateral;
-        let (party_params, _, funding_inputs_info) =
-            self.get_party_params(contract.offer_collateral, contract.fee_rate)?;
+        let cet_fee_reserve_multiplier = self.config.cet_fee_reserve_multiplier.filter(|x| *x != 1);
+        let fee_share = self.get_approximate_fee_share(
+            fee_rate,
+            cet_fee_reserve_multiplier,
+            opts.fee_contribution,
+            true,
+        )?;
+        let (party_params, _, funding_inputs_info) = self.get_party_params(
+            contract.offer_collateral,
+            fee_share,
+            fee_rate,
+            funding_source,
+        )?;
 
         let fund_output_serial_id = thread_rng().next_u64();
         let contract_info = contract
@@ -216,10 +1660,14 @@ where
             total_collateral,
             funding_inputs_info,
             fund_output_serial_id,
-            fee_rate_per_vb: contract.fee_rate,
-            contract_maturity_bound: contract.maturity_time,
-            contract_timeout: contract.maturity_time + REFUND_DELAY,
+            fee_rate_per_vb: fee_rate,
+            contract_maturity_bound: cet_locktime,
+            contract_timeout: refund_locktime,
             counter_party,
+            fee_contribution: opts.fee_contribution,
+            cet_sig_hash_type,
+            cet_csv_delays: self.config.cet_csv_delays,
+            cet_fee_reserve_multiplier,
         };
 
         let offer_msg: OfferDlc = (&offered_contract).into();
@@ -231,6 +1679,52 @@ where
         Ok(offer_msg)
     }
 
+    /// Returns the sighash type of the CET signatures to offer, `None` if it
+    /// is `ALL`, see [`ManagerConfig::cet_sig_hash_type`].
+    fn get_offered_cet_sig_hash_type(&self) -> Result<Option<SigHashType>, Error> {
+        let sig_hash_type = self.config.cet_sig_hash_type.unwrap_or(SigHashType::All);
+        validate_cet_sig_hash_type(sig_hash_type)?;
+        Ok(Some(sig_hash_type).filter(|x| *x != SigHashType::All))
+    }
+
+    /// Returns the sighash type of the funding input signatures, see
+    /// [`ManagerConfig::funding_sig_hash_type`].
+    fn get_funding_sig_hash_type(&self) -> Result<SigHashType, Error> {
+        let sig_hash_type = self
+            .config
+            .funding_sig_hash_type
+            .unwrap_or(SigHashType::All);
+        dlc::validate_funding_sig_hash_type(sig_hash_type).map_err(|_| {
+            Error::InvalidParameters(format!(
+                "Sighash type {} cannot be used for funding signatures.",
+                sig_hash_type
+            ))
+        })?;
+        Ok(sig_hash_type)
+    }
+
+    /// Checks that the configured attestation deadline of a contract with the
+    /// given maturity and refund locktime ends before its refund grace period,
+    /// as it would otherwise never shorten the wait for the refund.
+    fn validate_attestation_deadline(
+        &self,
+        maturity: u32,
+        refund_locktime: u32,
+    ) -> Result<(), Error> {
+        let deadline = match self.config.attestation_deadline {
+            Some(deadline) => deadline,
+            None => return Ok(()),
+        };
+        if maturity as u64 + deadline.as_secs()
+            >= refund_locktime as u64 + self.config.refund_grace_period.as_secs()
+        {
+            return Err(Error::InvalidParameters(
+                "Attestation deadline must end before the refund grace period.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     fn on_offer_message(
         &mut self,
         offered_message: &OfferDlc,
@@ -238,40 +1732,311 @@ where
     ) -> Result<(), Error> {
         let contract: OfferedContract =
             OfferedContract::try_from_offer_dlc(offered_message, counter_party)?;
+        validate_cet_csv_delays(&contract.cet_csv_delays)?;
+        validate_cet_sig_hash_type(contract.get_cet_sig_hash_type())?;
+        get_cet_fee_rate(
+            contract.fee_rate_per_vb,
+            contract.cet_fee_reserve_multiplier,
+        )?;
+        if self.store.get_contract(&contract.id)?.is_some() {
+            return Err(Error::DuplicateTemporaryContractId);
+        }
         self.store.create_contract(&contract)?;
 
         Ok(())
     }
 
+    /// Function to call to decline a DLC for which an offer was received. The
+    /// contract is moved to the rejected state and the returned message should
+    /// be sent to the offering party.
+    pub fn reject_offer(&mut self, contract_id: &ContractId) -> Result<OfferReject, Error> {
+        let offered_contract = match self.store.get_contract(contract_id)? {
+            Some(Contract::Offered(offered)) if !offered.is_offer_party => offered,
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Offered],
+                    found: c.get_state(),
+                })
+            }
+        };
+
+        let reject = OfferReject {
+            temporary_contract_id: offered_contract.id,
+        };
+
+        self.store
+            .update_contract(&Contract::Rejected(offered_contract))?;
+
+        Ok(reject)
+    }
+
+    /// Function to call to propose to the counter party of a signed contract to
+    /// close it right away with a transaction spending the fund output, paying
+    /// `own_payout` to the local party and `counter_payout` to the counter
+    /// party. What remains of the fund output value is used as fee, and payouts
+    /// below the dust limit are discarded. The returned message should be sent
+    /// to the counter party, which can agree using
+    /// [`Manager::accept_collaborative_close`]. Pending close offers, sent and
+    /// received, are kept in the storage provider if it supports
+    /// [`Storage::upsert_contract_data`], and in memory only otherwise, in
+    /// which case the close must be offered again after a restart.
+    pub fn offer_collaborative_close(
+        &mut self,
+        contract_id: &ContractId,
+        own_payout: u64,
+        counter_payout: u64,
+    ) -> Result<CollaborativeCloseOffer, Error> {
+        let signed_contract = self.get_closable_contract(contract_id)?;
+        let (offer_payout, accept_payout) = if signed_contract
+            .accepted_contract
+            .offered_contract
+            .is_offer_party
+        {
+            (own_payout, counter_payout)
+        } else {
+            (counter_payout, own_payout)
+        };
+        let (close_tx, fund_privkey, _) =
+            self.get_collaborative_close_info(&signed_contract, offer_payout, accept_payout)?;
+        let dlc_transactions = &signed_contract.accepted_contract.dlc_transactions;
+
+        let close_offer = CollaborativeCloseOffer {
+            contract_id: *contract_id,
+            offer_payout,
+            accept_payout,
+            close_signature: dlc::util::get_raw_sig_for_tx_input(
+                &self.secp,
+                &close_tx,
+                0,
+                &dlc_transactions.funding_script_pubkey,
+                dlc_transactions.get_fund_output().value,
+                &fund_privkey,
+            ),
+        };
+        self.upsert_contract_data(ContractDataKind::SentCloseOffer, contract_id, &close_offer)?;
+        self.sent_close_offers
+            .insert(*contract_id, close_offer.clone());
+
+        Ok(close_offer)
+    }
+
+    /// Function to call to agree to a collaborative close offer received for
+    /// the given contract. The close transaction is broadcast, the contract is
+    /// moved to the collaboratively closed state and the returned message
+    /// should be sent to the counter party.
+    pub fn accept_collaborative_close(
+        &mut self,
+        contract_id: &ContractId,
+    ) -> Result<CollaborativeCloseAccept, Error> {
+        let close_offer = self
+            .received_close_offers
+            .get(contract_id)
+            .cloned()
+            .ok_or_else(|| {
+                Error::InvalidParameters("No close offer for the contract.".to_string())
+            })?;
+        let signed_contract = self.get_closable_contract(contract_id)?;
+        let (mut close_tx, fund_privkey, counter_fund_pubkey) = self.get_collaborative_close_info(
+            &signed_contract,
+            close_offer.offer_payout,
+            close_offer.accept_payout,
+        )?;
+        let dlc_transactions = &signed_contract.accepted_contract.dlc_transactions;
+        let fund_output_value = dlc_transactions.get_fund_output().value;
+
+        let close_signature = dlc::util::get_raw_sig_for_tx_input(
+            &self.secp,
+            &close_tx,
+            0,
+            &dlc_transactions.funding_script_pubkey,
+            fund_output_value,
+            &fund_privkey,
+        );
+        dlc::util::sign_multi_sig_input(
+            &self.secp,
+            &mut close_tx,
+            &close_offer.close_signature,
+            &counter_fund_pubkey,
+            &fund_privkey,
+            &dlc_transactions.funding_script_pubkey,
+            fund_output_value,
+            0,
+        );
+
+        self.blockchain.send_transaction(&close_tx)?;
+        self.received_close_offers.remove(contract_id);
+        self.store
+            .delete_contract_data(ContractDataKind::ReceivedCloseOffer, contract_id)?;
+        self.store
+            .update_contract(&Contract::CollaborativelyClosed(
+                CollaborativelyClosedContract {
+                    signed_contract,
+                    close_tx,
+                },
+            ))?;
+
+        Ok(CollaborativeCloseAccept {
+            contract_id: *contract_id,
+            close_signature,
+        })
+    }
+
     /// Function to call to accept a DLC for which an offer was received.
     pub fn accept_contract_offer(
         &mut self,
         contract_id: &ContractId,
+    ) -> Result<(ContractId, PublicKey, AcceptDlc), Error> {
+        self.accept_contract_offer_internal(contract_id, FundingSource::Wallet, None)
+    }
+
+    /// Same as [`Manager::accept_contract_offer`], funding the contract with
+    /// exactly the given wallet UTXOs instead of selecting them automatically.
+    /// Returns an error if they do not cover the collateral and fees.
+    pub fn accept_contract_offer_with_inputs(
+        &mut self,
+        contract_id: &ContractId,
+        inputs: &[OutPoint],
+    ) -> Result<(ContractId, PublicKey, AcceptDlc), Error> {
+        self.accept_contract_offer_internal(contract_id, FundingSource::Outpoints(inputs), None)
+    }
+
+    /// Same as [`Manager::accept_contract_offer`], funding the contract with
+    /// the outputs paying to this party in the transaction that settled the
+    /// closed contract with given id, as done by [`Manager::renew_offer`].
+    /// Returns an error if the offer does not come from the counter party of
+    /// the closed contract.
+    pub fn accept_renewal_offer(
+        &mut self,
+        contract_id: &ContractId,
+        closed_contract_id: &ContractId,
+    ) -> Result<(ContractId, PublicKey, AcceptDlc), Error> {
+        let closed_counter_party = self
+            .store
+            .get_contract(closed_contract_id)?
+            .ok_or(Error::UnknownContract)?
+            .get_offered_contract()
+            .counter_party;
+        let offer_counter_party = self
+            .store
+            .get_contract(contract_id)?
+            .ok_or(Error::UnknownContract)?
+            .get_offered_contract()
+            .counter_party;
+        if closed_counter_party != offer_counter_party {
+            return Err(Error::InvalidParameters(
+                "The offer does not come from the counter party of the closed contract."
+                    .to_string(),
+            ));
+        }
+        self.accept_contract_offer_internal(
+            contract_id,
+            FundingSource::Renewal(closed_contract_id),
+            None,
+        )
+    }
+
+    /// Same as [`Manager::accept_contract_offer`], restricting the contract to
+    /// the given oracle, which must be one of the oracles listed in the offer.
+    /// The selection is communicated to the offer party through the
+    /// negotiation fields of the accept message.
+    pub fn accept_contract_offer_with_oracle(
+        &mut self,
+        contract_id: &ContractId,
+        oracle_public_key: &SchnorrPublicKey,
+    ) -> Result<(ContractId, PublicKey, AcceptDlc), Error> {
+        self.accept_contract_offer_internal(
+            contract_id,
+            FundingSource::Wallet,
+            Some(oracle_public_key),
+        )
+    }
+
+    fn accept_contract_offer_internal(
+        &mut self,
+        contract_id: &ContractId,
+        funding_source: FundingSource,
+        oracle_public_key: Option<&SchnorrPublicKey>,
     ) -> Result<(ContractId, PublicKey, AcceptDlc), Error> {
         let contract = self.store.get_contract(contract_id)?;
-        let offered_contract = match contract {
+        let mut offered_contract = match contract {
             Some(Contract::Offered(offered)) => offered,
-            None => return Err(Error::InvalidParameters("Unknown contract id.".to_string())),
-            _ => return Err(Error::InvalidState),
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Offered],
+                    found: c.get_state(),
+                })
+            }
         };
 
+        if let Some(oracle_public_key) = oracle_public_key {
+            select_oracle(&mut offered_contract, oracle_public_key)?;
+        }
+
         let total_collateral = offered_contract.total_collateral;
 
+        if let Some(bounds) = &self.config.offer_collateral_ratio_bounds {
+            if !bounds.contains(offered_contract.offer_params.collateral, total_collateral) {
+                return Err(Error::CollateralRatioOutOfBounds);
+            }
+        }
+
+        self.validate_attestation_deadline(
+            offered_contract.contract_maturity_bound,
+            offered_contract.contract_timeout,
+        )?;
+
+        // The own funding inputs are signed once the sign message is received.
+        self.get_funding_sig_hash_type()?;
+
+        if let Some(max_contract_memory) = self.config.max_contract_memory {
+            let footprint: usize = offered_contract
+                .contract_info
+                .iter()
+                .map(|x| {
+                    Contract::estimate_memory_footprint(&x.contract_descriptor, total_collateral)
+                })
+                .sum();
+            if footprint > max_contract_memory {
+                return Err(Error::MemoryBudgetExceeded);
+            }
+        }
+
+        offered_contract
+            .fee_contribution
+            .validate()
+            .map_err(|_| Error::InvalidParameters("Invalid fee contribution.".to_string()))?;
+        let fee_share = self.get_approximate_fee_share(
+            offered_contract.fee_rate_per_vb,
+            offered_contract.cet_fee_reserve_multiplier,
+            offered_contract.fee_contribution,
+            false,
+        )?;
         let (accept_params, fund_secret_key, funding_inputs) = self.get_party_params(
             offered_contract.offer_params.collateral,
+            fee_share,
             offered_contract.fee_rate_per_vb,
+            funding_source,
         )?;
 
-        let dlc_transactions = dlc::create_dlc_transactions(
+        let payouts = offered_contract.contract_info[0].get_payouts(total_collateral);
+        let mut dlc_transactions = dlc::create_dlc_transactions_with_params(
             &offered_contract.offer_params,
             &accept_params,
-            &offered_contract.contract_info[0].get_payouts(total_collateral),
-            offered_contract.contract_timeout,
-            offered_contract.fee_rate_per_vb,
-            0,
-            offered_contract.contract_maturity_bound,
-            offered_contract.fund_output_serial_id,
+            &payouts,
+            &get_dlc_transactions_params(&offered_contract)?,
         )?;
+        if offered_contract.cet_csv_delays.is_some() {
+            let cet_input = dlc_transactions.cets[0].input[0].clone();
+            dlc_transactions.cets = create_cets(
+                &cet_input,
+                &offered_contract,
+                &accept_params,
+                &payouts,
+                offered_contract.contract_maturity_bound,
+            );
+        }
 
         self.wallet.import_address(&Address::p2wsh(
             &dlc_transactions.funding_script_pubkey,
@@ -280,45 +2045,40 @@ where
 
         let fund_output_value = dlc_transactions.get_fund_output().value;
 
-        let cet_input = dlc_transactions.cets[0].input[0].clone();
+        let DlcTransactions {
+            fund,
+            mut cets,
+            refund,
+            funding_script_pubkey,
+        } = dlc_transactions;
+        let fund_output = SigHashParams {
+            script_pubkey: &funding_script_pubkey,
+            value: fund_output_value,
+            sig_hash_type: offered_contract.get_cet_sig_hash_type(),
+        };
+
+        let cet_input = cets[0].input[0].clone();
         let (adaptor_info, adaptor_sig) = offered_contract.contract_info[0].get_adaptor_info(
             &self.secp,
             offered_contract.total_collateral,
             &fund_secret_key,
-            &dlc_transactions.funding_script_pubkey,
-            fund_output_value,
-            &dlc_transactions.cets,
+            &fund_output,
+            &cets,
             0,
         )?;
         let mut adaptor_infos = vec![adaptor_info];
         let mut adaptor_sigs = adaptor_sig;
 
-        let DlcTransactions {
-            fund,
-            mut cets,
-            refund,
-            funding_script_pubkey,
-        } = dlc_transactions;
-
         for contract_info in offered_contract.contract_info.iter().skip(1) {
             let payouts = contract_info.get_payouts(total_collateral);
 
-            let tmp_cets = dlc::create_cets(
-                &cet_input,
-                &offered_contract.offer_params.payout_script_pubkey,
-                offered_contract.offer_params.payout_serial_id,
-                &accept_params.payout_script_pubkey,
-                accept_params.payout_serial_id,
-                &payouts,
-                0,
-            );
+            let tmp_cets = create_cets(&cet_input, &offered_contract, &accept_params, &payouts, 0);
 
             let (adaptor_info, adaptor_sig) = contract_info.get_adaptor_info(
                 &self.secp,
                 offered_contract.total_collateral,
                 &fund_secret_key,
-                &funding_script_pubkey,
-                fund_output_value,
+                &fund_output,
                 &tmp_cets,
                 adaptor_sigs.len(),
             )?;
@@ -347,7 +2107,7 @@ where
 
         let counter_party = offered_contract.counter_party;
 
-        let mut accepted_contract = AcceptedContract {
+        let accepted_contract = AcceptedContract {
             offered_contract,
             adaptor_infos,
             adaptor_signatures: Some(adaptor_sigs),
@@ -357,28 +2117,142 @@ where
             accept_refund_signature: refund_signature,
         };
 
-        let accept_msg: AcceptDlc = (&accepted_contract).into();
-
-        // Drop own adaptor signatures as no point keeping them.
-        accepted_contract.adaptor_signatures = None;
+        let mut accept_msg: AcceptDlc = (&accepted_contract).into();
+        accept_msg.negotiation_fields = oracle_public_key.map(|x| {
+            NegotiationFields::Oracle(OracleNegotiationFields {
+                oracle_public_key: *x,
+            })
+        });
 
         let contract_id = accepted_contract.get_contract_id();
 
         self.store
             .update_contract(&Contract::Accepted(accepted_contract))?;
+        self.apply_early_sign_message(&contract_id);
 
         Ok((contract_id, counter_party, accept_msg))
     }
 
+    /// Keeps the given sign message, received from the given counter party
+    /// for a contract that is not known yet, until the contract is accepted.
+    /// Returns an error if too many messages are already buffered, see
+    /// [`ManagerConfig::buffer_early_sign_messages`].
+    fn buffer_early_sign_message(
+        &mut self,
+        sign_message: &SignDlc,
+        counter_party: &PublicKey,
+    ) -> Result<(), Error> {
+        self.evict_early_sign_messages()?;
+        let key = (*counter_party, sign_message.contract_id);
+        if !self.early_sign_messages.contains_key(&key) {
+            let nb_counter_party_messages = self
+                .early_sign_messages
+                .keys()
+                .filter(|(x, _)| x == counter_party)
+                .count();
+            if nb_counter_party_messages >= MAX_EARLY_SIGN_MESSAGES_PER_COUNTER_PARTY
+                || self.early_sign_messages.len() >= MAX_EARLY_SIGN_MESSAGES
+            {
+                return Err(Error::InvalidParameters(
+                    "Too many sign messages are buffered.".to_string(),
+                ));
+            }
+        }
+
+        warn!("Buffering sign message received for an unknown contract");
+        let buffered = EarlySignMessage {
+            counter_party: *counter_party,
+            sign_message: sign_message.clone(),
+            received_time: self.time.unix_time_now(),
+        };
+        self.upsert_contract_data(
+            ContractDataKind::EarlySignMessage,
+            &get_early_sign_message_key(counter_party, &sign_message.contract_id),
+            &buffered,
+        )?;
+        self.early_sign_messages.insert(key, buffered);
+        Ok(())
+    }
+
+    /// Drops the buffered sign messages that were received more than
+    /// [`EARLY_SIGN_MESSAGE_TIMEOUT`] seconds ago.
+    fn evict_early_sign_messages(&mut self) -> Result<(), Error> {
+        let now = self.time.unix_time_now();
+        let expired: Vec<_> = self
+            .early_sign_messages
+            .iter()
+            .filter(|(_, x)| x.received_time.saturating_add(EARLY_SIGN_MESSAGE_TIMEOUT) < now)
+            .map(|(key, _)| *key)
+            .collect();
+        for (counter_party, contract_id) in expired {
+            warn!("Dropping expired buffered sign message");
+            self.store.delete_contract_data(
+                ContractDataKind::EarlySignMessage,
+                &get_early_sign_message_key(&counter_party, &contract_id),
+            )?;
+            self.early_sign_messages
+                .remove(&(counter_party, contract_id));
+        }
+        Ok(())
+    }
+
+    /// Applies the sign message buffered for the contract with given id, if
+    /// any, once the contract is accepted. Only a message sent by the counter
+    /// party of the contract is applied, others being dropped once they
+    /// expire. Errors are logged, an invalid sign message moving the contract
+    /// to the failed sign state as usual.
+    fn apply_early_sign_message(&mut self, contract_id: &ContractId) {
+        if !self
+            .early_sign_messages
+            .keys()
+            .any(|(_, id)| id == contract_id)
+        {
+            return;
+        }
+        let counter_party = match self.store.get_contract(contract_id) {
+            Ok(Some(Contract::Accepted(a))) => a.offered_contract.counter_party,
+            Ok(_) => return,
+            Err(e) => {
+                error!("Error retrieving contract of buffered sign message: {}", e);
+                return;
+            }
+        };
+        if let Some(buffered) = self
+            .early_sign_messages
+            .remove(&(counter_party, *contract_id))
+        {
+            if let Err(e) = self.store.delete_contract_data(
+                ContractDataKind::EarlySignMessage,
+                &get_early_sign_message_key(&counter_party, contract_id),
+            ) {
+                error!("Error deleting buffered sign message: {}", e);
+            }
+            if let Err(e) = self.on_sign_message(&buffered.sign_message, &counter_party) {
+                error!("Error applying buffered sign message: {}", e);
+            }
+        }
+    }
+
     fn on_accept_message(&mut self, accept_msg: &AcceptDlc) -> Result<DlcMessage, Error> {
         let contract = self.store.get_contract(&accept_msg.temporary_contract_id)?;
 
-        let offered_contract = match contract {
+        let mut offered_contract = match contract {
             Some(Contract::Offered(offered)) => offered,
-            None => return Err(Error::InvalidParameters("Unknown contract id.".to_string())),
-            _ => return Err(Error::InvalidState),
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Offered],
+                    found: c.get_state(),
+                })
+            }
         };
 
+        if let Some(NegotiationFields::Oracle(oracle)) = &accept_msg.negotiation_fields {
+            select_oracle(&mut offered_contract, &oracle.oracle_public_key)?;
+        }
+
+        let funding_sig_hash_type = self.get_funding_sig_hash_type()?;
+
         let (tx_input_infos, input_amount) = get_tx_input_infos(&accept_msg.funding_inputs)?;
 
         let accept_params = PartyParams {
@@ -395,16 +2269,23 @@ where
         let total_collateral =
             offered_contract.offer_params.collateral + accept_msg.accept_collateral;
 
-        let dlc_transactions = dlc::create_dlc_transactions(
+        let payouts = offered_contract.contract_info[0].get_payouts(total_collateral);
+        let mut dlc_transactions = dlc::create_dlc_transactions_with_params(
             &offered_contract.offer_params,
             &accept_params,
-            &offered_contract.contract_info[0].get_payouts(total_collateral),
-            offered_contract.contract_timeout,
-            offered_contract.fee_rate_per_vb,
-            0,
-            offered_contract.contract_maturity_bound,
-            offered_contract.fund_output_serial_id,
+            &payouts,
+            &get_dlc_transactions_params(&offered_contract)?,
         )?;
+        if offered_contract.cet_csv_delays.is_some() {
+            let cet_input = dlc_transactions.cets[0].input[0].clone();
+            dlc_transactions.cets = create_cets(
+                &cet_input,
+                &offered_contract,
+                &accept_params,
+                &payouts,
+                offered_contract.contract_maturity_bound,
+            );
+        }
 
         self.wallet.import_address(&Address::p2wsh(
             &dlc_transactions.funding_script_pubkey,
@@ -419,6 +2300,11 @@ where
             refund,
             funding_script_pubkey,
         } = dlc_transactions;
+        let fund_output = SigHashParams {
+            script_pubkey: &funding_script_pubkey,
+            value: fund_output_value,
+            sig_hash_type: offered_contract.get_cet_sig_hash_type(),
+        };
 
         let refund_verify_result = dlc::verify_tx_input_sig(
             &self.secp,
@@ -443,8 +2329,7 @@ where
             &self.secp,
             offered_contract.total_collateral,
             &accept_params.fund_pubkey,
-            &funding_script_pubkey,
-            fund_output_value,
+            &fund_output,
             &cets,
             &adaptor_signatures,
             0,
@@ -460,26 +2345,19 @@ where
         for contract_info in offered_contract.contract_info.iter().skip(1) {
             let payouts = contract_info.get_payouts(total_collateral);
 
-            let tmp_cets = dlc::create_cets(
-                &cet_input,
-                &offered_contract.offer_params.payout_script_pubkey,
-                offered_contract.offer_params.payout_serial_id,
-                &accept_params.payout_script_pubkey,
-                accept_params.payout_serial_id,
-                &payouts,
-                0,
-            );
+            let tmp_cets = create_cets(&cet_input, &offered_contract, &accept_params, &payouts, 0);
 
-            let (adaptor_info, tmp_adaptor_index) = contract_info.verify_and_get_adaptor_info(
-                &self.secp,
-                offered_contract.total_collateral,
-                &accept_params.fund_pubkey,
-                &funding_script_pubkey,
-                fund_output_value,
-                &tmp_cets,
-                &adaptor_signatures,
-                adaptor_index,
-            )?;
+            let (adaptor_info, tmp_adaptor_index) = contract_info
+                .verify_and_get_adaptor_info(
+                    &self.secp,
+                    offered_contract.total_collateral,
+                    &accept_params.fund_pubkey,
+                    &fund_output,
+                    &tmp_cets,
+                    &adaptor_signatures,
+                    adaptor_index,
+                )
+                .map_err(signature_verification_error)?;
 
             adaptor_index = tmp_adaptor_index;
 
@@ -503,8 +2381,7 @@ where
                 &self.secp,
                 adaptor_info,
                 &fund_privkey,
-                &funding_script_pubkey,
-                fund_output_value,
+                &fund_output,
                 &cets,
             )?;
             own_signatures.extend(sigs);
@@ -523,12 +2400,14 @@ where
             .funding_inputs_info
             .iter()
             .map(|x| {
-                let address = x.address.as_ref().ok_or(Error::InvalidState)?;
+                let address = x.address.as_ref().ok_or_else(|| {
+                    Error::Other("Missing address for own funding input.".to_string())
+                })?;
                 let sk = self.wallet.get_secret_key_for_address(&address)?;
                 let input_index = input_serial_ids
                     .iter()
                     .position(|y| y == &x.funding_input.input_serial_id)
-                    .ok_or(Error::InvalidState)?;
+                    .ok_or_else(|| Error::Other("Unknown input serial id.".to_string()))?;
                 let tx = Transaction::consensus_decode(&*x.funding_input.prev_tx).or(Err(
                     Error::InvalidParameters(
                         "Could not decode funding input previous tx parameter".to_string(),
@@ -547,7 +2426,7 @@ where
                     &sk,
                     &fund,
                     input_index,
-                    bitcoin::SigHashType::All,
+                    funding_sig_hash_type,
                     tx_out.value,
                 );
                 let witness_elements = witness
@@ -586,7 +2465,7 @@ where
             dlc_transactions: dlc_transactions.clone(),
         };
 
-        let mut signed_contract = SignedContract {
+        let signed_contract = SignedContract {
             accepted_contract,
             adaptor_signatures: Some(own_signatures),
             offer_refund_signature,
@@ -595,24 +2474,320 @@ where
 
         let signed_msg: SignDlc = (&signed_contract).into();
 
-        // Drop own adaptor signatures as no point keeping them.
-        signed_contract.adaptor_signatures = None;
-
         self.store
             .update_contract(&Contract::Signed(signed_contract))?;
 
         Ok(DlcMessage::Sign(signed_msg))
     }
 
-    fn on_sign_message(&mut self, sign_message: &SignDlc) -> Result<(), Error> {
-        let contract = self.store.get_contract(&sign_message.contract_id)?;
+    fn on_reject_message(&mut self, reject_message: &OfferReject) -> Result<(), Error> {
+        let offered_contract = match self
+            .store
+            .get_contract(&reject_message.temporary_contract_id)?
+        {
+            Some(Contract::Offered(offered)) if offered.is_offer_party => offered,
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Offered],
+                    found: c.get_state(),
+                })
+            }
+        };
+
+        let funding_inputs: Vec<FundingInput> = offered_contract
+            .funding_inputs_info
+            .iter()
+            .map(|x| x.funding_input.clone())
+            .collect();
+        let (input_infos, _) = get_tx_input_infos(&funding_inputs)?;
+
+        self.store
+            .update_contract(&Contract::Rejected(offered_contract))?;
+
+        let mut outpoints: Vec<OutPoint> = input_infos.into_iter().map(|x| x.outpoint).collect();
+        // Inputs of renewal offers are settlement outputs that the wallet
+        // never reserved.
+        match self.wallet.get_reserved_utxos() {
+            Ok(reserved) => outpoints.retain(|x| reserved.contains(x)),
+            Err(Error::Unsupported) => {}
+            Err(e) => return Err(e),
+        }
+        self.wallet.unreserve_utxos(&outpoints)
+    }
+
+    fn on_collaborative_close_offer(
+        &mut self,
+        close_offer: &CollaborativeCloseOffer,
+    ) -> Result<(), Error> {
+        let signed_contract = self.get_closable_contract(&close_offer.contract_id)?;
+        let (close_tx, _, counter_fund_pubkey) = self.get_collaborative_close_info(
+            &signed_contract,
+            close_offer.offer_payout,
+            close_offer.accept_payout,
+        )?;
+        let dlc_transactions = &signed_contract.accepted_contract.dlc_transactions;
+
+        dlc::verify_tx_input_sig(
+            &self.secp,
+            &close_offer.close_signature,
+            &close_tx,
+            0,
+            &dlc_transactions.funding_script_pubkey,
+            dlc_transactions.get_fund_output().value,
+            &counter_fund_pubkey,
+        )
+        .map_err(signature_verification_error)?;
+
+        self.upsert_contract_data(
+            ContractDataKind::ReceivedCloseOffer,
+            &close_offer.contract_id,
+            close_offer,
+        )?;
+        self.received_close_offers
+            .insert(close_offer.contract_id, close_offer.clone());
+
+        Ok(())
+    }
+
+    fn on_collaborative_close_accept(
+        &mut self,
+        close_accept: &CollaborativeCloseAccept,
+    ) -> Result<(), Error> {
+        let close_offer = self
+            .sent_close_offers
+            .get(&close_accept.contract_id)
+            .cloned()
+            .ok_or_else(|| {
+                Error::InvalidParameters("No close offer for the contract.".to_string())
+            })?;
+        let signed_contract = self.get_closable_contract(&close_accept.contract_id)?;
+        let (mut close_tx, fund_privkey, counter_fund_pubkey) = self.get_collaborative_close_info(
+            &signed_contract,
+            close_offer.offer_payout,
+            close_offer.accept_payout,
+        )?;
+        let dlc_transactions = &signed_contract.accepted_contract.dlc_transactions;
+        let fund_output_value = dlc_transactions.get_fund_output().value;
+
+        dlc::verify_tx_input_sig(
+            &self.secp,
+            &close_accept.close_signature,
+            &close_tx,
+            0,
+            &dlc_transactions.funding_script_pubkey,
+            fund_output_value,
+            &counter_fund_pubkey,
+        )
+        .map_err(signature_verification_error)?;
+        dlc::util::sign_multi_sig_input(
+            &self.secp,
+            &mut close_tx,
+            &close_accept.close_signature,
+            &counter_fund_pubkey,
+            &fund_privkey,
+            &dlc_transactions.funding_script_pubkey,
+            fund_output_value,
+            0,
+        );
+
+        // The counter party already broadcast the transaction, but doing it
+        // again ensures that it propagates.
+        self.blockchain.send_transaction(&close_tx)?;
+        self.sent_close_offers.remove(&close_accept.contract_id);
+        self.store
+            .delete_contract_data(ContractDataKind::SentCloseOffer, &close_accept.contract_id)?;
+        self.store
+            .update_contract(&Contract::CollaborativelyClosed(
+                CollaborativelyClosedContract {
+                    signed_contract,
+                    close_tx,
+                },
+            ))?;
+
+        Ok(())
+    }
+
+    /// Closes the given signed or confirmed contract without waiting for its
+    /// maturity, using the attestations of the oracles known to the manager.
+    /// The given attestation is used for the oracle that signed it if that
+    /// oracle is not known to the manager, after checking it against the
+    /// announcement of the contract. Returns the actions taken, which are
+    /// empty when the CET is handed over in manual broadcast mode.
+    pub fn force_close_contract(
+        &mut self,
+        contract_id: &ContractId,
+        attestation: Option<OracleAttestation>,
+    ) -> Result<Vec<ManagerAction>, Error> {
+        let contract = self.get_closable_contract(contract_id)?;
+        let contract_infos = &contract.accepted_contract.offered_contract.contract_info;
+        for (contract_info, adaptor_info) in contract_infos
+            .iter()
+            .zip(contract.accepted_contract.adaptor_infos.iter())
+        {
+            let mut attestations = Vec::new();
+            for (i, announcement) in contract_info.oracle_announcements.iter().enumerate() {
+                let oracle_attestation = if self.get_announcement_oracle(announcement).is_some() {
+                    self.get_validated_attestation(announcement)
+                } else {
+                    match &attestation {
+                        Some(a) if a.oracle_public_key == announcement.oracle_public_key => {
+                            self.validate_attestation(announcement, a)?;
+                            Some(a.clone())
+                        }
+                        _ => None,
+                    }
+                };
+                if let Some(oracle_attestation) = oracle_attestation {
+                    attestations.push((i, oracle_attestation));
+                }
+            }
+            if attestations.len() < contract_info.threshold {
+                continue;
+            }
+            let outcomes: Vec<(usize, &Vec<String>)> = attestations
+                .iter()
+                .map(|(i, x)| (*i, &x.outcomes))
+                .collect();
+            if contract_info.is_outcome_out_of_range(&outcomes) {
+                return Err(Error::OutcomeNotCovered);
+            }
+            let mut actions = Vec::new();
+            self.try_close_contract(
+                &contract,
+                contract_info,
+                adaptor_info,
+                &attestations,
+                &mut actions,
+            )?;
+            if !actions.is_empty() || self.pending_cets.contains_key(contract_id) {
+                return Ok(actions);
+            }
+        }
+
+        Err(Error::MissingAttestations)
+    }
+
+    fn get_closable_contract(&self, contract_id: &ContractId) -> Result<SignedContract, Error> {
+        match self.store.get_contract(contract_id)? {
+            Some(Contract::Signed(s)) | Some(Contract::Confirmed(s)) => Ok(s),
+            None => Err(Error::UnknownContract),
+            Some(c) => Err(Error::InvalidState {
+                expected: vec![ContractState::Signed, ContractState::Confirmed],
+                found: c.get_state(),
+            }),
+        }
+    }
+
+    /// Returns the unsigned transaction closing the given contract with the
+    /// given payouts, together with the local fund secret key and the fund
+    /// public key of the counter party.
+    fn get_collaborative_close_info(
+        &self,
+        contract: &SignedContract,
+        offer_payout: u64,
+        accept_payout: u64,
+    ) -> Result<(Transaction, SecretKey, PublicKey), Error> {
+        let accepted_contract = &contract.accepted_contract;
+        let offer_params = &accepted_contract.offered_contract.offer_params;
+        let accept_params = &accepted_contract.accept_params;
+        let dlc_transactions = &accepted_contract.dlc_transactions;
+
+        let fund_output_value = dlc_transactions.get_fund_output().value;
+        if offer_payout
+            .checked_add(accept_payout)
+            .is_none_or(|x| x > fund_output_value)
+        {
+            return Err(Error::InvalidParameters(
+                "Close payouts exceed the fund output value.".to_string(),
+            ));
+        }
+
+        let fund_tx_in = TxIn {
+            previous_output: OutPoint {
+                txid: dlc_transactions.fund.txid(),
+                vout: dlc_transactions.get_fund_output_index() as u32,
+            },
+            script_sig: Script::new(),
+            sequence: 0xffffffff,
+            witness: Vec::new(),
+        };
+        let close_tx = dlc::create_cet(
+            TxOut {
+                value: offer_payout,
+                script_pubkey: offer_params.payout_script_pubkey.clone(),
+            },
+            offer_params.payout_serial_id,
+            TxOut {
+                value: accept_payout,
+                script_pubkey: accept_params.payout_script_pubkey.clone(),
+            },
+            accept_params.payout_serial_id,
+            &fund_tx_in,
+            0,
+        );
+        if close_tx.output.is_empty() {
+            return Err(Error::InvalidParameters(
+                "Close payouts are all below the dust limit.".to_string(),
+            ));
+        }
+
+        let (own_fund_pubkey, counter_fund_pubkey) =
+            if accepted_contract.offered_contract.is_offer_party {
+                (&offer_params.fund_pubkey, accept_params.fund_pubkey)
+            } else {
+                (&accept_params.fund_pubkey, offer_params.fund_pubkey)
+            };
+        let fund_privkey = self.wallet.get_secret_key_for_pubkey(own_fund_pubkey)?;
+
+        Ok((close_tx, fund_privkey, counter_fund_pubkey))
+    }
+
+    /// Returns the contract that the given sign message refers to. If no
+    /// contract has the id computed by the counter party, the only contract
+    /// accepted with it is returned if any, so that diverging contract ids get
+    /// reported instead of the contract being unknown.
+    fn get_contract_for_sign_message(
+        &self,
+        sign_message: &SignDlc,
+        counter_party: &PublicKey,
+    ) -> Result<Option<Contract>, Error> {
+        if let Some(contract) = self.store.get_contract(&sign_message.contract_id)? {
+            return Ok(Some(contract));
+        }
+        let mut accepted = self.store.get_contracts()?.into_iter().filter(|x| {
+            matches!(x, Contract::Accepted(a) if &a.offered_contract.counter_party == counter_party)
+        });
+        match (accepted.next(), accepted.next()) {
+            (Some(contract), None) => Ok(Some(contract)),
+            _ => Ok(None),
+        }
+    }
+
+    fn on_sign_message(
+        &mut self,
+        sign_message: &SignDlc,
+        counter_party: &PublicKey,
+    ) -> Result<(), Error> {
+        let contract = self.get_contract_for_sign_message(sign_message, counter_party)?;
         let accepted_contract = match contract {
             Some(Contract::Accepted(accepted)) => accepted,
-            None => return Err(Error::InvalidParameters("Unknown contract id.".to_string())),
-            _ => return Err(Error::InvalidState),
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Accepted],
+                    found: c.get_state(),
+                })
+            }
         };
 
+        self.assert_contract_id_agreement(
+            &accepted_contract.get_contract_id(),
+            sign_message.contract_id,
+        )?;
+
         let offered_contract = &accepted_contract.offered_contract;
+        let fund_output = accepted_contract.get_cet_sig_hash_params();
 
         let verify_result = dlc::verify_tx_input_sig(
             &self.secp,
@@ -643,8 +2818,7 @@ where
             let adaptor_verify_result = contract_info.verify_adaptor_info(
                 &self.secp,
                 &offered_contract.offer_params.fund_pubkey,
-                &accepted_contract.dlc_transactions.funding_script_pubkey,
-                accepted_contract.dlc_transactions.get_fund_output().value,
+                &fund_output,
                 &accepted_contract.dlc_transactions.cets,
                 &adaptor_signatures,
                 adaptor_sig_start,
@@ -655,146 +2829,1245 @@ where
                 self.sign_fail_on_error(&accepted_contract, sign_message, adaptor_verify_result)?;
         }
 
-        let mut input_serials: Vec<_> = offered_contract
-            .funding_inputs_info
-            .iter()
-            .chain(accepted_contract.funding_inputs.iter())
-            .map(|x| x.funding_input.input_serial_id)
-            .collect();
-        input_serials.sort();
+        let signed_contract = SignedContract {
+            accepted_contract,
+            adaptor_signatures: Some(adaptor_signatures),
+            offer_refund_signature: sign_message.refund_signature,
+            funding_signatures: sign_message.funding_signatures.clone(),
+        };
+
+        let fund_tx = self.get_signed_fund_tx(&signed_contract)?;
+
+        let contract_id = signed_contract.accepted_contract.get_contract_id();
+
+        self.store
+            .update_contract(&Contract::Signed(signed_contract))?;
+
+        self.blockchain.send_transaction(&fund_tx)?;
+        self.set_funding_broadcast_time(&contract_id)?;
+
+        Ok(())
+    }
+
+    /// Returns the fund transaction of the given contract, signed using the
+    /// funding signatures of the offer party and the wallet for the inputs of
+    /// the accept party. Can therefore only be called by the accept party.
+    fn get_signed_fund_tx(&self, contract: &SignedContract) -> Result<Transaction, Error> {
+        let accepted_contract = &contract.accepted_contract;
+        let offered_contract = &accepted_contract.offered_contract;
+        let funding_sig_hash_type = self.get_funding_sig_hash_type()?;
+
+        let mut input_serials: Vec<_> = offered_contract
+            .funding_inputs_info
+            .iter()
+            .chain(accepted_contract.funding_inputs.iter())
+            .map(|x| x.funding_input.input_serial_id)
+            .collect();
+        input_serials.sort();
+
+        let mut fund_tx = accepted_contract.dlc_transactions.fund.clone();
+
+        for (funding_input, funding_signatures) in offered_contract
+            .funding_inputs_info
+            .iter()
+            .zip(contract.funding_signatures.funding_signatures.iter())
+        {
+            let input_index = input_serials
+                .iter()
+                .position(|x| x == &funding_input.funding_input.input_serial_id)
+                .ok_or_else(|| Error::Other("Unknown input serial id.".to_string()))?;
+
+            fund_tx.input[input_index].witness = funding_signatures
+                .witness_elements
+                .iter()
+                .map(|x| x.witness.clone())
+                .collect();
+        }
+
+        for funding_input_info in &accepted_contract.funding_inputs {
+            let input_index = input_serials
+                .iter()
+                .position(|x| x == &funding_input_info.funding_input.input_serial_id)
+                .ok_or_else(|| Error::Other("Unknown input serial id.".to_string()))?;
+            let address = funding_input_info.address.as_ref().ok_or_else(|| {
+                Error::Other("Missing address for own funding input.".to_string())
+            })?;
+            let sk = self.wallet.get_secret_key_for_address(&address)?;
+            let tx = Transaction::consensus_decode(&*funding_input_info.funding_input.prev_tx).or(
+                Err(Error::InvalidParameters(
+                    "Could not decode funding input previous tx parameter".to_string(),
+                )),
+            )?;
+            let vout = funding_input_info.funding_input.prev_tx_vout;
+            let tx_out = tx
+                .output
+                .get(vout as usize)
+                .ok_or(Error::InvalidParameters(format!(
+                    "Previous tx output not found at index {}",
+                    vout
+                )))?;
+            dlc::util::sign_p2wpkh_input(
+                &self.secp,
+                &sk,
+                &mut fund_tx,
+                input_index,
+                funding_sig_hash_type,
+                tx_out.value,
+            );
+        }
+
+        Ok(fund_tx)
+    }
+
+    fn sign_fail_on_error<R>(
+        &mut self,
+        accepted_contract: &AcceptedContract,
+        sign_message: &SignDlc,
+        result: Result<R, dlc::Error>,
+    ) -> Result<R, Error> {
+        match result {
+            Err(e) => {
+                error!("Error in on_sign {}", e);
+                self.store
+                    .update_contract(&Contract::FailedSign(FailedSignContract {
+                        accepted_contract: accepted_contract.clone(),
+                        sign_message: sign_message.clone(),
+                        error_message: e.to_string(),
+                    }))?;
+                Err(signature_verification_error(e))
+            }
+            Ok(val) => Ok(val),
+        }
+    }
+
+    fn accept_fail_on_error<R>(
+        &mut self,
+        offered_contract: &OfferedContract,
+        accept_message: &AcceptDlc,
+        result: Result<R, dlc::Error>,
+    ) -> Result<R, Error> {
+        match result {
+            Err(e) => {
+                error!("Error in on_accept {}", e);
+                self.store
+                    .update_contract(&Contract::FailedAccept(FailedAcceptContract {
+                        offered_contract: offered_contract.clone(),
+                        accept_message: accept_message.clone(),
+                        error_message: e.to_string(),
+                    }))?;
+                Err(signature_verification_error(e))
+            }
+            Ok(val) => Ok(val),
+        }
+    }
+
+    /// Function to call when the funding transaction of a signed contract was
+    /// confirmed at the given block height, for deployments where the chain is
+    /// watched externally. The contract is moved to the confirmed state without
+    /// querying the wallet for the number of confirmations.
+    pub fn notify_funding_confirmed(
+        &mut self,
+        contract_id: &ContractId,
+        block_height: u32,
+    ) -> Result<(), Error> {
+        let signed_contract = match self.store.get_contract(contract_id)? {
+            Some(Contract::Signed(signed)) => signed,
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Signed],
+                    found: c.get_state(),
+                })
+            }
+        };
+
+        self.store
+            .update_contract(&Contract::Confirmed(signed_contract))?;
+        self.funding_confirmation_heights
+            .insert(*contract_id, block_height);
+        self.upsert_contract_data(
+            ContractDataKind::FundingConfirmationHeight,
+            contract_id,
+            &block_height,
+        )?;
+        self.clear_funding_broadcast_time(contract_id)?;
+
+        Ok(())
+    }
+
+    /// Returns the block height at which the funding transaction of the contract
+    /// with given id was confirmed, if it was provided through
+    /// [`Manager::notify_funding_confirmed`]. The height is kept in the
+    /// storage provider if it supports [`Storage::upsert_contract_data`], and
+    /// in memory only otherwise.
+    pub fn get_funding_confirmation_height(&self, contract_id: &ContractId) -> Option<u32> {
+        self.funding_confirmation_heights.get(contract_id).cloned()
+    }
+
+    /// Stores the given data of the contract with given id in the storage
+    /// provider, unless it does not support contract data.
+    fn upsert_contract_data<V: Serializable>(
+        &mut self,
+        kind: ContractDataKind,
+        contract_id: &ContractId,
+        value: &V,
+    ) -> Result<(), Error> {
+        match self
+            .store
+            .upsert_contract_data(kind, contract_id, &value.serialize()?)
+        {
+            Err(Error::Unsupported) => Ok(()),
+            res => res,
+        }
+    }
+
+    /// Records the current time as the broadcast time of the fund transaction
+    /// of the contract with given id, unless one is already recorded.
+    fn set_funding_broadcast_time(&mut self, contract_id: &ContractId) -> Result<(), Error> {
+        if self.funding_broadcast_times.contains_key(contract_id) {
+            return Ok(());
+        }
+        let now = self.time.unix_time_now();
+        self.funding_broadcast_times.insert(*contract_id, now);
+        self.upsert_contract_data(ContractDataKind::FundingBroadcastTime, contract_id, &now)
+    }
+
+    fn clear_funding_broadcast_time(&mut self, contract_id: &ContractId) -> Result<(), Error> {
+        if self.funding_broadcast_times.remove(contract_id).is_some() {
+            self.store
+                .delete_contract_data(ContractDataKind::FundingBroadcastTime, contract_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the id computed locally for the contract stored under the
+    /// given id is equal to `expected`, which is usually the one computed by
+    /// the counter party. A mismatch indicates a protocol bug.
+    pub fn assert_contract_id_agreement(
+        &self,
+        contract_id: &ContractId,
+        expected: ContractId,
+    ) -> Result<(), Error> {
+        let contract = self
+            .store
+            .get_contract(contract_id)?
+            .ok_or(Error::UnknownContract)?;
+        let accepted_contract = contract.get_accepted_contract_or_err()?;
+        if accepted_contract.get_contract_id() != expected {
+            return Err(Error::ContractIdMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the ids of the contracts whose fund transaction was broadcast by
+    /// this manager more than `older_than` ago and is still not confirmed,
+    /// which can indicate that its fee rate was too low. Broadcast times are
+    /// kept in the storage provider if it supports
+    /// [`Storage::upsert_contract_data`], and in memory only otherwise.
+    pub fn stale_funding_contracts(&self, older_than: Duration) -> Vec<ContractId> {
+        let now = self.time.unix_time_now();
+        self.funding_broadcast_times
+            .iter()
+            .filter(|(_, broadcast_time)| {
+                now.saturating_sub(**broadcast_time) > older_than.as_secs()
+            })
+            .map(|(contract_id, _)| *contract_id)
+            .collect()
+    }
+
+    /// Returns the transactions whose confirmations are currently monitored by
+    /// the manager during [`Manager::periodic_check`], together with the reason
+    /// for which they are. Errors reading the storage are logged and the
+    /// affected contracts skipped.
+    pub fn watched_transactions(&self) -> Vec<(Txid, WatchReason)> {
+        let mut watched = Vec::new();
+        match self.store.get_signed_contracts() {
+            Ok(contracts) => watched.extend(contracts.iter().map(|c| {
+                (
+                    c.accepted_contract.dlc_transactions.fund.txid(),
+                    WatchReason::Funding,
+                )
+            })),
+            Err(e) => error!("Error retrieving signed contracts: {}", e),
+        }
+        match self.store.get_confirmed_contracts() {
+            Ok(contracts) => watched.extend(contracts.iter().map(|c| {
+                (
+                    c.accepted_contract.dlc_transactions.refund.txid(),
+                    WatchReason::Refund,
+                )
+            })),
+            Err(e) => error!("Error retrieving confirmed contracts: {}", e),
+        }
+        watched.extend(
+            self.broadcast_cets
+                .values()
+                .map(|x| (x.cet.txid(), WatchReason::Cet)),
+        );
+
+        watched
+    }
+
+    /// Returns the CETs of the accepted contract with given id together with
+    /// the adaptor and refund signatures of both parties, as far as they are
+    /// known. The local adaptor signatures are the ones that were sent to the
+    /// counter party, as adaptor signatures are randomized and generating them
+    /// again would give different ones. Returns an error for contracts stored
+    /// without their local adaptor signatures. The stored contract is not
+    /// modified.
+    pub fn get_contract_signatures(
+        &self,
+        contract_id: &ContractId,
+    ) -> Result<ContractSignatures, Error> {
+        let contract = self
+            .store
+            .get_contract(contract_id)?
+            .ok_or(Error::UnknownContract)?;
+        let accepted_contract = contract.get_accepted_contract_or_err()?;
+        let signed_contract = match &contract {
+            Contract::Signed(s) | Contract::Confirmed(s) | Contract::Refunded(s) => Some(s),
+            Contract::Closed(c) => Some(&c.signed_contract),
+            Contract::CollaborativelyClosed(c) => Some(&c.signed_contract),
+            _ => None,
+        };
+        let offered_contract = &accepted_contract.offered_contract;
+        let dlc_transactions = &accepted_contract.dlc_transactions;
+
+        let accept_adaptor_signatures = accepted_contract.adaptor_signatures.clone();
+        let offer_adaptor_signatures = signed_contract.and_then(|s| s.adaptor_signatures.clone());
+        let (local_adaptor_signatures, counter_party_adaptor_signatures) =
+            if offered_contract.is_offer_party {
+                (offer_adaptor_signatures, accept_adaptor_signatures)
+            } else {
+                (accept_adaptor_signatures, offer_adaptor_signatures)
+            };
+        let local_adaptor_signatures = local_adaptor_signatures.ok_or_else(|| {
+            Error::StorageError(
+                "Local adaptor signatures are not stored for this contract.".to_string(),
+            )
+        })?;
+
+        let offer_refund_signature = signed_contract.map(|s| s.offer_refund_signature);
+        let accept_refund_signature = accepted_contract.accept_refund_signature;
+        let (local_refund_signature, counter_party_refund_signature) =
+            if offered_contract.is_offer_party {
+                (
+                    offer_refund_signature.ok_or_else(|| Error::InvalidState {
+                        expected: vec![
+                            ContractState::Signed,
+                            ContractState::Confirmed,
+                            ContractState::Refunded,
+                        ],
+                        found: contract.get_state(),
+                    })?,
+                    Some(accept_refund_signature),
+                )
+            } else {
+                (accept_refund_signature, offer_refund_signature)
+            };
+
+        Ok(ContractSignatures {
+            cets: dlc_transactions.cets.clone(),
+            local_adaptor_signatures,
+            counter_party_adaptor_signatures,
+            local_refund_signature,
+            counter_party_refund_signature,
+        })
+    }
+
+    /// Returns the attestation status of each oracle announcement of the
+    /// contract with given id, ordered by contract info and announcement, by
+    /// querying the configured oracles. Oracles that are not configured are
+    /// reported as not having attested. Nothing is stored or broadcast.
+    pub fn get_contract_oracle_status(
+        &self,
+        contract_id: &ContractId,
+    ) -> Result<Vec<OracleStatus>, Error> {
+        let contract = self
+            .store
+            .get_contract(contract_id)?
+            .ok_or(Error::UnknownContract)?;
+
+        Ok(contract
+            .get_offered_contract()
+            .contract_info
+            .iter()
+            .flat_map(|x| x.oracle_announcements.iter())
+            .map(|announcement| {
+                let event_id = &announcement.oracle_event.event_id;
+                let outcomes = self
+                    .oracles
+                    .get(&announcement.oracle_public_key)
+                    .and_then(|oracle| oracle.get_attestation(event_id).ok())
+                    .map(|attestation| attestation.outcomes);
+                OracleStatus {
+                    oracle_public_key: announcement.oracle_public_key,
+                    event_id: event_id.clone(),
+                    outcomes,
+                }
+            })
+            .collect())
+    }
+
+    /// Returns, for each outcome of the contract with given id, a label
+    /// describing it along with the payouts of the offer and accept parties,
+    /// which can be used to review an offer before accepting it. For
+    /// numerical contracts, consecutive outcomes sharing the same rounded
+    /// payout are grouped, with labels of the form `start-end`, instead of
+    /// listing every possible value.
+    pub fn get_payout_preview(
+        &self,
+        contract_id: &ContractId,
+    ) -> Result<Vec<(String, u64, u64)>, Error> {
+        let contract = self
+            .store
+            .get_contract(contract_id)?
+            .ok_or(Error::UnknownContract)?;
+        let offered_contract = contract.get_offered_contract();
+
+        let mut preview = Vec::new();
+        for contract_info in &offered_contract.contract_info {
+            match &contract_info.contract_descriptor {
+                ContractDescriptor::Enum(e) => {
+                    preview.extend(
+                        e.outcome_payouts
+                            .iter()
+                            .map(|x| (x.outcome.clone(), x.payout.offer, x.payout.accept)),
+                    );
+                }
+                ContractDescriptor::Numerical(n) => {
+                    preview.extend(
+                        n.get_range_payouts(offered_contract.total_collateral)
+                            .iter()
+                            .filter(|x| x.count > 0)
+                            .map(|x| {
+                                let label = if x.count == 1 {
+                                    x.start.to_string()
+                                } else {
+                                    format!("{}-{}", x.start, x.start + x.count - 1)
+                                };
+                                (label, x.payout.offer, x.payout.accept)
+                            }),
+                    );
+                }
+            }
+        }
+
+        Ok(preview)
+    }
+
+    /// Returns the CET and payouts that would result from the given outcome
+    /// being attested by a threshold of the contract oracles, without signing
+    /// or broadcasting anything.
+    pub fn simulate_outcome(
+        &self,
+        contract_id: &ContractId,
+        outcome: &Outcome,
+    ) -> Result<SimulatedClose, Error> {
+        let contract = match self.store.get_contract(contract_id)? {
+            Some(Contract::Signed(c)) | Some(Contract::Confirmed(c)) => c,
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Signed, ContractState::Confirmed],
+                    found: c.get_state(),
+                })
+            }
+        };
+        let accepted_contract = &contract.accepted_contract;
+        let offered_contract = &accepted_contract.offered_contract;
+
+        for (contract_info, adaptor_info) in offered_contract
+            .contract_info
+            .iter()
+            .zip(accepted_contract.adaptor_infos.iter())
+        {
+            let outcome_values: Vec<String> = match (&contract_info.contract_descriptor, outcome) {
+                (ContractDescriptor::Enum(e), Outcome::Enum(o)) => match e.get_attested_value(o) {
+                    Some(value) => vec![value],
+                    None => continue,
+                },
+                (ContractDescriptor::Numerical(n), Outcome::Numerical(v)) => {
+                    decompose_value(*v, n.info.base, n.info.nb_digits)
+                        .iter()
+                        .map(|x| x.to_string())
+                        .collect()
+                }
+                _ => continue,
+            };
+            let oracle_outcomes: Vec<Vec<String>> = contract_info
+                .oracle_announcements
+                .iter()
+                .take(contract_info.threshold)
+                .map(|x| {
+                    let mut values = x.oracle_event.event_descriptor.get_prefix_outcomes();
+                    values.extend(outcome_values.iter().cloned());
+                    values
+                })
+                .collect();
+            let outcomes: Vec<(usize, &Vec<String>)> = oracle_outcomes.iter().enumerate().collect();
+
+            if let Ok(Some((_, range_info))) =
+                contract_info.get_range_info_for_outcome(adaptor_info, &outcomes, 0)
+            {
+                let cet = accepted_contract.dlc_transactions.cets[range_info.cet_index].clone();
+                let get_payout = |script_pubkey| {
+                    cet.output
+                        .iter()
+                        .filter(|x| &x.script_pubkey == script_pubkey)
+                        .map(|x| x.value)
+                        .sum()
+                };
+                let (offer_script_pubkey, accept_script_pubkey) = get_cet_payout_script_pubkeys(
+                    offered_contract,
+                    &accepted_contract.accept_params,
+                );
+                let offer_payout = get_payout(&offer_script_pubkey);
+                let accept_payout = get_payout(&accept_script_pubkey);
+                return Ok(SimulatedClose {
+                    cet,
+                    offer_payout,
+                    accept_payout,
+                });
+            }
+        }
+
+        Err(Error::OutcomeNotCovered)
+    }
+
+    /// Closes the contract with given id using the refund transaction, for
+    /// example when the oracles never attested. The refund transaction is
+    /// broadcast as soon as it becomes valid regardless of the configured
+    /// refund grace period, and the contract is moved to the refunded state.
+    /// Returns an error if the refund locktime has not passed yet.
+    pub fn refund_contract(&mut self, contract_id: &ContractId) -> Result<Transaction, Error> {
+        let contract = match self.store.get_contract(contract_id)? {
+            Some(Contract::Signed(c)) | Some(Contract::Confirmed(c)) => c,
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Signed, ContractState::Confirmed],
+                    found: c.get_state(),
+                })
+            }
+        };
+
+        let lock_time = contract.accepted_contract.dlc_transactions.refund.lock_time;
+        if lock_time as u64 > self.time.unix_time_now() {
+            return Err(Error::RefundNotYetValid { lock_time });
+        }
+
+        let refund = self.get_signed_refund(&contract)?;
+        self.blockchain.send_transaction(&refund)?;
+        self.store.update_contract(&Contract::Refunded(contract))?;
+
+        Ok(refund)
+    }
+
+    /// Accelerates the confirmation of the fund transaction of the given
+    /// signed contract by spending the local change output with a child
+    /// transaction paying to a new wallet address (CPFP). The fee of the child
+    /// is chosen so that the fee rate of the fund and child transactions taken
+    /// together reaches `target_fee_rate` (in sats/vbyte), using the maximum
+    /// witness size of the inputs to estimate their weight. The child
+    /// transaction is signed, broadcast and returned. Returns an error if the
+    /// fund transaction has no P2WPKH output belonging to the wallet.
+    pub fn create_fund_cpfp(
+        &mut self,
+        contract_id: &ContractId,
+        target_fee_rate: u64,
+    ) -> Result<Transaction, Error> {
+        let contract = match self.store.get_contract(contract_id)? {
+            Some(Contract::Signed(c)) => c,
+            None => return Err(Error::UnknownContract),
+            Some(c) => {
+                return Err(Error::InvalidState {
+                    expected: vec![ContractState::Signed],
+                    found: c.get_state(),
+                })
+            }
+        };
+        let funding_sig_hash_type = self.get_funding_sig_hash_type()?;
+        let accepted_contract = &contract.accepted_contract;
+        let offered_contract = &accepted_contract.offered_contract;
+        let fund_tx = &accepted_contract.dlc_transactions.fund;
+        let change_script_pubkey = if offered_contract.is_offer_party {
+            &offered_contract.offer_params.change_script_pubkey
+        } else {
+            &accepted_contract.accept_params.change_script_pubkey
+        };
+        let (vout, change_output) = fund_tx
+            .output
+            .iter()
+            .enumerate()
+            .find(|(_, x)| &x.script_pubkey == change_script_pubkey && x.value > 0)
+            .filter(|_| change_script_pubkey.is_v0_p2wpkh())
+            .ok_or_else(|| {
+                Error::InvalidParameters(
+                    "The fund transaction has no spendable output belonging to the wallet."
+                        .to_string(),
+                )
+            })?;
+        let address = Address::from_script(change_script_pubkey, self.blockchain.get_network()?)
+            .ok_or_else(|| Error::Other("Invalid change script pubkey.".to_string()))?;
+        let sk = self.wallet.get_secret_key_for_address(&address)?;
+
+        let mut fund_input_value = 0;
+        let mut fund_witness_weight = 0;
+        for funding_input in offered_contract
+            .funding_inputs_info
+            .iter()
+            .chain(accepted_contract.funding_inputs.iter())
+            .map(|x| &x.funding_input)
+        {
+            let prev_tx = Transaction::consensus_decode(&*funding_input.prev_tx).or(Err(
+                Error::InvalidParameters(
+                    "Could not decode funding input previous tx parameter".to_string(),
+                ),
+            ))?;
+            fund_input_value += prev_tx
+                .output
+                .get(funding_input.prev_tx_vout as usize)
+                .ok_or_else(|| {
+                    Error::InvalidParameters(format!(
+                        "Previous tx output not found at index {}",
+                        funding_input.prev_tx_vout
+                    ))
+                })?
+                .value;
+            fund_witness_weight += funding_input.max_witness_len as usize + MAX_SIG_LEN_MARGIN;
+        }
+        let fund_fee = fund_input_value
+            .checked_sub(fund_tx.output.iter().map(|x| x.value).sum())
+            .ok_or_else(|| Error::Other("Fund transaction outputs exceed inputs.".to_string()))?;
+        // Two weight units account for the segwit marker and flag.
+        let fund_weight = fund_tx.get_weight() + 2 + fund_witness_weight;
+
+        let mut child = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: fund_tx.txid(),
+                    vout: vout as u32,
+                },
+                script_sig: Script::new(),
+                sequence: 0xffffffff,
+                witness: Vec::new(),
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: self.wallet.get_new_address()?.script_pubkey(),
+            }],
+        };
+        let child_weight = child.get_weight() + 2 + dlc::P2WPKH_WITNESS_SIZE + MAX_SIG_LEN_MARGIN;
+        let package_fee = (fund_weight + child_weight).div_ceil(4) as u64 * target_fee_rate;
+        let child_fee = package_fee.saturating_sub(fund_fee);
+        let child_value = change_output
+            .value
+            .checked_sub(child_fee)
+            .filter(|x| *x >= dlc::DUST_LIMIT)
+            .ok_or(Error::InsufficientFunds {
+                required: child_fee + dlc::DUST_LIMIT,
+                available: change_output.value,
+            })?;
+        child.output[0].value = child_value;
+
+        dlc::util::sign_p2wpkh_input(
+            &self.secp,
+            &sk,
+            &mut child,
+            0,
+            funding_sig_hash_type,
+            change_output.value,
+        );
+        self.blockchain.send_transaction(&child)?;
+
+        Ok(child)
+    }
+
+    /// Function to call once the CET of the contract with given id, provided
+    /// through [`ManagerEvent::CetReadyForBroadcast`], was broadcast by an
+    /// external service. The contract is then moved to the closed state. The
+    /// manager does not rebroadcast such CETs if they get reorged out. Signed
+    /// CETs waiting for broadcast are kept in the storage provider if it
+    /// supports [`Storage::upsert_contract_data`], and handed over again
+    /// through [`ManagerEvent::CetReadyForBroadcast`] after a restart.
+    /// Otherwise they are only kept in memory and signed again after a
+    /// restart.
+    pub fn notify_cet_broadcast(&mut self, contract_id: &ContractId) -> Result<(), Error> {
+        let pending = self.pending_cets.remove(contract_id).ok_or_else(|| {
+            Error::InvalidParameters("No CET is waiting to be broadcast.".to_string())
+        })?;
+
+        self.store
+            .update_contract(&Contract::Closed(pending.closed_contract))?;
+        self.store
+            .delete_contract_data(ContractDataKind::PendingCet, contract_id)
+    }
+
+    /// Returns the events generated since the last call to this function.
+    pub fn get_and_clear_pending_events(&mut self) -> Vec<ManagerEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    /// Returns a hash of the state of all the contracts known to the manager,
+    /// which changes whenever a contract is created, updated or deleted. This
+    /// makes it possible to detect changes without comparing whole contracts.
+    pub fn state_hash(&self) -> Result<[u8; 32], Error> {
+        let mut contracts = self.store.get_contracts()?;
+        contracts.sort_by_key(|c| c.get_id());
+
+        let mut engine = sha256::Hash::engine();
+        for contract in &contracts {
+            let (state, data) = serialize_contract(contract)?;
+            engine.input(&contract.get_id());
+            engine.input(&[state]);
+            engine.input(&(data.len() as u64).to_be_bytes());
+            engine.input(&data);
+        }
+
+        Ok(sha256::Hash::from_engine(engine).into_inner())
+    }
+
+    /// Serializes the state of the manager, to back it up or move it to
+    /// another storage provider using [`Manager::import_state`]. The state
+    /// includes all the contracts known to the manager, the data it keeps
+    /// about them (see [`ContractDataKind`]), including the data only kept in
+    /// memory when the storage provider does not support
+    /// [`Storage::upsert_contract_data`], and the ids of the paused contracts.
+    /// The data starts with a [`STATE_FORMAT_VERSION`] byte.
+    pub fn export_state(&self) -> Result<Vec<u8>, Error> {
+        let mut contracts = self.store.get_contracts()?;
+        contracts.sort_by_key(|c| c.get_id());
+
+        let mut res = vec![STATE_FORMAT_VERSION];
+        res.extend_from_slice(&(contracts.len() as u64).to_be_bytes());
+        for contract in &contracts {
+            let (state, data) = serialize_contract(contract)?;
+            res.push(state);
+            res.extend_from_slice(&(data.len() as u64).to_be_bytes());
+            res.extend_from_slice(&data);
+        }
+
+        let contract_data = self.get_exported_contract_data()?;
+        res.extend_from_slice(&(contract_data.len() as u64).to_be_bytes());
+        for (kind, id, data) in &contract_data {
+            res.push(*kind as u8);
+            res.extend_from_slice(id);
+            res.extend_from_slice(&(data.len() as u64).to_be_bytes());
+            res.extend_from_slice(data);
+        }
+
+        let mut paused: Vec<_> = self.get_paused_contracts()?.into_iter().collect();
+        paused.sort();
+        res.extend_from_slice(&(paused.len() as u64).to_be_bytes());
+        for id in &paused {
+            res.extend_from_slice(id);
+        }
+
+        Ok(res)
+    }
+
+    /// Returns the serialized data of every kind kept by the manager, sorted
+    /// by kind and id.
+    fn get_exported_contract_data(
+        &self,
+    ) -> Result<Vec<(ContractDataKind, ContractId, Vec<u8>)>, Error> {
+        let mut res = Vec::new();
+        serialize_contract_data(
+            ContractDataKind::FundingConfirmationHeight,
+            &self.funding_confirmation_heights,
+            &mut res,
+        )?;
+        serialize_contract_data(
+            ContractDataKind::FundingBroadcastTime,
+            &self.funding_broadcast_times,
+            &mut res,
+        )?;
+        serialize_contract_data(
+            ContractDataKind::BroadcastCet,
+            &self.broadcast_cets,
+            &mut res,
+        )?;
+        serialize_contract_data(ContractDataKind::PendingCet, &self.pending_cets, &mut res)?;
+        serialize_contract_data(
+            ContractDataKind::SentCloseOffer,
+            &self.sent_close_offers,
+            &mut res,
+        )?;
+        serialize_contract_data(
+            ContractDataKind::ReceivedCloseOffer,
+            &self.received_close_offers,
+            &mut res,
+        )?;
+        serialize_contract_data(
+            ContractDataKind::ReservedUtxo,
+            &self.reserved_utxos,
+            &mut res,
+        )?;
+        for ((counter_party, contract_id), buffered) in &self.early_sign_messages {
+            res.push((
+                ContractDataKind::EarlySignMessage,
+                get_early_sign_message_key(counter_party, contract_id),
+                buffered.serialize()?,
+            ));
+        }
+        for ((oracle_public_key, event_id), attestation) in &self.attestations {
+            let cached = CachedAttestation {
+                event_id: event_id.clone(),
+                attestation: attestation.clone(),
+            };
+            res.push((
+                ContractDataKind::ValidatedAttestation,
+                get_attestation_key(oracle_public_key, event_id),
+                cached.serialize()?,
+            ));
+        }
+        res.sort_by_key(|(kind, id, _)| (*kind as u8, *id));
+        Ok(res)
+    }
+
+    /// Stores the state serialized by [`Manager::export_state`] into the
+    /// given storage provider, which a new manager can then be created with.
+    /// Contracts already in the storage provider are overwritten, along with
+    /// their data and paused state. The storage provider must support
+    /// [`Storage::upsert_contract_data`] and [`Storage::set_contract_paused`]
+    /// if the state includes contract data or paused contracts, and
+    /// [`Error::Unsupported`] is returned otherwise. Data produced by
+    /// previous versions of the format is also accepted, the first one only
+    /// including the contracts. Returns [`Error::UnsupportedVersion`] if the
+    /// data was produced using a newer version of the format.
+    pub fn import_state(storage: &mut S::Target, bytes: &[u8]) -> Result<(), Error> {
+        fn invalid_data() -> Error {
+            Error::InvalidParameters("Invalid state data.".to_string())
+        }
+        fn read_u64(bytes: &[u8], pos: &mut usize) -> Result<u64, Error> {
+            let end = pos.checked_add(8).ok_or_else(invalid_data)?;
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(bytes.get(*pos..end).ok_or_else(invalid_data)?);
+            *pos = end;
+            Ok(u64::from_be_bytes(buf))
+        }
+        fn read_id(bytes: &[u8], pos: &mut usize) -> Result<ContractId, Error> {
+            let end = pos.checked_add(32).ok_or_else(invalid_data)?;
+            let mut id = [0u8; 32];
+            id.copy_from_slice(bytes.get(*pos..end).ok_or_else(invalid_data)?);
+            *pos = end;
+            Ok(id)
+        }
+
+        let version = *bytes.first().ok_or_else(invalid_data)?;
+        if version == 0 || version > STATE_FORMAT_VERSION {
+            return Err(Error::UnsupportedVersion);
+        }
+        let mut pos = 1;
+        let nb_contracts = read_u64(bytes, &mut pos)?;
+        let mut contracts = Vec::new();
+        for _ in 0..nb_contracts {
+            let state = *bytes.get(pos).ok_or_else(invalid_data)?;
+            pos += 1;
+            let len = read_u64(bytes, &mut pos)? as usize;
+            let end = pos.checked_add(len).ok_or_else(invalid_data)?;
+            let data = bytes.get(pos..end).ok_or_else(invalid_data)?;
+            pos = end;
+            contracts.push(deserialize_contract(state, data)?);
+        }
+        let mut contract_data = Vec::new();
+        let mut paused = HashSet::new();
+        if version > 1 {
+            let nb_data = read_u64(bytes, &mut pos)?;
+            for _ in 0..nb_data {
+                let kind_value = *bytes.get(pos).ok_or_else(invalid_data)?;
+                pos += 1;
+                let kind = CONTRACT_DATA_KINDS
+                    .iter()
+                    .find(|x| **x as u8 == kind_value)
+                    .ok_or_else(invalid_data)?;
+                let id = read_id(bytes, &mut pos)?;
+                let len = read_u64(bytes, &mut pos)? as usize;
+                let end = pos.checked_add(len).ok_or_else(invalid_data)?;
+                let data = bytes.get(pos..end).ok_or_else(invalid_data)?;
+                pos = end;
+                contract_data.push((*kind, id, data));
+            }
+            let nb_paused = read_u64(bytes, &mut pos)?;
+            for _ in 0..nb_paused {
+                paused.insert(read_id(bytes, &mut pos)?);
+            }
+        }
+        if pos != bytes.len() {
+            return Err(invalid_data());
+        }
+
+        for contract in contracts {
+            let id = contract.get_id();
+            for kind in CONTRACT_DATA_KINDS.iter() {
+                storage.delete_contract_data(*kind, &id)?;
+            }
+            if !paused.contains(&id) {
+                match storage.set_contract_paused(&id, false) {
+                    Err(Error::Unsupported) => {}
+                    res => res?,
+                }
+            }
+            let temporary_id = contract.get_temporary_id();
+            storage.delete_contract(&id)?;
+            storage.delete_contract(&temporary_id)?;
+            storage.create_contract(contract.get_offered_contract())?;
+            if !matches!(contract, Contract::Offered(_)) {
+                storage.update_contract(&contract)?;
+                if id != temporary_id {
+                    storage.delete_contract(&temporary_id)?;
+                }
+            }
+        }
+        for (kind, id, data) in contract_data {
+            storage.upsert_contract_data(kind, &id, data)?;
+        }
+        for id in paused {
+            storage.set_contract_paused(&id, true)?;
+        }
+
+        Ok(())
+    }
 
-        let mut fund_tx = accepted_contract.dlc_transactions.fund.clone();
+    /// Function to call to check the state of the currently executing DLCs and
+    /// update them if possible, including applying buffered sign messages (see
+    /// [`ManagerConfig::buffer_early_sign_messages`]). Returns the actions that
+    /// were taken.
+    pub fn periodic_check(&mut self) -> Result<Vec<ManagerAction>, Error> {
+        let mut actions = Vec::new();
+        let paused = self.get_paused_contracts()?;
+        self.evict_early_sign_messages()?;
+        let buffered: Vec<ContractId> = self
+            .early_sign_messages
+            .keys()
+            .map(|(_, contract_id)| *contract_id)
+            .filter(|x| !paused.contains(x))
+            .collect();
+        for contract_id in buffered {
+            self.apply_early_sign_message(&contract_id);
+        }
+        self.check_signed_contracts(&paused, &mut actions)?;
+        self.check_confirmed_contracts(&paused, &mut actions)?;
+        self.check_broadcast_cets(&paused);
+        self.evict_attestations()?;
+        self.prune_expired_announcements()?;
 
-        for (funding_input, funding_signatures) in offered_contract
-            .funding_inputs_info
-            .iter()
-            .zip(sign_message.funding_signatures.funding_signatures.iter())
-        {
-            let input_index = input_serials
-                .iter()
-                .position(|x| x == &funding_input.funding_input.input_serial_id)
-                .ok_or(Error::InvalidState)?;
+        Ok(actions)
+    }
 
-            fund_tx.input[input_index].witness = funding_signatures
-                .witness_elements
-                .iter()
-                .map(|x| x.witness.clone())
-                .collect();
+    /// Removes the oracle announcements of the contracts that reached a
+    /// terminal state, that is that were closed, refunded, rejected or failed,
+    /// once the events of all the announcements of one of their contract infos
+    /// matured more than [`ManagerConfig::announcement_expiry`] ago. The
+    /// announcements of pending and executing contracts are kept. Returns the
+    /// number of removed announcements, which is zero if no expiry is
+    /// configured.
+    ///
+    /// Contracts whose announcements were removed are not returned by
+    /// [`Manager::contracts_for_event`] anymore, and
+    /// [`Contract::to_audit_record`] fails for them.
+    pub fn prune_expired_announcements(&mut self) -> Result<usize, Error> {
+        let expiry = match self.config.announcement_expiry {
+            Some(expiry) => expiry.as_secs(),
+            None => return Ok(0),
+        };
+        let now = self.time.unix_time_now();
+        let mut pruned = 0;
+        for mut contract in self.store.get_contracts()? {
+            let offered_contract = match &mut contract {
+                Contract::Closed(c) => &mut c.signed_contract.accepted_contract.offered_contract,
+                Contract::CollaborativelyClosed(c) => {
+                    &mut c.signed_contract.accepted_contract.offered_contract
+                }
+                Contract::Refunded(s) => &mut s.accepted_contract.offered_contract,
+                Contract::Rejected(o) => o,
+                Contract::FailedAccept(f) => &mut f.offered_contract,
+                Contract::FailedSign(f) => &mut f.accepted_contract.offered_contract,
+                _ => continue,
+            };
+            let mut updated = false;
+            for contract_info in &mut offered_contract.contract_info {
+                let announcements = &mut contract_info.oracle_announcements;
+                if announcements.is_empty()
+                    || announcements.iter().any(|x| {
+                        (x.oracle_event.event_maturity_epoch as u64).saturating_add(expiry) >= now
+                    })
+                {
+                    continue;
+                }
+                pruned += announcements.len();
+                announcements.clear();
+                updated = true;
+            }
+            if updated {
+                self.store.update_contract(&contract)?;
+            }
         }
+        Ok(pruned)
+    }
 
-        for funding_input_info in &accepted_contract.funding_inputs {
-            let input_index = input_serials
-                .iter()
-                .position(|x| x == &funding_input_info.funding_input.input_serial_id)
-                .ok_or(Error::InvalidState)?;
-            let address = funding_input_info
-                .address
-                .as_ref()
-                .ok_or(Error::InvalidState)?;
-            let sk = self.wallet.get_secret_key_for_address(&address)?;
-            let tx = Transaction::consensus_decode(&*funding_input_info.funding_input.prev_tx).or(
-                Err(Error::InvalidParameters(
-                    "Could not decode funding input previous tx parameter".to_string(),
-                )),
-            )?;
-            let vout = funding_input_info.funding_input.prev_tx_vout;
-            let tx_out = tx
-                .output
-                .get(vout as usize)
-                .ok_or(Error::InvalidParameters(format!(
-                    "Previous tx output not found at index {}",
-                    vout
-                )))?;
-            dlc::util::sign_p2wpkh_input(
-                &self.secp,
-                &sk,
-                &mut fund_tx,
-                input_index,
-                bitcoin::SigHashType::All,
-                tx_out.value,
-            );
+    /// Pauses the contract with given id: [`Manager::periodic_check`] skips
+    /// it and [`Manager::on_dlc_message`] returns [`Error::ContractPaused`]
+    /// for messages related to it, until [`Manager::resume_contract`] is
+    /// called. Other contracts are not affected. The paused state is kept in
+    /// the storage provider.
+    pub fn pause_contract(&mut self, contract_id: &ContractId) -> Result<(), Error> {
+        if self.store.get_contract(contract_id)?.is_none() {
+            return Err(Error::UnknownContract);
         }
+        self.store.set_contract_paused(contract_id, true)
+    }
 
-        let signed_contract = SignedContract {
-            accepted_contract,
-            adaptor_signatures: Some(adaptor_signatures),
-            offer_refund_signature: sign_message.refund_signature,
-            funding_signatures: sign_message.funding_signatures.clone(),
-        };
+    /// Resumes the processing of a contract paused using
+    /// [`Manager::pause_contract`].
+    pub fn resume_contract(&mut self, contract_id: &ContractId) -> Result<(), Error> {
+        self.store.set_contract_paused(contract_id, false)
+    }
 
-        self.store
-            .update_contract(&Contract::Signed(signed_contract))?;
+    fn get_paused_contracts(&self) -> Result<HashSet<ContractId>, Error> {
+        Ok(self.store.get_paused_contracts()?.into_iter().collect())
+    }
 
-        self.blockchain.send_transaction(&fund_tx)?;
+    /// Rebroadcasts the CETs that were seen in the chain but disappeared
+    /// before getting [`NB_CONFIRMATIONS`] confirmations, which happens when
+    /// their block gets reorged out. CETs are tracked in the storage provider
+    /// if it supports [`Storage::upsert_contract_data`], and in memory only
+    /// otherwise, in which case tracking stops if the manager is restarted.
+    fn check_broadcast_cets(&mut self, paused: &HashSet<ContractId>) {
+        let mut buried = Vec::new();
+        let mut updated = Vec::new();
+        for (contract_id, broadcast) in self.broadcast_cets.iter_mut() {
+            if paused.contains(contract_id) {
+                continue;
+            }
+            let txid = broadcast.cet.txid();
+            let confirmations = match self.wallet.get_transaction_confirmations(&txid) {
+                Ok(confirmations) => confirmations,
+                Err(e) => {
+                    error!("Error checking confirmations of CET {}: {}", txid, e);
+                    continue;
+                }
+            };
+            if confirmations >= NB_CONFIRMATIONS {
+                buried.push(*contract_id);
+            } else if confirmations > 0 {
+                if !broadcast.seen_in_chain {
+                    broadcast.seen_in_chain = true;
+                    updated.push(*contract_id);
+                }
+            } else if broadcast.seen_in_chain {
+                warn!("CET {} disappeared from the chain, rebroadcasting it", txid);
+                match self.blockchain.send_transaction(&broadcast.cet) {
+                    Ok(()) => {
+                        broadcast.seen_in_chain = false;
+                        updated.push(*contract_id);
+                    }
+                    Err(e) => error!("Error rebroadcasting CET {}: {}", txid, e),
+                }
+            }
+        }
 
-        Ok(())
+        for contract_id in updated {
+            if let Err(e) = self.store_broadcast_cet(&contract_id) {
+                error!("Error storing broadcast CET: {}", e);
+            }
+        }
+        for contract_id in buried {
+            self.broadcast_cets.remove(&contract_id);
+            if let Err(e) = self
+                .store
+                .delete_contract_data(ContractDataKind::BroadcastCet, &contract_id)
+            {
+                error!("Error deleting broadcast CET: {}", e);
+            }
+        }
     }
 
-    fn sign_fail_on_error<R>(
+    /// Writes the tracked broadcast CET of the contract with given id to the
+    /// storage provider.
+    fn store_broadcast_cet(&mut self, contract_id: &ContractId) -> Result<(), Error> {
+        let data = match self.broadcast_cets.get(contract_id) {
+            Some(broadcast) => broadcast.serialize()?,
+            None => return Ok(()),
+        };
+        match self
+            .store
+            .upsert_contract_data(ContractDataKind::BroadcastCet, contract_id, &data)
+        {
+            Err(Error::Unsupported) => Ok(()),
+            res => res,
+        }
+    }
+
+    fn check_signed_contract(
         &mut self,
-        accepted_contract: &AcceptedContract,
-        sign_message: &SignDlc,
-        result: Result<R, dlc::Error>,
-    ) -> Result<R, Error> {
-        match result {
-            Err(e) => {
-                error!("Error in on_sign {}", e);
-                self.store
-                    .update_contract(&Contract::FailedSign(FailedSignContract {
-                        accepted_contract: accepted_contract.clone(),
-                        sign_message: sign_message.clone(),
-                        error_message: e.to_string(),
-                    }))?;
-                Err(e.into())
+        contract: &SignedContract,
+        actions: &mut Vec<ManagerAction>,
+    ) -> Result<(), Error> {
+        let dlc_transactions = &contract.accepted_contract.dlc_transactions;
+        let fund_outpoint = OutPoint {
+            txid: dlc_transactions.fund.txid(),
+            vout: dlc_transactions.get_fund_output_index() as u32,
+        };
+        let confirmations = match self.blockchain.get_utxo_proof(&fund_outpoint) {
+            Ok(proof) => {
+                if &proof.tx_out != dlc_transactions.get_fund_output() {
+                    return Err(Error::UtxoProofMismatch);
+                }
+                proof.confirmations
             }
-            Ok(val) => Ok(val),
+            Err(Error::Unsupported) => self
+                .wallet
+                .get_transaction_confirmations(&fund_outpoint.txid)?,
+            Err(e) => return Err(e),
+        };
+        if confirmations >= NB_CONFIRMATIONS {
+            self.store
+                .update_contract(&Contract::Confirmed(contract.clone()))?;
+            let contract_id = contract.accepted_contract.get_contract_id();
+            self.clear_funding_broadcast_time(&contract_id)?;
+            actions.push(ManagerAction::ContractConfirmed(contract_id));
         }
+        Ok(())
     }
 
-    fn accept_fail_on_error<R>(
-        &mut self,
-        offered_contract: &OfferedContract,
-        accept_message: &AcceptDlc,
-        result: Result<R, dlc::Error>,
-    ) -> Result<R, Error> {
-        match result {
-            Err(e) => {
-                error!("Error in on_accept {}", e);
+    /// Checks the on-chain state of the transactions of all signed and
+    /// confirmed contracts, updating the stored state of the contracts whose
+    /// fund, CET or refund transaction got confirmed. Useful after the manager
+    /// was offline for a long period. The refund and fund transactions are
+    /// checked first, the CETs, of which there can be many, being only looked
+    /// up once the fund output is spent, or confirmed if the blockchain
+    /// provider does not support [`Blockchain::is_output_spent`]. Note that
+    /// the attestations of contracts closed this way are not available.
+    pub fn reconcile_with_chain(&mut self) -> Result<ReconcileReport, Error> {
+        let mut report = ReconcileReport::default();
+        for contract in self.store.get_contracts()? {
+            let (signed_contract, is_confirmed) = match contract {
+                Contract::Signed(s) => (s, false),
+                Contract::Confirmed(s) => (s, true),
+                _ => continue,
+            };
+            let contract_id = signed_contract.accepted_contract.get_contract_id();
+            let dlc_transactions = &signed_contract.accepted_contract.dlc_transactions;
+
+            if self
+                .wallet
+                .get_transaction_confirmations(&dlc_transactions.refund.txid())?
+                > 0
+            {
                 self.store
-                    .update_contract(&Contract::FailedAccept(FailedAcceptContract {
-                        offered_contract: offered_contract.clone(),
-                        accept_message: accept_message.clone(),
-                        error_message: e.to_string(),
+                    .update_contract(&Contract::Refunded(signed_contract))?;
+                report.refunded.push(contract_id);
+                continue;
+            }
+
+            let fund_confirmations = self
+                .wallet
+                .get_transaction_confirmations(&dlc_transactions.fund.txid())?;
+            let fund_spent = fund_confirmations > 0
+                && match self.blockchain.is_output_spent(&OutPoint {
+                    txid: dlc_transactions.fund.txid(),
+                    vout: dlc_transactions.get_fund_output_index() as u32,
+                }) {
+                    Ok(spent) => spent,
+                    Err(Error::Unsupported) => true,
+                    Err(e) => return Err(e),
+                };
+
+            let mut cet_index = None;
+            if fund_spent {
+                for (i, cet) in dlc_transactions.cets.iter().enumerate() {
+                    if self.wallet.get_transaction_confirmations(&cet.txid())? > 0 {
+                        cet_index = Some(i);
+                        break;
+                    }
+                }
+            }
+
+            if let Some(cet_index) = cet_index {
+                self.store
+                    .update_contract(&Contract::Closed(ClosedContract {
+                        signed_contract,
+                        attestations: Vec::new(),
+                        cet_index,
                     }))?;
-                Err(e.into())
+                report.closed.push(contract_id);
+            } else if !is_confirmed && fund_confirmations >= NB_CONFIRMATIONS {
+                self.store
+                    .update_contract(&Contract::Confirmed(signed_contract))?;
+                self.clear_funding_broadcast_time(&contract_id)?;
+                report.confirmed.push(contract_id);
             }
-            Ok(val) => Ok(val),
         }
-    }
-
-    /// Function to call to check the state of the currently executing DLCs and
-    /// update them if possible.
-    pub fn periodic_check(&mut self) -> Result<(), Error> {
-        self.check_signed_contracts()?;
-        self.check_confirmed_contracts()?;
 
-        Ok(())
+        Ok(report)
     }
 
-    fn check_signed_contract(&mut self, contract: &SignedContract) -> Result<(), Error> {
-        let confirmations = self.wallet.get_transaction_confirmations(
-            &contract.accepted_contract.dlc_transactions.fund.txid(),
-        )?;
-        if confirmations >= NB_CONFIRMATIONS {
-            self.store
-                .update_contract(&Contract::Confirmed(contract.clone()))?;
+    /// Checks the fund transaction of the signed and confirmed contracts, to
+    /// call after a restart. Fund transactions that the wallet does not see
+    /// confirmed are broadcast again in case they were dropped, which only the
+    /// accept party, who broadcasts them in the first place, can do. Signed
+    /// contracts whose fund transaction reached [`NB_CONFIRMATIONS`]
+    /// confirmations are moved to the confirmed state. Calling this function
+    /// repeatedly is harmless.
+    pub fn resync_on_startup(&mut self) -> Result<(), Error> {
+        for contract in self.store.get_contracts()? {
+            let (signed_contract, is_confirmed) = match contract {
+                Contract::Signed(s) => (s, false),
+                Contract::Confirmed(s) => (s, true),
+                _ => continue,
+            };
+            let contract_id = signed_contract.accepted_contract.get_contract_id();
+            let fund_txid = signed_contract
+                .accepted_contract
+                .dlc_transactions
+                .fund
+                .txid();
+            let confirmations = self.wallet.get_transaction_confirmations(&fund_txid)?;
+
+            if confirmations == 0 {
+                if signed_contract
+                    .accepted_contract
+                    .offered_contract
+                    .is_offer_party
+                {
+                    continue;
+                }
+                let fund_tx = self.get_signed_fund_tx(&signed_contract)?;
+                // Fails if the transaction is still in the mempool.
+                if let Err(e) = self.blockchain.send_transaction(&fund_tx) {
+                    warn!(
+                        "Could not rebroadcast fund transaction of contract {}: {}",
+                        signed_contract.accepted_contract.get_contract_id_string(),
+                        e
+                    );
+                }
+                self.set_funding_broadcast_time(&contract_id)?;
+            } else if !is_confirmed && confirmations >= NB_CONFIRMATIONS {
+                self.store
+                    .update_contract(&Contract::Confirmed(signed_contract))?;
+                self.clear_funding_broadcast_time(&contract_id)?;
+            }
         }
+
         Ok(())
     }
 
-    fn check_signed_contracts(&mut self) -> Result<(), Error> {
+    fn check_signed_contracts(
+        &mut self,
+        paused: &HashSet<ContractId>,
+        actions: &mut Vec<ManagerAction>,
+    ) -> Result<(), Error> {
         for c in self.store.get_signed_contracts()? {
-            match self.check_signed_contract(&c) {
+            if paused.contains(&c.accepted_contract.get_contract_id()) {
+                continue;
+            }
+            match self.check_signed_contract(&c, actions) {
                 Err(e) => error!(
                     "Error checking confirmed contract {}: {}",
                     c.accepted_contract.get_contract_id_string(),
@@ -807,9 +4080,16 @@ where
         Ok(())
     }
 
-    fn check_confirmed_contracts(&mut self) -> Result<(), Error> {
+    fn check_confirmed_contracts(
+        &mut self,
+        paused: &HashSet<ContractId>,
+        actions: &mut Vec<ManagerAction>,
+    ) -> Result<(), Error> {
         for c in self.store.get_confirmed_contracts()? {
-            match self.check_confirmed_contract(&c) {
+            if paused.contains(&c.accepted_contract.get_contract_id()) {
+                continue;
+            }
+            match self.check_confirmed_contract(&c, actions) {
                 Err(e) => error!(
                     "Error checking confirmed contract {}: {}",
                     c.accepted_contract.get_contract_id_string(),
@@ -822,8 +4102,20 @@ where
         Ok(())
     }
 
-    fn check_confirmed_contract(&mut self, contract: &SignedContract) -> Result<(), Error> {
+    fn check_confirmed_contract(
+        &mut self,
+        contract: &SignedContract,
+        actions: &mut Vec<ManagerAction>,
+    ) -> Result<(), Error> {
+        if self
+            .pending_cets
+            .contains_key(&contract.accepted_contract.get_contract_id())
+        {
+            return Ok(());
+        }
+
         let contract_infos = &contract.accepted_contract.offered_contract.contract_info;
+        let mut threshold_met = false;
         for (contract_info, adaptor_info) in contract_infos
             .iter()
             .zip(contract.accepted_contract.adaptor_infos.iter())
@@ -837,24 +4129,36 @@ where
                 .enumerate()
                 .collect();
             if matured.len() >= contract_info.threshold {
-                let attestations: Vec<_> = matured
+                let mut attestations = Vec::new();
+                let mut missing = Vec::new();
+                for (i, announcement) in &matured {
+                    match self.get_validated_attestation(announcement) {
+                        Some(attestation) => attestations.push((*i, attestation)),
+                        None => missing.push(*announcement),
+                    }
+                }
+                let outcomes: Vec<(usize, &Vec<String>)> = attestations
                     .iter()
-                    .filter_map(|(i, announcement)| {
-                        let oracle = self.oracles.get(&announcement.oracle_public_key)?;
-                        Some((
-                            *i,
-                            oracle
-                                .get_attestation(&announcement.oracle_event.event_id)
-                                .ok()?,
-                        ))
-                    })
+                    .map(|(i, x)| (*i, &x.outcomes))
                     .collect();
-                if attestations.len() >= contract_info.threshold {
+                threshold_met |= attestations.len() >= contract_info.threshold;
+                if attestations.len() < contract_info.threshold {
+                    let contract_id = contract.accepted_contract.get_contract_id();
+                    for announcement in missing {
+                        self.notify_missing_attestation(contract_id, announcement);
+                    }
+                } else if contract_info.is_outcome_out_of_range(&outcomes) {
+                    warn!(
+                        "Attested outcome is not covered by contract {}, waiting for refund",
+                        contract.accepted_contract.get_contract_id_string()
+                    );
+                } else {
                     match self.try_close_contract(
                         contract,
                         contract_info,
                         adaptor_info,
                         &attestations,
+                        actions,
                     ) {
                         Ok(()) => return Ok(()),
                         Err(e) => {
@@ -870,17 +4174,163 @@ where
             }
         }
 
-        self.check_refund(contract)?;
+        let deadline_passed = match self.config.attestation_deadline {
+            Some(deadline) => {
+                contract
+                    .accepted_contract
+                    .offered_contract
+                    .contract_maturity_bound as u64
+                    + deadline.as_secs()
+                    <= self.time.unix_time_now()
+            }
+            None => false,
+        };
+        self.check_refund(contract, !threshold_met && deadline_passed, actions)?;
+
+        Ok(())
+    }
+
+    /// Checks that the given attestation was produced by the oracle of the
+    /// given announcement using the announced nonces.
+    fn validate_attestation(
+        &self,
+        announcement: &OracleAnnouncement,
+        attestation: &OracleAttestation,
+    ) -> Result<(), Error> {
+        let nonces = &announcement.oracle_event.oracle_nonces;
+        if attestation.outcomes.len() != attestation.signatures.len()
+            || attestation.signatures.len() > nonces.len()
+        {
+            return Err(Error::OracleMismatch);
+        }
+        for ((outcome, signature), nonce) in attestation
+            .outcomes
+            .iter()
+            .zip(attestation.signatures.iter())
+            .zip(nonces.iter())
+        {
+            let (sig_nonce, _) = dlc::secp_utils::schnorrsig_decompose(signature)?;
+            let msg = Message::from_hashed_data::<sha256::Hash>(outcome.as_bytes());
+            if &sig_nonce != nonce
+                || self
+                    .secp
+                    .schnorrsig_verify(signature, &msg, &announcement.oracle_public_key)
+                    .is_err()
+            {
+                return Err(Error::OracleMismatch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the attestation for the event of the given announcement. It is
+    /// fetched from the oracle and validated the first time it is requested,
+    /// and reused afterwards so that contracts referencing the same event do
+    /// not fetch and validate it again. Invalid attestations are discarded.
+    /// Cached attestations are evicted once all the contracts referencing
+    /// their event are closed.
+    fn get_validated_attestation(
+        &mut self,
+        announcement: &OracleAnnouncement,
+    ) -> Option<OracleAttestation> {
+        let key = (
+            announcement.oracle_public_key,
+            announcement.oracle_event.event_id.clone(),
+        );
+        if let Some(attestation) = self.attestations.get(&key) {
+            return Some(attestation.clone());
+        }
+        let attestation = self
+            .get_announcement_oracle(announcement)?
+            .get_attestation(&key.1)
+            .ok()?;
+        if let Err(e) = self.validate_attestation(announcement, &attestation) {
+            warn!("Discarding attestation for event {}: {}", key.1, e);
+            return None;
+        }
+        if let Some(hook) = &self.attestation_validated_hook {
+            hook(&key.0, &key.1);
+        }
+        let cached = CachedAttestation {
+            event_id: key.1.clone(),
+            attestation: attestation.clone(),
+        };
+        if let Err(e) = self.upsert_contract_data(
+            ContractDataKind::ValidatedAttestation,
+            &get_attestation_key(&key.0, &key.1),
+            &cached,
+        ) {
+            error!("Error storing attestation for event {}: {}", key.1, e);
+        }
+        self.attestations.insert(key, attestation.clone());
+        Some(attestation)
+    }
 
+    /// Removes from the cache the attestations of the events that are not
+    /// referenced by any signed or confirmed contract anymore.
+    fn evict_attestations(&mut self) -> Result<(), Error> {
+        if self.attestations.is_empty() {
+            return Ok(());
+        }
+        let mut referenced = HashSet::new();
+        let contracts = self
+            .store
+            .get_signed_contracts()?
+            .into_iter()
+            .chain(self.store.get_confirmed_contracts()?);
+        for contract in contracts {
+            let offered_contract = &contract.accepted_contract.offered_contract;
+            for contract_info in &offered_contract.contract_info {
+                for announcement in &contract_info.oracle_announcements {
+                    referenced.insert((
+                        announcement.oracle_public_key,
+                        announcement.oracle_event.event_id.clone(),
+                    ));
+                }
+            }
+        }
+        let evicted: Vec<_> = self
+            .attestations
+            .keys()
+            .filter(|key| !referenced.contains(*key))
+            .cloned()
+            .collect();
+        for (oracle_public_key, event_id) in evicted {
+            self.store.delete_contract_data(
+                ContractDataKind::ValidatedAttestation,
+                &get_attestation_key(&oracle_public_key, &event_id),
+            )?;
+            self.attestations.remove(&(oracle_public_key, event_id));
+        }
         Ok(())
     }
 
+    fn notify_missing_attestation(
+        &mut self,
+        contract_id: ContractId,
+        announcement: &OracleAnnouncement,
+    ) {
+        let key = (
+            contract_id,
+            announcement.oracle_public_key,
+            announcement.oracle_event.event_id.clone(),
+        );
+        if self.missing_attestations.insert(key) {
+            self.pending_events.push(ManagerEvent::AttestationMissing {
+                contract_id,
+                event_id: announcement.oracle_event.event_id.clone(),
+                oracle: announcement.oracle_public_key,
+            });
+        }
+    }
+
     fn try_close_contract(
         &mut self,
         contract: &SignedContract,
         contract_info: &ContractInfo,
         adaptor_info: &AdaptorInfo,
         attestations: &[(usize, OracleAttestation)],
+        actions: &mut Vec<ManagerAction>,
     ) -> Result<(), Error> {
         let offered_contract = &contract.accepted_contract.offered_contract;
         let outcomes = attestations
@@ -899,10 +4349,8 @@ where
             let mut cet =
                 contract.accepted_contract.dlc_transactions.cets[range_info.cet_index].clone();
 
-            let confirmations = self
-                .wallet
-                .get_transaction_confirmations(&cet.txid())
-                .unwrap();
+            let txid = cet.txid();
+            let confirmations = self.wallet.get_transaction_confirmations(&txid).unwrap();
 
             if confirmations < 1 {
                 let (adaptor_sigs, fund_pubkey, other_pubkey) = if offered_contract.is_offer_party {
@@ -925,29 +4373,51 @@ where
 
                 let funding_sk = self.wallet.get_secret_key_for_pubkey(fund_pubkey)?;
 
-                dlc::sign_cet(
+                dlc::sign_cet_with_sig_hash_type(
                     &self.secp,
                     &mut cet,
                     &adaptor_sigs[range_info.adaptor_index],
                     &sigs,
                     &funding_sk,
                     other_pubkey,
-                    &contract
-                        .accepted_contract
-                        .dlc_transactions
-                        .funding_script_pubkey,
-                    contract
-                        .accepted_contract
-                        .dlc_transactions
-                        .get_fund_output()
-                        .value,
+                    &contract.accepted_contract.get_cet_sig_hash_params(),
                 )?;
 
+                if self.config.cet_broadcast_mode == BroadcastMode::Manual {
+                    let contract_id = contract.accepted_contract.get_contract_id();
+                    let pending = PendingCet {
+                        cet: cet.clone(),
+                        closed_contract: ClosedContract {
+                            signed_contract: contract.clone(),
+                            attestations: attestations.iter().map(|x| x.1.clone()).collect(),
+                            cet_index: range_info.cet_index,
+                        },
+                    };
+                    self.upsert_contract_data(
+                        ContractDataKind::PendingCet,
+                        &contract_id,
+                        &pending,
+                    )?;
+                    self.pending_cets.insert(contract_id, pending);
+                    self.pending_events
+                        .push(ManagerEvent::CetReadyForBroadcast { contract_id, cet });
+                    return Ok(());
+                }
+
                 // TODO(tibo): if this fails because another tx is already in
                 // mempool or blockchain, we might have been cheated. There is
                 // not much to be done apart from possibly extracting a fraud
                 // proof but ideally it should be handled.
                 self.blockchain.send_transaction(&cet)?;
+                let contract_id = contract.accepted_contract.get_contract_id();
+                self.broadcast_cets.insert(
+                    contract_id,
+                    BroadcastCet {
+                        cet,
+                        seen_in_chain: false,
+                    },
+                );
+                self.store_broadcast_cet(&contract_id)?;
             }
 
             let closed_contract = ClosedContract {
@@ -958,58 +4428,79 @@ where
 
             self.store
                 .update_contract(&Contract::Closed(closed_contract))?;
+            actions.push(ManagerAction::ClosedByOutcome {
+                contract_id: contract.accepted_contract.get_contract_id(),
+                txid,
+            });
         }
 
         Ok(())
     }
 
-    fn check_refund(&mut self, contract: &SignedContract) -> Result<(), Error> {
+    fn check_refund(
+        &mut self,
+        contract: &SignedContract,
+        skip_grace_period: bool,
+        actions: &mut Vec<ManagerAction>,
+    ) -> Result<(), Error> {
+        let grace_period = if skip_grace_period {
+            0
+        } else {
+            self.config.refund_grace_period.as_secs()
+        };
         // TODO(tibo): should check for confirmation of refund before updating state
-        if contract.accepted_contract.dlc_transactions.refund.lock_time as u64
+        if contract.accepted_contract.dlc_transactions.refund.lock_time as u64 + grace_period
             <= self.time.unix_time_now()
         {
-            let offered_contract = &contract.accepted_contract.offered_contract;
-            let accepted_contract = &contract.accepted_contract;
-            let mut refund = accepted_contract.dlc_transactions.refund.clone();
+            let refund = &contract.accepted_contract.dlc_transactions.refund;
             let confirmations = self.wallet.get_transaction_confirmations(&refund.txid())?;
             if confirmations == 0 {
-                let funding_script_pubkey =
-                    &accepted_contract.dlc_transactions.funding_script_pubkey;
-                let fund_output_value = accepted_contract.dlc_transactions.get_fund_output().value;
-                let (fund_pubkey, other_fund_pubkey, other_sig) = if offered_contract.is_offer_party
-                {
-                    (
-                        &offered_contract.offer_params.fund_pubkey,
-                        &accepted_contract.accept_params.fund_pubkey,
-                        &accepted_contract.accept_refund_signature,
-                    )
-                } else {
-                    (
-                        &accepted_contract.accept_params.fund_pubkey,
-                        &offered_contract.offer_params.fund_pubkey,
-                        &contract.offer_refund_signature,
-                    )
-                };
-
-                let fund_priv_key = self.wallet.get_secret_key_for_pubkey(fund_pubkey)?;
-                dlc::util::sign_multi_sig_input(
-                    &self.secp,
-                    &mut refund,
-                    &other_sig,
-                    other_fund_pubkey,
-                    &fund_priv_key,
-                    &funding_script_pubkey,
-                    fund_output_value,
-                    0,
-                );
-
+                let refund = self.get_signed_refund(contract)?;
                 self.blockchain.send_transaction(&refund)?;
             }
 
             self.store
                 .update_contract(&Contract::Refunded(contract.clone()))?;
+            actions.push(ManagerAction::RefundBroadcast(
+                contract.accepted_contract.get_contract_id(),
+            ));
         }
 
         Ok(())
     }
+
+    fn get_signed_refund(&self, contract: &SignedContract) -> Result<Transaction, Error> {
+        let offered_contract = &contract.accepted_contract.offered_contract;
+        let accepted_contract = &contract.accepted_contract;
+        let mut refund = accepted_contract.dlc_transactions.refund.clone();
+        let funding_script_pubkey = &accepted_contract.dlc_transactions.funding_script_pubkey;
+        let fund_output_value = accepted_contract.dlc_transactions.get_fund_output().value;
+        let (fund_pubkey, other_fund_pubkey, other_sig) = if offered_contract.is_offer_party {
+            (
+                &offered_contract.offer_params.fund_pubkey,
+                &accepted_contract.accept_params.fund_pubkey,
+                &accepted_contract.accept_refund_signature,
+            )
+        } else {
+            (
+                &accepted_contract.accept_params.fund_pubkey,
+                &offered_contract.offer_params.fund_pubkey,
+                &contract.offer_refund_signature,
+            )
+        };
+
+        let fund_priv_key = self.wallet.get_secret_key_for_pubkey(fund_pubkey)?;
+        dlc::util::sign_multi_sig_input(
+            &self.secp,
+            &mut refund,
+            &other_sig,
+            other_fund_pubkey,
+            &fund_priv_key,
+            &funding_script_pubkey,
+            fund_output_value,
+            0,
+        );
+
+        Ok(refund)
+    }
 }
@@ -47,6 +47,146 @@ impl PayoutFunction {
         }
         range_payouts
     }
+
+    /// Returns the first and last outcomes covered by the function.
+    pub fn get_outcome_range(&self) -> (u64, u64) {
+        let first = self
+            .payout_function_pieces
+            .first()
+            .map_or(0, |x| x.get_first_point().event_outcome);
+        let last = self
+            .payout_function_pieces
+            .last()
+            .map_or(0, |x| x.get_last_point().event_outcome);
+        (first, last)
+    }
+
+    /// Returns a function covering all outcomes between `min_outcome` and
+    /// `max_outcome`, by extending this one with flat pieces giving the
+    /// outcomes outside of its range the payout of the closest covered outcome.
+    pub fn clamp_to_range(&self, min_outcome: u64, max_outcome: u64) -> PayoutFunction {
+        let mut pieces = self.payout_function_pieces.clone();
+        if let Some(first_point) = pieces.first().map(|x| x.get_first_point().clone()) {
+            if min_outcome < first_point.event_outcome {
+                let start = PayoutPoint {
+                    event_outcome: min_outcome,
+                    ..first_point.clone()
+                };
+                pieces.insert(0, flat_piece(start, first_point));
+            }
+        }
+        if let Some(last_point) = pieces.last().map(|x| x.get_last_point().clone()) {
+            if last_point.event_outcome < max_outcome {
+                let end = PayoutPoint {
+                    event_outcome: max_outcome,
+                    ..last_point.clone()
+                };
+                pieces.push(flat_piece(last_point, end));
+            }
+        }
+
+        PayoutFunction {
+            payout_function_pieces: pieces,
+        }
+    }
+}
+
+fn flat_piece(start: PayoutPoint, end: PayoutPoint) -> PayoutFunctionPiece {
+    PayoutFunctionPiece::PolynomialPayoutCurvePiece(PolynomialPayoutCurvePiece {
+        payout_points: vec![start, end],
+    })
+}
+
+/// A row of a [`PayoutTable`], giving the payout of the offer party for the
+/// outcomes between `start` and `end` (both included).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct PayoutTableRow {
+    /// The first outcome of the row.
+    pub start: u64,
+    /// The last outcome of the row.
+    pub end: u64,
+    /// The payout of the offer party for the outcomes of the row.
+    pub payout: u64,
+}
+
+/// A discrete set of payouts given per outcome range, which can be used in
+/// place of an analytic curve.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct PayoutTable {
+    rows: Vec<PayoutTableRow>,
+}
+
+impl PayoutTable {
+    /// Create a new payout table. Rows must be ordered, contiguous and not
+    /// overlapping, and cover at least two outcomes.
+    pub fn new(rows: Vec<PayoutTableRow>) -> Result<PayoutTable, Error> {
+        let is_valid = !rows.is_empty()
+            && rows.iter().all(|x| x.start <= x.end)
+            && rows
+                .iter()
+                .zip(rows.iter().skip(1))
+                .all(|(cur, next)| cur.end.checked_add(1) == Some(next.start))
+            && rows[0].start < rows[rows.len() - 1].end;
+        if is_valid {
+            Ok(PayoutTable { rows })
+        } else {
+            Err(Error::InvalidParameters(
+                "Payout table rows must be contiguous and not overlapping.".to_string(),
+            ))
+        }
+    }
+
+    /// Returns the rows of the table.
+    pub fn rows(&self) -> &[PayoutTableRow] {
+        &self.rows
+    }
+
+    /// Returns a payout function that is constant over each row of the table,
+    /// the payouts of which are thus only affected by rounding intervals.
+    pub fn to_payout_function(&self) -> PayoutFunction {
+        let point = |event_outcome, outcome_payout| PayoutPoint {
+            event_outcome,
+            outcome_payout,
+            extra_precision: 0,
+        };
+        let mut pieces = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            if i > 0 {
+                let prev = &self.rows[i - 1];
+                pieces.push(vec![
+                    point(prev.end, prev.payout),
+                    point(row.start, row.payout),
+                ]);
+            }
+            if row.start < row.end {
+                pieces.push(vec![
+                    point(row.start, row.payout),
+                    point(row.end, row.payout),
+                ]);
+            }
+        }
+
+        PayoutFunction {
+            payout_function_pieces: pieces
+                .into_iter()
+                .map(|payout_points| {
+                    PayoutFunctionPiece::PolynomialPayoutCurvePiece(PolynomialPayoutCurvePiece {
+                        payout_points,
+                    })
+                })
+                .collect(),
+        }
+    }
 }
 
 /// A piece of a payout function.
@@ -356,6 +496,64 @@ pub struct RoundingIntervals {
 }
 
 impl RoundingIntervals {
+    /// Generate a set of rounding intervals covering the outcomes between zero
+    /// and `max_outcome` using the given outcome probability function. Each
+    /// interval spans `interval_length` outcomes and is assigned a rounding
+    /// modulus that decreases with the probability of its middle outcome, from
+    /// `max_rounding_mod` for outcomes considered impossible down to
+    /// `min_rounding_mod` for the most probable one. This results in more precise
+    /// payouts (and thus more CETs) in high probability regions and coarser ones
+    /// elsewhere.
+    pub fn from_outcome_probabilities<F>(
+        max_outcome: u64,
+        interval_length: u64,
+        min_rounding_mod: u64,
+        max_rounding_mod: u64,
+        probability: F,
+    ) -> Result<RoundingIntervals, Error>
+    where
+        F: Fn(u64) -> f64,
+    {
+        if interval_length == 0 || min_rounding_mod == 0 || min_rounding_mod > max_rounding_mod {
+            return Err(Error::InvalidParameters(
+                "Invalid rounding interval parameters.".to_string(),
+            ));
+        }
+
+        let probabilities: Vec<(u64, f64)> = (0..=max_outcome / interval_length)
+            .map(|i| {
+                let begin_interval = i * interval_length;
+                let middle = begin_interval + interval_length / 2;
+                (
+                    begin_interval,
+                    probability(middle.min(max_outcome)).max(0.0),
+                )
+            })
+            .collect();
+        let max_probability = probabilities.iter().fold(0.0, |acc: f64, x| acc.max(x.1));
+
+        let mut intervals: Vec<RoundingInterval> = Vec::new();
+        for (begin_interval, p) in probabilities {
+            let ratio = if max_probability > 0.0 {
+                p / max_probability
+            } else {
+                0.0
+            };
+            let rounding_mod = (max_rounding_mod as f64
+                - ratio * ((max_rounding_mod - min_rounding_mod) as f64))
+                .round() as u64;
+            match intervals.last() {
+                Some(last) if last.rounding_mod == rounding_mod => {}
+                _ => intervals.push(RoundingInterval {
+                    begin_interval,
+                    rounding_mod,
+                }),
+            }
+        }
+
+        Ok(RoundingIntervals { intervals })
+    }
+
     /// Round the given payout based on the rounding modulus matching the given
     /// outcome.
     pub fn round(&self, outcome: u64, payout: f64) -> u64 {
@@ -660,6 +858,64 @@ mod test {
         }
     }
 
+    #[test]
+    fn payout_table_test() {
+        let row = |start, end, payout| PayoutTableRow { start, end, payout };
+        let invalid = vec![
+            vec![],
+            // Rows should cover at least two outcomes
+            vec![row(3, 3, 0)],
+            // Rows should not overlap
+            vec![row(0, 5, 0), row(5, 10, 1)],
+            // Rows should be contiguous
+            vec![row(0, 5, 0), row(7, 10, 1)],
+            vec![row(5, 4, 0), row(5, 10, 1)],
+        ];
+
+        for rows in invalid {
+            PayoutTable::new(rows).expect_err("Invalid rows should error");
+        }
+
+        let table = PayoutTable::new(vec![row(0, 4, 2), row(5, 5, 7), row(6, 10, 4)]).unwrap();
+        assert_eq!(
+            vec![
+                RangePayout {
+                    start: 0,
+                    count: 5,
+                    payout: Payout {
+                        offer: 2,
+                        accept: 8
+                    }
+                },
+                RangePayout {
+                    start: 5,
+                    count: 1,
+                    payout: Payout {
+                        offer: 7,
+                        accept: 3
+                    }
+                },
+                RangePayout {
+                    start: 6,
+                    count: 5,
+                    payout: Payout {
+                        offer: 4,
+                        accept: 6
+                    }
+                }
+            ],
+            table.to_payout_function().to_range_payouts(
+                10,
+                &RoundingIntervals {
+                    intervals: vec![RoundingInterval {
+                        begin_interval: 0,
+                        rounding_mod: 1
+                    }]
+                }
+            )
+        );
+    }
+
     #[test]
     fn hyperbola_validity_test() {
         HyperbolaPayoutCurvePiece::new(
@@ -774,4 +1030,61 @@ mod test {
             PayoutFunction::new(pieces).expect_err("Invalid pieces should error");
         }
     }
+
+    #[test]
+    fn rounding_intervals_from_peaked_probabilities_test() {
+        let max_outcome = 1023;
+        let total_collateral = 1023000;
+        let payout_function =
+            PayoutFunction::new(vec![PayoutFunctionPiece::PolynomialPayoutCurvePiece(
+                PolynomialPayoutCurvePiece::new(vec![
+                    PayoutPoint {
+                        event_outcome: 0,
+                        outcome_payout: 0,
+                        extra_precision: 0,
+                    },
+                    PayoutPoint {
+                        event_outcome: max_outcome,
+                        outcome_payout: total_collateral,
+                        extra_precision: 0,
+                    },
+                ])
+                .unwrap(),
+            )])
+            .unwrap();
+        let peaked = |outcome: u64| {
+            let x = (outcome as f64 - 512.0) / 64.0;
+            (-x * x / 2.0).exp()
+        };
+        let model_intervals =
+            RoundingIntervals::from_outcome_probabilities(max_outcome, 32, 1000, 100000, peaked)
+                .unwrap();
+        let uniform_intervals = RoundingIntervals {
+            intervals: vec![RoundingInterval {
+                begin_interval: 0,
+                rounding_mod: 10000,
+            }],
+        };
+        let count_cets = |intervals: &RoundingIntervals, start: usize, end: usize| {
+            payout_function
+                .to_range_payouts(total_collateral, intervals)
+                .iter()
+                .filter(|x| x.start >= start && x.start < end)
+                .count()
+        };
+
+        assert!(count_cets(&model_intervals, 480, 544) > count_cets(&uniform_intervals, 480, 544));
+        assert!(count_cets(&model_intervals, 0, 128) < count_cets(&uniform_intervals, 0, 128));
+        assert!(
+            count_cets(&model_intervals, 896, 1024) < count_cets(&uniform_intervals, 896, 1024)
+        );
+    }
+
+    #[test]
+    fn rounding_intervals_from_probabilities_invalid_parameters_test() {
+        RoundingIntervals::from_outcome_probabilities(100, 0, 1, 10, |_| 1.0)
+            .expect_err("Zero interval length should error");
+        RoundingIntervals::from_outcome_probabilities(100, 10, 20, 10, |_| 1.0)
+            .expect_err("Min rounding mod greater than max should error");
+    }
 }
@@ -10,16 +10,16 @@ use bitcoincore_rpc::RpcApi;
 use dlc::{EnumerationPayout, Payout};
 use dlc_manager::contract::{
     contract_input::{ContractInput, ContractInputInfo, OracleInput},
-    enum_descriptor::EnumDescriptor,
+    enum_descriptor::{EnumDescriptor, EnumOutcomeCommitment},
     numerical_descriptor::{DifferenceParams, NumericalDescriptor, NumericalEventInfo},
     Contract, ContractDescriptor,
 };
-use dlc_manager::manager::Manager;
+use dlc_manager::manager::{Manager, ManagerConfig, ManagerEvent, Outcome};
 use dlc_manager::payout_curve::{
     PayoutFunction, PayoutFunctionPiece, PayoutPoint, PolynomialPayoutCurvePiece, RoundingInterval,
     RoundingIntervals,
 };
-use dlc_manager::{Oracle, Storage};
+use dlc_manager::{AddressType, Oracle, Storage, Wallet};
 use dlc_messages::oracle_msgs::{
     DigitDecompositionEventDescriptor, EnumEventDescriptor, EventDescriptor,
 };
@@ -35,6 +35,7 @@ use serde_json::{from_str, to_writer_pretty};
 use std::collections::HashMap;
 use std::sync::{mpsc::channel, Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 struct TestVectorPart<T> {
@@ -162,11 +163,15 @@ const BASE: u32 = 2;
 const EVENT_MATURITY: u32 = 1623133104;
 const EVENT_ID: &str = "Test";
 const COLLATERAL: u64 = 100000000;
+const REFUND_GRACE_PERIOD: u64 = 3600;
+/// The largest number of oracles for which test vectors are generated.
+const MAX_NB_ORACLES: usize = 10;
 
 #[derive(Eq, PartialEq, Clone)]
 enum TestPath {
     Close,
     Refund,
+    RefundWithGracePeriod,
     BadAcceptCetSignature,
     BadAcceptRefundSignature,
     BadSignCetSignature,
@@ -212,20 +217,20 @@ fn get_difference_params() -> DifferenceParams {
     }
 }
 
-fn get_enum_contract_descriptor() -> ContractDescriptor {
+fn get_enum_contract_descriptor(total_collateral: u64) -> ContractDescriptor {
     let outcome_payouts: Vec<_> = enum_outcomes()
         .iter()
         .enumerate()
         .map(|(i, x)| {
             let payout = if i % 2 == 0 {
                 Payout {
-                    offer: 2 * COLLATERAL,
+                    offer: total_collateral,
                     accept: 0,
                 }
             } else {
                 Payout {
                     offer: 0,
-                    accept: 2 * COLLATERAL,
+                    accept: total_collateral,
                 }
             };
             EnumerationPayout {
@@ -234,7 +239,10 @@ fn get_enum_contract_descriptor() -> ContractDescriptor {
             }
         })
         .collect();
-    ContractDescriptor::Enum(EnumDescriptor { outcome_payouts })
+    ContractDescriptor::Enum(EnumDescriptor {
+        outcome_payouts,
+        outcome_commitment: EnumOutcomeCommitment::String,
+    })
 }
 
 fn get_enum_oracle() -> MockOracle {
@@ -274,7 +282,22 @@ fn get_enum_test_params(
     oracles: Option<Vec<MockOracle>>,
 ) -> TestParams {
     let oracles = oracles.unwrap_or_else(|| get_enum_oracles(nb_oracles, threshold));
-    let contract_descriptor = get_enum_contract_descriptor();
+    get_enum_test_params_with(
+        oracles,
+        threshold,
+        get_enum_contract_descriptor(2 * COLLATERAL),
+        COLLATERAL,
+        COLLATERAL,
+    )
+}
+
+fn get_enum_test_params_with(
+    oracles: Vec<MockOracle>,
+    threshold: usize,
+    contract_descriptor: ContractDescriptor,
+    offer_collateral: u64,
+    accept_collateral: u64,
+) -> TestParams {
     let contract_info = ContractInputInfo {
         contract_descriptor,
         oracles: OracleInput {
@@ -285,8 +308,8 @@ fn get_enum_test_params(
     };
 
     let contract_input = ContractInput {
-        offer_collateral: COLLATERAL,
-        accept_collateral: COLLATERAL,
+        offer_collateral,
+        accept_collateral,
         maturity_time: EVENT_MATURITY,
         fee_rate: 2,
         contract_infos: vec![contract_info],
@@ -298,6 +321,49 @@ fn get_enum_test_params(
     }
 }
 
+/// Enum contract whose event has a single possible outcome.
+fn get_single_outcome_enum_test_params() -> TestParams {
+    let outcome = enum_outcomes()[0].clone();
+    let mut oracle = MockOracle::new();
+    oracle.add_event(
+        EVENT_ID,
+        &EventDescriptor::EnumEvent(EnumEventDescriptor {
+            outcomes: vec![outcome.clone()],
+        }),
+        EVENT_MATURITY,
+    );
+    oracle.add_attestation(EVENT_ID, &vec![outcome.clone()]);
+    let contract_descriptor = ContractDescriptor::Enum(EnumDescriptor {
+        outcome_payouts: vec![EnumerationPayout {
+            outcome,
+            payout: Payout {
+                offer: COLLATERAL,
+                accept: COLLATERAL,
+            },
+        }],
+        outcome_commitment: EnumOutcomeCommitment::String,
+    });
+
+    get_enum_test_params_with(vec![oracle], 1, contract_descriptor, COLLATERAL, COLLATERAL)
+}
+
+/// Enum contract to which the accept party does not contribute any collateral.
+fn get_zero_collateral_enum_test_params() -> TestParams {
+    get_enum_test_params_with(
+        get_enum_oracles(1, 1),
+        1,
+        get_enum_contract_descriptor(COLLATERAL),
+        COLLATERAL,
+        0,
+    )
+}
+
+/// Enum contract using the largest number of oracles for which test vectors
+/// are generated.
+fn get_max_oracles_enum_test_params() -> TestParams {
+    get_enum_test_params(MAX_NB_ORACLES, MAX_NB_ORACLES, None)
+}
+
 fn get_numerical_contract_descriptor(
     difference_params: Option<DifferenceParams>,
 ) -> ContractDescriptor {
@@ -448,7 +514,7 @@ fn get_enum_and_numerical_test_params(
     difference_params: Option<DifferenceParams>,
 ) -> TestParams {
     let enum_oracles = get_enum_oracles(nb_oracles, threshold);
-    let enum_contract_descriptor = get_enum_contract_descriptor();
+    let enum_contract_descriptor = get_enum_contract_descriptor(2 * COLLATERAL);
     let enum_contract_info = ContractInputInfo {
         oracles: OracleInput {
             public_keys: enum_oracles.iter().map(|x| x.get_public_key()).collect(),
@@ -563,6 +629,24 @@ fn enum_3_of_5_test() {
     manager_execution_test(get_enum_test_params(5, 3, None), TestPath::Close);
 }
 
+#[test]
+#[ignore]
+fn enum_single_outcome_test() {
+    manager_execution_test(get_single_outcome_enum_test_params(), TestPath::Close);
+}
+
+#[test]
+#[ignore]
+fn enum_zero_accept_collateral_test() {
+    manager_execution_test(get_zero_collateral_enum_test_params(), TestPath::Close);
+}
+
+#[test]
+#[ignore]
+fn enum_max_oracles_test() {
+    manager_execution_test(get_max_oracles_enum_test_params(), TestPath::Close);
+}
+
 #[test]
 #[ignore]
 fn enum_and_numerical_with_diff_3_of_5_test() {
@@ -608,6 +692,15 @@ fn enum_single_oracle_refund_test() {
     );
 }
 
+#[test]
+#[ignore]
+fn enum_single_oracle_refund_with_grace_period_test() {
+    manager_execution_test(
+        get_enum_test_params(1, 1, Some(get_enum_oracles(1, 0))),
+        TestPath::RefundWithGracePeriod,
+    );
+}
+
 #[test]
 #[ignore]
 fn enum_single_oracle_bad_accept_cet_sig_test() {
@@ -644,6 +737,111 @@ fn enum_single_oracle_bad_sign_refund_sig_test() {
     );
 }
 
+#[test]
+#[ignore]
+fn bitcoin_core_provider_generates_p2tr_addresses_test() {
+    let (alice_rpc, _, _) = init_clients();
+    let provider = BitcoinCoreProvider { client: alice_rpc };
+
+    let script_pubkey = provider
+        .get_new_address_of_type(AddressType::P2tr)
+        .expect("Error generating address")
+        .script_pubkey();
+
+    assert_eq!(34, script_pubkey.len());
+    assert_eq!(0x51, script_pubkey[0]);
+}
+
+#[test]
+#[ignore]
+fn bitcoin_core_provider_lists_reserved_utxos_test() {
+    let (alice_rpc, _, _) = init_clients();
+    let provider = BitcoinCoreProvider { client: alice_rpc };
+
+    let utxos = provider
+        .get_utxos_for_amount(1000, None, true)
+        .expect("Error selecting utxos");
+    let reserved = provider
+        .get_reserved_utxos()
+        .expect("Error listing reserved utxos");
+    assert!(utxos.iter().all(|x| reserved.contains(&x.outpoint)));
+
+    let outpoints: Vec<_> = utxos.iter().map(|x| x.outpoint).collect();
+    provider.unreserve_utxos(&outpoints).unwrap();
+    let reserved = provider.get_reserved_utxos().unwrap();
+    assert!(outpoints.iter().all(|x| !reserved.contains(x)));
+}
+
+/// Checks that the given message round trips through both its wire and JSON
+/// serializations.
+fn assert_message_roundtrip<T>(msg: &T)
+where
+    T: Writeable
+        + Type
+        + serde::Serialize
+        + serde::de::DeserializeOwned
+        + PartialEq
+        + std::fmt::Debug,
+{
+    let mut buf = Vec::new();
+    msg.type_id().write(&mut buf).unwrap();
+    msg.write(&mut buf).unwrap();
+    dlc_messages::assert_roundtrip(&buf).expect("Message does not round trip");
+    let json = serde_json::to_string(msg).unwrap();
+    assert_eq!(msg, &from_str::<T>(&json).unwrap());
+}
+
+#[test]
+fn edge_case_messages_roundtrip_test() {
+    let counter_party = "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166"
+        .parse()
+        .unwrap();
+    for test_params in vec![
+        get_single_outcome_enum_test_params(),
+        get_zero_collateral_enum_test_params(),
+        get_max_oracles_enum_test_params(),
+    ] {
+        let oracles: HashMap<_, _> = test_params
+            .oracles
+            .into_iter()
+            .map(|x| (x.get_public_key(), Arc::new(x)))
+            .collect();
+        let get_manager = || {
+            let wallet = Arc::new(mocks::mock_wallet::MockWallet::new());
+            wallet.fund(3 * COLLATERAL);
+            Manager::new(
+                wallet,
+                Arc::new(mocks::mock_blockchain::MockBlockchain::new()),
+                Box::new(mocks::memory_storage_provider::MemoryStorage::new()),
+                oracles.clone(),
+                Arc::new(mocks::mock_time::MockTime {}),
+            )
+        };
+        let mut offer_manager = get_manager();
+        let mut accept_manager = get_manager();
+
+        let offer = offer_manager
+            .send_offer(&test_params.contract_input, counter_party)
+            .expect("Send offer error");
+        assert_message_roundtrip(&offer);
+        accept_manager
+            .on_dlc_message(&Message::Offer(offer.clone()), counter_party)
+            .expect("Error processing offer");
+        let (_, _, accept) = accept_manager
+            .accept_contract_offer(&offer.get_hash().unwrap())
+            .expect("Error accepting offer");
+        assert_message_roundtrip(&accept);
+        let sign = match offer_manager
+            .on_dlc_message(&Message::Accept(accept), counter_party)
+            .expect("Error processing accept")
+        {
+            Some(Message::Sign(sign)) => sign,
+            _ => panic!("Expected a sign message"),
+        };
+        assert_message_roundtrip(&sign);
+    }
+}
+
 fn alter_adaptor_sig(input: &mut CetAdaptorSignatures) {
     let sig_index = thread_rng().next_u32() as usize % input.ecdsa_adaptor_signatures.len();
 
@@ -676,6 +874,20 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
     let alice_bitcoin_core = Arc::new(BitcoinCoreProvider { client: alice_rpc });
     let bob_bitcoin_core = Arc::new(BitcoinCoreProvider { client: bob_rpc });
 
+    // The attested outcome of single enum contracts, used to check that the
+    // simulated CET is the one that gets broadcast.
+    let enum_outcome = match test_params.contract_input.contract_infos.as_slice() {
+        [ContractInputInfo {
+            contract_descriptor: ContractDescriptor::Enum(_),
+            ..
+        }] => test_params
+            .oracles
+            .iter()
+            .find_map(|x| x.get_attestation(EVENT_ID).ok())
+            .map(|x| x.outcomes[0].clone()),
+        _ => None,
+    };
+
     let mut alice_oracles = HashMap::with_capacity(1);
     let mut bob_oracles = HashMap::with_capacity(1);
 
@@ -690,23 +902,35 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
     let mock_time = Arc::new(mocks::mock_time::MockTime {});
     mocks::mock_time::set_time((test_params.contract_input.maturity_time as u64) - 1);
 
-    let alice_manager = Arc::new(Mutex::new(Manager::new(
+    let refund_grace_period = if path == TestPath::RefundWithGracePeriod {
+        Duration::from_secs(REFUND_GRACE_PERIOD)
+    } else {
+        Duration::from_secs(0)
+    };
+    let config = ManagerConfig {
+        refund_grace_period,
+        ..Default::default()
+    };
+
+    let alice_manager = Arc::new(Mutex::new(Manager::new_with_config(
         Arc::clone(&alice_bitcoin_core),
         Arc::clone(&alice_bitcoin_core),
         Box::new(alice_store),
         alice_oracles,
         Arc::clone(&mock_time),
+        config.clone(),
     )));
 
     let alice_manager_loop = Arc::clone(&alice_manager);
     let alice_manager_send = Arc::clone(&alice_manager);
 
-    let bob_manager = Arc::new(Mutex::new(Manager::new(
+    let bob_manager = Arc::new(Mutex::new(Manager::new_with_config(
         Arc::clone(&bob_bitcoin_core),
         Arc::clone(&bob_bitcoin_core),
         Box::new(bob_store),
         bob_oracles,
         Arc::clone(&mock_time),
+        config,
     )));
 
     let bob_manager_loop = Arc::clone(&bob_manager);
@@ -843,8 +1067,27 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
 
             match path {
                 TestPath::Close => {
+                    let simulated = enum_outcome.as_ref().map(|outcome| {
+                        first
+                            .lock()
+                            .unwrap()
+                            .simulate_outcome(&contract_id, &Outcome::Enum(outcome.clone()))
+                            .expect("Error simulating outcome")
+                    });
+
                     periodic_check!(first, contract_id, Closed);
 
+                    if let Some(simulated) = simulated {
+                        match first.lock().unwrap().get_store().get_contract(&contract_id) {
+                            Ok(Some(Contract::Closed(c))) => {
+                                let cets =
+                                    &c.signed_contract.accepted_contract.dlc_transactions.cets;
+                                assert_eq!(simulated.cet.txid(), cets[c.cet_index].txid());
+                            }
+                            _ => panic!("Expected closed contract"),
+                        }
+                    }
+
                     // Randomly check with or without having the CET mined
                     if thread_rng().next_u32() % 2 == 0 {
                         sink_rpc
@@ -854,20 +1097,37 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
 
                     periodic_check!(second, contract_id, Closed);
                 }
-                TestPath::Refund => {
+                TestPath::Refund | TestPath::RefundWithGracePeriod => {
                     periodic_check!(first, contract_id, Confirmed);
 
                     periodic_check!(second, contract_id, Confirmed);
 
-                    mocks::mock_time::set_time(
-                        ((test_params.contract_input.maturity_time
-                            + dlc_manager::manager::REFUND_DELAY) as u64)
-                            + 1,
-                    );
+                    for manager in &[&first, &second] {
+                        let events = manager.lock().unwrap().get_and_clear_pending_events();
+                        assert!(events.iter().any(|e| matches!(
+                            e,
+                            ManagerEvent::AttestationMissing { contract_id: id, .. }
+                                if *id == contract_id
+                        )));
+                    }
+
+                    let refund_locktime = (test_params.contract_input.maturity_time
+                        + dlc_manager::manager::REFUND_DELAY)
+                        as u64;
+                    mocks::mock_time::set_time(refund_locktime + 1);
                     sink_rpc
                         .generate_to_address(10, &sink_address)
                         .expect("RPC Error");
 
+                    if path == TestPath::RefundWithGracePeriod {
+                        periodic_check!(first, contract_id, Confirmed);
+
+                        mocks::mock_time::set_time(refund_locktime + REFUND_GRACE_PERIOD - 1);
+                        periodic_check!(first, contract_id, Confirmed);
+
+                        mocks::mock_time::set_time(refund_locktime + REFUND_GRACE_PERIOD + 1);
+                    }
+
                     periodic_check!(first, contract_id, Refunded);
 
                     // Randomly check with or without having the Refund mined.
@@ -892,3 +1152,72 @@ fn manager_execution_test(test_params: TestParams, path: TestPath) {
 
     create_test_vector();
 }
+
+#[test]
+#[ignore]
+fn contracts_for_event_test() {
+    const OTHER_EVENT_ID: &str = "Other";
+    let (_, bob_rpc, sink_rpc) = init_clients();
+
+    // Each offer locks a UTXO, so provide additional mature coins for the
+    // three offers.
+    let bob_address = bob_rpc.get_new_address(None, None).expect("RPC Error");
+    let sink_address = sink_rpc.get_new_address(None, None).expect("RPC Error");
+    sink_rpc
+        .generate_to_address(2, &bob_address)
+        .expect("RPC Error");
+    sink_rpc
+        .generate_to_address(100, &sink_address)
+        .expect("RPC Error");
+
+    let mut oracle = get_enum_oracle();
+    oracle.add_event(
+        OTHER_EVENT_ID,
+        &EventDescriptor::EnumEvent(EnumEventDescriptor {
+            outcomes: enum_outcomes(),
+        }),
+        EVENT_MATURITY,
+    );
+    let oracle = Arc::new(oracle);
+    let mut oracles = HashMap::with_capacity(1);
+    oracles.insert(oracle.get_public_key(), Arc::clone(&oracle));
+
+    let bob_bitcoin_core = Arc::new(BitcoinCoreProvider { client: bob_rpc });
+    let mut bob_manager = Manager::new(
+        Arc::clone(&bob_bitcoin_core),
+        Arc::clone(&bob_bitcoin_core),
+        Box::new(mocks::memory_storage_provider::MemoryStorage::new()),
+        oracles,
+        Arc::new(mocks::mock_time::MockTime {}),
+    );
+
+    let counter_party = "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166"
+        .parse()
+        .unwrap();
+    let mut send_offer = |event_id: &str| {
+        let mut contract_input =
+            get_enum_test_params(1, 1, Some(vec![(*oracle).clone()])).contract_input;
+        contract_input.contract_infos[0].oracles.event_id = event_id.to_owned();
+        bob_manager
+            .send_offer(&contract_input, counter_party)
+            .expect("Send offer error")
+            .get_hash()
+            .unwrap()
+    };
+
+    let first_id = send_offer(EVENT_ID);
+    let second_id = send_offer(EVENT_ID);
+    let other_id = send_offer(OTHER_EVENT_ID);
+
+    let mut contract_ids = bob_manager
+        .contracts_for_event(EVENT_ID)
+        .expect("Error querying contracts");
+    let mut expected = vec![first_id, second_id];
+    contract_ids.sort();
+    expected.sort();
+    assert_eq!(expected, contract_ids);
+    assert_eq!(
+        vec![other_id],
+        bob_manager.contracts_for_event(OTHER_EVENT_ID).unwrap()
+    );
+}
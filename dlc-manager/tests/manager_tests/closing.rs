@@ -0,0 +1,702 @@
+use crate::common::{
+    enum_outcomes, establish_contract, establish_contract_with_configs, get_accept_party_pubkey,
+    get_enum_contract_input, get_enum_oracle, get_funded_manager, get_funded_manager_with,
+    get_manager, get_manager_with, get_offer_party_pubkey, get_signed_contract,
+    get_store_with_signed_contract, run_contract_setup, TestManager, COLLATERAL, EVENT_ID,
+    EVENT_MATURITY,
+};
+use bitcoin::OutPoint;
+use dlc_manager::contract::{Contract, ContractState};
+use dlc_manager::error::Error;
+use dlc_manager::manager::{
+    BroadcastMode, ManagerAction, ManagerConfig, ManagerEvent, ReconcileReport, WatchReason,
+    NB_CONFIRMATIONS,
+};
+use dlc_manager::Storage;
+use dlc_messages::Message;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_time::set_time;
+use mocks::mock_wallet::MockWallet;
+use std::sync::Arc;
+
+#[test]
+fn broadcast_cet_is_watched_after_restart_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    set_time(EVENT_MATURITY as u64 - 1);
+    let (_, mut accept_manager, contract_id) = establish_contract(oracle);
+    accept_manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    let cet_txid = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => {
+            c.signed_contract.accepted_contract.dlc_transactions.cets[c.cet_index].txid()
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let store = accept_manager.get_store().share();
+    drop(accept_manager);
+
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut restarted_manager = get_manager_with(store, wallet.clone(), blockchain.clone());
+    // The CET gets mined and then reorged out.
+    wallet.set_confirmations(&cet_txid, 1);
+    restarted_manager.periodic_check().unwrap();
+    wallet.set_confirmations(&cet_txid, 0);
+    restarted_manager.periodic_check().unwrap();
+    assert!(blockchain
+        .get_transactions()
+        .iter()
+        .any(|x| x.txid() == cet_txid));
+}
+
+#[test]
+fn pending_cet_is_handed_over_after_restart_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    set_time(EVENT_MATURITY as u64 - 1);
+    let (_, mut accept_manager, contract_id) = establish_contract_with_configs(
+        oracle,
+        ManagerConfig::default(),
+        ManagerConfig {
+            cet_broadcast_mode: BroadcastMode::Manual,
+            ..Default::default()
+        },
+    );
+    accept_manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    let cet = match accept_manager.get_and_clear_pending_events().as_slice() {
+        [ManagerEvent::CetReadyForBroadcast { cet, .. }] => cet.clone(),
+        e => panic!("Unexpected events {:?}", e),
+    };
+    let store = accept_manager.get_store().share();
+    drop(accept_manager);
+
+    let mut restarted_manager = get_manager(store);
+    match restarted_manager.get_and_clear_pending_events().as_slice() {
+        [ManagerEvent::CetReadyForBroadcast {
+            contract_id: id,
+            cet: pending,
+        }] if *id == contract_id => assert_eq!(cet, *pending),
+        e => panic!("Unexpected events {:?}", e),
+    };
+    restarted_manager
+        .notify_cet_broadcast(&contract_id)
+        .expect("Error notifying CET broadcast");
+    match restarted_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let store = restarted_manager.get_store().share();
+    drop(restarted_manager);
+    assert!(get_manager(store).get_and_clear_pending_events().is_empty());
+}
+
+#[test]
+fn reconcile_with_chain_closes_contract_with_broadcast_cet_test() {
+    let signed = get_signed_contract();
+    let contract_id = signed.accepted_contract.get_contract_id();
+    let cets = &signed.accepted_contract.dlc_transactions.cets;
+    let cet_index = cets.len() - 1;
+    let cet_txid = cets[cet_index].txid();
+    let dlc_transactions = &signed.accepted_contract.dlc_transactions;
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut manager = get_manager_with(
+        get_store_with_signed_contract(&signed),
+        wallet.clone(),
+        blockchain.clone(),
+    );
+
+    wallet.set_confirmations(&dlc_transactions.fund.txid(), 2);
+    blockchain.set_output_spent(OutPoint {
+        txid: dlc_transactions.fund.txid(),
+        vout: dlc_transactions.get_fund_output_index() as u32,
+    });
+    wallet.set_confirmations(&cet_txid, 1);
+    let report = manager
+        .reconcile_with_chain()
+        .expect("Error reconciling with chain");
+
+    assert_eq!(
+        ReconcileReport {
+            closed: vec![contract_id],
+            ..Default::default()
+        },
+        report
+    );
+    match manager.get_store().get_contract(&contract_id).unwrap() {
+        Some(Contract::Closed(c)) => assert_eq!(cet_index, c.cet_index),
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+}
+
+#[test]
+fn reconcile_with_chain_skips_cets_while_fund_output_is_unspent_test() {
+    let signed = get_signed_contract();
+    let contract_id = signed.accepted_contract.get_contract_id();
+    let dlc_transactions = &signed.accepted_contract.dlc_transactions;
+    let wallet = Arc::new(MockWallet::new());
+    let mut manager = get_manager_with(
+        get_store_with_signed_contract(&signed),
+        wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+
+    wallet.set_confirmations(&dlc_transactions.fund.txid(), NB_CONFIRMATIONS);
+    // Not possible while the fund output is unspent, so never looked up.
+    wallet.set_confirmations(&dlc_transactions.cets[0].txid(), 1);
+    let report = manager
+        .reconcile_with_chain()
+        .expect("Error reconciling with chain");
+
+    assert_eq!(
+        ReconcileReport {
+            confirmed: vec![contract_id],
+            ..Default::default()
+        },
+        report
+    );
+}
+
+#[test]
+fn reorged_cet_is_rebroadcast_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    let fund_txid = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.fund.txid(),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    wallet.set_confirmations(&fund_txid, 6);
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager
+        .periodic_check()
+        .expect("Periodic check error");
+
+    let cet_txid = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => {
+            c.signed_contract.accepted_contract.dlc_transactions.cets[c.cet_index].txid()
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let nb_cet_broadcasts = || {
+        blockchain
+            .get_transactions()
+            .iter()
+            .filter(|x| x.txid() == cet_txid)
+            .count()
+    };
+    assert_eq!(1, nb_cet_broadcasts());
+
+    // The CET gets mined and then reorged out.
+    wallet.set_confirmations(&cet_txid, 1);
+    accept_manager.periodic_check().unwrap();
+    assert_eq!(1, nb_cet_broadcasts());
+    wallet.set_confirmations(&cet_txid, 0);
+    accept_manager.periodic_check().unwrap();
+    assert_eq!(2, nb_cet_broadcasts());
+
+    // Once buried deep enough, the CET is not tracked anymore.
+    wallet.set_confirmations(&cet_txid, 6);
+    accept_manager.periodic_check().unwrap();
+    wallet.set_confirmations(&cet_txid, 0);
+    accept_manager.periodic_check().unwrap();
+    assert_eq!(2, nb_cet_broadcasts());
+}
+
+#[test]
+fn manual_broadcast_mode_hands_over_signed_cet_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig {
+            cet_broadcast_mode: BroadcastMode::Manual,
+            ..Default::default()
+        },
+        wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    let signed = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let dlc_transactions = &signed.accepted_contract.dlc_transactions;
+    wallet.set_confirmations(&dlc_transactions.fund.txid(), 6);
+    set_time(EVENT_MATURITY as u64 + 1);
+    let nb_broadcasts = blockchain.get_transactions().len();
+    accept_manager.periodic_check().unwrap();
+
+    let cet = match accept_manager.get_and_clear_pending_events().as_slice() {
+        [ManagerEvent::CetReadyForBroadcast {
+            contract_id: id,
+            cet,
+        }] if *id == contract_id => cet.clone(),
+        e => panic!("Unexpected events {:?}", e),
+    };
+    assert!(dlc_transactions.cets.iter().any(|x| x.txid() == cet.txid()));
+    assert!(!cet.input[0].witness.is_empty());
+    assert_eq!(nb_broadcasts, blockchain.get_transactions().len());
+
+    // The contract waits for the broadcast to be reported.
+    accept_manager.periodic_check().unwrap();
+    assert!(accept_manager.get_and_clear_pending_events().is_empty());
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    accept_manager
+        .notify_cet_broadcast(&contract_id)
+        .expect("Error notifying CET broadcast");
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => assert_eq!(
+            cet.txid(),
+            c.signed_contract.accepted_contract.dlc_transactions.cets[c.cet_index].txid()
+        ),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert_eq!(nb_broadcasts, blockchain.get_transactions().len());
+    assert!(accept_manager.notify_cet_broadcast(&contract_id).is_err());
+}
+
+#[test]
+fn refund_contract_broadcasts_refund_after_locktime_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        Arc::new(MockWallet::new()),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let refund_lock_time = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.refund.lock_time,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    // The oracle never attests.
+    set_time(refund_lock_time as u64 - 1);
+    assert!(matches!(
+        accept_manager.refund_contract(&contract_id),
+        Err(Error::RefundNotYetValid { lock_time }) if lock_time == refund_lock_time
+    ));
+    let nb_broadcasts = blockchain.get_transactions().len();
+
+    set_time(refund_lock_time as u64);
+    let refund = accept_manager
+        .refund_contract(&contract_id)
+        .expect("Error refunding contract");
+    assert_eq!(refund_lock_time, refund.lock_time);
+    assert!(!refund.input[0].witness.is_empty());
+    let broadcasts = blockchain.get_transactions();
+    assert_eq!(nb_broadcasts + 1, broadcasts.len());
+    assert_eq!(refund.txid(), broadcasts.last().unwrap().txid());
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Refunded(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    match accept_manager.refund_contract(&contract_id) {
+        Err(Error::InvalidState { expected, found }) => {
+            assert_eq!(
+                vec![ContractState::Signed, ContractState::Confirmed],
+                expected
+            );
+            assert_eq!(ContractState::Refunded, found);
+        }
+        res => panic!("Unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn periodic_check_reports_actions_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    assert!(accept_manager.periodic_check().unwrap().is_empty());
+
+    let fund_txid = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.fund.txid(),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    wallet.set_confirmations(&fund_txid, 6);
+    assert_eq!(
+        vec![ManagerAction::ContractConfirmed(contract_id)],
+        accept_manager.periodic_check().unwrap()
+    );
+
+    set_time(EVENT_MATURITY as u64 + 1);
+    let actions = accept_manager.periodic_check().unwrap();
+    let cet_txid = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => {
+            c.signed_contract.accepted_contract.dlc_transactions.cets[c.cet_index].txid()
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert_eq!(
+        vec![ManagerAction::ClosedByOutcome {
+            contract_id,
+            txid: cet_txid
+        }],
+        actions
+    );
+    assert!(blockchain
+        .get_transactions()
+        .iter()
+        .any(|x| x.txid() == cet_txid));
+    assert!(accept_manager.periodic_check().unwrap().is_empty());
+}
+
+#[test]
+fn collaborative_close_offers_are_kept_after_restart_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        blockchain.clone(),
+    );
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        accept_wallet.clone(),
+        blockchain.clone(),
+    );
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let close_offer = offer_manager
+        .offer_collaborative_close(&contract_id, COLLATERAL, COLLATERAL - 10000)
+        .expect("Error offering close");
+    accept_manager
+        .on_dlc_message(
+            &Message::CollaborativeCloseOffer(close_offer),
+            get_offer_party_pubkey(),
+        )
+        .expect("Error processing close offer");
+
+    let restart = |manager: TestManager, wallet: &Arc<MockWallet>| {
+        let store = manager.get_store().share();
+        drop(manager);
+        get_manager_with(store, wallet.clone(), blockchain.clone())
+    };
+    let mut offer_manager = restart(offer_manager, &offer_wallet);
+    let mut accept_manager = restart(accept_manager, &accept_wallet);
+
+    let close_accept = accept_manager
+        .accept_collaborative_close(&contract_id)
+        .expect("Error accepting close offer");
+    offer_manager
+        .on_dlc_message(
+            &Message::CollaborativeCloseAccept(close_accept),
+            get_accept_party_pubkey(),
+        )
+        .expect("Error processing close accept");
+    for manager in &[&offer_manager, &accept_manager] {
+        match manager.get_store().get_contract(&contract_id).unwrap() {
+            Some(Contract::CollaborativelyClosed(_)) => {}
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+    }
+}
+
+#[test]
+fn collaborative_close_pays_agreed_split_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_blockchain = Arc::new(MockBlockchain::new());
+    let accept_blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        Arc::new(MockWallet::new()),
+        offer_blockchain.clone(),
+    );
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        Arc::new(MockWallet::new()),
+        accept_blockchain.clone(),
+    );
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let offer_payout = COLLATERAL + COLLATERAL / 2;
+    let accept_payout = COLLATERAL / 2 - 10000;
+
+    let close_offer = offer_manager
+        .offer_collaborative_close(&contract_id, offer_payout, accept_payout)
+        .expect("Error offering close");
+    assert!(accept_manager
+        .on_dlc_message(
+            &Message::CollaborativeCloseOffer(close_offer),
+            get_offer_party_pubkey()
+        )
+        .expect("Error processing close offer")
+        .is_none());
+    let close_accept = accept_manager
+        .accept_collaborative_close(&contract_id)
+        .expect("Error accepting close offer");
+    offer_manager
+        .on_dlc_message(
+            &Message::CollaborativeCloseAccept(close_accept),
+            get_accept_party_pubkey(),
+        )
+        .expect("Error processing close accept");
+
+    let mut close_txs = Vec::new();
+    for &(manager, blockchain) in &[
+        (&offer_manager, &offer_blockchain),
+        (&accept_manager, &accept_blockchain),
+    ] {
+        let contract = match manager.get_store().get_contract(&contract_id).unwrap() {
+            Some(Contract::CollaborativelyClosed(c)) => c,
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+        let accepted_contract = &contract.signed_contract.accepted_contract;
+        let get_payout = |script_pubkey| {
+            dlc::util::get_output_for_script_pubkey(&contract.close_tx, script_pubkey)
+                .map(|(_, x)| x.value)
+        };
+        assert_eq!(
+            Some(offer_payout),
+            get_payout(
+                &accepted_contract
+                    .offered_contract
+                    .offer_params
+                    .payout_script_pubkey
+            )
+        );
+        assert_eq!(
+            Some(accept_payout),
+            get_payout(&accepted_contract.accept_params.payout_script_pubkey)
+        );
+        assert_eq!(4, contract.close_tx.input[0].witness.len());
+        assert_eq!(
+            Some(&contract.close_tx),
+            blockchain.get_transactions().last()
+        );
+        close_txs.push(contract.close_tx.clone());
+    }
+    assert_eq!(close_txs[0], close_txs[1]);
+}
+
+#[test]
+fn watched_transactions_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        accept_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    // Each offer and accept reserves a whole UTXO.
+    offer_wallet.fund(3 * COLLATERAL);
+    offer_wallet.fund(3 * COLLATERAL);
+    accept_wallet.fund(3 * COLLATERAL);
+    set_time(EVENT_MATURITY as u64 - 1);
+
+    let signed_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let closing_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let mut fund_txids = Vec::new();
+    for contract_id in &[signed_id, closing_id] {
+        match accept_manager
+            .get_store()
+            .get_contract(contract_id)
+            .unwrap()
+        {
+            Some(Contract::Signed(s)) => {
+                fund_txids.push(s.accepted_contract.dlc_transactions.fund.txid())
+            }
+            c => panic!("Unexpected contract state {:?}", c),
+        }
+    }
+
+    accept_wallet.set_confirmations(&fund_txids[1], 6);
+    accept_manager.periodic_check().unwrap();
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    let cet_txid = match accept_manager
+        .get_store()
+        .get_contract(&closing_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => {
+            c.signed_contract.accepted_contract.dlc_transactions.cets[c.cet_index].txid()
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    let watched = accept_manager.watched_transactions();
+    assert_eq!(2, watched.len());
+    assert!(watched.contains(&(fund_txids[0], WatchReason::Funding)));
+    assert!(watched.contains(&(cet_txid, WatchReason::Cet)));
+}
+
+#[test]
+fn paused_contract_is_not_refunded_until_resumed_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let refund = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.refund,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    wallet.set_confirmations(&refund.input[0].previous_output.txid, 6);
+    accept_manager.periodic_check().unwrap();
+
+    accept_manager
+        .pause_contract(&contract_id)
+        .expect("Error pausing contract");
+    set_time(refund.lock_time as u64 + 1);
+    assert!(accept_manager.periodic_check().unwrap().is_empty());
+    assert!(!blockchain
+        .get_transactions()
+        .iter()
+        .any(|x| x.txid() == refund.txid()));
+    let close_offer = offer_manager
+        .offer_collaborative_close(&contract_id, COLLATERAL, COLLATERAL)
+        .expect("Error offering collaborative close");
+    assert!(matches!(
+        accept_manager.on_dlc_message(
+            &Message::CollaborativeCloseOffer(close_offer),
+            get_offer_party_pubkey()
+        ),
+        Err(Error::ContractPaused)
+    ));
+
+    accept_manager
+        .resume_contract(&contract_id)
+        .expect("Error resuming contract");
+    assert_eq!(
+        vec![ManagerAction::RefundBroadcast(contract_id)],
+        accept_manager.periodic_check().unwrap()
+    );
+    assert!(blockchain
+        .get_transactions()
+        .iter()
+        .any(|x| x.txid() == refund.txid()));
+}
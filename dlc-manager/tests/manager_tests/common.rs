@@ -0,0 +1,242 @@
+use dlc::{EnumerationPayout, Payout};
+use dlc_manager::contract::{
+    contract_input::{ContractInput, ContractInputInfo, OracleInput},
+    enum_descriptor::{EnumDescriptor, EnumOutcomeCommitment},
+    offered_contract::OfferedContract,
+    ser::Serializable,
+    signed_contract::SignedContract,
+    Contract, ContractDescriptor,
+};
+use dlc_manager::manager::{Manager, ManagerConfig};
+use dlc_manager::{ContractId, Oracle, Storage};
+use dlc_messages::oracle_msgs::{EnumEventDescriptor, EventDescriptor};
+use dlc_messages::Message;
+use mocks::memory_storage_provider::MemoryStorage;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_oracle_provider::MockOracle;
+use mocks::mock_time::MockTime;
+use mocks::mock_wallet::MockWallet;
+use secp256k1_zkp::schnorrsig::PublicKey as SchnorrPublicKey;
+use secp256k1_zkp::{PublicKey, Secp256k1, SecretKey};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub const EVENT_ID: &str = "Test";
+pub const EVENT_MATURITY: u32 = 1623133104;
+pub const COLLATERAL: u64 = 100000000;
+
+pub type TestManager = Manager<
+    Arc<MockWallet>,
+    Arc<MockBlockchain>,
+    Box<MemoryStorage>,
+    Arc<MockOracle>,
+    Arc<MockTime>,
+>;
+
+pub type BorrowedStoreManager<'a> = Manager<
+    Arc<MockWallet>,
+    Arc<MockBlockchain>,
+    &'a mut MemoryStorage,
+    Arc<MockOracle>,
+    Arc<MockTime>,
+>;
+
+pub fn get_manager(store: MemoryStorage) -> TestManager {
+    get_manager_with(
+        store,
+        Arc::new(MockWallet::new()),
+        Arc::new(MockBlockchain::new()),
+    )
+}
+
+pub fn get_manager_with(
+    store: MemoryStorage,
+    wallet: Arc<MockWallet>,
+    blockchain: Arc<MockBlockchain>,
+) -> TestManager {
+    let oracles: HashMap<SchnorrPublicKey, Arc<MockOracle>> = HashMap::new();
+    Manager::new(
+        wallet,
+        blockchain,
+        Box::new(store),
+        oracles,
+        Arc::new(MockTime {}),
+    )
+}
+
+pub fn get_manager_for_store(store: &mut MemoryStorage) -> BorrowedStoreManager<'_> {
+    Manager::new(
+        Arc::new(MockWallet::new()),
+        Arc::new(MockBlockchain::new()),
+        store,
+        HashMap::new(),
+        Arc::new(MockTime {}),
+    )
+}
+
+pub fn get_offer_party_pubkey() -> PublicKey {
+    PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&[2; 32]).unwrap())
+}
+
+pub fn get_accept_party_pubkey() -> PublicKey {
+    PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&[3; 32]).unwrap())
+}
+
+pub fn enum_outcomes() -> Vec<String> {
+    vec![
+        "a".to_owned(),
+        "b".to_owned(),
+        "c".to_owned(),
+        "d".to_owned(),
+    ]
+}
+
+pub fn get_enum_oracle() -> MockOracle {
+    let mut oracle = MockOracle::new();
+    oracle.add_event(
+        EVENT_ID,
+        &EventDescriptor::EnumEvent(EnumEventDescriptor {
+            outcomes: enum_outcomes(),
+        }),
+        EVENT_MATURITY,
+    );
+    oracle
+}
+
+pub fn get_enum_contract_input(oracle: &MockOracle) -> ContractInput {
+    let outcome_payouts = enum_outcomes()
+        .into_iter()
+        .enumerate()
+        .map(|(i, outcome)| EnumerationPayout {
+            outcome,
+            payout: if i % 2 == 0 {
+                Payout {
+                    offer: 2 * COLLATERAL,
+                    accept: 0,
+                }
+            } else {
+                Payout {
+                    offer: 0,
+                    accept: 2 * COLLATERAL,
+                }
+            },
+        })
+        .collect();
+    ContractInput {
+        offer_collateral: COLLATERAL,
+        accept_collateral: COLLATERAL,
+        maturity_time: EVENT_MATURITY,
+        fee_rate: 2,
+        contract_infos: vec![ContractInputInfo {
+            contract_descriptor: ContractDescriptor::Enum(EnumDescriptor {
+                outcome_payouts,
+                outcome_commitment: EnumOutcomeCommitment::String,
+            }),
+            oracles: OracleInput {
+                public_keys: vec![oracle.get_public_key()],
+                event_id: EVENT_ID.to_owned(),
+                threshold: 1,
+            },
+        }],
+    }
+}
+
+pub fn get_funded_manager(oracle: &Arc<MockOracle>, config: ManagerConfig) -> TestManager {
+    get_funded_manager_with(
+        oracle,
+        config,
+        Arc::new(MockWallet::new()),
+        Arc::new(MockBlockchain::new()),
+    )
+}
+
+pub fn get_funded_manager_with(
+    oracle: &Arc<MockOracle>,
+    config: ManagerConfig,
+    wallet: Arc<MockWallet>,
+    blockchain: Arc<MockBlockchain>,
+) -> TestManager {
+    wallet.fund(3 * COLLATERAL);
+    let mut oracles = HashMap::new();
+    oracles.insert(oracle.get_public_key(), Arc::clone(oracle));
+    Manager::new_with_config(
+        wallet,
+        blockchain,
+        Box::new(MemoryStorage::new()),
+        oracles,
+        Arc::new(MockTime {}),
+        config,
+    )
+}
+
+/// Runs the offer, accept and sign exchange between two new managers,
+/// returning the offer and accept party managers together with the id of the
+/// signed contract.
+pub fn establish_contract(oracle: MockOracle) -> (TestManager, TestManager, ContractId) {
+    establish_contract_with_configs(oracle, ManagerConfig::default(), ManagerConfig::default())
+}
+
+/// Same as [`establish_contract`], using the given configuration for each
+/// manager.
+pub fn establish_contract_with_configs(
+    oracle: MockOracle,
+    offer_config: ManagerConfig,
+    accept_config: ManagerConfig,
+) -> (TestManager, TestManager, ContractId) {
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, offer_config);
+    let mut accept_manager = get_funded_manager(&oracle, accept_config);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    (offer_manager, accept_manager, contract_id)
+}
+
+/// Runs the offer, accept and sign exchange between the given managers,
+/// returning the id of the signed contract.
+pub fn run_contract_setup(
+    offer_manager: &mut TestManager,
+    accept_manager: &mut TestManager,
+    contract_input: &ContractInput,
+) -> ContractId {
+    let offer = offer_manager
+        .send_offer(contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting offer");
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .expect("Error processing sign");
+
+    contract_id
+}
+
+pub fn get_offered_contract() -> OfferedContract {
+    let mut cursor = std::io::Cursor::new(include_bytes!("../../test_files/Offered"));
+    OfferedContract::deserialize(&mut cursor).expect("Error deserializing contract")
+}
+
+pub fn get_signed_contract() -> SignedContract {
+    let mut cursor = std::io::Cursor::new(include_bytes!("../../test_files/Signed"));
+    SignedContract::deserialize(&mut cursor).expect("Error deserializing contract")
+}
+
+pub fn get_store_with_signed_contract(signed: &SignedContract) -> MemoryStorage {
+    let mut store = MemoryStorage::new();
+    store
+        .create_contract(&signed.accepted_contract.offered_contract)
+        .unwrap();
+    store
+        .update_contract(&Contract::Signed(signed.clone()))
+        .unwrap();
+    store
+}
@@ -0,0 +1,923 @@
+use crate::common::{
+    enum_outcomes, get_accept_party_pubkey, get_enum_contract_input, get_enum_oracle,
+    get_funded_manager, get_funded_manager_with, get_offer_party_pubkey, run_contract_setup,
+    COLLATERAL, EVENT_ID, EVENT_MATURITY,
+};
+use dlc::{EnumerationPayout, Payout};
+use dlc_manager::contract::{
+    contract_input::{ContractInput, ContractInputInfo, ContractTemplate, OracleInput},
+    enum_descriptor::{EnumDescriptor, EnumOutcomeCommitment},
+    numerical_descriptor::{NumericalDescriptor, NumericalEventInfo},
+    Contract, ContractDescriptor, CET_MEMORY_FOOTPRINT,
+};
+use dlc_manager::error::Error;
+use dlc_manager::manager::{
+    CollateralRatioBounds, ManagerConfig, OfferOptions, OutOfRangePolicy, Outcome,
+};
+use dlc_manager::payout_curve::{
+    PayoutFunction, PayoutFunctionPiece, PayoutPoint, PayoutTable, PayoutTableRow,
+    PolynomialPayoutCurvePiece, RoundingInterval, RoundingIntervals,
+};
+use dlc_manager::{Oracle, Storage};
+use dlc_messages::oracle_msgs::{
+    DigitDecompositionEventDescriptor, EnumEventDescriptor, EventDescriptor,
+};
+use dlc_messages::Message;
+use dlc_trie::digit_decomposition::decompose_value;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_oracle_provider::MockOracle;
+use mocks::mock_time::set_time;
+use mocks::mock_wallet::MockWallet;
+use secp256k1_zkp::{Message as SecpMessage, Secp256k1, Signature};
+use std::sync::Arc;
+
+#[test]
+fn index_committed_enum_contract_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &["1".to_string()]);
+    let mut contract_input = get_enum_contract_input(&oracle);
+    match &mut contract_input.contract_infos[0].contract_descriptor {
+        ContractDescriptor::Enum(e) => e.outcome_commitment = EnumOutcomeCommitment::Index,
+        _ => unreachable!(),
+    };
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    let signed = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let accepted = &signed.accepted_contract;
+    let contract_info = &accepted.offered_contract.contract_info[0];
+    match &contract_info.contract_descriptor {
+        ContractDescriptor::Enum(e) => {
+            assert_eq!(EnumOutcomeCommitment::Index, e.outcome_commitment)
+        }
+        _ => unreachable!(),
+    };
+    let secp = Secp256k1::new();
+    let oracle_infos = contract_info.get_oracle_infos();
+    let offer_adaptor_sigs = signed.adaptor_signatures.as_ref().unwrap();
+    let verify = |cet_index: usize, outcome: &str| {
+        dlc::verify_cet_adaptor_sig_from_oracle_info(
+            &secp,
+            &offer_adaptor_sigs[cet_index],
+            &accepted.dlc_transactions.cets[cet_index],
+            &oracle_infos,
+            &accepted.offered_contract.offer_params.fund_pubkey,
+            &accepted.dlc_transactions.funding_script_pubkey,
+            accepted.dlc_transactions.get_fund_output().value,
+            &vec![vec![SecpMessage::from_hashed_data::<
+                secp256k1_zkp::bitcoin_hashes::sha256::Hash,
+            >(outcome.as_bytes())]],
+        )
+    };
+    for (i, outcome) in enum_outcomes().iter().enumerate() {
+        verify(i, &i.to_string()).expect("Adaptor signature should commit to the index");
+        assert!(verify(i, outcome).is_err());
+    }
+
+    wallet.set_confirmations(&accepted.dlc_transactions.fund.txid(), 6);
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => assert_eq!(1, c.cet_index),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+}
+
+#[test]
+fn offer_collateral_ratio_is_validated_on_accept_test() {
+    let oracle = get_enum_oracle();
+    let balanced_input = get_enum_contract_input(&oracle);
+    let mut lopsided_input = get_enum_contract_input(&oracle);
+    lopsided_input.offer_collateral = COLLATERAL / 10;
+    lopsided_input.accept_collateral = 2 * COLLATERAL - COLLATERAL / 10;
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let accept_config = ManagerConfig {
+        offer_collateral_ratio_bounds: Some(CollateralRatioBounds { min: 0.1, max: 0.9 }),
+        ..Default::default()
+    };
+    let mut accept_manager = get_funded_manager(&oracle, accept_config);
+
+    let mut receive_offer = |contract_input: &ContractInput| {
+        offer_wallet.fund(3 * COLLATERAL);
+        let offer = offer_manager
+            .send_offer(contract_input, get_accept_party_pubkey())
+            .expect("Error sending offer");
+        let temporary_contract_id = offer.get_hash().unwrap();
+        accept_manager
+            .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+            .expect("Error processing offer");
+        temporary_contract_id
+    };
+    let lopsided_id = receive_offer(&lopsided_input);
+    let balanced_id = receive_offer(&balanced_input);
+
+    match accept_manager.accept_contract_offer(&lopsided_id) {
+        Err(Error::CollateralRatioOutOfBounds) => {}
+        Err(e) => panic!("Unexpected error {}", e),
+        Ok(_) => panic!("Lopsided offer should be rejected"),
+    };
+    accept_manager
+        .accept_contract_offer(&balanced_id)
+        .expect("Balanced offer should be accepted");
+}
+
+const SIGNED_EVENT_NB_DIGITS: u16 = 6;
+
+/// Returns an oracle attesting to the given sign and value for a signed event,
+/// and the input of a contract on this event.
+fn get_signed_event_contract(sign: &str, outcome_value: usize) -> (MockOracle, ContractInput) {
+    let max_value = (1 << SIGNED_EVENT_NB_DIGITS) - 1;
+    let mut oracle = MockOracle::new();
+    oracle.add_event(
+        EVENT_ID,
+        &EventDescriptor::DigitDecompositionEvent(DigitDecompositionEventDescriptor {
+            base: 2,
+            is_signed: true,
+            unit: "sats/sec".to_string(),
+            precision: 0,
+            nb_digits: SIGNED_EVENT_NB_DIGITS,
+        }),
+        EVENT_MATURITY,
+    );
+    assert_eq!(
+        SIGNED_EVENT_NB_DIGITS as usize + 1,
+        oracle
+            .get_announcement(EVENT_ID)
+            .unwrap()
+            .oracle_event
+            .oracle_nonces
+            .len()
+    );
+    let mut outcomes = vec![sign.to_string()];
+    outcomes.extend(
+        decompose_value(outcome_value, 2, SIGNED_EVENT_NB_DIGITS as usize)
+            .iter()
+            .map(|x| x.to_string()),
+    );
+    oracle.add_attestation(EVENT_ID, &outcomes);
+
+    let contract_input = ContractInput {
+        offer_collateral: COLLATERAL,
+        accept_collateral: COLLATERAL,
+        maturity_time: EVENT_MATURITY,
+        fee_rate: 2,
+        contract_infos: vec![ContractInputInfo {
+            contract_descriptor: ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function: PayoutFunction::new(vec![
+                    PayoutFunctionPiece::PolynomialPayoutCurvePiece(
+                        PolynomialPayoutCurvePiece::new(vec![
+                            PayoutPoint {
+                                event_outcome: 0,
+                                outcome_payout: 0,
+                                extra_precision: 0,
+                            },
+                            PayoutPoint {
+                                event_outcome: max_value,
+                                outcome_payout: 2 * COLLATERAL,
+                                extra_precision: 0,
+                            },
+                        ])
+                        .unwrap(),
+                    ),
+                ])
+                .unwrap(),
+                rounding_intervals: RoundingIntervals {
+                    intervals: vec![RoundingInterval {
+                        begin_interval: 0,
+                        rounding_mod: 1,
+                    }],
+                },
+                info: NumericalEventInfo {
+                    base: 2,
+                    nb_digits: SIGNED_EVENT_NB_DIGITS as usize,
+                    unit: "sats/sec".to_string(),
+                },
+                difference_params: None,
+            }),
+            oracles: OracleInput {
+                public_keys: vec![oracle.get_public_key()],
+                event_id: EVENT_ID.to_owned(),
+                threshold: 1,
+            },
+        }],
+    };
+
+    (oracle, contract_input)
+}
+
+#[test]
+fn signed_numerical_event_contract_test() {
+    let (oracle, contract_input) = get_signed_event_contract("+", 40);
+
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    let signed = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let dlc_transactions = &signed.accepted_contract.dlc_transactions;
+    wallet.set_confirmations(&dlc_transactions.fund.txid(), 6);
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    // The adapted offer party signature is only valid if the sign nonce was
+    // included in the adaptor point.
+    let cet = blockchain.get_transactions().pop().unwrap();
+    let secp = Secp256k1::verification_only();
+    let fund_pubkeys = [
+        signed
+            .accepted_contract
+            .offered_contract
+            .offer_params
+            .fund_pubkey,
+        signed.accepted_contract.accept_params.fund_pubkey,
+    ];
+    let witness = &cet.input[0].witness;
+    for pubkey in &fund_pubkeys {
+        let sig_index = if fund_pubkeys.iter().all(|x| pubkey <= x) {
+            1
+        } else {
+            2
+        };
+        let der_sig = &witness[sig_index][..witness[sig_index].len() - 1];
+        dlc::verify_tx_input_sig(
+            &secp,
+            &Signature::from_der(der_sig).unwrap(),
+            &cet,
+            0,
+            &dlc_transactions.funding_script_pubkey,
+            dlc_transactions.get_fund_output().value,
+            pubkey,
+        )
+        .expect("Invalid CET signature");
+    }
+}
+
+#[test]
+fn negative_outcome_of_signed_event_is_refunded_test() {
+    let (oracle, contract_input) = get_signed_event_contract("-", 40);
+    let oracle = Arc::new(oracle);
+
+    let mut clamp_manager = get_funded_manager(
+        &oracle,
+        ManagerConfig {
+            out_of_range_policy: OutOfRangePolicy::Clamp,
+            ..Default::default()
+        },
+    );
+    match clamp_manager.send_offer(&contract_input, get_accept_party_pubkey()) {
+        Err(Error::InvalidParameters(_)) => {}
+        Err(e) => panic!("Unexpected error {}", e),
+        Ok(_) => panic!("Clamping contracts on signed events should be rejected"),
+    };
+
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let refund = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => {
+            wallet.set_confirmations(&s.accepted_contract.dlc_transactions.fund.txid(), 6);
+            s.accepted_contract.dlc_transactions.refund
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    accept_manager.periodic_check().unwrap();
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    set_time(refund.lock_time as u64);
+    accept_manager.periodic_check().unwrap();
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Refunded(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert!(blockchain
+        .get_transactions()
+        .iter()
+        .any(|x| x.txid() == refund.txid()));
+}
+
+#[test]
+fn contract_template_is_instantiated_for_each_event_test() {
+    let mut oracle = get_enum_oracle();
+    let next_event_id = "Test2";
+    let next_event_maturity = EVENT_MATURITY + 7 * 86400;
+    oracle.add_event(
+        next_event_id,
+        &EventDescriptor::EnumEvent(EnumEventDescriptor {
+            outcomes: enum_outcomes(),
+        }),
+        next_event_maturity,
+    );
+    let mut contract_input = get_enum_contract_input(&oracle);
+    let contract_info = contract_input.contract_infos.remove(0);
+    let template = ContractTemplate {
+        offer_collateral: contract_input.offer_collateral,
+        accept_collateral: contract_input.accept_collateral,
+        fee_rate: contract_input.fee_rate,
+        contract_descriptor: contract_info.contract_descriptor,
+        oracle_public_keys: contract_info.oracles.public_keys,
+        threshold: contract_info.oracles.threshold,
+    };
+
+    let first = template
+        .instantiate(&oracle.get_announcement(EVENT_ID).unwrap())
+        .expect("Error instantiating template");
+    let second = template
+        .instantiate(&oracle.get_announcement(next_event_id).unwrap())
+        .expect("Error instantiating template");
+
+    assert_eq!(
+        format!("{:?}", first.contract_infos[0].contract_descriptor),
+        format!("{:?}", second.contract_infos[0].contract_descriptor)
+    );
+    assert_eq!(first.offer_collateral, second.offer_collateral);
+    assert_eq!(first.accept_collateral, second.accept_collateral);
+    assert_eq!(EVENT_ID, first.contract_infos[0].oracles.event_id);
+    assert_eq!(next_event_id, second.contract_infos[0].oracles.event_id);
+    assert_eq!(EVENT_MATURITY, first.maturity_time);
+    assert_eq!(next_event_maturity, second.maturity_time);
+    assert_eq!(
+        template.oracle_public_keys,
+        second.contract_infos[0].oracles.public_keys
+    );
+    assert_eq!(
+        template.threshold,
+        second.contract_infos[0].oracles.threshold
+    );
+
+    // Announcements of other oracles cannot be used.
+    let other_oracle = get_enum_oracle();
+    assert!(matches!(
+        template.instantiate(&other_oracle.get_announcement(EVENT_ID).unwrap()),
+        Err(Error::InvalidParameters(_))
+    ));
+
+    // Offers can be made for both events, each of them reserving a funding
+    // UTXO.
+    let wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &Arc::new(oracle),
+        ManagerConfig::default(),
+        Arc::clone(&wallet),
+        Arc::new(MockBlockchain::new()),
+    );
+    wallet.fund(3 * COLLATERAL);
+    for input in &[first, second] {
+        offer_manager
+            .send_offer(input, get_accept_party_pubkey())
+            .expect("Error sending offer");
+    }
+}
+
+#[test]
+fn payout_table_contract_test() {
+    const NB_DIGITS: u16 = 6;
+    let mut oracle = MockOracle::new();
+    oracle.add_event(
+        EVENT_ID,
+        &EventDescriptor::DigitDecompositionEvent(DigitDecompositionEventDescriptor {
+            base: 2,
+            is_signed: false,
+            unit: "sats/sec".to_string(),
+            precision: 0,
+            nb_digits: NB_DIGITS,
+        }),
+        EVENT_MATURITY,
+    );
+    let rows = vec![
+        PayoutTableRow {
+            start: 0,
+            end: 19,
+            payout: COLLATERAL / 2,
+        },
+        PayoutTableRow {
+            start: 20,
+            end: 39,
+            payout: COLLATERAL,
+        },
+        PayoutTableRow {
+            start: 40,
+            end: (1 << NB_DIGITS) - 1,
+            payout: 3 * COLLATERAL / 2,
+        },
+    ];
+    let table = PayoutTable::new(rows.clone()).unwrap();
+    let contract_input = ContractInput {
+        offer_collateral: COLLATERAL,
+        accept_collateral: COLLATERAL,
+        maturity_time: EVENT_MATURITY,
+        fee_rate: 2,
+        contract_infos: vec![ContractInputInfo {
+            contract_descriptor: ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function: table.to_payout_function(),
+                rounding_intervals: RoundingIntervals {
+                    intervals: vec![RoundingInterval {
+                        begin_interval: 0,
+                        rounding_mod: 1,
+                    }],
+                },
+                info: NumericalEventInfo {
+                    base: 2,
+                    nb_digits: NB_DIGITS as usize,
+                    unit: "sats/sec".to_string(),
+                },
+                difference_params: None,
+            }),
+            oracles: OracleInput {
+                public_keys: vec![oracle.get_public_key()],
+                event_id: EVENT_ID.to_owned(),
+                threshold: 1,
+            },
+        }],
+    };
+
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => {
+            assert_eq!(rows.len(), s.accepted_contract.dlc_transactions.cets.len())
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    for row in &rows {
+        for outcome in &[row.start, (row.start + row.end) / 2, row.end] {
+            let simulated = accept_manager
+                .simulate_outcome(&contract_id, &Outcome::Numerical(*outcome as usize))
+                .expect("Error simulating outcome");
+            assert_eq!(row.payout, simulated.offer_payout);
+            assert_eq!(2 * COLLATERAL - row.payout, simulated.accept_payout);
+        }
+    }
+}
+
+#[test]
+fn out_of_range_outcome_is_resolved_per_policy_test() {
+    const NB_DIGITS: u16 = 5;
+    let out_of_range_value = 25;
+    for policy in &[OutOfRangePolicy::Clamp, OutOfRangePolicy::Refund] {
+        let mut oracle = MockOracle::new();
+        oracle.add_event(
+            EVENT_ID,
+            &EventDescriptor::DigitDecompositionEvent(DigitDecompositionEventDescriptor {
+                base: 2,
+                is_signed: false,
+                unit: "sats/sec".to_string(),
+                precision: 0,
+                nb_digits: NB_DIGITS,
+            }),
+            EVENT_MATURITY,
+        );
+        let outcomes: Vec<String> = decompose_value(out_of_range_value, 2, NB_DIGITS as usize)
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        oracle.add_attestation(EVENT_ID, &outcomes);
+        let table = PayoutTable::new(vec![
+            PayoutTableRow {
+                start: 0,
+                end: 9,
+                payout: COLLATERAL / 2,
+            },
+            PayoutTableRow {
+                start: 10,
+                end: 19,
+                payout: 3 * COLLATERAL / 2,
+            },
+        ])
+        .unwrap();
+        let contract_input = ContractInput {
+            offer_collateral: COLLATERAL,
+            accept_collateral: COLLATERAL,
+            maturity_time: EVENT_MATURITY,
+            fee_rate: 2,
+            contract_infos: vec![ContractInputInfo {
+                contract_descriptor: ContractDescriptor::Numerical(NumericalDescriptor {
+                    payout_function: table.to_payout_function(),
+                    rounding_intervals: RoundingIntervals {
+                        intervals: vec![RoundingInterval {
+                            begin_interval: 0,
+                            rounding_mod: 1,
+                        }],
+                    },
+                    info: NumericalEventInfo {
+                        base: 2,
+                        nb_digits: NB_DIGITS as usize,
+                        unit: "sats/sec".to_string(),
+                    },
+                    difference_params: None,
+                }),
+                oracles: OracleInput {
+                    public_keys: vec![oracle.get_public_key()],
+                    event_id: EVENT_ID.to_owned(),
+                    threshold: 1,
+                },
+            }],
+        };
+
+        let oracle = Arc::new(oracle);
+        let wallet = Arc::new(MockWallet::new());
+        let blockchain = Arc::new(MockBlockchain::new());
+        let mut offer_manager = get_funded_manager(
+            &oracle,
+            ManagerConfig {
+                out_of_range_policy: *policy,
+                ..Default::default()
+            },
+        );
+        let mut accept_manager = get_funded_manager_with(
+            &oracle,
+            ManagerConfig::default(),
+            wallet.clone(),
+            blockchain.clone(),
+        );
+        set_time(EVENT_MATURITY as u64 - 1);
+        let contract_id =
+            run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+        let signed = match accept_manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap()
+        {
+            Some(Contract::Signed(s)) => s,
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+        let dlc_transactions = &signed.accepted_contract.dlc_transactions;
+        wallet.set_confirmations(&dlc_transactions.fund.txid(), 6);
+        let last_covered = accept_manager
+            .simulate_outcome(&contract_id, &Outcome::Numerical(19))
+            .unwrap();
+        set_time(EVENT_MATURITY as u64 + 1);
+        accept_manager.periodic_check().unwrap();
+        accept_manager.periodic_check().unwrap();
+
+        let contract = accept_manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap();
+        match (policy, contract) {
+            (OutOfRangePolicy::Clamp, Some(Contract::Closed(c))) => {
+                let cet = &dlc_transactions.cets[c.cet_index];
+                assert_eq!(last_covered.cet.txid(), cet.txid());
+                assert_eq!(3 * COLLATERAL / 2, last_covered.offer_payout);
+                assert!(blockchain
+                    .get_transactions()
+                    .iter()
+                    .any(|x| x.txid() == cet.txid()));
+            }
+            (OutOfRangePolicy::Refund, Some(Contract::Confirmed(_))) => {
+                set_time(dlc_transactions.refund.lock_time as u64);
+                accept_manager.periodic_check().unwrap();
+                match accept_manager
+                    .get_store()
+                    .get_contract(&contract_id)
+                    .unwrap()
+                {
+                    Some(Contract::Refunded(_)) => {}
+                    c => panic!("Unexpected contract state {:?}", c),
+                };
+                assert!(blockchain
+                    .get_transactions()
+                    .iter()
+                    .any(|x| x.txid() == dlc_transactions.refund.txid()));
+            }
+            (_, c) => panic!("Unexpected contract state {:?} for {:?}", c, policy),
+        }
+    }
+}
+
+#[test]
+fn offer_options_override_contract_input_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let mut manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let balance = wallet.get_available_balance();
+
+    let res = manager.send_offer_with_options(
+        &contract_input,
+        get_accept_party_pubkey(),
+        OfferOptions {
+            cet_locktime: Some(EVENT_MATURITY + 10),
+            refund_locktime: Some(EVENT_MATURITY + 10),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(res, Err(Error::InvalidParameters(_))));
+    assert_eq!(balance, wallet.get_available_balance());
+    assert!(manager.get_store().get_contracts().unwrap().is_empty());
+
+    let offer = manager
+        .send_offer_with_options(
+            &contract_input,
+            get_accept_party_pubkey(),
+            OfferOptions {
+                fee_rate_sat_per_vb: Some(10),
+                cet_locktime: None,
+                refund_locktime: Some(EVENT_MATURITY + 3600),
+                ..Default::default()
+            },
+        )
+        .expect("Error sending offer");
+    assert_eq!(10, offer.fee_rate_per_vb);
+    assert_eq!(EVENT_MATURITY, offer.contract_maturity_bound);
+    assert_eq!(EVENT_MATURITY + 3600, offer.contract_timeout);
+}
+
+#[test]
+fn hash_committed_enum_contract_test() {
+    use lightning::util::ser::Writeable;
+    use secp256k1_zkp::bitcoin_hashes::{sha256, Hash};
+
+    let outcomes: Vec<String> = (0..3).map(|i| format!("private outcome {}", i)).collect();
+    let hashes: Vec<String> = outcomes
+        .iter()
+        .map(|x| sha256::Hash::hash(x.as_bytes()).to_string())
+        .collect();
+    // The oracle only announces the outcome hashes and attests by revealing
+    // the preimage.
+    let mut oracle = MockOracle::new();
+    oracle.add_event(
+        EVENT_ID,
+        &EventDescriptor::EnumEvent(EnumEventDescriptor {
+            outcomes: hashes.clone(),
+        }),
+        EVENT_MATURITY,
+    );
+    oracle.add_attestation(EVENT_ID, &[outcomes[2].clone()]);
+    let mut contract_input = get_enum_contract_input(&oracle);
+    let descriptor = ContractDescriptor::Enum(EnumDescriptor {
+        outcome_payouts: hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| EnumerationPayout {
+                outcome: hash.clone(),
+                payout: Payout {
+                    offer: i as u64 * COLLATERAL,
+                    accept: (2 - i as u64) * COLLATERAL,
+                },
+            })
+            .collect(),
+        outcome_commitment: EnumOutcomeCommitment::Hash,
+    });
+    contract_input.contract_infos[0].contract_descriptor = descriptor;
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let serialized_offer = offer.encode();
+    for outcome in &outcomes {
+        assert!(!serialized_offer
+            .windows(outcome.len())
+            .any(|x| x == outcome.as_bytes()));
+    }
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting offer");
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .expect("Error processing sign");
+
+    let fund_txid = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => {
+            match &s.accepted_contract.offered_contract.contract_info[0].contract_descriptor {
+                ContractDescriptor::Enum(e) => {
+                    assert_eq!(EnumOutcomeCommitment::Hash, e.outcome_commitment)
+                }
+                _ => unreachable!(),
+            };
+            s.accepted_contract.dlc_transactions.fund.txid()
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    wallet.set_confirmations(&fund_txid, 6);
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => assert_eq!(2, c.cet_index),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+}
+
+#[test]
+fn memory_budget_is_enforced_on_accept_test() {
+    let oracle = get_enum_oracle();
+    let large_input = get_enum_contract_input(&oracle);
+    let get_small_input = || {
+        let mut input = get_enum_contract_input(&oracle);
+        match &mut input.contract_infos[0].contract_descriptor {
+            ContractDescriptor::Enum(e) => e.outcome_payouts.truncate(2),
+            _ => unreachable!(),
+        };
+        input
+    };
+    let small_input = get_small_input();
+    let footprint = |input: &ContractInput| {
+        Contract::estimate_memory_footprint(
+            &input.contract_infos[0].contract_descriptor,
+            input.offer_collateral + input.accept_collateral,
+        )
+    };
+    assert_eq!(2 * CET_MEMORY_FOOTPRINT, footprint(&small_input));
+    assert_eq!(2 * footprint(&small_input), footprint(&large_input));
+    // Each contract info is within the budget, but not their CETs together.
+    let mut split_input = get_small_input();
+    split_input
+        .contract_infos
+        .extend(get_small_input().contract_infos);
+
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let accept_config = ManagerConfig {
+        max_contract_memory: Some(3 * CET_MEMORY_FOOTPRINT),
+        ..Default::default()
+    };
+    let mut accept_manager = get_funded_manager(&oracle, accept_config);
+
+    let mut receive_offer = |contract_input: &ContractInput| {
+        offer_wallet.fund(3 * COLLATERAL);
+        let offer = offer_manager
+            .send_offer(contract_input, get_accept_party_pubkey())
+            .expect("Error sending offer");
+        let temporary_contract_id = offer.get_hash().unwrap();
+        accept_manager
+            .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+            .expect("Error processing offer");
+        temporary_contract_id
+    };
+    let large_id = receive_offer(&large_input);
+    let split_id = receive_offer(&split_input);
+    let small_id = receive_offer(&small_input);
+
+    for id in &[large_id, split_id] {
+        match accept_manager.accept_contract_offer(id) {
+            Err(Error::MemoryBudgetExceeded) => {}
+            Err(e) => panic!("Unexpected error {}", e),
+            Ok(_) => panic!("Large offer should be rejected"),
+        };
+    }
+    accept_manager
+        .accept_contract_offer(&small_id)
+        .expect("Small offer should be accepted");
+}
+
+#[test]
+fn payout_preview_matches_enum_descriptor_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+
+    let preview = accept_manager
+        .get_payout_preview(&temporary_contract_id)
+        .expect("Error computing payout preview");
+
+    let expected: Vec<(String, u64, u64)> =
+        match &contract_input.contract_infos[0].contract_descriptor {
+            ContractDescriptor::Enum(e) => e
+                .outcome_payouts
+                .iter()
+                .map(|x| (x.outcome.clone(), x.payout.offer, x.payout.accept))
+                .collect(),
+            _ => unreachable!(),
+        };
+    assert_eq!(expected, preview);
+    assert!(preview
+        .iter()
+        .all(|(_, offer, accept)| offer + accept == 2 * COLLATERAL));
+    assert!(matches!(
+        accept_manager.get_payout_preview(&[0u8; 32]),
+        Err(Error::UnknownContract)
+    ));
+}
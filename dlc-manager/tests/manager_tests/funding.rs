@@ -0,0 +1,741 @@
+use crate::common::{
+    enum_outcomes, establish_contract, get_accept_party_pubkey, get_enum_contract_input,
+    get_enum_oracle, get_funded_manager, get_funded_manager_with, get_manager, get_manager_with,
+    get_offer_party_pubkey, get_offered_contract, get_signed_contract,
+    get_store_with_signed_contract, run_contract_setup, TestManager, COLLATERAL, EVENT_ID,
+    EVENT_MATURITY,
+};
+use bitcoin::OutPoint;
+use dlc::FeeContribution;
+use dlc_manager::contract::{ClosedContract, Contract, ContractState};
+use dlc_manager::error::Error;
+use dlc_manager::manager::{Manager, ManagerConfig, OfferOptions};
+use dlc_manager::{Oracle, Storage, UtxoProof, Wallet};
+use dlc_messages::Message;
+use mocks::memory_storage_provider::MemoryStorage;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_time::{set_time, MockTime};
+use mocks::mock_wallet::MockWallet;
+use secp256k1_zkp::Secp256k1;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn notify_funding_confirmed_updates_state_test() {
+    let signed = get_signed_contract();
+    let mut manager = get_manager(get_store_with_signed_contract(&signed));
+    let contract_id = signed.accepted_contract.get_contract_id();
+
+    manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+
+    match manager.get_store().get_contract(&contract_id).unwrap() {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+    assert_eq!(
+        Some(150),
+        manager.get_funding_confirmation_height(&contract_id)
+    );
+
+    manager
+        .notify_funding_confirmed(&contract_id, 151)
+        .expect_err("Confirmed contract should not be confirmed again");
+    assert_eq!(
+        Some(150),
+        manager.get_funding_confirmation_height(&contract_id)
+    );
+}
+
+#[test]
+fn funding_confirmation_height_is_kept_after_restart_test() {
+    let signed = get_signed_contract();
+    let store = get_store_with_signed_contract(&signed);
+    let contract_id = signed.accepted_contract.get_contract_id();
+    let mut manager = get_manager(store.share());
+    manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+    drop(manager);
+
+    let restarted_manager = get_manager(store);
+    assert_eq!(
+        Some(150),
+        restarted_manager.get_funding_confirmation_height(&contract_id)
+    );
+}
+
+#[test]
+fn utxo_proof_is_used_to_confirm_funding_test() {
+    let signed = get_signed_contract();
+    let contract_id = signed.accepted_contract.get_contract_id();
+    let dlc_transactions = &signed.accepted_contract.dlc_transactions;
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut manager = get_manager_with(
+        get_store_with_signed_contract(&signed),
+        Arc::new(MockWallet::new()),
+        blockchain.clone(),
+    );
+
+    // The wallet does not know about the fund transaction.
+    manager.periodic_check().expect("Periodic check error");
+    match manager.get_store().get_contract(&contract_id).unwrap() {
+        Some(Contract::Signed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+
+    blockchain.set_utxo_proof(
+        OutPoint {
+            txid: dlc_transactions.fund.txid(),
+            vout: dlc_transactions.get_fund_output_index() as u32,
+        },
+        UtxoProof {
+            tx_out: dlc_transactions.get_fund_output().clone(),
+            confirmations: 6,
+            proof: Vec::new(),
+        },
+    );
+    manager.periodic_check().expect("Periodic check error");
+
+    match manager.get_store().get_contract(&contract_id).unwrap() {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+}
+
+#[test]
+fn stale_funding_contracts_test() {
+    let broadcast_time = EVENT_MATURITY as u64 - 86400;
+    let older_than = Duration::from_secs(3600);
+    set_time(broadcast_time);
+    let (_, mut accept_manager, contract_id) = establish_contract(get_enum_oracle());
+
+    set_time(broadcast_time + older_than.as_secs());
+    assert!(accept_manager
+        .stale_funding_contracts(older_than)
+        .is_empty());
+
+    set_time(broadcast_time + older_than.as_secs() + 1);
+    assert_eq!(
+        vec![contract_id],
+        accept_manager.stale_funding_contracts(older_than)
+    );
+
+    accept_manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+    assert!(accept_manager
+        .stale_funding_contracts(older_than)
+        .is_empty());
+}
+
+#[test]
+fn funding_broadcast_time_is_kept_after_restart_test() {
+    let broadcast_time = EVENT_MATURITY as u64 - 86400;
+    let older_than = Duration::from_secs(3600);
+    set_time(broadcast_time);
+    let (_, accept_manager, contract_id) = establish_contract(get_enum_oracle());
+    let store = accept_manager.get_store().share();
+    drop(accept_manager);
+
+    let restarted_manager = get_manager(store);
+    set_time(broadcast_time + older_than.as_secs() + 1);
+    assert_eq!(
+        vec![contract_id],
+        restarted_manager.stale_funding_contracts(older_than)
+    );
+}
+
+#[test]
+fn total_locked_collateral_excludes_closed_contracts_test() {
+    let mut store = MemoryStorage::new();
+
+    let mut offered = get_offered_contract();
+    offered.id = [1; 32];
+    offered.is_offer_party = true;
+    offered.offer_params.collateral = 1000;
+    store.create_contract(&offered).unwrap();
+
+    let get_signed = |temporary_id, is_offer_party, collateral| {
+        let mut signed = get_signed_contract();
+        let accepted = &mut signed.accepted_contract;
+        accepted.offered_contract.id = temporary_id;
+        accepted.offered_contract.is_offer_party = is_offer_party;
+        if is_offer_party {
+            accepted.offered_contract.offer_params.collateral = collateral;
+        } else {
+            accepted.accept_params.collateral = collateral;
+        }
+        signed
+    };
+    store
+        .update_contract(&Contract::Signed(get_signed([2; 32], true, 2000)))
+        .unwrap();
+    store
+        .update_contract(&Contract::Confirmed(get_signed([3; 32], false, 3000)))
+        .unwrap();
+    store
+        .update_contract(&Contract::Closed(ClosedContract {
+            signed_contract: get_signed([4; 32], true, 4000),
+            attestations: Vec::new(),
+            cet_index: 0,
+        }))
+        .unwrap();
+
+    let manager = get_manager(store);
+    assert_eq!(
+        6000,
+        manager
+            .total_locked_collateral()
+            .expect("Error computing locked collateral")
+    );
+}
+
+#[test]
+fn accept_with_explicit_inputs_uses_exactly_these_inputs_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let small_utxo = accept_wallet.fund(1000);
+    let chosen_utxo = accept_wallet.fund(3 * COLLATERAL);
+    let other_utxo = accept_wallet.fund(3 * COLLATERAL);
+    let mut oracles = HashMap::new();
+    oracles.insert(oracle.get_public_key(), Arc::clone(&oracle));
+    let mut accept_manager: TestManager = Manager::new(
+        accept_wallet,
+        Arc::new(MockBlockchain::new()),
+        Box::new(MemoryStorage::new()),
+        oracles,
+        Arc::new(MockTime {}),
+    );
+
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+
+    match accept_manager
+        .accept_contract_offer_with_inputs(&temporary_contract_id, &[small_utxo.outpoint])
+    {
+        Err(Error::InsufficientFunds {
+            required,
+            available,
+        }) => {
+            assert_eq!(small_utxo.tx_out.value, available);
+            assert!(required > available);
+        }
+        res => panic!("Unexpected result {:?}", res.map(|x| x.0)),
+    }
+
+    let (contract_id, _, _) = accept_manager
+        .accept_contract_offer_with_inputs(&temporary_contract_id, &[chosen_utxo.outpoint])
+        .expect("Error accepting offer");
+
+    let accepted = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Accepted(a)) => a,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let accept_outpoints: Vec<_> = accepted
+        .accept_params
+        .inputs
+        .iter()
+        .map(|x| x.outpoint)
+        .collect();
+    assert_eq!(vec![chosen_utxo.outpoint], accept_outpoints);
+    let fund_outpoints: Vec<_> = accepted
+        .dlc_transactions
+        .fund
+        .input
+        .iter()
+        .map(|x| x.previous_output)
+        .collect();
+    assert!(fund_outpoints.contains(&chosen_utxo.outpoint));
+    assert!(!fund_outpoints.contains(&other_utxo.outpoint));
+    assert!(!fund_outpoints.contains(&small_utxo.outpoint));
+}
+
+#[test]
+fn reserve_proof_covers_active_contracts_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        accept_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut fund_txs = Vec::new();
+    for _ in 0..2 {
+        offer_wallet.fund(3 * COLLATERAL);
+        accept_wallet.fund(3 * COLLATERAL);
+        let contract_id =
+            run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+        match accept_manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap()
+        {
+            Some(Contract::Signed(s)) => fund_txs.push(s.accepted_contract.dlc_transactions.fund),
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+    }
+
+    let challenge = b"Prove reserves at block 700000";
+    let proof = accept_manager
+        .prove_reserves(challenge)
+        .expect("Error generating reserve proof");
+    assert_eq!(2, proof.entries.len());
+
+    // Check the proof as a third party would, using the broadcast fund
+    // transactions as the source of truth for the funding outputs.
+    let secp = Secp256k1::verification_only();
+    proof
+        .verify(&secp, challenge)
+        .expect("Reserve proof should be valid");
+    for entry in &proof.entries {
+        let fund_tx = fund_txs
+            .iter()
+            .find(|x| x.txid() == entry.outpoint.txid)
+            .expect("Unknown funding outpoint");
+        let output = &fund_tx.output[entry.outpoint.vout as usize];
+        assert_eq!(output.value, entry.value);
+        assert_eq!(output.script_pubkey, entry.funding_script.to_v0_p2wsh());
+    }
+    assert!(proof.total_value() > 4 * COLLATERAL);
+    assert!(proof.verify(&secp, b"Another challenge").is_err());
+}
+
+#[test]
+fn orphan_reservations_are_reclaimed_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let store = MemoryStorage::new();
+    let mut store_handle = store.share();
+    let mut oracles = HashMap::new();
+    oracles.insert(oracle.get_public_key(), Arc::new(oracle));
+    let mut manager: TestManager = Manager::new_with_config(
+        wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+        Box::new(store),
+        oracles,
+        Arc::new(MockTime {}),
+        ManagerConfig::default(),
+    );
+    wallet.fund(3 * COLLATERAL);
+    let kept_offer = manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    assert_eq!(0, manager.reclaim_orphan_reservations().unwrap());
+
+    // Reservation made for an offer that was never stored.
+    wallet.fund(3 * COLLATERAL);
+    let lost_offer = manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    store_handle
+        .delete_contract(&lost_offer.get_hash().unwrap())
+        .unwrap();
+    // Reservation made by another application sharing the wallet.
+    wallet.fund(COLLATERAL);
+    wallet
+        .get_utxos_for_amount(COLLATERAL, None, true)
+        .expect("Error reserving UTXO");
+    assert_eq!(0, wallet.get_available_balance());
+
+    assert_eq!(1, manager.reclaim_orphan_reservations().unwrap());
+    assert_eq!(3 * COLLATERAL, wallet.get_available_balance());
+    // The reservations of the stored offer and of the other application are
+    // kept.
+    assert_eq!(2, wallet.get_reserved_utxos().unwrap().len());
+    assert!(store_handle
+        .get_contract(&kept_offer.get_hash().unwrap())
+        .unwrap()
+        .is_some());
+    assert_eq!(0, manager.reclaim_orphan_reservations().unwrap());
+}
+
+#[test]
+fn rejected_offer_releases_offer_party_inputs_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    assert_eq!(0, offer_wallet.get_available_balance());
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+
+    // Only received offers can be rejected.
+    assert!(matches!(
+        offer_manager.reject_offer(&temporary_contract_id),
+        Err(Error::InvalidState {
+            found: ContractState::Offered,
+            ..
+        })
+    ));
+
+    let reject = accept_manager
+        .reject_offer(&temporary_contract_id)
+        .expect("Error rejecting offer");
+    assert!(offer_manager
+        .on_dlc_message(&Message::Reject(reject), get_accept_party_pubkey())
+        .expect("Error processing reject")
+        .is_none());
+
+    for manager in &[&offer_manager, &accept_manager] {
+        match manager
+            .get_store()
+            .get_contract(&temporary_contract_id)
+            .unwrap()
+        {
+            Some(Contract::Rejected(_)) => {}
+            c => panic!("Unexpected contract state {:?}", c),
+        }
+    }
+    assert!(offer_wallet.get_reserved_utxos().unwrap().is_empty());
+    assert_eq!(3 * COLLATERAL, offer_wallet.get_available_balance());
+    assert!(accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .is_err());
+}
+
+#[test]
+fn resync_on_startup_rebroadcasts_dropped_fund_tx_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let accept_wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        accept_wallet.clone(),
+        blockchain.clone(),
+    );
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let fund_tx = blockchain.get_transactions().last().unwrap().clone();
+
+    // The node restarts and the fund transaction never reached the network.
+    let restart_blockchain = Arc::new(MockBlockchain::new());
+    let mut oracles = HashMap::new();
+    oracles.insert(oracle.get_public_key(), Arc::clone(&oracle));
+    let mut restarted_manager: TestManager = Manager::new(
+        accept_wallet.clone(),
+        restart_blockchain.clone(),
+        Box::new(MemoryStorage::new()),
+        oracles,
+        Arc::new(MockTime {}),
+    );
+    restarted_manager
+        .merge_from(accept_manager.get_store())
+        .unwrap();
+
+    restarted_manager.resync_on_startup().unwrap();
+    restarted_manager.resync_on_startup().unwrap();
+
+    let broadcasts = restart_blockchain.get_transactions();
+    assert_eq!(2, broadcasts.len());
+    assert!(broadcasts.iter().all(|x| x == &fund_tx));
+    match restarted_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    accept_wallet.set_confirmations(&fund_tx.txid(), 6);
+    restarted_manager.resync_on_startup().unwrap();
+    restarted_manager.resync_on_startup().unwrap();
+
+    assert_eq!(2, restart_blockchain.get_transactions().len());
+    match restarted_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+}
+
+#[test]
+fn renewed_contract_spends_prior_payouts_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        accept_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    let fund_txid = match offer_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.fund.txid(),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    offer_wallet.set_confirmations(&fund_txid, 6);
+    set_time(EVENT_MATURITY as u64 + 1);
+    offer_manager
+        .periodic_check()
+        .expect("Periodic check error");
+
+    // The attested outcome pays the whole collateral to the offer party.
+    let prior_payouts: Vec<OutPoint> = match offer_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => {
+            let accepted = &c.signed_contract.accepted_contract;
+            let cet = &accepted.dlc_transactions.cets[c.cet_index];
+            cet.output
+                .iter()
+                .enumerate()
+                .filter(|(_, x)| {
+                    x.script_pubkey == accepted.offered_contract.offer_params.payout_script_pubkey
+                })
+                .map(|(vout, _)| OutPoint {
+                    txid: cet.txid(),
+                    vout: vout as u32,
+                })
+                .collect()
+        }
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert!(!prior_payouts.is_empty());
+
+    let renewal = offer_manager
+        .renew_offer(&contract_id, &contract_input)
+        .expect("Error renewing contract");
+    let temporary_contract_id = renewal.get_hash().unwrap();
+    accept_wallet.fund(3 * COLLATERAL);
+    accept_manager
+        .on_dlc_message(&Message::Offer(renewal), get_offer_party_pubkey())
+        .expect("Error processing renewal offer");
+    let (new_contract_id, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting renewal offer");
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .expect("Error processing sign");
+
+    let fund = match accept_manager
+        .get_store()
+        .get_contract(&new_contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.fund,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    for outpoint in prior_payouts {
+        assert!(fund.input.iter().any(|x| x.previous_output == outpoint));
+    }
+}
+
+#[test]
+fn fund_cpfp_meets_target_package_fee_rate_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        Arc::new(MockWallet::new()),
+        Arc::clone(&blockchain),
+    );
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let signed = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let accepted = &signed.accepted_contract;
+    let fund_txid = accepted.dlc_transactions.fund.txid();
+    let fund_tx = blockchain
+        .get_transactions()
+        .into_iter()
+        .find(|x| x.txid() == fund_txid)
+        .expect("Fund transaction was not broadcast");
+
+    let target_fee_rate = 50;
+    let child = accept_manager
+        .create_fund_cpfp(&contract_id, target_fee_rate)
+        .expect("Error creating CPFP transaction");
+
+    assert_eq!(1, child.input.len());
+    let spent_outpoint = child.input[0].previous_output;
+    assert_eq!(fund_txid, spent_outpoint.txid);
+    let spent_output = &fund_tx.output[spent_outpoint.vout as usize];
+    assert_eq!(
+        accepted.accept_params.change_script_pubkey,
+        spent_output.script_pubkey
+    );
+    assert!(blockchain
+        .get_transactions()
+        .iter()
+        .any(|x| x.txid() == child.txid()));
+
+    let fund_input_value: u64 = accepted
+        .offered_contract
+        .funding_inputs_info
+        .iter()
+        .chain(accepted.funding_inputs.iter())
+        .map(|x| {
+            let prev_tx: bitcoin::Transaction =
+                bitcoin::consensus::deserialize(&x.funding_input.prev_tx).unwrap();
+            prev_tx.output[x.funding_input.prev_tx_vout as usize].value
+        })
+        .sum();
+    let fund_fee = fund_input_value - fund_tx.output.iter().map(|x| x.value).sum::<u64>();
+    let child_fee = spent_output.value - child.output.iter().map(|x| x.value).sum::<u64>();
+    let package_weight = (fund_tx.get_weight() + child.get_weight()) as u64;
+    assert!((fund_fee + child_fee) * 4 >= target_fee_rate * package_weight);
+}
+
+#[test]
+fn offerer_pays_all_fees_when_negotiated_in_offer_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+
+    assert!(matches!(
+        offer_manager.send_offer_with_options(
+            &contract_input,
+            get_accept_party_pubkey(),
+            OfferOptions {
+                fee_contribution: FeeContribution::Ratio(0, 0),
+                ..Default::default()
+            },
+        ),
+        Err(Error::InvalidParameters(_))
+    ));
+
+    let offer = offer_manager
+        .send_offer_with_options(
+            &contract_input,
+            get_accept_party_pubkey(),
+            OfferOptions {
+                fee_contribution: FeeContribution::OffererPays,
+                ..Default::default()
+            },
+        )
+        .expect("Error sending offer");
+    assert_eq!(FeeContribution::OffererPays, offer.fee_contribution);
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting offer");
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .expect("Error processing sign");
+
+    let contract = accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+        .expect("Contract not found");
+    let signed = match &contract {
+        Contract::Signed(s) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let accepted = &signed.accepted_contract;
+    let offered = &accepted.offered_contract;
+    let accept_params = &accepted.accept_params;
+    let fund = &accepted.dlc_transactions.fund;
+    let accept_change: u64 = fund
+        .output
+        .iter()
+        .filter(|x| x.script_pubkey == accept_params.change_script_pubkey)
+        .map(|x| x.value)
+        .sum();
+    assert_eq!(
+        accept_params.input_amount - accept_params.collateral,
+        accept_change
+    );
+    let fund_fee = offered.offer_params.input_amount + accept_params.input_amount
+        - fund.output.iter().map(|x| x.value).sum::<u64>();
+    let offer_change: u64 = fund
+        .output
+        .iter()
+        .filter(|x| x.script_pubkey == offered.offer_params.change_script_pubkey)
+        .map(|x| x.value)
+        .sum();
+    let fund_output_value = accepted.dlc_transactions.get_fund_output().value;
+    assert_eq!(
+        offered.offer_params.input_amount - offered.offer_params.collateral - offer_change,
+        fund_fee + fund_output_value - offered.total_collateral
+    );
+    contract
+        .verify_change_outputs(fund)
+        .expect("Invalid change outputs");
+}
@@ -0,0 +1,10 @@
+extern crate dlc_manager;
+
+mod closing;
+mod common;
+mod contracts;
+mod funding;
+mod messages;
+mod oracles;
+mod signatures;
+mod state;
@@ -0,0 +1,421 @@
+use crate::common::{
+    establish_contract, get_accept_party_pubkey, get_enum_contract_input, get_enum_oracle,
+    get_funded_manager, get_funded_manager_with, get_manager, get_offer_party_pubkey,
+    get_offered_contract, TestManager, COLLATERAL, EVENT_MATURITY,
+};
+#[cfg(feature = "tokio")]
+use dlc_manager::async_manager::AsyncManager;
+use dlc_manager::contract::{Contract, ContractState};
+use dlc_manager::error::Error;
+use dlc_manager::manager::{
+    Manager, ManagerConfig, EARLY_SIGN_MESSAGE_TIMEOUT, MAX_EARLY_SIGN_MESSAGES_PER_COUNTER_PARTY,
+};
+use dlc_manager::{ContractDataKind, Oracle, Storage};
+use dlc_messages::{Message, OfferDlc};
+use mocks::memory_storage_provider::MemoryStorage;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_time::{set_time, MockTime};
+use mocks::mock_wallet::MockWallet;
+use secp256k1_zkp::{PublicKey, Secp256k1, SecretKey};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[test]
+fn offer_with_duplicate_temporary_id_is_rejected_test() {
+    let offer: OfferDlc = (&get_offered_contract()).into();
+    let temporary_contract_id = offer.get_hash().unwrap();
+    let first_counter_party = "0218845781f631c48f1c9709e23092067d06837f30aa0cd0544ac887fe91ddd166"
+        .parse()
+        .unwrap();
+    let second_counter_party =
+        PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&[1; 32]).unwrap());
+    let mut manager = get_manager(MemoryStorage::new());
+
+    manager
+        .on_dlc_message(&Message::Offer(offer.clone()), first_counter_party)
+        .expect("Error processing offer");
+    let res = manager.on_dlc_message(&Message::Offer(offer), second_counter_party);
+
+    assert!(matches!(res, Err(Error::DuplicateTemporaryContractId)));
+    match manager
+        .get_store()
+        .get_contract(&temporary_contract_id)
+        .unwrap()
+    {
+        Some(Contract::Offered(o)) => assert_eq!(first_counter_party, o.counter_party),
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+}
+
+#[test]
+fn divergent_contract_ids_are_detected_test() {
+    let (offer_manager, accept_manager, contract_id) = establish_contract(get_enum_oracle());
+
+    offer_manager
+        .assert_contract_id_agreement(&contract_id, contract_id)
+        .expect("Offer party to agree on contract id");
+    accept_manager
+        .assert_contract_id_agreement(&contract_id, contract_id)
+        .expect("Accept party to agree on contract id");
+
+    let mut divergent_id = contract_id;
+    divergent_id[0] ^= 1;
+    assert!(matches!(
+        accept_manager.assert_contract_id_agreement(&contract_id, divergent_id),
+        Err(Error::ContractIdMismatch)
+    ));
+}
+
+#[test]
+fn batched_messages_are_processed_per_counter_party_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let third_wallet = Arc::new(MockWallet::new());
+    let mut third_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        third_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    // The first offer reserves the whole funding UTXO.
+    third_wallet.fund(3 * COLLATERAL);
+    let secp = Secp256k1::new();
+    let third_party =
+        PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[4u8; 32]).unwrap());
+
+    let offer = manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .unwrap();
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .unwrap();
+    let (_, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .unwrap();
+
+    let third_offer = third_manager
+        .send_offer(&contract_input, get_offer_party_pubkey())
+        .unwrap();
+    let skipped_offer = third_manager
+        .send_offer(&contract_input, get_offer_party_pubkey())
+        .unwrap();
+    let skipped_id = skipped_offer.get_hash().unwrap();
+
+    let responses = manager
+        .on_dlc_messages(&[
+            (Message::Offer(third_offer.clone()), third_party),
+            // Fails as the offer was already received.
+            (Message::Offer(third_offer.clone()), third_party),
+            (Message::Offer(skipped_offer), third_party),
+            (Message::Accept(accept), get_accept_party_pubkey()),
+        ])
+        .unwrap();
+
+    assert_eq!(4, responses.len());
+    assert!(matches!(responses[0], Ok(None)));
+    assert!(matches!(
+        responses[1],
+        Err(Error::DuplicateTemporaryContractId)
+    ));
+    assert!(matches!(responses[2], Err(Error::SkippedMessage)));
+    match &responses[3] {
+        Ok(Some(Message::Sign(_))) => {}
+        r => panic!("Expected a sign message, got {:?}", r),
+    };
+    match manager
+        .get_store()
+        .get_contract(&third_offer.get_hash().unwrap())
+        .unwrap()
+    {
+        Some(Contract::Offered(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert!(manager
+        .get_store()
+        .get_contract(&skipped_id)
+        .unwrap()
+        .is_none());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_manager_offer_accept_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_manager = AsyncManager::new(get_funded_manager(&oracle, ManagerConfig::default()));
+    let accept_manager = AsyncManager::new(get_funded_manager(&oracle, ManagerConfig::default()));
+
+    let offer = offer_manager
+        .send_offer(contract_input, get_accept_party_pubkey())
+        .await
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    assert!(accept_manager
+        .on_dlc_message(Message::Offer(offer), get_offer_party_pubkey())
+        .await
+        .expect("Error processing offer")
+        .is_none());
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer(temporary_contract_id)
+        .await
+        .expect("Error accepting offer");
+    let sign = offer_manager
+        .on_dlc_message(Message::Accept(accept), get_accept_party_pubkey())
+        .await
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    accept_manager
+        .on_dlc_message(sign, get_offer_party_pubkey())
+        .await
+        .expect("Error processing sign");
+
+    for manager in &[offer_manager, accept_manager] {
+        let manager = manager.get_manager();
+        let manager = manager.lock().unwrap();
+        match manager.get_store().get_contract(&contract_id).unwrap() {
+            Some(Contract::Signed(_)) => {}
+            c => panic!("Unexpected contract state {:?}", c),
+        }
+    }
+}
+
+#[test]
+fn sign_message_with_divergent_contract_id_is_rejected_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting offer");
+    let mut sign = match offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+    {
+        Some(Message::Sign(s)) => s,
+        m => panic!("Unexpected message {:?}", m),
+    };
+    sign.contract_id[0] ^= 1;
+
+    match accept_manager.on_dlc_message(&Message::Sign(sign), get_offer_party_pubkey()) {
+        Err(Error::ContractIdMismatch) => {}
+        r => panic!("Unexpected result {:?}", r),
+    };
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Accepted(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+}
+
+#[test]
+fn replayed_sign_message_returns_invalid_state_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let (_, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting offer");
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .expect("Error processing sign");
+
+    match accept_manager.on_dlc_message(&sign, get_offer_party_pubkey()) {
+        Err(Error::InvalidState { expected, found }) => {
+            assert_eq!(vec![ContractState::Accepted], expected);
+            assert_eq!(ContractState::Signed, found);
+        }
+        res => panic!("Unexpected result {:?}", res),
+    }
+}
+
+/// Sets up an accept party manager buffering early sign messages, whose
+/// storage is rolled back to before it accepted an offer, as if the sign
+/// message of the offer party overtook the accept message. Returns the accept
+/// party manager, a handle to its storage, the accepted contract and the sign
+/// message.
+fn get_overtaken_sign_message() -> (TestManager, MemoryStorage, Contract, Message) {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let accept_wallet = Arc::new(MockWallet::new());
+    accept_wallet.fund(3 * COLLATERAL);
+    let accept_store = MemoryStorage::new();
+    let mut accept_store_handle = accept_store.share();
+    let mut oracles = HashMap::new();
+    oracles.insert(oracle.get_public_key(), Arc::clone(&oracle));
+    let mut accept_manager: TestManager = Manager::new_with_config(
+        accept_wallet,
+        Arc::new(MockBlockchain::new()),
+        Box::new(accept_store),
+        oracles,
+        Arc::new(MockTime {}),
+        ManagerConfig {
+            buffer_early_sign_messages: true,
+            ..Default::default()
+        },
+    );
+
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let offered = accept_store_handle
+        .get_contract(&temporary_contract_id)
+        .unwrap()
+        .unwrap();
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting offer");
+    let accepted = accept_store_handle
+        .get_contract(&contract_id)
+        .unwrap()
+        .unwrap();
+
+    // Roll the storage back to before the accept was stored, as if the sign
+    // message overtook it.
+    accept_store_handle.delete_contract(&contract_id).unwrap();
+    accept_store_handle.update_contract(&offered).unwrap();
+
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    (accept_manager, accept_store_handle, accepted, sign)
+}
+
+#[test]
+fn early_sign_message_is_buffered_and_applied_test() {
+    let (mut accept_manager, mut accept_store_handle, accepted, sign) =
+        get_overtaken_sign_message();
+    let contract_id = accepted.get_id();
+
+    assert!(accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .expect("Sign message should be buffered")
+        .is_none());
+    assert!(accept_store_handle
+        .get_contract(&contract_id)
+        .unwrap()
+        .is_none());
+
+    accept_store_handle.update_contract(&accepted).unwrap();
+    accept_manager
+        .periodic_check()
+        .expect("Error in periodic check");
+
+    match accept_store_handle.get_contract(&contract_id).unwrap() {
+        Some(Contract::Signed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+}
+
+#[test]
+fn early_sign_message_of_other_peer_is_not_applied_test() {
+    set_time(EVENT_MATURITY as u64 - 2 * EARLY_SIGN_MESSAGE_TIMEOUT);
+    let (mut accept_manager, mut accept_store_handle, accepted, sign) =
+        get_overtaken_sign_message();
+    let contract_id = accepted.get_id();
+    let secp = Secp256k1::new();
+    let other_party =
+        PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[4u8; 32]).unwrap());
+
+    accept_manager
+        .on_dlc_message(&sign, other_party)
+        .expect("Sign message should be buffered");
+    accept_store_handle.update_contract(&accepted).unwrap();
+    accept_manager
+        .periodic_check()
+        .expect("Error in periodic check");
+
+    match accept_store_handle.get_contract(&contract_id).unwrap() {
+        Some(Contract::Accepted(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+    assert_eq!(
+        1,
+        accept_store_handle
+            .get_contract_data(ContractDataKind::EarlySignMessage)
+            .unwrap()
+            .len()
+    );
+
+    set_time(EVENT_MATURITY as u64 - EARLY_SIGN_MESSAGE_TIMEOUT + 1);
+    accept_manager
+        .periodic_check()
+        .expect("Error in periodic check");
+    assert!(accept_store_handle
+        .get_contract_data(ContractDataKind::EarlySignMessage)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn early_sign_messages_are_capped_per_peer_test() {
+    set_time(EVENT_MATURITY as u64 - 2 * EARLY_SIGN_MESSAGE_TIMEOUT);
+    let (mut accept_manager, _, _, sign) = get_overtaken_sign_message();
+    let sign = match sign {
+        Message::Sign(s) => s,
+        _ => unreachable!(),
+    };
+
+    for i in 0..MAX_EARLY_SIGN_MESSAGES_PER_COUNTER_PARTY {
+        let mut sign = sign.clone();
+        sign.contract_id = [i as u8; 32];
+        accept_manager
+            .on_dlc_message(&Message::Sign(sign), get_offer_party_pubkey())
+            .expect("Sign message should be buffered");
+    }
+
+    assert!(matches!(
+        accept_manager.on_dlc_message(&Message::Sign(sign.clone()), get_offer_party_pubkey()),
+        Err(Error::InvalidParameters(_))
+    ));
+    // Other peers are not affected.
+    let secp = Secp256k1::new();
+    let other_party =
+        PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[4u8; 32]).unwrap());
+    accept_manager
+        .on_dlc_message(&Message::Sign(sign.clone()), other_party)
+        .expect("Sign message should be buffered");
+
+    // Expired messages no longer count towards the limit.
+    set_time(EVENT_MATURITY as u64 - EARLY_SIGN_MESSAGE_TIMEOUT + 1);
+    accept_manager
+        .on_dlc_message(&Message::Sign(sign), get_offer_party_pubkey())
+        .expect("Sign message should be buffered");
+}
@@ -0,0 +1,633 @@
+use crate::common::{
+    enum_outcomes, establish_contract, get_accept_party_pubkey, get_enum_contract_input,
+    get_enum_oracle, get_funded_manager, get_funded_manager_with, get_manager_with,
+    get_offer_party_pubkey, run_contract_setup, TestManager, COLLATERAL, EVENT_ID, EVENT_MATURITY,
+};
+use dlc_manager::contract::{
+    contract_input::{ContractInput, ContractInputInfo, OracleInput},
+    numerical_descriptor::{NumericalDescriptor, NumericalEventInfo},
+    Contract, ContractDescriptor,
+};
+use dlc_manager::error::Error;
+use dlc_manager::manager::{Manager, ManagerAction, ManagerConfig, REFUND_DELAY};
+use dlc_manager::payout_curve::{PayoutTable, PayoutTableRow, RoundingInterval, RoundingIntervals};
+use dlc_manager::{ContractId, Oracle, Storage};
+use dlc_messages::oracle_msgs::{
+    DigitDecompositionEventDescriptor, EnumEventDescriptor, EventDescriptor, OracleKeyDelegation,
+};
+use dlc_messages::Message;
+use mocks::memory_storage_provider::MemoryStorage;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_oracle_provider::MockOracle;
+use mocks::mock_time::{set_time, MockTime};
+use mocks::mock_wallet::MockWallet;
+use secp256k1_zkp::schnorrsig::{KeyPair, PublicKey as SchnorrPublicKey};
+use secp256k1_zkp::{Secp256k1, SecretKey};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn attestation_is_verified_once_for_contracts_on_same_event_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        accept_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let mut contract_ids = Vec::new();
+    for _ in 0..2 {
+        offer_wallet.fund(3 * COLLATERAL);
+        accept_wallet.fund(3 * COLLATERAL);
+        let contract_id =
+            run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+        match accept_manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap()
+        {
+            Some(Contract::Signed(s)) => accept_wallet
+                .set_confirmations(&s.accepted_contract.dlc_transactions.fund.txid(), 6),
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+        contract_ids.push(contract_id);
+    }
+    let verifications = Arc::new(AtomicUsize::new(0));
+    let counter = verifications.clone();
+    accept_manager.set_attestation_validated_hook(move |_, event_id| {
+        assert_eq!(EVENT_ID, event_id);
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    accept_manager.periodic_check().unwrap();
+
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    assert_eq!(1, verifications.load(Ordering::SeqCst));
+    for contract_id in &contract_ids {
+        assert!(matches!(
+            accept_manager
+                .get_store()
+                .get_contract(contract_id)
+                .unwrap(),
+            Some(Contract::Closed(_))
+        ));
+    }
+}
+
+/// Makes the given oracle sign with a key delegated by the given master key,
+/// the delegation being signed by `delegation_signer`.
+fn delegate_oracle_key(oracle: &mut MockOracle, master: &KeyPair, delegation_signer: &KeyPair) {
+    let secp = Secp256k1::new();
+    let delegated_public_key = oracle.get_signing_public_key();
+    let msg = OracleKeyDelegation::get_delegation_message(&delegated_public_key);
+    oracle.set_key_delegation(OracleKeyDelegation {
+        master_public_key: SchnorrPublicKey::from_keypair(&secp, master),
+        delegated_public_key,
+        delegation_signature: secp.schnorrsig_sign(&msg, delegation_signer),
+    });
+}
+
+#[test]
+fn contract_with_delegated_oracle_key_closes_test() {
+    let secp = Secp256k1::new();
+    let master = KeyPair::from_secret_key(&secp, SecretKey::from_slice(&[3; 32]).unwrap());
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    delegate_oracle_key(&mut oracle, &master, &master);
+    set_time(EVENT_MATURITY as u64 - 1);
+    let (_, mut accept_manager, contract_id) = establish_contract(oracle);
+    accept_manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    assert!(matches!(
+        accept_manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap(),
+        Some(Contract::Closed(_))
+    ));
+}
+
+#[test]
+fn offer_with_forged_oracle_key_delegation_is_rejected_test() {
+    let secp = Secp256k1::new();
+    let master = KeyPair::from_secret_key(&secp, SecretKey::from_slice(&[3; 32]).unwrap());
+    let forger = KeyPair::from_secret_key(&secp, SecretKey::from_slice(&[4; 32]).unwrap());
+    let mut oracle = get_enum_oracle();
+    delegate_oracle_key(&mut oracle, &master, &forger);
+    let contract_input = get_enum_contract_input(&oracle);
+    let mut manager = get_funded_manager(&Arc::new(oracle), ManagerConfig::default());
+
+    assert!(matches!(
+        manager.send_offer(&contract_input, get_accept_party_pubkey()),
+        Err(Error::OracleMismatch)
+    ));
+}
+
+#[test]
+fn offer_using_oracle_added_after_construction_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    // The failing offer locks the first UTXO.
+    wallet.fund(3 * COLLATERAL);
+    wallet.fund(3 * COLLATERAL);
+    let mut manager = get_manager_with(
+        MemoryStorage::new(),
+        wallet,
+        Arc::new(MockBlockchain::new()),
+    );
+
+    assert!(manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .is_err());
+
+    assert!(manager.add_oracle(Arc::clone(&oracle)).is_none());
+    assert_eq!(vec![oracle.get_public_key()], manager.list_oracles());
+
+    let offer = manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    match manager
+        .get_store()
+        .get_contract(&offer.get_hash().unwrap())
+        .unwrap()
+    {
+        Some(Contract::Offered(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+
+    assert!(manager.remove_oracle(&oracle.get_public_key()).is_some());
+    assert!(manager.list_oracles().is_empty());
+}
+
+#[test]
+fn expired_announcements_of_terminal_contracts_are_pruned_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let expiry = Duration::from_secs(3600);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig {
+            announcement_expiry: Some(expiry),
+            ..Default::default()
+        },
+        Arc::clone(&offer_wallet),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        Arc::clone(&accept_wallet),
+        Arc::new(MockBlockchain::new()),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let closed_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    offer_wallet.fund(3 * COLLATERAL);
+    accept_wallet.fund(3 * COLLATERAL);
+    let signed_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    offer_manager
+        .notify_funding_confirmed(&closed_id, 150)
+        .expect("Error notifying funding confirmation");
+    set_time(EVENT_MATURITY as u64 + 1);
+    offer_manager.periodic_check().unwrap();
+    assert!(matches!(
+        offer_manager.get_store().get_contract(&closed_id).unwrap(),
+        Some(Contract::Closed(_))
+    ));
+
+    let get_announcement_count = |manager: &TestManager, id: &ContractId| -> usize {
+        manager
+            .get_store()
+            .get_contract(id)
+            .unwrap()
+            .unwrap()
+            .get_offered_contract()
+            .contract_info
+            .iter()
+            .map(|x| x.oracle_announcements.len())
+            .sum()
+    };
+
+    set_time(EVENT_MATURITY as u64 + expiry.as_secs());
+    assert_eq!(0, offer_manager.prune_expired_announcements().unwrap());
+    assert_eq!(1, get_announcement_count(&offer_manager, &closed_id));
+
+    set_time(EVENT_MATURITY as u64 + expiry.as_secs() + 1);
+    assert_eq!(1, offer_manager.prune_expired_announcements().unwrap());
+    assert_eq!(0, get_announcement_count(&offer_manager, &closed_id));
+    // The signed contract still depends on its announcement.
+    assert_eq!(1, get_announcement_count(&offer_manager, &signed_id));
+    assert_eq!(
+        vec![signed_id],
+        offer_manager.contracts_for_event(EVENT_ID).unwrap()
+    );
+
+    // Without an expiry nothing is pruned.
+    assert_eq!(0, accept_manager.prune_expired_announcements().unwrap());
+}
+
+#[test]
+fn accept_party_selects_offered_oracle_test() {
+    let first_oracle = get_enum_oracle();
+    let second_oracle = get_enum_oracle();
+    let mut contract_input = get_enum_contract_input(&first_oracle);
+    contract_input.contract_infos[0]
+        .oracles
+        .public_keys
+        .push(second_oracle.get_public_key());
+    let chosen_key = second_oracle.get_public_key();
+    let first_oracle = Arc::new(first_oracle);
+    let second_oracle = Arc::new(second_oracle);
+
+    let offer_wallet = Arc::new(MockWallet::new());
+    offer_wallet.fund(3 * COLLATERAL);
+    let mut oracles = HashMap::new();
+    oracles.insert(first_oracle.get_public_key(), Arc::clone(&first_oracle));
+    oracles.insert(second_oracle.get_public_key(), Arc::clone(&second_oracle));
+    let mut offer_manager: TestManager = Manager::new(
+        offer_wallet,
+        Arc::new(MockBlockchain::new()),
+        Box::new(MemoryStorage::new()),
+        oracles,
+        Arc::new(MockTime {}),
+    );
+    let mut accept_manager = get_funded_manager(&second_oracle, ManagerConfig::default());
+
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+
+    assert!(matches!(
+        accept_manager.accept_contract_offer_with_oracle(
+            &temporary_contract_id,
+            &MockOracle::new().get_public_key()
+        ),
+        Err(Error::OracleMismatch)
+    ));
+
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer_with_oracle(&temporary_contract_id, &chosen_key)
+        .expect("Error accepting offer");
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .expect("Error processing accept")
+        .expect("Expected a sign message");
+    accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .expect("Error processing sign");
+
+    for manager in &[&offer_manager, &accept_manager] {
+        let contract = manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap()
+            .expect("Contract should be stored");
+        let announcements = &contract.get_offered_contract().contract_info[0].oracle_announcements;
+        assert_eq!(1, announcements.len());
+        assert_eq!(chosen_key, announcements[0].oracle_public_key);
+    }
+}
+
+#[test]
+fn required_nonces_match_announcement_test() {
+    const NB_DIGITS: u16 = 2;
+    let mut oracle = MockOracle::new();
+    oracle.add_event(
+        EVENT_ID,
+        &EventDescriptor::DigitDecompositionEvent(DigitDecompositionEventDescriptor {
+            base: 2,
+            is_signed: false,
+            unit: "sats/sec".to_string(),
+            precision: 0,
+            nb_digits: NB_DIGITS,
+        }),
+        EVENT_MATURITY,
+    );
+    let table = PayoutTable::new(vec![
+        PayoutTableRow {
+            start: 0,
+            end: 1,
+            payout: 0,
+        },
+        PayoutTableRow {
+            start: 2,
+            end: 3,
+            payout: 2 * COLLATERAL,
+        },
+    ])
+    .unwrap();
+    let contract_input = ContractInput {
+        offer_collateral: COLLATERAL,
+        accept_collateral: COLLATERAL,
+        maturity_time: EVENT_MATURITY,
+        fee_rate: 2,
+        contract_infos: vec![ContractInputInfo {
+            contract_descriptor: ContractDescriptor::Numerical(NumericalDescriptor {
+                payout_function: table.to_payout_function(),
+                rounding_intervals: RoundingIntervals {
+                    intervals: vec![RoundingInterval {
+                        begin_interval: 0,
+                        rounding_mod: 1,
+                    }],
+                },
+                info: NumericalEventInfo {
+                    base: 2,
+                    nb_digits: NB_DIGITS as usize,
+                    unit: "sats/sec".to_string(),
+                },
+                difference_params: None,
+            }),
+            oracles: OracleInput {
+                public_keys: vec![oracle.get_public_key()],
+                event_id: EVENT_ID.to_owned(),
+                threshold: 1,
+            },
+        }],
+    };
+    let announced_nonces = oracle
+        .get_announcement(EVENT_ID)
+        .unwrap()
+        .oracle_event
+        .oracle_nonces;
+
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    for manager in &[offer_manager, accept_manager] {
+        let nonces = manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap()
+            .expect("Contract should exist")
+            .required_nonces();
+        assert_eq!(NB_DIGITS as usize, nonces.len());
+        assert_eq!(announced_nonces, nonces);
+    }
+}
+
+#[test]
+fn insufficient_attestations_trigger_refund_after_deadline_test() {
+    let mut oracles: Vec<MockOracle> = (0..3)
+        .map(|_| {
+            let mut oracle = MockOracle::new();
+            oracle.add_event(
+                EVENT_ID,
+                &EventDescriptor::EnumEvent(EnumEventDescriptor {
+                    outcomes: enum_outcomes(),
+                }),
+                EVENT_MATURITY,
+            );
+            oracle
+        })
+        .collect();
+    // Only one of the three oracles attests while two are required.
+    oracles[0].add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let mut contract_input = get_enum_contract_input(&oracles[0]);
+    contract_input.contract_infos[0].oracles = OracleInput {
+        public_keys: oracles.iter().map(|x| x.get_public_key()).collect(),
+        event_id: EVENT_ID.to_owned(),
+        threshold: 2,
+    };
+    let oracles: HashMap<SchnorrPublicKey, Arc<MockOracle>> = oracles
+        .into_iter()
+        .map(|x| (x.get_public_key(), Arc::new(x)))
+        .collect();
+    let refund_grace_period = Duration::from_secs(30 * 86400);
+    let new_manager = |config: ManagerConfig, wallet: Arc<MockWallet>, blockchain| {
+        wallet.fund(3 * COLLATERAL);
+        Manager::new_with_config(
+            wallet,
+            blockchain,
+            Box::new(MemoryStorage::new()),
+            oracles.clone(),
+            Arc::new(MockTime {}),
+            config,
+        )
+    };
+    let offer_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = new_manager(
+        ManagerConfig {
+            refund_grace_period,
+            ..Default::default()
+        },
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let accept_wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut accept_manager = new_manager(
+        ManagerConfig {
+            refund_grace_period,
+            attestation_deadline: Some(Duration::from_secs(86400)),
+            ..Default::default()
+        },
+        accept_wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let refund = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.refund,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let fund_txid = refund.input[0].previous_output.txid;
+    offer_wallet.set_confirmations(&fund_txid, 6);
+    accept_wallet.set_confirmations(&fund_txid, 6);
+    offer_manager.periodic_check().unwrap();
+    accept_manager.periodic_check().unwrap();
+
+    set_time(refund.lock_time as u64 - 1);
+    assert!(accept_manager.periodic_check().unwrap().is_empty());
+
+    set_time(refund.lock_time as u64);
+    assert_eq!(
+        vec![ManagerAction::RefundBroadcast(contract_id)],
+        accept_manager.periodic_check().unwrap()
+    );
+    assert!(blockchain
+        .get_transactions()
+        .iter()
+        .any(|x| x.txid() == refund.txid()));
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Refunded(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+
+    // Without a deadline, the refund grace period still applies.
+    assert!(offer_manager.periodic_check().unwrap().is_empty());
+    match offer_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+}
+
+#[test]
+fn contract_oracle_status_reports_attestations_test() {
+    let mut oracles: Vec<MockOracle> = (0..3)
+        .map(|_| {
+            let mut oracle = MockOracle::new();
+            oracle.add_event(
+                EVENT_ID,
+                &EventDescriptor::EnumEvent(EnumEventDescriptor {
+                    outcomes: enum_outcomes(),
+                }),
+                EVENT_MATURITY,
+            );
+            oracle
+        })
+        .collect();
+    oracles[0].add_attestation(EVENT_ID, &[enum_outcomes()[1].clone()]);
+    oracles[2].add_attestation(EVENT_ID, &[enum_outcomes()[1].clone()]);
+    let mut contract_input = get_enum_contract_input(&oracles[0]);
+    let public_keys: Vec<SchnorrPublicKey> = oracles.iter().map(|x| x.get_public_key()).collect();
+    contract_input.contract_infos[0].oracles = OracleInput {
+        public_keys: public_keys.clone(),
+        event_id: EVENT_ID.to_owned(),
+        threshold: 2,
+    };
+    let wallet = Arc::new(MockWallet::new());
+    wallet.fund(3 * COLLATERAL);
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut manager: TestManager = Manager::new(
+        wallet,
+        blockchain.clone(),
+        Box::new(MemoryStorage::new()),
+        oracles
+            .into_iter()
+            .map(|x| (x.get_public_key(), Arc::new(x)))
+            .collect(),
+        Arc::new(MockTime {}),
+    );
+    let offer = manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .unwrap();
+    let nb_broadcasts = blockchain.get_transactions().len();
+
+    let statuses = manager
+        .get_contract_oracle_status(&offer.get_hash().unwrap())
+        .expect("Error getting oracle status");
+
+    assert_eq!(3, statuses.len());
+    for (status, public_key) in statuses.iter().zip(public_keys.iter()) {
+        assert_eq!(public_key, &status.oracle_public_key);
+        assert_eq!(EVENT_ID, status.event_id);
+    }
+    assert_eq!(
+        vec![true, false, true],
+        statuses.iter().map(|x| x.is_attested()).collect::<Vec<_>>()
+    );
+    assert_eq!(Some(vec![enum_outcomes()[1].clone()]), statuses[0].outcomes);
+    assert_eq!(None, statuses[1].outcomes);
+    assert_eq!(Some(vec![enum_outcomes()[1].clone()]), statuses[2].outcomes);
+    assert_eq!(nb_broadcasts, blockchain.get_transactions().len());
+}
+
+#[test]
+fn attestation_deadline_ending_after_refund_grace_period_is_rejected_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let late_deadline_config = ManagerConfig {
+        refund_grace_period: Duration::from_secs(86400),
+        attestation_deadline: Some(Duration::from_secs(REFUND_DELAY as u64 + 86400)),
+        ..Default::default()
+    };
+
+    let mut late_manager = get_funded_manager(&oracle, late_deadline_config.clone());
+    match late_manager.send_offer(&contract_input, get_accept_party_pubkey()) {
+        Err(Error::InvalidParameters(_)) => {}
+        r => panic!("Unexpected result {:?}", r),
+    };
+
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    late_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    match late_manager.accept_contract_offer(&temporary_contract_id) {
+        Err(Error::InvalidParameters(_)) => {}
+        r => panic!("Unexpected result {:?}", r),
+    };
+}
+
+#[test]
+fn force_close_with_external_attestation_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    let attestation = oracle.get_attestation(EVENT_ID).unwrap();
+    let oracle_public_key = oracle.get_public_key();
+    let (_, mut accept_manager, contract_id) = establish_contract(oracle);
+    accept_manager.remove_oracle(&oracle_public_key);
+
+    assert!(matches!(
+        accept_manager.force_close_contract(&contract_id, None),
+        Err(Error::MissingAttestations)
+    ));
+    let mut forged = attestation.clone();
+    forged.outcomes = vec![enum_outcomes()[1].clone()];
+    assert!(matches!(
+        accept_manager.force_close_contract(&contract_id, Some(forged)),
+        Err(Error::OracleMismatch)
+    ));
+
+    let actions = accept_manager
+        .force_close_contract(&contract_id, Some(attestation.clone()))
+        .expect("Error closing contract");
+    assert!(matches!(
+        actions.as_slice(),
+        [ManagerAction::ClosedByOutcome { .. }]
+    ));
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => assert_eq!(vec![attestation], c.attestations),
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+}
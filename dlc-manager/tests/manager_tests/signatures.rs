@@ -0,0 +1,556 @@
+use crate::common::{
+    enum_outcomes, establish_contract, establish_contract_with_configs, get_accept_party_pubkey,
+    get_enum_contract_input, get_enum_oracle, get_funded_manager, get_funded_manager_with,
+    get_offer_party_pubkey, get_offered_contract, get_signed_contract, run_contract_setup,
+    EVENT_ID, EVENT_MATURITY,
+};
+use bitcoin::SigHashType;
+use dlc_manager::contract::{
+    audit_record::{verify_audit_record, AuditRecord},
+    ser::Serializable,
+    Contract, ContractState, Party,
+};
+use dlc_manager::error::Error;
+use dlc_manager::manager::{BroadcastMode, CetCsvDelays, ManagerConfig, ManagerEvent};
+use dlc_manager::{AddressType, Storage};
+use dlc_messages::Message;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_time::set_time;
+use mocks::mock_wallet::MockWallet;
+use secp256k1_zkp::{Secp256k1, Signature};
+use std::sync::Arc;
+
+#[test]
+fn refund_sighash_matches_refund_signatures_test() {
+    let signed = get_signed_contract();
+    let contract = Contract::Signed(signed.clone());
+    let sighash = contract
+        .refund_sighash()
+        .expect("Error computing refund sighash");
+    let accepted = &signed.accepted_contract;
+
+    let secp = Secp256k1::new();
+    secp.verify(
+        &sighash,
+        &signed.offer_refund_signature,
+        &accepted.offered_contract.offer_params.fund_pubkey,
+    )
+    .expect("Invalid offer refund signature");
+    secp.verify(
+        &sighash,
+        &accepted.accept_refund_signature,
+        &accepted.accept_params.fund_pubkey,
+    )
+    .expect("Invalid accept refund signature");
+    assert!(matches!(
+        Contract::Offered(accepted.offered_contract.clone()).refund_sighash(),
+        Err(Error::InvalidState {
+            found: ContractState::Offered,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn cet_csv_delays_gate_manager_built_cets_test() {
+    let config = ManagerConfig {
+        cet_csv_delays: Some(CetCsvDelays {
+            offer: 144,
+            accept: 288,
+        }),
+        ..Default::default()
+    };
+    // The accepting party uses the delays of the offer.
+    let (_, accept_manager, contract_id) =
+        establish_contract_with_configs(get_enum_oracle(), config, ManagerConfig::default());
+    let signed = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let accepted = &signed.accepted_contract;
+    let offer_params = &accepted.offered_contract.offer_params;
+    let accept_params = &accepted.accept_params;
+    let offer_spk = dlc::make_csv_payout_script(&offer_params.fund_pubkey, 144).to_v0_p2wsh();
+    let accept_spk = dlc::make_csv_payout_script(&accept_params.fund_pubkey, 288).to_v0_p2wsh();
+
+    let mut seen_offer = false;
+    let mut seen_accept = false;
+    for cet in &accepted.dlc_transactions.cets {
+        for output in &cet.output {
+            if output.script_pubkey == offer_spk {
+                seen_offer = true;
+            } else if output.script_pubkey == accept_spk {
+                seen_accept = true;
+            } else {
+                panic!("Unexpected CET output {:?}", output);
+            }
+        }
+    }
+    assert!(seen_offer && seen_accept);
+
+    let refund_spks: Vec<_> = accepted
+        .dlc_transactions
+        .refund
+        .output
+        .iter()
+        .map(|x| x.script_pubkey.clone())
+        .collect();
+    assert!(refund_spks.contains(&offer_params.payout_script_pubkey));
+    assert!(refund_spks.contains(&accept_params.payout_script_pubkey));
+}
+
+#[test]
+fn invalid_cet_csv_delays_are_rejected_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let invalid_delays = CetCsvDelays {
+        offer: 0,
+        accept: 288,
+    };
+
+    let mut offer_manager = get_funded_manager(
+        &oracle,
+        ManagerConfig {
+            cet_csv_delays: Some(invalid_delays),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        offer_manager.send_offer(&contract_input, get_accept_party_pubkey()),
+        Err(Error::InvalidParameters(_))
+    ));
+
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    offer.cet_csv_delays = Some(invalid_delays);
+    assert!(matches!(
+        accept_manager.on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey()),
+        Err(Error::InvalidParameters(_))
+    ));
+}
+
+#[test]
+fn taproot_payout_address_is_used_in_cets_test() {
+    let accept_config = ManagerConfig {
+        payout_address_type: AddressType::P2tr,
+        ..Default::default()
+    };
+    let (offer_manager, _, contract_id) =
+        establish_contract_with_configs(get_enum_oracle(), ManagerConfig::default(), accept_config);
+
+    let signed = match offer_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let accepted = &signed.accepted_contract;
+    let accept_payout_spk = &accepted.accept_params.payout_script_pubkey;
+    let offer_payout_spk = &accepted.offered_contract.offer_params.payout_script_pubkey;
+
+    // Taproot script pubkeys are a version 1 witness program of 32 bytes.
+    assert_eq!(34, accept_payout_spk.len());
+    assert_eq!(0x51, accept_payout_spk[0]);
+    assert_eq!(0x20, accept_payout_spk[1]);
+    assert!(offer_payout_spk.is_v0_p2wpkh());
+    assert!(accepted.dlc_transactions.cets.iter().any(|cet| cet
+        .output
+        .iter()
+        .any(|o| &o.script_pubkey == accept_payout_spk)));
+}
+
+#[test]
+fn cet_sighashes_match_cet_signatures_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[1].clone()]);
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let blockchain = Arc::new(MockBlockchain::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        blockchain.clone(),
+    );
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    let contract = accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+        .unwrap();
+    let sighashes = contract.cet_sighashes().expect("Error computing sighashes");
+    let signed = match contract {
+        Contract::Signed(s) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert_eq!(
+        signed.accepted_contract.dlc_transactions.cets.len(),
+        sighashes.len()
+    );
+    assert!(matches!(
+        Contract::Offered(signed.accepted_contract.offered_contract.clone()).cet_sighashes(),
+        Err(Error::InvalidState {
+            found: ContractState::Offered,
+            ..
+        })
+    ));
+
+    wallet.set_confirmations(&signed.accepted_contract.dlc_transactions.fund.txid(), 6);
+    accept_manager.periodic_check().unwrap();
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    let cet_index = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(c)) => c.cet_index,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let cet = blockchain.get_transactions().last().unwrap().clone();
+    assert_eq!(
+        signed.accepted_contract.dlc_transactions.cets[cet_index].txid(),
+        cet.txid()
+    );
+
+    let secp = Secp256k1::new();
+    let fund_pubkeys = [
+        signed
+            .accepted_contract
+            .offered_contract
+            .offer_params
+            .fund_pubkey,
+        signed.accepted_contract.accept_params.fund_pubkey,
+    ];
+    let witness = &cet.input[0].witness;
+    for der_sig in &witness[1..3] {
+        let sig = Signature::from_der(&der_sig[..der_sig.len() - 1]).unwrap();
+        assert!(fund_pubkeys
+            .iter()
+            .any(|pk| secp.verify(&sighashes[cet_index], &sig, pk).is_ok()));
+    }
+}
+
+#[test]
+fn get_contract_signatures_of_accepted_contract_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .unwrap();
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .unwrap();
+    let (contract_id, _, accept) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .unwrap();
+    let state_hash = accept_manager.state_hash().unwrap();
+
+    let signatures = accept_manager
+        .get_contract_signatures(&contract_id)
+        .expect("Error getting contract signatures");
+
+    assert_eq!(enum_outcomes().len(), signatures.cets.len());
+    // Adaptor signatures are randomized, so the ones returned must be the
+    // ones that were sent.
+    assert_eq!(
+        accept
+            .cet_adaptor_signatures
+            .ecdsa_adaptor_signatures
+            .iter()
+            .map(|x| x.signature)
+            .collect::<Vec<_>>(),
+        signatures.local_adaptor_signatures
+    );
+    assert!(signatures.counter_party_adaptor_signatures.is_none());
+    assert_eq!(accept.refund_signature, signatures.local_refund_signature);
+    assert!(signatures.counter_party_refund_signature.is_none());
+    assert_eq!(state_hash, accept_manager.state_hash().unwrap());
+
+    let sign = offer_manager
+        .on_dlc_message(&Message::Accept(accept), get_accept_party_pubkey())
+        .unwrap()
+        .unwrap();
+    accept_manager
+        .on_dlc_message(&sign, get_offer_party_pubkey())
+        .unwrap();
+    let signatures = accept_manager
+        .get_contract_signatures(&contract_id)
+        .unwrap();
+    assert_eq!(
+        Some(signatures.cets.len()),
+        signatures
+            .counter_party_adaptor_signatures
+            .as_ref()
+            .map(|x| x.len())
+    );
+    assert!(signatures.counter_party_refund_signature.is_some());
+    let offer_signatures = offer_manager.get_contract_signatures(&contract_id).unwrap();
+    assert_eq!(
+        Some(offer_signatures.local_adaptor_signatures),
+        signatures.counter_party_adaptor_signatures
+    );
+    assert_eq!(
+        offer_signatures.counter_party_adaptor_signatures,
+        Some(signatures.local_adaptor_signatures)
+    );
+    assert!(matches!(
+        accept_manager.get_contract_signatures(&temporary_contract_id),
+        Err(Error::UnknownContract)
+    ));
+}
+
+#[test]
+fn cets_are_signed_with_offered_sig_hash_type_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    set_time(EVENT_MATURITY as u64 - 1);
+    // The accepting party uses the type of the offer, not its own.
+    let (_, mut accept_manager, contract_id) = establish_contract_with_configs(
+        oracle,
+        ManagerConfig {
+            cet_sig_hash_type: Some(SigHashType::AllPlusAnyoneCanPay),
+            ..Default::default()
+        },
+        ManagerConfig {
+            cet_broadcast_mode: BroadcastMode::Manual,
+            ..Default::default()
+        },
+    );
+    let signed = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    accept_manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    let cet = match accept_manager.get_and_clear_pending_events().as_slice() {
+        [ManagerEvent::CetReadyForBroadcast { cet, .. }] => cet.clone(),
+        e => panic!("Unexpected events {:?}", e),
+    };
+
+    let accepted = &signed.accepted_contract;
+    let fund_output = dlc::SigHashParams {
+        script_pubkey: &accepted.dlc_transactions.funding_script_pubkey,
+        value: accepted.dlc_transactions.get_fund_output().value,
+        sig_hash_type: SigHashType::AllPlusAnyoneCanPay,
+    };
+    assert_eq!(fund_output, accepted.get_cet_sig_hash_params());
+    // The audit record commits to the sighashes of the negotiated type.
+    let record = Contract::Signed(signed.clone())
+        .to_audit_record()
+        .expect("Error creating audit record");
+    verify_audit_record(&record).expect("Invalid audit record");
+    let fund_pubkeys = [
+        accepted.offered_contract.offer_params.fund_pubkey,
+        accepted.accept_params.fund_pubkey,
+    ];
+    let secp = Secp256k1::verification_only();
+    for sig in &cet.input[0].witness[1..3] {
+        let (sig_hash_type, der_sig) = sig.split_last().unwrap();
+        assert_eq!(
+            SigHashType::AllPlusAnyoneCanPay.as_u32() as u8,
+            *sig_hash_type
+        );
+        let sig = Signature::from_der(der_sig).unwrap();
+        assert!(fund_pubkeys.iter().any(|pk| {
+            dlc::verify_tx_input_sig_with_sig_hash_type(&secp, &sig, &cet, 0, &fund_output, pk)
+                .is_ok()
+        }));
+    }
+}
+
+#[test]
+fn unsafe_sig_hash_types_are_rejected_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+
+    for config in [
+        ManagerConfig {
+            cet_sig_hash_type: Some(SigHashType::Single),
+            ..Default::default()
+        },
+        ManagerConfig {
+            funding_sig_hash_type: Some(SigHashType::AllPlusAnyoneCanPay),
+            ..Default::default()
+        },
+    ] {
+        let mut offer_manager = get_funded_manager(&oracle, config);
+        assert!(matches!(
+            offer_manager.send_offer(&contract_input, get_accept_party_pubkey()),
+            Err(Error::InvalidParameters(_))
+        ));
+    }
+
+    // Offers negotiating an unsafe CET sighash type are rejected.
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    offer.cet_sig_hash_type = Some(SigHashType::Single);
+    assert!(matches!(
+        accept_manager.on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey()),
+        Err(Error::InvalidParameters(_))
+    ));
+}
+
+#[test]
+fn cet_fee_reserve_multiplier_inflates_cet_fee_rate_test() {
+    let config = ManagerConfig {
+        cet_fee_reserve_multiplier: Some(3),
+        ..Default::default()
+    };
+    // The accepting party uses the multiplier of the offer.
+    let (_, accept_manager, contract_id) =
+        establish_contract_with_configs(get_enum_oracle(), config, ManagerConfig::default());
+    let signed = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s,
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    let accepted = &signed.accepted_contract;
+    let offered = &accepted.offered_contract;
+    let fee_rate = offered.fee_rate_per_vb;
+    let (offer_change, offer_fund_fee, offer_cet_fee) = offered
+        .offer_params
+        .get_change_output_and_fees(fee_rate)
+        .unwrap();
+    let (_, accept_fund_fee, accept_cet_fee) = accepted
+        .accept_params
+        .get_change_output_and_fees(fee_rate)
+        .unwrap();
+    let estimated_cet_fee = offer_cet_fee + accept_cet_fee;
+
+    let dlc_transactions = &accepted.dlc_transactions;
+    let fund_output_value = dlc_transactions.get_fund_output().value;
+    assert_eq!(
+        offered.total_collateral + 3 * estimated_cet_fee,
+        fund_output_value
+    );
+    for cet in &dlc_transactions.cets {
+        let cet_fee = fund_output_value - cet.output.iter().map(|x| x.value).sum::<u64>();
+        assert_eq!(3 * estimated_cet_fee, cet_fee);
+    }
+
+    // The fund transaction still pays the contract fee rate.
+    let fund_fee = offered.offer_params.input_amount + accepted.accept_params.input_amount
+        - dlc_transactions
+            .fund
+            .output
+            .iter()
+            .map(|x| x.value)
+            .sum::<u64>();
+    assert_eq!(offer_fund_fee + accept_fund_fee, fund_fee);
+    assert!(dlc_transactions
+        .fund
+        .output
+        .iter()
+        .any(|x| x.value == offer_change.value - 2 * offer_cet_fee));
+}
+
+#[test]
+fn invalid_cet_fee_reserve_multiplier_is_rejected_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+
+    let mut offer_manager = get_funded_manager(
+        &oracle,
+        ManagerConfig {
+            cet_fee_reserve_multiplier: Some(0),
+            ..Default::default()
+        },
+    );
+    assert!(matches!(
+        offer_manager.send_offer(&contract_input, get_accept_party_pubkey()),
+        Err(Error::InvalidParameters(_))
+    ));
+
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    offer.cet_fee_reserve_multiplier = Some(u64::MAX);
+    assert!(matches!(
+        accept_manager.on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey()),
+        Err(Error::InvalidParameters(_))
+    ));
+}
+
+#[test]
+fn audit_record_is_verified_after_serialization_test() {
+    let (offer_manager, accept_manager, contract_id) = establish_contract(get_enum_oracle());
+    for (manager, signer) in [
+        (&offer_manager, Party::Accept),
+        (&accept_manager, Party::Offer),
+    ] {
+        let contract = manager
+            .get_store()
+            .get_contract(&contract_id)
+            .unwrap()
+            .expect("Contract not found");
+        let record = contract
+            .to_audit_record()
+            .expect("Error creating audit record");
+        assert_eq!(contract_id, record.contract_id);
+        assert_eq!(signer, record.adaptor_signer);
+        assert_eq!(enum_outcomes().len(), record.adaptor_signatures.len());
+
+        let serialized = record.serialize().unwrap();
+        let deserialized = AuditRecord::deserialize(&mut std::io::Cursor::new(&serialized))
+            .expect("Error deserializing audit record");
+        assert_eq!(record, deserialized);
+        verify_audit_record(&deserialized).expect("Invalid audit record");
+
+        let mut tampered = deserialized.clone();
+        tampered.adaptor_signatures[0].adaptor_point = tampered.adaptor_signatures[1].adaptor_point;
+        assert!(matches!(
+            verify_audit_record(&tampered),
+            Err(Error::InvalidParameters(_))
+        ));
+        let mut tampered = deserialized;
+        std::mem::swap(
+            &mut tampered.offer_refund_signature,
+            &mut tampered.accept_refund_signature,
+        );
+        assert!(matches!(
+            verify_audit_record(&tampered),
+            Err(Error::InvalidParameters(_))
+        ));
+    }
+
+    let offered = Contract::Offered(get_offered_contract());
+    assert!(matches!(
+        offered.to_audit_record(),
+        Err(Error::InvalidState { .. })
+    ));
+}
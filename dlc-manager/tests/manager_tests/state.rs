@@ -0,0 +1,397 @@
+use crate::common::{
+    enum_outcomes, establish_contract_with_configs, get_accept_party_pubkey,
+    get_enum_contract_input, get_enum_oracle, get_funded_manager, get_funded_manager_with,
+    get_manager, get_manager_for_store, get_offer_party_pubkey, get_signed_contract,
+    get_store_with_signed_contract, run_contract_setup, TestManager, COLLATERAL, EVENT_ID,
+    EVENT_MATURITY,
+};
+use dlc::FeeContribution;
+use dlc_manager::contract::{ser::Serializable, Contract, ContractState};
+use dlc_manager::error::Error;
+use dlc_manager::manager::{
+    BroadcastMode, ContractStateFilter, Manager, ManagerConfig, ManagerEvent, STATE_FORMAT_VERSION,
+};
+use dlc_manager::{Oracle, Storage};
+use dlc_messages::Message;
+use mocks::memory_storage_provider::MemoryStorage;
+use mocks::mock_blockchain::MockBlockchain;
+use mocks::mock_time::{set_time, MockTime};
+use mocks::mock_wallet::MockWallet;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[test]
+fn state_is_intact_after_shutdown_test() {
+    let signed = get_signed_contract();
+    let contract_id = signed.accepted_contract.get_contract_id();
+    let mut store = get_store_with_signed_contract(&signed);
+    let mut manager = get_manager_for_store(&mut store);
+    manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+    manager.shutdown().expect("Error shutting down manager");
+
+    let manager = get_manager_for_store(&mut store);
+    match manager.get_store().get_contract(&contract_id).unwrap() {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    }
+}
+
+#[test]
+fn state_hash_changes_only_on_contract_transition_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let empty_hash = accept_manager.state_hash().unwrap();
+    set_time(EVENT_MATURITY as u64 - 1);
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    let signed_hash = accept_manager.state_hash().unwrap();
+    assert_ne!(empty_hash, signed_hash);
+    accept_manager.periodic_check().unwrap();
+    assert_eq!(signed_hash, accept_manager.state_hash().unwrap());
+
+    let fund_txid = match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Signed(s)) => s.accepted_contract.dlc_transactions.fund.txid(),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    wallet.set_confirmations(&fund_txid, 6);
+    accept_manager.periodic_check().unwrap();
+    match accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Confirmed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert_ne!(signed_hash, accept_manager.state_hash().unwrap());
+}
+
+#[test]
+fn list_contracts_filters_and_paginates_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let offer_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        offer_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let mut accept_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let signed_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+    offer_wallet.fund(3 * COLLATERAL);
+    let offered_id = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer")
+        .get_hash()
+        .unwrap();
+
+    let all = offer_manager
+        .list_contracts(ContractStateFilter::default())
+        .unwrap();
+    assert_eq!(2, all.len());
+    assert!(all.iter().all(|x| x.is_offer_party
+        && x.counter_party == get_accept_party_pubkey()
+        && x.offer_collateral == COLLATERAL
+        && x.accept_collateral == COLLATERAL));
+
+    let offered = offer_manager
+        .list_contracts(ContractStateFilter {
+            states: vec![ContractState::Offered],
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(1, offered.len());
+    assert_eq!(offered_id, offered[0].id);
+
+    let signed = offer_manager
+        .list_contracts(ContractStateFilter {
+            states: vec![ContractState::Signed, ContractState::Confirmed],
+            counter_party: Some(get_accept_party_pubkey()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(1, signed.len());
+    assert_eq!(signed_id, signed[0].id);
+    assert_eq!(ContractState::Signed, signed[0].state);
+
+    assert!(offer_manager
+        .list_contracts(ContractStateFilter {
+            counter_party: Some(get_offer_party_pubkey()),
+            ..Default::default()
+        })
+        .unwrap()
+        .is_empty());
+
+    let pages: Vec<_> = (0..3)
+        .map(|offset| {
+            offer_manager
+                .list_contracts(ContractStateFilter {
+                    offset,
+                    limit: Some(1),
+                    ..Default::default()
+                })
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(vec![all[0].clone()], pages[0]);
+    assert_eq!(vec![all[1].clone()], pages[1]);
+    assert!(pages[2].is_empty());
+}
+
+#[test]
+fn merge_from_imports_distinct_contracts_and_reports_collisions_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let accept_wallet = Arc::new(MockWallet::new());
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        accept_wallet.clone(),
+        Arc::new(MockBlockchain::new()),
+    );
+    let contract_id = run_contract_setup(&mut offer_manager, &mut accept_manager, &contract_input);
+
+    let mut other_store = MemoryStorage::new();
+    let shared = accept_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+        .unwrap();
+    other_store
+        .create_contract(shared.get_offered_contract())
+        .unwrap();
+    other_store.update_contract(&shared).unwrap();
+    let mut offer_ids = Vec::new();
+    for _ in 0..2 {
+        accept_wallet.fund(3 * COLLATERAL);
+        let offer = accept_manager
+            .send_offer(&contract_input, get_offer_party_pubkey())
+            .unwrap();
+        let id = offer.get_hash().unwrap();
+        match accept_manager.get_store().get_contract(&id).unwrap() {
+            Some(Contract::Offered(o)) => other_store.create_contract(&o).unwrap(),
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+        offer_ids.push(id);
+    }
+    let signed = get_signed_contract();
+    let signed_id = signed.accepted_contract.get_contract_id();
+    other_store
+        .create_contract(&signed.accepted_contract.offered_contract)
+        .unwrap();
+    other_store
+        .update_contract(&Contract::Signed(signed.clone()))
+        .unwrap();
+
+    let report = offer_manager
+        .merge_from(&Box::new(other_store))
+        .expect("Error merging storage");
+
+    assert_eq!(vec![contract_id], report.collisions);
+    let mut expected_imported = offer_ids.clone();
+    expected_imported.push(signed_id);
+    expected_imported.sort();
+    let mut imported = report.imported;
+    imported.sort();
+    assert_eq!(expected_imported, imported);
+    let store = offer_manager.get_store();
+    match store.get_contract(&contract_id).unwrap() {
+        Some(Contract::Signed(s)) => assert!(s.accepted_contract.offered_contract.is_offer_party),
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    for id in &offer_ids {
+        match store.get_contract(id).unwrap() {
+            Some(Contract::Offered(o)) => assert!(o.is_offer_party),
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+    }
+    match store.get_contract(&signed_id).unwrap() {
+        Some(Contract::Signed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+    assert!(store
+        .get_contract(&signed.accepted_contract.offered_contract.id)
+        .unwrap()
+        .is_none());
+    assert_eq!(4, store.get_contracts().unwrap().len());
+}
+
+#[test]
+fn exported_state_is_restored_by_import_test() {
+    let oracle = get_enum_oracle();
+    let contract_input = get_enum_contract_input(&oracle);
+    let oracle = Arc::new(oracle);
+    let mut offer_manager = get_funded_manager(&oracle, ManagerConfig::default());
+    let accept_wallet = Arc::new(MockWallet::new());
+    let mut accept_manager = get_funded_manager_with(
+        &oracle,
+        ManagerConfig::default(),
+        Arc::clone(&accept_wallet),
+        Arc::new(MockBlockchain::new()),
+    );
+    let offer = offer_manager
+        .send_offer(&contract_input, get_accept_party_pubkey())
+        .expect("Error sending offer");
+    let temporary_contract_id = offer.get_hash().unwrap();
+    accept_manager
+        .on_dlc_message(&Message::Offer(offer), get_offer_party_pubkey())
+        .expect("Error processing offer");
+    let (contract_id, _, _) = accept_manager
+        .accept_contract_offer(&temporary_contract_id)
+        .expect("Error accepting offer");
+
+    let state = accept_manager
+        .export_state()
+        .expect("Error exporting state");
+    let mut store = MemoryStorage::new();
+    TestManager::import_state(&mut store, &state).expect("Error importing state");
+    let mut oracles = HashMap::new();
+    oracles.insert(oracle.get_public_key(), Arc::clone(&oracle));
+    let restored_manager: TestManager = Manager::new_with_config(
+        accept_wallet,
+        Arc::new(MockBlockchain::new()),
+        Box::new(store),
+        oracles,
+        Arc::new(MockTime {}),
+        ManagerConfig::default(),
+    );
+
+    assert_eq!(
+        accept_manager
+            .get_contract_signatures(&contract_id)
+            .unwrap(),
+        restored_manager
+            .get_contract_signatures(&contract_id)
+            .unwrap()
+    );
+    assert_eq!(
+        accept_manager.state_hash().unwrap(),
+        restored_manager.state_hash().unwrap()
+    );
+
+    let mut unsupported = state;
+    unsupported[0] += 1;
+    assert!(matches!(
+        TestManager::import_state(&mut MemoryStorage::new(), &unsupported),
+        Err(Error::UnsupportedVersion)
+    ));
+}
+
+#[test]
+fn state_exported_by_previous_versions_is_imported_test() {
+    let serialized = include_bytes!("../../test_files/Signed");
+    let contract_id = Contract::Signed(get_signed_contract()).get_id();
+
+    for version in 1u8..STATE_FORMAT_VERSION {
+        let mut state = vec![version];
+        state.extend_from_slice(&1u64.to_be_bytes());
+        // The state identifier of signed contracts.
+        state.push(2);
+        state.extend_from_slice(&(serialized.len() as u64).to_be_bytes());
+        state.extend_from_slice(serialized);
+        if version > 1 {
+            // No contract data and no paused contracts.
+            state.extend_from_slice(&0u64.to_be_bytes());
+            state.extend_from_slice(&0u64.to_be_bytes());
+        }
+        let mut store = MemoryStorage::new();
+        TestManager::import_state(&mut store, &state).expect("Error importing state");
+
+        match store.get_contract(&contract_id).unwrap() {
+            Some(Contract::Signed(s)) => {
+                assert_eq!(
+                    FeeContribution::Equal,
+                    s.accepted_contract.offered_contract.fee_contribution
+                );
+                assert_eq!(serialized[..], s.serialize().unwrap()[..]);
+            }
+            c => panic!("Unexpected contract state {:?}", c),
+        };
+    }
+}
+
+#[test]
+fn exported_state_includes_contract_data_test() {
+    let mut oracle = get_enum_oracle();
+    oracle.add_attestation(EVENT_ID, &[enum_outcomes()[0].clone()]);
+    set_time(EVENT_MATURITY as u64 - 1);
+    let (_, mut accept_manager, contract_id) = establish_contract_with_configs(
+        oracle,
+        ManagerConfig::default(),
+        ManagerConfig {
+            cet_broadcast_mode: BroadcastMode::Manual,
+            ..Default::default()
+        },
+    );
+    accept_manager
+        .notify_funding_confirmed(&contract_id, 150)
+        .expect("Error notifying funding confirmation");
+    set_time(EVENT_MATURITY as u64 + 1);
+    accept_manager.periodic_check().unwrap();
+    let cet = match accept_manager.get_and_clear_pending_events().as_slice() {
+        [ManagerEvent::CetReadyForBroadcast { cet, .. }] => cet.clone(),
+        e => panic!("Unexpected events {:?}", e),
+    };
+    accept_manager
+        .pause_contract(&contract_id)
+        .expect("Error pausing contract");
+
+    let state = accept_manager
+        .export_state()
+        .expect("Error exporting state");
+    let mut store = MemoryStorage::new();
+    TestManager::import_state(&mut store, &state).expect("Error importing state");
+    let mut restored_manager = get_manager(store);
+
+    assert_eq!(
+        state,
+        restored_manager
+            .export_state()
+            .expect("Error exporting state")
+    );
+    assert_eq!(
+        Some(150),
+        restored_manager.get_funding_confirmation_height(&contract_id)
+    );
+    assert_eq!(
+        vec![contract_id],
+        restored_manager.get_store().get_paused_contracts().unwrap()
+    );
+    match restored_manager.get_and_clear_pending_events().as_slice() {
+        [ManagerEvent::CetReadyForBroadcast {
+            contract_id: id,
+            cet: pending,
+        }] if *id == contract_id => assert_eq!(cet, *pending),
+        e => panic!("Unexpected events {:?}", e),
+    };
+    restored_manager
+        .notify_cet_broadcast(&contract_id)
+        .expect("Error notifying CET broadcast");
+    match restored_manager
+        .get_store()
+        .get_contract(&contract_id)
+        .unwrap()
+    {
+        Some(Contract::Closed(_)) => {}
+        c => panic!("Unexpected contract state {:?}", c),
+    };
+}
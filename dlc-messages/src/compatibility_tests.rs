@@ -401,7 +401,7 @@ fn assert_msg_eq<M: Encode + Writeable>(expected_str: &str, actual: M) {
     let mut actual_hex = Vec::new();
     write(&actual, &mut actual_hex).unwrap();
     let expected_hex = str_to_hex(expected_str);
-    assert_eq!(expected_hex, actual_hex);
+    assert_bytes_eq(&expected_hex, &actual_hex);
 }
 
 fn test_single(case: TestCase, secp: &secp256k1::Secp256k1<secp256k1::All>) {
@@ -470,10 +470,7 @@ fn test_single(case: TestCase, secp: &secp256k1::Secp256k1<secp256k1::All>) {
         .iter()
         .map(|x| vec![vec![secp256k1::Message::from_slice(x).unwrap()]])
         .collect();
-    let oracle_infos = vec![DlcOracleInfo {
-        public_key: oracle_pub_key,
-        nonces: vec![oracle_nonce],
-    }];
+    let oracle_infos = vec![DlcOracleInfo::new(oracle_pub_key, vec![oracle_nonce])];
 
     let (offer_cets_sigs, offer_refund_sig) = get_cets_and_refund_sigs(
         secp,
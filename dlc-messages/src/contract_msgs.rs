@@ -90,10 +90,16 @@ impl_dlc_writeable!(ContractInfoInner, { (contract_descriptor, writeable), (orac
 pub enum ContractDescriptor {
     EnumeratedContractDescriptor(EnumeratedContractDescriptor),
     NumericOutcomeContractDescriptor(NumericOutcomeContractDescriptor),
+    /// An enumerated descriptor for which the oracle signs the index of the
+    /// outcome instead of the outcome itself.
+    IndexedEnumeratedContractDescriptor(EnumeratedContractDescriptor),
+    /// An enumerated descriptor whose outcomes are the hex encoded SHA256
+    /// hashes of the outcomes signed by the oracle.
+    HashedEnumeratedContractDescriptor(EnumeratedContractDescriptor),
 }
 
 impl_dlc_writeable_enum!(
-    ContractDescriptor, (0, EnumeratedContractDescriptor), (1, NumericOutcomeContractDescriptor);;
+    ContractDescriptor, (0, EnumeratedContractDescriptor), (1, NumericOutcomeContractDescriptor), (2, IndexedEnumeratedContractDescriptor), (3, HashedEnumeratedContractDescriptor);;
 );
 
 #[derive(Clone, Debug, PartialEq)]
@@ -18,20 +18,28 @@ extern crate serde_json;
 
 pub mod contract_msgs;
 pub mod oracle_msgs;
+pub mod segmentation;
 
 #[cfg(any(test, feature = "serde"))]
 pub mod serde_utils;
 
-use bitcoin::{consensus::Decodable, hash_types::Txid, OutPoint, Script, Transaction};
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+use bitcoin::{
+    consensus::Decodable, hash_types::Txid, OutPoint, Script, SigHashType, Transaction,
+};
 use contract_msgs::ContractInfo;
-use dlc::TxInputInfo;
+use dlc::{CetCsvDelays, FeeContribution, TxInputInfo};
 use lightning::ln::msgs::DecodeError;
 use lightning::ln::wire::Type;
 use lightning::util::ser::{Readable, Writeable, Writer};
 use secp256k1_zkp::bitcoin_hashes::*;
+use secp256k1_zkp::schnorrsig::PublicKey as SchnorrPublicKey;
 use secp256k1_zkp::EcdsaAdaptorSignature;
 use secp256k1_zkp::{PublicKey, Signature};
-use ser_impls::{read_ecdsa_adaptor_signature, write_ecdsa_adaptor_signature};
+use ser_impls::{
+    read_ecdsa_adaptor_signature, read_schnorr_pubkey, write_ecdsa_adaptor_signature,
+    write_schnorr_pubkey,
+};
 
 pub const OFFER_TYPE: u16 = 42778;
 
@@ -39,6 +47,42 @@ pub const ACCEPT_TYPE: u16 = 42780;
 
 pub const SIGN_TYPE: u16 = 42782;
 
+pub const REJECT_TYPE: u16 = 43024;
+
+/// The type of [`CollaborativeCloseOffer`] messages. Collaborative close
+/// messages are not part of the DLC specification, so other implementations
+/// may not understand them or use this type for other messages.
+pub const CLOSE_OFFER_TYPE: u16 = 43026;
+
+/// The type of [`CollaborativeCloseAccept`] messages, which are not part of
+/// the DLC specification either.
+pub const CLOSE_ACCEPT_TYPE: u16 = 43028;
+
+/// The type of the TLV record appended to [`OfferDlc`] messages whose fees are
+/// not split equally. This extension is not part of the DLC specification and
+/// its type is even so that implementations not understanding it reject the
+/// offer instead of ignoring the fee split.
+pub const FEE_CONTRIBUTION_TLV_TYPE: u64 = 43030;
+
+/// The type of the TLV record appended to [`OfferDlc`] messages whose CET
+/// outputs are timelocked. Like the fee contribution record, it is not part of
+/// the DLC specification and its type is even.
+pub const CET_CSV_DELAYS_TLV_TYPE: u64 = 43032;
+
+/// The type of the TLV record appended to [`OfferDlc`] messages whose CET
+/// signatures use another sighash type than `ALL`. Like the fee contribution
+/// record, it is not part of the DLC specification and its type is even.
+pub const CET_SIG_HASH_TYPE_TLV_TYPE: u64 = 43034;
+
+/// The type of the TLV record appended to [`OfferDlc`] messages whose CETs and
+/// refund transaction use a multiple of the fee rate of the fund transaction.
+/// Like the fee contribution record, it is not part of the DLC specification
+/// and its type is even.
+pub const CET_FEE_RESERVE_MULTIPLIER_TLV_TYPE: u64 = 43036;
+
+/// The human readable part of bech32 encoded offer messages.
+pub const OFFER_BECH32_HRP: &str = "dlcoffer";
+
 /// Contains information about a specific input to be used in a funding transaction,
 /// as well as its corresponding on-chain UTXO.
 #[derive(Clone, Debug, PartialEq)]
@@ -186,9 +230,10 @@ impl_dlc_writeable!(WitnessElement, { (witness, vec) });
 pub enum NegotiationFields {
     Single(SingleNegotiationFields),
     Disjoint(DisjointNegotiationFields),
+    Oracle(OracleNegotiationFields),
 }
 
-impl_dlc_writeable_enum!(NegotiationFields, (0, Single), (1, Disjoint);;);
+impl_dlc_writeable_enum!(NegotiationFields, (0, Single), (1, Disjoint), (2, Oracle);;);
 
 ///
 #[derive(Clone, Debug, PartialEq)]
@@ -216,6 +261,23 @@ pub struct DisjointNegotiationFields {
 
 impl_dlc_writeable!(DisjointNegotiationFields, { (negotiation_fields, vec) });
 
+/// Selection by the accepting party of the oracle to use among the ones listed
+/// in the offer.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct OracleNegotiationFields {
+    /// The public key of the selected oracle.
+    pub oracle_public_key: SchnorrPublicKey,
+}
+
+impl_dlc_writeable!(OracleNegotiationFields, {
+    (oracle_public_key, {cb_writeable, write_schnorr_pubkey, read_schnorr_pubkey})
+});
+
 /// Contains information about a party wishing to enter into a DLC with
 /// another party. The contained information is sufficient for any other party
 /// to create a set of transactions representing the contract and its terms.
@@ -248,6 +310,28 @@ pub struct OfferDlc {
     pub fee_rate_per_vb: u64,
     pub contract_maturity_bound: u32,
     pub contract_timeout: u32,
+    /// How the fees are split between the parties. Only serialized when not
+    /// [`FeeContribution::Equal`], as a [`FEE_CONTRIBUTION_TLV_TYPE`] record
+    /// at the end of the message, so that such offers keep the format of the
+    /// specification.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fee_contribution: FeeContribution,
+    /// The relative delays after which the parties can claim their CET
+    /// outputs, if they are timelocked. Only serialized when set, as a
+    /// [`CET_CSV_DELAYS_TLV_TYPE`] record at the end of the message.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cet_csv_delays: Option<CetCsvDelays>,
+    /// The sighash type of the CET signatures of both parties, `ALL` if not
+    /// set. Only serialized when set, as a [`CET_SIG_HASH_TYPE_TLV_TYPE`]
+    /// record at the end of the message.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cet_sig_hash_type: Option<SigHashType>,
+    /// The factor by which the fee rate is multiplied to get the fee rate of
+    /// the CETs and refund transaction, `1` if not set. Only serialized when
+    /// set, as a [`CET_FEE_RESERVE_MULTIPLIER_TLV_TYPE`] record at the end of
+    /// the message.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cet_fee_reserve_multiplier: Option<u64>,
 }
 
 impl Type for OfferDlc {
@@ -264,6 +348,31 @@ impl OfferDlc {
         Ok(sha256::Hash::hash(&buff).into_inner())
     }
 
+    /// Returns the offer encoded as a bech32 string with the
+    /// [`OFFER_BECH32_HRP`] prefix, making it easy to share as text.
+    pub fn to_bech32_string(&self) -> String {
+        let mut buff = Vec::new();
+        self.write(&mut buff)
+            .expect("Writing to a vector should not fail");
+        bech32::encode(OFFER_BECH32_HRP, buff.to_base32(), Variant::Bech32)
+            .expect("The human readable part to be valid")
+    }
+
+    /// Parses an offer encoded with [`OfferDlc::to_bech32_string`].
+    pub fn from_bech32_str(s: &str) -> Result<OfferDlc, DecodeError> {
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| DecodeError::InvalidValue)?;
+        if hrp != OFFER_BECH32_HRP || variant != Variant::Bech32 {
+            return Err(DecodeError::InvalidValue);
+        }
+        let buff = Vec::<u8>::from_base32(&data).map_err(|_| DecodeError::InvalidValue)?;
+        let mut cursor = ::std::io::Cursor::new(&buff);
+        let offer = Readable::read(&mut cursor)?;
+        if cursor.position() as usize != buff.len() {
+            return Err(DecodeError::InvalidValue);
+        }
+        Ok(offer)
+    }
+
     pub fn get_total_collateral(&self) -> u64 {
         match &self.contract_info {
             ContractInfo::SingleContractInfo(single) => single.total_collateral,
@@ -272,23 +381,103 @@ impl OfferDlc {
     }
 }
 
-impl_dlc_writeable!(OfferDlc, {
-        (protocol_version, writeable),
-        (contract_flags, writeable),
-        (chain_hash, writeable),
-        (contract_info, writeable),
-        (funding_pubkey, writeable),
-        (payout_spk, writeable),
-        (payout_serial_id, writeable),
-        (offer_collateral, writeable),
-        (funding_inputs, vec),
-        (change_spk, writeable),
-        (change_serial_id, writeable),
-        (fund_output_serial_id, writeable),
-        (fee_rate_per_vb, writeable),
-        (contract_maturity_bound, writeable),
-        (contract_timeout, writeable)
-});
+impl Writeable for OfferDlc {
+    fn write<W: Writer>(&self, w: &mut W) -> Result<(), ::std::io::Error> {
+        self.protocol_version.write(w)?;
+        self.contract_flags.write(w)?;
+        self.chain_hash.write(w)?;
+        self.contract_info.write(w)?;
+        self.funding_pubkey.write(w)?;
+        self.payout_spk.write(w)?;
+        self.payout_serial_id.write(w)?;
+        self.offer_collateral.write(w)?;
+        ser_impls::write_vec(&self.funding_inputs, w)?;
+        self.change_spk.write(w)?;
+        self.change_serial_id.write(w)?;
+        self.fund_output_serial_id.write(w)?;
+        self.fee_rate_per_vb.write(w)?;
+        self.contract_maturity_bound.write(w)?;
+        self.contract_timeout.write(w)?;
+        if self.fee_contribution != FeeContribution::Equal {
+            ser_impls::write_tlv_record(FEE_CONTRIBUTION_TLV_TYPE, w, |v| {
+                ser_impls::write_fee_contribution(&self.fee_contribution, v)
+            })?;
+        }
+        if let Some(csv_delays) = &self.cet_csv_delays {
+            ser_impls::write_tlv_record(CET_CSV_DELAYS_TLV_TYPE, w, |v| {
+                csv_delays.offer.write(v)?;
+                csv_delays.accept.write(v)
+            })?;
+        }
+        if let Some(sig_hash_type) = self.cet_sig_hash_type {
+            ser_impls::write_tlv_record(CET_SIG_HASH_TYPE_TLV_TYPE, w, |v| {
+                sig_hash_type.as_u32().write(v)
+            })?;
+        }
+        if let Some(multiplier) = self.cet_fee_reserve_multiplier {
+            ser_impls::write_tlv_record(CET_FEE_RESERVE_MULTIPLIER_TLV_TYPE, w, |v| {
+                multiplier.write(v)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Readable for OfferDlc {
+    fn read<R: std::io::Read>(r: &mut R) -> Result<Self, DecodeError> {
+        let mut offer = OfferDlc {
+            protocol_version: Readable::read(r)?,
+            contract_flags: Readable::read(r)?,
+            chain_hash: Readable::read(r)?,
+            contract_info: Readable::read(r)?,
+            funding_pubkey: Readable::read(r)?,
+            payout_spk: Readable::read(r)?,
+            payout_serial_id: Readable::read(r)?,
+            offer_collateral: Readable::read(r)?,
+            funding_inputs: ser_impls::read_vec(r)?,
+            change_spk: Readable::read(r)?,
+            change_serial_id: Readable::read(r)?,
+            fund_output_serial_id: Readable::read(r)?,
+            fee_rate_per_vb: Readable::read(r)?,
+            contract_maturity_bound: Readable::read(r)?,
+            contract_timeout: Readable::read(r)?,
+            fee_contribution: FeeContribution::Equal,
+            cet_csv_delays: None,
+            cet_sig_hash_type: None,
+            cet_fee_reserve_multiplier: None,
+        };
+        ser_impls::read_tlv_stream(r, |tlv_type, value| {
+            match tlv_type {
+                FEE_CONTRIBUTION_TLV_TYPE => {
+                    offer.fee_contribution = ser_impls::read_fee_contribution(value)?;
+                    // An equal fee split is never encoded.
+                    if offer.fee_contribution == FeeContribution::Equal {
+                        return Err(DecodeError::InvalidValue);
+                    }
+                }
+                CET_CSV_DELAYS_TLV_TYPE => {
+                    offer.cet_csv_delays = Some(CetCsvDelays {
+                        offer: Readable::read(value)?,
+                        accept: Readable::read(value)?,
+                    });
+                }
+                CET_SIG_HASH_TYPE_TLV_TYPE => {
+                    let sig_hash_type: u32 = Readable::read(value)?;
+                    offer.cet_sig_hash_type = Some(
+                        SigHashType::from_u32_standard(sig_hash_type)
+                            .map_err(|_| DecodeError::InvalidValue)?,
+                    );
+                }
+                CET_FEE_RESERVE_MULTIPLIER_TLV_TYPE => {
+                    offer.cet_fee_reserve_multiplier = Some(Readable::read(value)?);
+                }
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(offer)
+    }
+}
 
 /// Contains information about a party wishing to accept a DLC offer. The contained
 /// information is sufficient for the offering party to re-build the set of
@@ -376,12 +565,113 @@ impl Type for SignDlc {
     }
 }
 
+/// Sent by the party that received an offer to inform the offering party that
+/// it is declined.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct OfferReject {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::serde_utils::serialize_hex",
+            deserialize_with = "crate::serde_utils::deserialize_hex_array"
+        )
+    )]
+    pub temporary_contract_id: [u8; 32],
+}
+
+impl_dlc_writeable!(OfferReject, { (temporary_contract_id, writeable) });
+
+impl Type for OfferReject {
+    fn type_id(&self) -> u16 {
+        REJECT_TYPE
+    }
+}
+
+/// Sent by a party to a signed contract to propose closing it with a
+/// transaction spending the fund output and paying the given amounts to the
+/// parties, without waiting for the oracle attestations.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct CollaborativeCloseOffer {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::serde_utils::serialize_hex",
+            deserialize_with = "crate::serde_utils::deserialize_hex_array"
+        )
+    )]
+    pub contract_id: [u8; 32],
+    /// The amount paid to the party that offered the contract.
+    pub offer_payout: u64,
+    /// The amount paid to the party that accepted the contract.
+    pub accept_payout: u64,
+    /// The signature of the sender for the close transaction.
+    pub close_signature: Signature,
+}
+
+impl_dlc_writeable!(CollaborativeCloseOffer, {
+    (contract_id, writeable),
+    (offer_payout, writeable),
+    (accept_payout, writeable),
+    (close_signature, writeable)
+});
+
+impl Type for CollaborativeCloseOffer {
+    fn type_id(&self) -> u16 {
+        CLOSE_OFFER_TYPE
+    }
+}
+
+/// Sent in reply to a [`CollaborativeCloseOffer`] to agree to close the
+/// contract, after which the close transaction can be broadcast.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct CollaborativeCloseAccept {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::serde_utils::serialize_hex",
+            deserialize_with = "crate::serde_utils::deserialize_hex_array"
+        )
+    )]
+    pub contract_id: [u8; 32],
+    /// The signature of the sender for the close transaction.
+    pub close_signature: Signature,
+}
+
+impl_dlc_writeable!(CollaborativeCloseAccept, {
+    (contract_id, writeable),
+    (close_signature, writeable)
+});
+
+impl Type for CollaborativeCloseAccept {
+    fn type_id(&self) -> u16 {
+        CLOSE_ACCEPT_TYPE
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub enum Message {
     Offer(OfferDlc),
     Accept(AcceptDlc),
     Sign(SignDlc),
+    Reject(OfferReject),
+    CollaborativeCloseOffer(CollaborativeCloseOffer),
+    CollaborativeCloseAccept(CollaborativeCloseAccept),
 }
 
 impl Type for Message {
@@ -390,6 +680,9 @@ impl Type for Message {
             Message::Offer(o) => o.type_id(),
             Message::Accept(a) => a.type_id(),
             Message::Sign(s) => s.type_id(),
+            Message::Reject(r) => r.type_id(),
+            Message::CollaborativeCloseOffer(c) => c.type_id(),
+            Message::CollaborativeCloseAccept(c) => c.type_id(),
         }
     }
 }
@@ -400,6 +693,9 @@ impl Writeable for Message {
             Message::Offer(o) => o.write(writer),
             Message::Accept(a) => a.write(writer),
             Message::Sign(s) => s.write(writer),
+            Message::Reject(r) => r.write(writer),
+            Message::CollaborativeCloseOffer(c) => c.write(writer),
+            Message::CollaborativeCloseAccept(c) => c.write(writer),
         }
     }
 }
@@ -419,6 +715,75 @@ pub fn compute_contract_id(
     res
 }
 
+/// The reasons for which a message can fail to round trip through
+/// [`assert_roundtrip`].
+#[derive(Debug)]
+pub enum RoundtripError {
+    /// The message could not be decoded.
+    Decode(DecodeError),
+    /// The type prefix of the message is not one of a DLC message.
+    UnknownType(u16),
+    /// The message could not be encoded back.
+    Encode(::std::io::Error),
+    /// The re-encoded message differs from the original bytes, starting at the
+    /// given offset.
+    Mismatch {
+        /// The offset of the first byte that differs, which is the length of
+        /// the shortest of the two if one is a prefix of the other.
+        offset: usize,
+    },
+}
+
+/// Decodes the given wire message (including its type prefix) and encodes it
+/// back, returning an error if the result differs from the original bytes.
+pub fn assert_roundtrip(bytes: &[u8]) -> Result<(), RoundtripError> {
+    let mut cursor = ::std::io::Cursor::new(bytes);
+    let message_type: u16 = Readable::read(&mut cursor).map_err(RoundtripError::Decode)?;
+    let message = match message_type {
+        OFFER_TYPE => Message::Offer(Readable::read(&mut cursor).map_err(RoundtripError::Decode)?),
+        ACCEPT_TYPE => {
+            Message::Accept(Readable::read(&mut cursor).map_err(RoundtripError::Decode)?)
+        }
+        SIGN_TYPE => Message::Sign(Readable::read(&mut cursor).map_err(RoundtripError::Decode)?),
+        REJECT_TYPE => {
+            Message::Reject(Readable::read(&mut cursor).map_err(RoundtripError::Decode)?)
+        }
+        CLOSE_OFFER_TYPE => Message::CollaborativeCloseOffer(
+            Readable::read(&mut cursor).map_err(RoundtripError::Decode)?,
+        ),
+        CLOSE_ACCEPT_TYPE => Message::CollaborativeCloseAccept(
+            Readable::read(&mut cursor).map_err(RoundtripError::Decode)?,
+        ),
+        _ => return Err(RoundtripError::UnknownType(message_type)),
+    };
+
+    let mut encoded = Vec::new();
+    message
+        .type_id()
+        .write(&mut encoded)
+        .map_err(RoundtripError::Encode)?;
+    message
+        .write(&mut encoded)
+        .map_err(RoundtripError::Encode)?;
+
+    let offset = bytes
+        .iter()
+        .zip(encoded.iter())
+        .position(|(a, b)| a != b)
+        .or_else(|| {
+            if bytes.len() != encoded.len() {
+                Some(bytes.len().min(encoded.len()))
+            } else {
+                None
+            }
+        });
+
+    match offset {
+        Some(offset) => Err(RoundtripError::Mismatch { offset }),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -430,12 +795,54 @@ mod tests {
         };
     }
 
-    fn test_roundtrip<T: Writeable + Readable + PartialEq + std::fmt::Debug>(msg: T) {
+    fn test_roundtrip<T: Type + Writeable + Readable + PartialEq + std::fmt::Debug>(msg: T) {
         let mut buf = Vec::new();
         msg.write(&mut buf).expect("Error writing message");
         let mut cursor = std::io::Cursor::new(&buf);
         let deser = Readable::read(&mut cursor).expect("Error reading message");
         assert_eq!(msg, deser);
+
+        let mut wire = Vec::new();
+        msg.type_id().write(&mut wire).unwrap();
+        wire.extend_from_slice(&buf);
+        assert_roundtrip(&wire).expect("Message should re-serialize to identical bytes");
+    }
+
+    fn get_offer_wire_bytes() -> Vec<u8> {
+        let input = include_str!("./test_inputs/offer_msg.json");
+        let offer: OfferDlc = serde_json::from_str(input).unwrap();
+        let mut wire = Vec::new();
+        offer.type_id().write(&mut wire).unwrap();
+        offer.write(&mut wire).unwrap();
+        wire
+    }
+
+    #[test]
+    fn assert_roundtrip_accepts_valid_message() {
+        assert!(assert_roundtrip(&get_offer_wire_bytes()).is_ok());
+    }
+
+    #[test]
+    fn assert_roundtrip_reports_mismatch_offset() {
+        let input = include_str!("./test_inputs/accept_msg.json");
+        let accept: AcceptDlc = serde_json::from_str(input).unwrap();
+        let mut wire = Vec::new();
+        accept.type_id().write(&mut wire).unwrap();
+        accept.write(&mut wire).unwrap();
+        let len = wire.len();
+        // Trailing data is ignored by the decoder, so it is lost when encoding
+        // the message back.
+        wire.push(0);
+        match assert_roundtrip(&wire) {
+            Err(RoundtripError::Mismatch { offset }) => assert_eq!(len, offset),
+            r => panic!("Unexpected result {:?}", r),
+        };
+
+        wire[0] = 0;
+        match assert_roundtrip(&wire) {
+            Err(RoundtripError::UnknownType(_)) => {}
+            r => panic!("Unexpected result {:?}", r),
+        };
     }
 
     #[test]
@@ -444,6 +851,99 @@ mod tests {
         roundtrip_test!(OfferDlc, input);
     }
 
+    #[test]
+    fn offer_msg_with_tlv_records_roundtrip() {
+        let input = include_str!("./test_inputs/offer_msg.json");
+        let mut offer: OfferDlc = serde_json::from_str(input).unwrap();
+        offer.fee_contribution = FeeContribution::Ratio(1, 3);
+        test_roundtrip(offer.clone());
+        offer.cet_sig_hash_type = Some(SigHashType::AllPlusAnyoneCanPay);
+        test_roundtrip(offer.clone());
+        offer.cet_csv_delays = Some(CetCsvDelays {
+            offer: 144,
+            accept: 288,
+        });
+        test_roundtrip(offer.clone());
+        offer.cet_fee_reserve_multiplier = Some(3);
+        test_roundtrip(offer.clone());
+        offer.fee_contribution = FeeContribution::Equal;
+        test_roundtrip(offer.clone());
+        offer.cet_sig_hash_type = None;
+        test_roundtrip(offer.clone());
+        offer.cet_csv_delays = None;
+        test_roundtrip(offer);
+    }
+
+    #[test]
+    fn offer_msg_with_unknown_trailing_data_is_rejected() {
+        let mut wire = get_offer_wire_bytes();
+        wire.push(0);
+        assert!(matches!(
+            assert_roundtrip(&wire),
+            Err(RoundtripError::Decode(DecodeError::ShortRead))
+        ));
+
+        // Unknown even records are rejected while unknown odd ones are skipped.
+        let mut wire = get_offer_wire_bytes();
+        wire.extend_from_slice(&[2, 1, 0]);
+        assert!(matches!(
+            assert_roundtrip(&wire),
+            Err(RoundtripError::Decode(DecodeError::UnknownRequiredFeature))
+        ));
+        let len = get_offer_wire_bytes().len();
+        let mut wire = get_offer_wire_bytes();
+        wire.extend_from_slice(&[1, 1, 0]);
+        match assert_roundtrip(&wire) {
+            Err(RoundtripError::Mismatch { offset }) => assert_eq!(len, offset),
+            r => panic!("Unexpected result {:?}", r),
+        }
+
+        let mut wire = get_offer_wire_bytes();
+        ser_impls::BigSize(FEE_CONTRIBUTION_TLV_TYPE)
+            .write(&mut wire)
+            .unwrap();
+        ser_impls::BigSize(1).write(&mut wire).unwrap();
+        // An equal fee contribution is never encoded.
+        wire.push(0);
+        assert!(matches!(
+            assert_roundtrip(&wire),
+            Err(RoundtripError::Decode(DecodeError::InvalidValue))
+        ));
+
+        let input = include_str!("./test_inputs/offer_msg.json");
+        let mut offer: OfferDlc = serde_json::from_str(input).unwrap();
+        offer.fee_contribution = FeeContribution::OffererPays;
+        let mut wire = Vec::new();
+        offer.type_id().write(&mut wire).unwrap();
+        offer.write(&mut wire).unwrap();
+        // Records must be ordered by increasing type.
+        let mut unordered = wire.clone();
+        unordered.extend_from_slice(&[1, 1, 0]);
+        assert!(matches!(
+            assert_roundtrip(&unordered),
+            Err(RoundtripError::Decode(DecodeError::InvalidValue))
+        ));
+        // The value of a known record must be read entirely.
+        let len_index = wire.len() - 2;
+        wire[len_index] = 2;
+        wire.push(0);
+        assert!(matches!(
+            assert_roundtrip(&wire),
+            Err(RoundtripError::Decode(DecodeError::InvalidValue))
+        ));
+    }
+
+    #[test]
+    fn offer_msg_bech32_roundtrip() {
+        let input = include_str!("./test_inputs/offer_msg.json");
+        let offer: OfferDlc = serde_json::from_str(input).unwrap();
+        let encoded = offer.to_bech32_string();
+        assert!(encoded.starts_with("dlcoffer1"));
+        let decoded = OfferDlc::from_bech32_str(&encoded).expect("Error decoding offer");
+        assert_eq!(offer, decoded);
+        assert!(OfferDlc::from_bech32_str(&encoded[..encoded.len() - 1]).is_err());
+    }
+
     #[test]
     fn accept_msg_roundtrip() {
         let input = include_str!("./test_inputs/accept_msg.json");
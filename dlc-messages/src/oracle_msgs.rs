@@ -6,12 +6,18 @@ use dlc::OracleInfo as DlcOracleInfo;
 use lightning::ln::msgs::DecodeError;
 use lightning::ln::wire::Type;
 use lightning::util::ser::{Readable, Writeable, Writer};
+use secp256k1_zkp::bitcoin_hashes::{sha256, Hash, HashEngine};
 use secp256k1_zkp::schnorrsig::{PublicKey as SchnorrPublicKey, Signature as SchnorrSignature};
+use secp256k1_zkp::{Error as SecpError, Message, Secp256k1, Signing, UpstreamError};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 pub const ANNOUNCEMENT_TYPE: u16 = 55332;
 pub const ATTESTATION_TYPE: u16 = 55400;
+/// The tag of the hash signed by oracle master keys to delegate to another
+/// key, so that delegation signatures cannot be mistaken for signatures of
+/// other messages.
+pub const KEY_DELEGATION_TAG: &[u8] = b"DLC/oracle/keydelegation/v0";
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[cfg_attr(
@@ -117,10 +123,103 @@ impl_dlc_writeable!(OracleAnnouncement, {
 
 impl From<&OracleAnnouncement> for DlcOracleInfo {
     fn from(input: &OracleAnnouncement) -> DlcOracleInfo {
-        DlcOracleInfo {
-            public_key: input.oracle_public_key,
-            nonces: input.oracle_event.oracle_nonces.clone(),
+        DlcOracleInfo::with_prefix_messages(
+            input.oracle_public_key,
+            input.oracle_event.oracle_nonces.clone(),
+            input
+                .oracle_event
+                .event_descriptor
+                .get_prefix_outcomes()
+                .iter()
+                .map(|x| Message::from_hashed_data::<sha256::Hash>(x.as_bytes()))
+                .collect(),
+        )
+    }
+}
+
+impl OracleAnnouncement {
+    /// Checks that the announcement signature is valid for the oracle event
+    /// and public key.
+    pub fn validate<C: Signing>(&self, secp: &Secp256k1<C>) -> Result<(), SecpError> {
+        let mut event_hex = Vec::new();
+        self.oracle_event
+            .write(&mut event_hex)
+            .expect("Error writing oracle event");
+        let msg = Message::from_hashed_data::<sha256::Hash>(&event_hex);
+        secp.schnorrsig_verify(&self.announcement_signature, &msg, &self.oracle_public_key)
+            .map_err(SecpError::Upstream)
+    }
+}
+
+/// A signature by the master key of an oracle authorizing a delegated key to
+/// sign announcements on its behalf.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct OracleKeyDelegation {
+    pub master_public_key: SchnorrPublicKey,
+    pub delegated_public_key: SchnorrPublicKey,
+    pub delegation_signature: SchnorrSignature,
+}
+
+impl_dlc_writeable!(OracleKeyDelegation, {
+    (master_public_key, {cb_writeable, write_schnorr_pubkey, read_schnorr_pubkey}),
+    (delegated_public_key, {cb_writeable, write_schnorr_pubkey, read_schnorr_pubkey}),
+    (delegation_signature, {cb_writeable, write_schnorrsig, read_schnorrsig})
+});
+
+impl OracleKeyDelegation {
+    /// Returns the message that the master key signs to delegate to the given
+    /// key, a BIP 340 style tagged hash of the key using
+    /// [`KEY_DELEGATION_TAG`].
+    pub fn get_delegation_message(delegated_public_key: &SchnorrPublicKey) -> Message {
+        let tag_hash = sha256::Hash::hash(KEY_DELEGATION_TAG);
+        let mut engine = sha256::Hash::engine();
+        engine.input(&tag_hash[..]);
+        engine.input(&tag_hash[..]);
+        engine.input(&delegated_public_key.serialize());
+        Message::from_slice(&sha256::Hash::from_engine(engine)[..]).expect("a 32 bytes hash")
+    }
+
+    /// Checks that the delegation signature is valid for the master key.
+    pub fn validate<C: Signing>(&self, secp: &Secp256k1<C>) -> Result<(), SecpError> {
+        let msg = Self::get_delegation_message(&self.delegated_public_key);
+        secp.schnorrsig_verify(&self.delegation_signature, &msg, &self.master_public_key)
+            .map_err(SecpError::Upstream)
+    }
+}
+
+/// An announcement signed by a delegated key, together with the delegation
+/// from the oracle master key.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct DelegatedOracleAnnouncement {
+    pub delegation: OracleKeyDelegation,
+    pub oracle_announcement: OracleAnnouncement,
+}
+
+impl_dlc_writeable!(DelegatedOracleAnnouncement, {
+    (delegation, writeable),
+    (oracle_announcement, {cb_writeable, write_as_tlv, read_as_tlv})
+});
+
+impl DelegatedOracleAnnouncement {
+    /// Checks the delegation chain: the master key must have signed the
+    /// delegated key, which in turn must have signed the announcement. Only
+    /// announcements passing this check should be attributed to the master key.
+    pub fn validate<C: Signing>(&self, secp: &Secp256k1<C>) -> Result<(), SecpError> {
+        self.delegation.validate(secp)?;
+        if self.oracle_announcement.oracle_public_key != self.delegation.delegated_public_key {
+            return Err(SecpError::Upstream(UpstreamError::InvalidPublicKey));
         }
+        self.oracle_announcement.validate(secp)
     }
 }
 
@@ -163,6 +262,17 @@ pub enum EventDescriptor {
 
 impl_dlc_writeable_enum_as_tlv!(EventDescriptor, (55302, EnumEvent), (55306, DigitDecompositionEvent););
 
+impl EventDescriptor {
+    /// Returns the outcomes that the oracle attests to using its first nonces,
+    /// before the ones attesting to the event outcome itself.
+    pub fn get_prefix_outcomes(&self) -> Vec<String> {
+        match self {
+            EventDescriptor::EnumEvent(_) => Vec::new(),
+            EventDescriptor::DigitDecompositionEvent(d) => d.get_prefix_outcomes(),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 #[cfg_attr(
     feature = "serde",
@@ -191,6 +301,24 @@ pub struct DigitDecompositionEventDescriptor {
     pub nb_digits: u16,
 }
 
+/// The outcome attested to by the sign nonce of signed digit decomposition
+/// events for non-negative values.
+pub const POSITIVE_SIGN_OUTCOME: &str = "+";
+
+impl DigitDecompositionEventDescriptor {
+    /// Returns the outcomes that the oracle attests to using its first nonces,
+    /// before the digits of the event outcome. For signed events, this is the
+    /// sign of the outcome, which is expected to be positive as contracts only
+    /// cover non-negative outcome values.
+    pub fn get_prefix_outcomes(&self) -> Vec<String> {
+        if self.is_signed {
+            vec![POSITIVE_SIGN_OUTCOME.to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 impl_dlc_writeable!(DigitDecompositionEventDescriptor, {
     (base, writeable),
     (is_signed, writeable),
@@ -199,7 +327,7 @@ impl_dlc_writeable!(DigitDecompositionEventDescriptor, {
     (nb_digits, writeable)
 });
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct OracleAttestation {
     pub oracle_public_key: SchnorrPublicKey,
     pub signatures: Vec<SchnorrSignature>,
@@ -217,3 +345,97 @@ impl_dlc_writeable!(OracleAttestation, {
     (signatures, {vec_u16_cb, write_schnorrsig, read_schnorrsig}),
     (outcomes, {cb_writeable, write_strings_u16, read_strings_u16})
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1_zkp::rand::thread_rng;
+    use secp256k1_zkp::schnorrsig::KeyPair;
+    use secp256k1_zkp::{All, SecretKey};
+
+    fn get_announcement(secp: &Secp256k1<All>, key_pair: &KeyPair) -> OracleAnnouncement {
+        let oracle_event = OracleEvent {
+            oracle_nonces: vec![SchnorrPublicKey::from_keypair(
+                secp,
+                &KeyPair::new(secp, &mut thread_rng()),
+            )],
+            event_maturity_epoch: 1623133104,
+            event_descriptor: EventDescriptor::EnumEvent(EnumEventDescriptor {
+                outcomes: vec!["a".to_string(), "b".to_string()],
+            }),
+            event_id: "Test".to_string(),
+        };
+        let mut event_hex = Vec::new();
+        oracle_event.write(&mut event_hex).unwrap();
+        let msg = Message::from_hashed_data::<sha256::Hash>(&event_hex);
+        OracleAnnouncement {
+            announcement_signature: secp.schnorrsig_sign(&msg, key_pair),
+            oracle_public_key: SchnorrPublicKey::from_keypair(secp, key_pair),
+            oracle_event,
+        }
+    }
+
+    fn get_delegated_announcement(
+        secp: &Secp256k1<All>,
+        master_key_pair: &KeyPair,
+    ) -> DelegatedOracleAnnouncement {
+        let delegated_key_pair = KeyPair::new(secp, &mut thread_rng());
+        let delegated_public_key = SchnorrPublicKey::from_keypair(secp, &delegated_key_pair);
+        let msg = OracleKeyDelegation::get_delegation_message(&delegated_public_key);
+        DelegatedOracleAnnouncement {
+            delegation: OracleKeyDelegation {
+                master_public_key: SchnorrPublicKey::from_keypair(secp, master_key_pair),
+                delegated_public_key,
+                delegation_signature: secp.schnorrsig_sign(&msg, master_key_pair),
+            },
+            oracle_announcement: get_announcement(secp, &delegated_key_pair),
+        }
+    }
+
+    #[test]
+    fn valid_delegation_is_accepted() {
+        let secp = Secp256k1::new();
+        let master_key_pair = KeyPair::new(&secp, &mut thread_rng());
+        let announcement = get_delegated_announcement(&secp, &master_key_pair);
+
+        announcement
+            .validate(&secp)
+            .expect("Delegation chain should be valid");
+
+        let mut buf = Vec::new();
+        announcement.write(&mut buf).unwrap();
+        let deser: DelegatedOracleAnnouncement =
+            Readable::read(&mut std::io::Cursor::new(&buf)).unwrap();
+        assert_eq!(announcement, deser);
+    }
+
+    #[test]
+    fn forged_delegation_is_rejected() {
+        let secp = Secp256k1::new();
+        let master_key_pair = KeyPair::new(&secp, &mut thread_rng());
+        let mut announcement = get_delegated_announcement(&secp, &master_key_pair);
+
+        // Delegation signed by another key than the claimed master key.
+        let forger_key_pair =
+            KeyPair::from_secret_key(&secp, SecretKey::from_slice(&[1; 32]).unwrap());
+        let msg = OracleKeyDelegation::get_delegation_message(
+            &announcement.delegation.delegated_public_key,
+        );
+        announcement.delegation.delegation_signature = secp.schnorrsig_sign(&msg, &forger_key_pair);
+        assert!(announcement.validate(&secp).is_err());
+
+        // Master key signature over the untagged hash of the delegated key.
+        let mut announcement = get_delegated_announcement(&secp, &master_key_pair);
+        let untagged_msg = Message::from_hashed_data::<sha256::Hash>(
+            &announcement.delegation.delegated_public_key.serialize(),
+        );
+        announcement.delegation.delegation_signature =
+            secp.schnorrsig_sign(&untagged_msg, &master_key_pair);
+        assert!(announcement.validate(&secp).is_err());
+
+        // Announcement signed by a key other than the delegated one.
+        let mut announcement = get_delegated_announcement(&secp, &master_key_pair);
+        announcement.oracle_announcement = get_announcement(&secp, &forger_key_pair);
+        assert!(announcement.validate(&secp).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Splitting of messages exceeding the maximum Lightning message size into
+//! ordered chunks, and reassembly of these chunks on the receiving side.
+
+use lightning::ln::msgs::DecodeError;
+use lightning::ln::wire::Type;
+use lightning::util::ser::{Readable, Writeable, Writer};
+
+/// The type of messages carrying a chunk of a segmented message.
+pub const SEGMENT_CHUNK_TYPE: u16 = 42900;
+
+/// The maximum size of a Lightning message, including its two bytes type prefix.
+pub const MAX_MESSAGE_SIZE: usize = 65535;
+
+// type (2) + message_type (2) + index (2) + nb_chunks (2) + data length (up to 3)
+const CHUNK_OVERHEAD: usize = 11;
+
+/// The maximum number of bytes of the wrapped message that a single chunk can carry.
+pub const MAX_CHUNK_DATA_SIZE: usize = MAX_MESSAGE_SIZE - CHUNK_OVERHEAD;
+
+/// A part of a message that was too large to be sent at once. The header
+/// (`message_type`, `index` and `nb_chunks`) enables the receiving side to
+/// reassemble the message regardless of the order in which chunks are received.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentChunk {
+    /// The type of the wrapped message.
+    pub message_type: u16,
+    /// The position of this chunk within the wrapped message.
+    pub index: u16,
+    /// The total number of chunks making up the wrapped message.
+    pub nb_chunks: u16,
+    /// The bytes of the wrapped message contained in this chunk.
+    pub data: Vec<u8>,
+}
+
+impl_dlc_writeable!(SegmentChunk, {
+    (message_type, writeable),
+    (index, writeable),
+    (nb_chunks, writeable),
+    (data, vec)
+});
+
+impl Type for SegmentChunk {
+    fn type_id(&self) -> u16 {
+        SEGMENT_CHUNK_TYPE
+    }
+}
+
+/// Returns whether the given message needs to be split to be sent over the
+/// Lightning transport.
+pub fn needs_segmentation<T: Writeable>(msg: &T) -> bool {
+    // Account for the type prefix of the message.
+    msg.serialized_length() + 2 > MAX_MESSAGE_SIZE
+}
+
+/// Splits the given message into a set of ordered chunks.
+pub fn get_segments<T: Type + Writeable>(msg: &T) -> Result<Vec<SegmentChunk>, ::std::io::Error> {
+    let mut buf = Vec::new();
+    msg.write(&mut buf)?;
+    let nb_chunks = buf.len().div_ceil(MAX_CHUNK_DATA_SIZE);
+    if nb_chunks > u16::MAX as usize {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            "Message too large to be segmented",
+        ));
+    }
+
+    Ok(buf
+        .chunks(MAX_CHUNK_DATA_SIZE)
+        .enumerate()
+        .map(|(i, data)| SegmentChunk {
+            message_type: msg.type_id(),
+            index: i as u16,
+            nb_chunks: nb_chunks as u16,
+            data: data.to_vec(),
+        })
+        .collect())
+}
+
+struct PendingMessage {
+    message_type: u16,
+    chunks: Vec<Option<Vec<u8>>>,
+    nb_received: usize,
+}
+
+/// Reassembles messages from the chunks received from a single peer.
+#[derive(Default)]
+pub struct SegmentReader {
+    pending: Option<PendingMessage>,
+}
+
+impl SegmentReader {
+    /// Creates a new empty `SegmentReader`.
+    pub fn new() -> Self {
+        SegmentReader { pending: None }
+    }
+
+    /// Returns whether some chunks were received for a message that is not yet
+    /// complete.
+    pub fn is_waiting(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Drops any partially received message.
+    pub fn reset(&mut self) {
+        self.pending = None;
+    }
+
+    /// Processes the given chunk, returning the type and serialized content of
+    /// the wrapped message once all its chunks have been received. Chunks that
+    /// are inconsistent with the ones received previously result in an error
+    /// and the partially received message being dropped.
+    pub fn process_chunk(
+        &mut self,
+        chunk: SegmentChunk,
+    ) -> Result<Option<(u16, Vec<u8>)>, DecodeError> {
+        if chunk.nb_chunks == 0 || chunk.index >= chunk.nb_chunks {
+            self.reset();
+            return Err(DecodeError::InvalidValue);
+        }
+
+        let pending = self.pending.get_or_insert_with(|| PendingMessage {
+            message_type: chunk.message_type,
+            chunks: vec![None; chunk.nb_chunks as usize],
+            nb_received: 0,
+        });
+
+        if pending.message_type != chunk.message_type
+            || pending.chunks.len() != chunk.nb_chunks as usize
+            || pending.chunks[chunk.index as usize].is_some()
+        {
+            self.reset();
+            return Err(DecodeError::InvalidValue);
+        }
+
+        pending.chunks[chunk.index as usize] = Some(chunk.data);
+        pending.nb_received += 1;
+
+        if pending.nb_received < pending.chunks.len() {
+            return Ok(None);
+        }
+
+        let pending = self.pending.take().expect("to have a pending message");
+        let data = pending
+            .chunks
+            .into_iter()
+            .flat_map(|x| x.expect("to have received all chunks"))
+            .collect();
+
+        Ok(Some((pending.message_type, data)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SignDlc, SIGN_TYPE};
+    use secp256k1_zkp::rand::{seq::SliceRandom, thread_rng};
+
+    fn get_large_sign_message() -> SignDlc {
+        let input = include_str!("./test_inputs/sign_msg.json");
+        let mut sign: SignDlc = serde_json::from_str(input).unwrap();
+        let sig = sign.cet_adaptor_signatures.ecdsa_adaptor_signatures[0].clone();
+        sign.cet_adaptor_signatures.ecdsa_adaptor_signatures = vec![sig; 1000];
+        sign
+    }
+
+    #[test]
+    fn shuffled_chunks_are_reassembled() {
+        let sign = get_large_sign_message();
+        assert!(needs_segmentation(&sign));
+
+        let mut chunks = get_segments(&sign).expect("Error segmenting message");
+        assert!(chunks.len() > 1);
+        assert!(chunks
+            .iter()
+            .all(|x| x.serialized_length() + 2 <= MAX_MESSAGE_SIZE));
+        chunks.shuffle(&mut thread_rng());
+
+        let mut reader = SegmentReader::new();
+        let last = chunks.pop().unwrap();
+        for chunk in chunks {
+            assert!(reader.process_chunk(chunk).unwrap().is_none());
+        }
+        assert!(reader.is_waiting());
+
+        let (message_type, data) = reader.process_chunk(last).unwrap().unwrap();
+        assert_eq!(SIGN_TYPE, message_type);
+        let mut cursor = std::io::Cursor::new(&data);
+        let deser: SignDlc = Readable::read(&mut cursor).expect("Error reading message");
+        assert_eq!(sign, deser);
+        assert!(!reader.is_waiting());
+    }
+
+    #[test]
+    fn duplicate_chunk_is_rejected() {
+        let chunks = get_segments(&get_large_sign_message()).unwrap();
+        let mut reader = SegmentReader::new();
+        reader.process_chunk(chunks[0].clone()).unwrap();
+        assert!(reader.process_chunk(chunks[0].clone()).is_err());
+        assert!(!reader.is_waiting());
+    }
+}
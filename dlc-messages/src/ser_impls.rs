@@ -1,12 +1,12 @@
 use bitcoin::network::constants::Network;
 use bitcoin::Address;
-use dlc::{EnumerationPayout, PartyParams, Payout, TxInputInfo};
+use dlc::{EnumerationPayout, FeeContribution, PartyParams, Payout, TxInputInfo};
 use lightning::ln::msgs::DecodeError;
 use lightning::ln::wire::Type;
 use lightning::util::ser::{Readable, Writeable, Writer};
 use secp256k1_zkp::{ffi::ECDSA_ADAPTOR_SIGNATURE_LENGTH, EcdsaAdaptorSignature};
 use std::convert::TryInto;
-use std::io::Read;
+use std::io::{Cursor, Read, Take};
 
 /// Taken from rust-lightning: https://github.com/rust-bitcoin/rust-lightning/blob/v0.0.101/lightning/src/util/ser.rs#L295
 ///
@@ -497,6 +497,91 @@ pub fn read_as_tlv<T: Type + Readable, R: ::std::io::Read>(
     Readable::read(reader)
 }
 
+pub fn write_fee_contribution<W: Writer>(
+    fee_contribution: &FeeContribution,
+    writer: &mut W,
+) -> Result<(), ::std::io::Error> {
+    match fee_contribution {
+        FeeContribution::Equal => 0u8.write(writer),
+        FeeContribution::OffererPays => 1u8.write(writer),
+        FeeContribution::AccepterPays => 2u8.write(writer),
+        FeeContribution::Ratio(offer, accept) => {
+            3u8.write(writer)?;
+            offer.write(writer)?;
+            accept.write(writer)
+        }
+    }
+}
+
+pub fn read_fee_contribution<R: ::std::io::Read>(
+    reader: &mut R,
+) -> Result<FeeContribution, DecodeError> {
+    let prefix: u8 = Readable::read(reader)?;
+    match prefix {
+        0 => Ok(FeeContribution::Equal),
+        1 => Ok(FeeContribution::OffererPays),
+        2 => Ok(FeeContribution::AccepterPays),
+        3 => Ok(FeeContribution::Ratio(
+            Readable::read(reader)?,
+            Readable::read(reader)?,
+        )),
+        _ => Err(DecodeError::InvalidValue),
+    }
+}
+
+/// Writes a TLV record of the given type, whose value is written by the given
+/// function.
+pub fn write_tlv_record<W: Writer, F>(
+    tlv_type: u64,
+    writer: &mut W,
+    write_value: F,
+) -> Result<(), ::std::io::Error>
+where
+    F: FnOnce(&mut Vec<u8>) -> Result<(), ::std::io::Error>,
+{
+    let mut value = Vec::new();
+    write_value(&mut value)?;
+    BigSize(tlv_type).write(writer)?;
+    BigSize(value.len() as u64).write(writer)?;
+    writer.write_all(&value)
+}
+
+/// Reads the TLV stream ending a message, calling the given function with the
+/// type of each record and a reader bounded by the length of its value. The
+/// function returns `false` for unknown types, whose records are skipped if
+/// odd and rejected if even. Records must be ordered by increasing type and
+/// known values must be read entirely. The stream ends when the reader is
+/// exhausted between two records, so the reader must be bounded by the length
+/// of the message.
+pub fn read_tlv_stream<R: Read, F>(reader: &mut R, mut read_value: F) -> Result<(), DecodeError>
+where
+    F: FnMut(u64, &mut Take<&mut R>) -> Result<bool, DecodeError>,
+{
+    let mut last_type = None;
+    loop {
+        let mut first_byte = [0u8; 1];
+        if reader.read(&mut first_byte)? == 0 {
+            return Ok(());
+        }
+        let tlv_type: BigSize = Readable::read(&mut Cursor::new(first_byte).chain(&mut *reader))?;
+        if matches!(last_type, Some(x) if tlv_type.0 <= x) {
+            return Err(DecodeError::InvalidValue);
+        }
+        last_type = Some(tlv_type.0);
+        let len: BigSize = Readable::read(reader)?;
+        let mut value = (&mut *reader).take(len.0);
+        if !read_value(tlv_type.0, &mut value)? {
+            if tlv_type.0 & 1 == 0 {
+                return Err(DecodeError::UnknownRequiredFeature);
+            }
+            ::std::io::copy(&mut value, &mut ::std::io::sink())?;
+        }
+        if value.limit() != 0 {
+            return Err(DecodeError::InvalidValue);
+        }
+    }
+}
+
 impl_dlc_writeable_external!(Payout, payout, { (offer, writeable), (accept, writeable) });
 impl_dlc_writeable_external!(EnumerationPayout, enum_payout, { (outcome, string), (payout, { cb_writeable, payout::write, payout::read} )});
 impl_dlc_writeable_external!(TxInputInfo, tx_input_info, { (outpoint, writeable), (max_witness_len, usize), (redeem_script, writeable), (serial_id, writeable)});
@@ -20,15 +20,40 @@ use dlc_manager::contract::accepted_contract::AcceptedContract;
 use dlc_manager::contract::offered_contract::OfferedContract;
 use dlc_manager::contract::ser::Serializable;
 use dlc_manager::contract::signed_contract::SignedContract;
-use dlc_manager::contract::{ClosedContract, Contract, FailedAcceptContract, FailedSignContract};
-use dlc_manager::{error::Error, ContractId, Storage};
-use sled::Db;
+use dlc_manager::contract::{
+    ClosedContract, CollaborativelyClosedContract, Contract, FailedAcceptContract,
+    FailedSignContract,
+};
+use dlc_manager::{error::Error, ContractDataKind, ContractId, Storage};
+use sled::transaction::{TransactionError, Transactional};
+use sled::{Db, IVec, Tree};
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::io::{Cursor, Read};
 
+/// Name of the tree holding the ids of the paused contracts, kept apart from
+/// the contracts so that iterating over them is not affected.
+const PAUSED_TREE: &str = "paused";
+/// Name of the tree holding the data that the manager keeps about contracts,
+/// keyed by the kind of data followed by the contract id.
+const CONTRACT_DATA_TREE: &str = "contract_data";
+
+/// The trees of the database written by the provider.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum TreeKind {
+    Contracts,
+    Paused,
+    ContractData,
+}
+
+/// Writes to the database, by tree and key, `None` values marking removals.
+type Writes = BTreeMap<(TreeKind, Vec<u8>), Option<IVec>>;
+
 /// Implementation of Storage interface using the sled DB backend.
 pub struct SledStorageProvider {
     db: Db,
+    /// The writes made since [`Storage::begin_batch`] was called, if it was.
+    batch: Option<Writes>,
 }
 
 macro_rules! convertible_enum {
@@ -69,6 +94,8 @@ convertible_enum!(
         FailedAccept,
         FailedSign,
         Refunded,
+        Rejected,
+        CollaborativelyClosed,
     }
 );
 
@@ -82,10 +109,19 @@ fn get_prefix(contract: &Contract) -> u8 {
         Contract::FailedAccept(_) => ContractPrefix::FailedAccept,
         Contract::FailedSign(_) => ContractPrefix::FailedSign,
         Contract::Refunded(_) => ContractPrefix::Refunded,
+        Contract::Rejected(_) => ContractPrefix::Rejected,
+        Contract::CollaborativelyClosed(_) => ContractPrefix::CollaborativelyClosed,
     };
     prefix.into()
 }
 
+fn get_contract_data_key(kind: ContractDataKind, contract_id: &ContractId) -> Vec<u8> {
+    let mut key = Vec::with_capacity(contract_id.len() + 1);
+    key.push(kind as u8);
+    key.extend_from_slice(contract_id);
+    key
+}
+
 fn to_storage_error<T>(e: T) -> Error
 where
     T: std::fmt::Display,
@@ -98,14 +134,96 @@ impl SledStorageProvider {
     pub fn new(path: &str) -> Result<Self, sled::Error> {
         Ok(SledStorageProvider {
             db: sled::open(path)?,
+            batch: None,
         })
     }
 
+    fn get_tree(&self, kind: TreeKind) -> Result<Tree, Error> {
+        match kind {
+            TreeKind::Contracts => Ok((*self.db).clone()),
+            TreeKind::Paused => self.db.open_tree(PAUSED_TREE).map_err(to_storage_error),
+            TreeKind::ContractData => self
+                .db
+                .open_tree(CONTRACT_DATA_TREE)
+                .map_err(to_storage_error),
+        }
+    }
+
+    /// Returns the value of the given key, including the writes of the
+    /// current batch.
+    fn get(&self, kind: TreeKind, key: &[u8]) -> Result<Option<IVec>, Error> {
+        if let Some(value) = self
+            .batch
+            .as_ref()
+            .and_then(|batch| batch.get(&(kind, key.to_vec())))
+        {
+            return Ok(value.clone());
+        }
+        self.get_tree(kind)?.get(key).map_err(to_storage_error)
+    }
+
+    /// Returns the entries of the given tree whose key starts with the given
+    /// prefix in key order, including the writes of the current batch.
+    fn scan_prefix(&self, kind: TreeKind, prefix: &[u8]) -> Result<Vec<(Vec<u8>, IVec)>, Error> {
+        let mut entries = self
+            .get_tree(kind)?
+            .scan_prefix(prefix)
+            .map(|entry| entry.map(|(key, value)| (key.to_vec(), value)))
+            .collect::<Result<BTreeMap<_, _>, _>>()
+            .map_err(to_storage_error)?;
+        if let Some(batch) = &self.batch {
+            for ((k, key), value) in batch.range((kind, prefix.to_vec())..) {
+                if *k != kind || !key.starts_with(prefix) {
+                    break;
+                }
+                match value {
+                    Some(value) => entries.insert(key.clone(), value.clone()),
+                    None => entries.remove(key),
+                };
+            }
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    /// Adds the given writes to the current batch, or applies them if no
+    /// batch was started.
+    fn write(&mut self, writes: Writes) -> Result<(), Error> {
+        match &mut self.batch {
+            Some(batch) => {
+                batch.extend(writes);
+                Ok(())
+            }
+            None => self.apply(&writes),
+        }
+    }
+
+    /// Applies the given writes in a single transaction.
+    fn apply(&self, writes: &Writes) -> Result<(), Error> {
+        let contracts = self.get_tree(TreeKind::Contracts)?;
+        let paused = self.get_tree(TreeKind::Paused)?;
+        let contract_data = self.get_tree(TreeKind::ContractData)?;
+        (&contracts, &paused, &contract_data)
+            .transaction(|(contracts, paused, contract_data)| {
+                for ((kind, key), value) in writes {
+                    let tree = match kind {
+                        TreeKind::Contracts => contracts,
+                        TreeKind::Paused => paused,
+                        TreeKind::ContractData => contract_data,
+                    };
+                    match value {
+                        Some(value) => tree.insert(key.as_slice(), value.clone())?,
+                        None => tree.remove(key.as_slice())?,
+                    };
+                }
+                Ok(())
+            })
+            .map_err(|e: TransactionError<Error>| to_storage_error(e))
+    }
+
     fn get_contracts_with_prefix<T: Serializable>(&self, prefix: u8) -> Result<Vec<T>, Error> {
-        let iter = self.db.iter();
-        iter.values()
-            .filter_map(|res| {
-                let value = res.unwrap();
+        self.scan_prefix(TreeKind::Contracts, &[])?
+            .into_iter()
+            .filter_map(|(_, value)| {
                 let mut cursor = Cursor::new(&value);
                 let mut pref = [0u8; 1];
                 cursor.read_exact(&mut pref).expect("Error reading prefix");
@@ -121,53 +239,49 @@ impl SledStorageProvider {
 
 impl Storage for SledStorageProvider {
     fn get_contract(&self, contract_id: &ContractId) -> Result<Option<Contract>, Error> {
-        match self.db.get(contract_id).map_err(to_storage_error)? {
+        match self.get(TreeKind::Contracts, contract_id)? {
             Some(res) => Ok(Some(deserialize_contract(&res)?)),
             None => Ok(None),
         }
     }
 
     fn get_contracts(&self) -> Result<Vec<Contract>, Error> {
-        Ok(self
-            .db
+        self.scan_prefix(TreeKind::Contracts, &[])?
             .iter()
-            .values()
-            .map(|x| Ok(deserialize_contract(&x.unwrap())?))
-            .collect::<Result<Vec<Contract>, Error>>()?)
+            .map(|(_, value)| deserialize_contract(value))
+            .collect()
     }
 
     fn create_contract(&mut self, contract: &OfferedContract) -> Result<(), Error> {
         let serialized = serialize_contract(&Contract::Offered(contract.clone()))?;
-        self.db
-            .insert(&contract.id, serialized)
-            .map_err(to_storage_error)?;
-        Ok(())
+        let mut writes = Writes::new();
+        writes.insert(
+            (TreeKind::Contracts, contract.id.to_vec()),
+            Some(serialized.into()),
+        );
+        self.write(writes)
     }
 
     fn delete_contract(&mut self, contract_id: &ContractId) -> Result<(), Error> {
-        self.db.remove(&contract_id).map_err(to_storage_error)?;
-        Ok(())
+        let mut writes = Writes::new();
+        writes.insert((TreeKind::Contracts, contract_id.to_vec()), None);
+        self.write(writes)
     }
 
     fn update_contract(&mut self, contract: &Contract) -> Result<(), Error> {
-        self.db
-            .transaction(|db| {
-                let serialized = match serialize_contract(contract) {
-                    Ok(b) => b,
-                    Err(e) => sled::transaction::abort(e)?,
-                };
-                match contract {
-                    a @ Contract::Accepted(_) | a @ Contract::Signed(_) => {
-                        db.remove(&a.get_temporary_id())?;
-                    }
-                    _ => {}
-                };
-
-                db.insert(&contract.get_id(), serialized)?;
-                Ok(())
-            })
-            .map_err(to_storage_error)?;
-        Ok(())
+        let serialized = serialize_contract(contract)?;
+        let mut writes = Writes::new();
+        match contract {
+            a @ Contract::Accepted(_) | a @ Contract::Signed(_) => {
+                writes.insert((TreeKind::Contracts, a.get_temporary_id().to_vec()), None);
+            }
+            _ => {}
+        };
+        writes.insert(
+            (TreeKind::Contracts, contract.get_id().to_vec()),
+            Some(serialized.into()),
+        );
+        self.write(writes)
     }
 
     fn get_signed_contracts(&self) -> Result<Vec<SignedContract>, Error> {
@@ -178,19 +292,100 @@ impl Storage for SledStorageProvider {
         self.get_contracts_with_prefix(ContractPrefix::Confirmed.into())
     }
 
+    fn flush(&mut self) -> Result<(), Error> {
+        self.db.flush().map_err(to_storage_error)?;
+        Ok(())
+    }
+
+    fn begin_batch(&mut self) -> Result<(), Error> {
+        self.batch.get_or_insert_with(Writes::new);
+        Ok(())
+    }
+
+    fn commit_batch(&mut self) -> Result<(), Error> {
+        if let Some(batch) = self.batch.take() {
+            self.apply(&batch)?;
+        }
+        self.flush()
+    }
+
     fn get_contract_offers(&self) -> Result<Vec<OfferedContract>, Error> {
         self.get_contracts_with_prefix(ContractPrefix::Offered.into())
     }
+
+    fn set_contract_paused(&mut self, contract_id: &ContractId, paused: bool) -> Result<(), Error> {
+        let mut writes = Writes::new();
+        writes.insert(
+            (TreeKind::Paused, contract_id.to_vec()),
+            Some(IVec::default()).filter(|_| paused),
+        );
+        self.write(writes)
+    }
+
+    fn get_paused_contracts(&self) -> Result<Vec<ContractId>, Error> {
+        self.scan_prefix(TreeKind::Paused, &[])?
+            .into_iter()
+            .map(|(key, _)| key.as_slice().try_into().map_err(to_storage_error))
+            .collect()
+    }
+
+    fn upsert_contract_data(
+        &mut self,
+        kind: ContractDataKind,
+        contract_id: &ContractId,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let mut writes = Writes::new();
+        writes.insert(
+            (
+                TreeKind::ContractData,
+                get_contract_data_key(kind, contract_id),
+            ),
+            Some(data.into()),
+        );
+        self.write(writes)
+    }
+
+    fn delete_contract_data(
+        &mut self,
+        kind: ContractDataKind,
+        contract_id: &ContractId,
+    ) -> Result<(), Error> {
+        let mut writes = Writes::new();
+        writes.insert(
+            (
+                TreeKind::ContractData,
+                get_contract_data_key(kind, contract_id),
+            ),
+            None,
+        );
+        self.write(writes)
+    }
+
+    fn get_contract_data(
+        &self,
+        kind: ContractDataKind,
+    ) -> Result<Vec<(ContractId, Vec<u8>)>, Error> {
+        self.scan_prefix(TreeKind::ContractData, &[kind as u8])?
+            .into_iter()
+            .map(|(key, value)| {
+                let contract_id: ContractId =
+                    key[1..].as_ref().try_into().map_err(to_storage_error)?;
+                Ok((contract_id, value.to_vec()))
+            })
+            .collect()
+    }
 }
 
 fn serialize_contract(contract: &Contract) -> Result<Vec<u8>, ::std::io::Error> {
     let serialized = match contract {
-        Contract::Offered(o) => o.serialize(),
+        Contract::Offered(o) | Contract::Rejected(o) => o.serialize(),
         Contract::Accepted(o) => o.serialize(),
         Contract::Signed(o) | Contract::Confirmed(o) | Contract::Refunded(o) => o.serialize(),
         Contract::FailedAccept(c) => c.serialize(),
         Contract::FailedSign(c) => c.serialize(),
         Contract::Closed(c) => c.serialize(),
+        Contract::CollaborativelyClosed(c) => c.serialize(),
     };
     let mut serialized = serialized?;
     let mut res = Vec::with_capacity(serialized.len() + 1);
@@ -229,6 +424,12 @@ fn deserialize_contract(buff: &sled::IVec) -> Result<Contract, Error> {
         ContractPrefix::Refunded => {
             Contract::Refunded(SignedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
         }
+        ContractPrefix::Rejected => {
+            Contract::Rejected(OfferedContract::deserialize(&mut cursor).map_err(to_storage_error)?)
+        }
+        ContractPrefix::CollaborativelyClosed => Contract::CollaborativelyClosed(
+            CollaborativelyClosedContract::deserialize(&mut cursor).map_err(to_storage_error)?,
+        ),
     };
     Ok(contract)
 }
@@ -396,4 +597,94 @@ mod tests {
             assert_eq!(1, offered_contracts.len());
         }
     );
+
+    sled_test!(
+        paused_contracts_are_not_listed_as_contracts,
+        |mut storage: SledStorageProvider| {
+            let serialized = include_bytes!("../test_files/Offered");
+            let contract: OfferedContract = deserialize_contract(serialized);
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+
+            storage
+                .set_contract_paused(&contract.id, true)
+                .expect("Error pausing contract");
+            assert_eq!(vec![contract.id], storage.get_paused_contracts().unwrap());
+            assert_eq!(1, storage.get_contracts().unwrap().len());
+
+            storage
+                .set_contract_paused(&contract.id, false)
+                .expect("Error resuming contract");
+            assert!(storage.get_paused_contracts().unwrap().is_empty());
+        }
+    );
+
+    sled_test!(
+        batched_writes_are_applied_on_commit,
+        |mut storage: SledStorageProvider| {
+            let serialized = include_bytes!("../test_files/Offered");
+            let contract: OfferedContract = deserialize_contract(serialized);
+            let kind = ContractDataKind::FundingConfirmationHeight;
+            storage
+                .upsert_contract_data(kind, &[2; 32], &[3])
+                .expect("Error storing contract data");
+
+            storage.begin_batch().expect("Error starting batch");
+            storage
+                .create_contract(&contract)
+                .expect("Error creating contract");
+            storage
+                .upsert_contract_data(kind, &[1; 32], &[1])
+                .expect("Error storing contract data");
+            storage
+                .delete_contract_data(kind, &[2; 32])
+                .expect("Error deleting contract data");
+
+            // Writes of the batch are visible but not applied.
+            assert_eq!(1, storage.get_contract_offers().unwrap().len());
+            assert_eq!(
+                vec![([1; 32], vec![1])],
+                storage.get_contract_data(kind).unwrap()
+            );
+            assert!(storage.db.get(contract.id).unwrap().is_none());
+
+            storage.commit_batch().expect("Error committing batch");
+            assert!(storage.db.get(contract.id).unwrap().is_some());
+            assert_eq!(1, storage.get_contracts().unwrap().len());
+            assert_eq!(
+                vec![([1; 32], vec![1])],
+                storage.get_contract_data(kind).unwrap()
+            );
+        }
+    );
+
+    sled_test!(
+        contract_data_is_kept_per_kind_and_contract,
+        |mut storage: SledStorageProvider| {
+            let kind = ContractDataKind::FundingConfirmationHeight;
+            storage
+                .upsert_contract_data(kind, &[1; 32], &[1, 2])
+                .expect("Error storing contract data");
+            storage
+                .upsert_contract_data(kind, &[2; 32], &[3])
+                .expect("Error storing contract data");
+            storage
+                .upsert_contract_data(kind, &[1; 32], &[4])
+                .expect("Error storing contract data");
+
+            let mut data = storage.get_contract_data(kind).unwrap();
+            data.sort();
+            assert_eq!(vec![([1; 32], vec![4]), ([2; 32], vec![3])], data);
+            assert!(storage.get_contracts().unwrap().is_empty());
+
+            storage
+                .delete_contract_data(kind, &[1; 32])
+                .expect("Error deleting contract data");
+            assert_eq!(
+                vec![([2; 32], vec![3])],
+                storage.get_contract_data(kind).unwrap()
+            );
+        }
+    );
 }
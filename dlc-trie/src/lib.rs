@@ -18,8 +18,8 @@ extern crate bitcoin;
 extern crate dlc;
 extern crate secp256k1_zkp;
 
-use bitcoin::{Script, Transaction};
-use dlc::{Error, OracleInfo, RangePayout};
+use bitcoin::Transaction;
+use dlc::{Error, OracleInfo, RangePayout, SigHashParams};
 use secp256k1_zkp::{All, EcdsaAdaptorSignature, PublicKey, Secp256k1, SecretKey, Verification};
 
 pub mod combination_iterator;
@@ -91,13 +91,13 @@ pub trait DlcTrie {
     where
         F: FnMut(&PublicKey, &RangeInfo) -> Result<(), Error>;
 
-    /// Generate the trie while verifying the provided adaptor signatures.
+    /// Generate the trie while verifying the provided adaptor signatures,
+    /// which must encrypt signatures of the fund output using its sighash type.
     fn generate_verify(
         &mut self,
         secp: &Secp256k1<secp256k1_zkp::All>,
         fund_pubkey: &PublicKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         outcomes: &Vec<RangePayout>,
         cets: &[Transaction],
         oracle_infos: &[OracleInfo],
@@ -110,14 +110,13 @@ pub trait DlcTrie {
                 let adaptor_sig = adaptor_sigs[adaptor_sig_index];
                 let cet = &cets[cet_index];
                 adaptor_sig_index += 1;
-                dlc::verify_cet_adaptor_sig_from_point(
+                dlc::verify_cet_adaptor_sig_from_point_with_sig_hash_type(
                     secp,
                     &adaptor_sig,
                     cet,
                     &adaptor_point,
                     &fund_pubkey,
-                    &funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                 )?;
                 Ok(adaptor_sig_index - 1)
             };
@@ -125,13 +124,13 @@ pub trait DlcTrie {
         Ok(adaptor_sig_index)
     }
 
-    /// Generate the trie while creating the set of adaptor signatures.
+    /// Generate the trie while creating the set of adaptor signatures, which
+    /// encrypt signatures of the fund output using its sighash type.
     fn generate_sign(
         &mut self,
         secp: &Secp256k1<All>,
         fund_privkey: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         outcomes: &Vec<RangePayout>,
         cets: &[Transaction],
         oracle_infos: &[OracleInfo],
@@ -141,13 +140,12 @@ pub trait DlcTrie {
         let mut adaptor_index = adaptor_index_start;
         let mut sign_callback =
             |cet_index: usize, adaptor_point: &PublicKey| -> Result<usize, crate::Error> {
-                let adaptor_pair = dlc::create_cet_adaptor_sig_from_point(
+                let adaptor_pair = dlc::create_cet_adaptor_sig_from_point_with_sig_hash_type(
                     &secp,
                     &cets[cet_index],
                     &adaptor_point,
                     fund_privkey,
-                    &funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                 )?;
                 adaptor_pairs.push(adaptor_pair);
                 adaptor_index += 1;
@@ -158,13 +156,12 @@ pub trait DlcTrie {
     }
 
     /// Verify that the provided signatures are valid with respect to the
-    /// information stored in the trie.
+    /// information stored in the trie and the given fund output.
     fn verify(
         &self,
         secp: &Secp256k1<All>,
         fund_pubkey: &PublicKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         adaptor_sigs: &[EcdsaAdaptorSignature],
         cets: &[Transaction],
         oracle_infos: &[OracleInfo],
@@ -177,14 +174,13 @@ pub trait DlcTrie {
                 if range_info.adaptor_index > max_adaptor_index {
                     max_adaptor_index = range_info.adaptor_index;
                 }
-                dlc::verify_cet_adaptor_sig_from_point(
+                dlc::verify_cet_adaptor_sig_from_point_with_sig_hash_type(
                     secp,
                     &adaptor_sig,
                     cet,
                     &adaptor_point,
                     &fund_pubkey,
-                    &funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                 )
             };
 
@@ -192,13 +188,13 @@ pub trait DlcTrie {
         Ok(max_adaptor_index + 1)
     }
 
-    /// Produce the set of adaptor signatures for the trie.
+    /// Produce the set of adaptor signatures for the trie, encrypting
+    /// signatures of the fund output using its sighash type.
     fn sign(
         &self,
         secp: &Secp256k1<All>,
         fund_privkey: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
         oracle_infos: &[OracleInfo],
     ) -> Result<Vec<EcdsaAdaptorSignature>, Error>;
@@ -8,7 +8,8 @@ use crate::multi_trie::{MultiTrie, MultiTrieDump, MultiTrieIterator};
 use crate::utils::get_adaptor_point_for_indexed_paths;
 use crate::DlcTrie;
 use crate::{Error, OracleInfo, RangeInfo, RangePayout};
-use bitcoin::{Script, Transaction};
+use bitcoin::Transaction;
+use dlc::SigHashParams;
 use secp256k1_zkp::{All, EcdsaAdaptorSignature, PublicKey, Secp256k1, SecretKey, Verification};
 
 /// Data structure used to store adaptor signature information for numerical
@@ -125,21 +126,19 @@ impl DlcTrie for MultiOracleTrieWithDiff {
         &self,
         secp: &Secp256k1<All>,
         fund_privkey: &SecretKey,
-        funding_script_pubkey: &Script,
-        fund_output_value: u64,
+        fund_output: &SigHashParams,
         cets: &[Transaction],
         oracle_infos: &[OracleInfo],
     ) -> Result<Vec<EcdsaAdaptorSignature>, Error> {
         let mut adaptor_pairs = Vec::<(usize, EcdsaAdaptorSignature)>::new();
         let mut callback =
             |adaptor_point: &PublicKey, range_info: &RangeInfo| -> Result<(), Error> {
-                let adaptor_pair = dlc::create_cet_adaptor_sig_from_point(
+                let adaptor_pair = dlc::create_cet_adaptor_sig_from_point_with_sig_hash_type(
                     &secp,
                     &cets[range_info.cet_index],
                     &adaptor_point,
                     fund_privkey,
-                    &funding_script_pubkey,
-                    fund_output_value,
+                    fund_output,
                 )?;
 
                 adaptor_pairs.push((range_info.adaptor_index, adaptor_pair));
@@ -25,7 +25,7 @@ extern crate serde;
 use bitcoin::blockdata::{
     opcodes,
     script::{Builder, Script},
-    transaction::{OutPoint, Transaction, TxIn, TxOut},
+    transaction::{OutPoint, SigHashType, Transaction, TxIn, TxOut},
 };
 use secp256k1_zkp::schnorrsig::{PublicKey as SchnorrPublicKey, Signature as SchnorrSignature};
 use secp256k1_zkp::EcdsaAdaptorSignature;
@@ -40,7 +40,7 @@ pub mod util;
 /// Minimum value that can be included in a transaction output. Under this value,
 /// outputs are discarded
 /// See: https://github.com/discreetlogcontracts/dlcspecs/blob/master/Transactions.md#change-outputs
-const DUST_LIMIT: u64 = 1000;
+pub const DUST_LIMIT: u64 = 1000;
 
 /// The transaction version
 /// See: https://github.com/discreetlogcontracts/dlcspecs/blob/master/Transactions.md#funding-transaction
@@ -162,6 +162,40 @@ pub struct OracleInfo {
     pub public_key: SchnorrPublicKey,
     /// The nonces that the oracle will use to attest to the event.
     pub nonces: Vec<SchnorrPublicKey>,
+    prefix_messages: Vec<Message>,
+}
+
+impl OracleInfo {
+    /// Creates the information of an oracle using all the given nonces to
+    /// attest to the event outcome.
+    pub fn new(public_key: SchnorrPublicKey, nonces: Vec<SchnorrPublicKey>) -> Self {
+        OracleInfo {
+            public_key,
+            nonces,
+            prefix_messages: Vec::new(),
+        }
+    }
+
+    /// Creates the information of an oracle signing the given messages using
+    /// its first nonces regardless of the event outcome (e.g. the sign of a
+    /// numerical outcome), the remaining nonces being used to attest to the
+    /// outcome itself.
+    pub fn with_prefix_messages(
+        public_key: SchnorrPublicKey,
+        nonces: Vec<SchnorrPublicKey>,
+        prefix_messages: Vec<Message>,
+    ) -> Self {
+        OracleInfo {
+            public_key,
+            nonces,
+            prefix_messages,
+        }
+    }
+
+    /// Returns the messages signed by the oracle using its first nonces.
+    pub fn get_prefix_messages(&self) -> &[Message] {
+        &self.prefix_messages
+    }
 }
 
 /// An error code.
@@ -222,6 +256,116 @@ pub struct PartyParams {
     pub collateral: u64,
 }
 
+/// Contains the information committed to by the signature of a segwit
+/// transaction input in addition to the transaction itself, for example the
+/// fund output when signing CETs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigHashParams<'a> {
+    /// The script of the output spent by the input, used as script code.
+    pub script_pubkey: &'a Script,
+    /// The value of the output spent by the input.
+    pub value: u64,
+    /// The sighash type of the signature.
+    pub sig_hash_type: SigHashType,
+}
+
+impl<'a> SigHashParams<'a> {
+    /// Returns the signature hash of the given input of the given transaction.
+    pub fn get_sig_hash_msg(&self, tx: &Transaction, input_index: usize) -> Message {
+        util::get_sig_hash_msg_with_sig_hash_type(
+            tx,
+            input_index,
+            self.script_pubkey,
+            self.value,
+            self.sig_hash_type,
+        )
+    }
+}
+
+/// Defines how the fees of the DLC transactions are split between the offer
+/// and accept parties.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FeeContribution {
+    /// Each party pays for its own inputs and outputs and half of the base
+    /// weight of the transactions.
+    #[default]
+    Equal,
+    /// The offer party pays all the fees.
+    OffererPays,
+    /// The accept party pays all the fees.
+    AccepterPays,
+    /// The fees are split according to the given offer and accept party
+    /// weights, the offer party paying `total * offer / (offer + accept)`.
+    Ratio(u32, u32),
+}
+
+impl FeeContribution {
+    /// Returns an error if the fees cannot be split as specified, which is
+    /// the case for a ratio with both weights set to zero.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            FeeContribution::Ratio(0, 0) => Err(Error::InvalidArgument),
+            _ => Ok(()),
+        }
+    }
+
+    /// Splits the fees computed for each party when they pay for their own
+    /// inputs and outputs, returning the shares of the offer and accept
+    /// parties.
+    pub fn split_fee(&self, offer_fee: u64, accept_fee: u64) -> Result<(u64, u64), Error> {
+        self.validate()?;
+        let total = offer_fee + accept_fee;
+        let offer_share = match *self {
+            FeeContribution::Equal => offer_fee,
+            FeeContribution::OffererPays => total,
+            FeeContribution::AccepterPays => 0,
+            FeeContribution::Ratio(offer, accept) => {
+                (total as u128 * offer as u128 / (offer as u128 + accept as u128)) as u64
+            }
+        };
+        Ok((offer_share, total - offer_share))
+    }
+}
+
+/// The change output of a party along with the fees that it pays for the fund
+/// transaction and the cet or refund transaction.
+pub type ChangeOutputAndFees = (TxOut, u64, u64);
+
+/// Returns the change output of each party as well as the fees that they are
+/// required to pay for the fund transaction and the cet or refund transaction,
+/// split according to the given fee contribution. Returns an error if the
+/// inputs of a party do not cover its collateral and its share of the fees.
+pub fn get_change_outputs_and_fees(
+    offer_params: &PartyParams,
+    accept_params: &PartyParams,
+    fee_rate_per_vb: u64,
+    cet_fee_rate_per_vb: u64,
+    fee_contribution: FeeContribution,
+) -> Result<(ChangeOutputAndFees, ChangeOutputAndFees), Error> {
+    let (offer_fund_fee, offer_cet_fee) =
+        offer_params.get_fees(fee_rate_per_vb, cet_fee_rate_per_vb);
+    let (accept_fund_fee, accept_cet_fee) =
+        accept_params.get_fees(fee_rate_per_vb, cet_fee_rate_per_vb);
+    let (offer_fund_fee, accept_fund_fee) =
+        fee_contribution.split_fee(offer_fund_fee, accept_fund_fee)?;
+    let (offer_cet_fee, accept_cet_fee) =
+        fee_contribution.split_fee(offer_cet_fee, accept_cet_fee)?;
+
+    Ok((
+        (
+            offer_params.get_change_output(offer_fund_fee, offer_cet_fee)?,
+            offer_fund_fee,
+            offer_cet_fee,
+        ),
+        (
+            accept_params.get_change_output(accept_fund_fee, accept_cet_fee)?,
+            accept_fund_fee,
+            accept_cet_fee,
+        ),
+    ))
+}
+
 impl PartyParams {
     /// Returns the change output for a single party as well as the fees that
     /// they are required to pay for the fund transaction and the cet or refund transaction.
@@ -232,6 +376,26 @@ impl PartyParams {
         &self,
         fee_rate_per_vb: u64,
     ) -> Result<(TxOut, u64, u64), Error> {
+        self.get_change_output_and_fees_with_cet_fee_rate(fee_rate_per_vb, fee_rate_per_vb)
+    }
+
+    /// Same as [`PartyParams::get_change_output_and_fees`], using a distinct
+    /// fee rate for the cet or refund transaction.
+    pub fn get_change_output_and_fees_with_cet_fee_rate(
+        &self,
+        fee_rate_per_vb: u64,
+        cet_fee_rate_per_vb: u64,
+    ) -> Result<(TxOut, u64, u64), Error> {
+        let (fund_fee, cet_or_refund_fee) = self.get_fees(fee_rate_per_vb, cet_fee_rate_per_vb);
+        let change_output = self.get_change_output(fund_fee, cet_or_refund_fee)?;
+
+        Ok((change_output, fund_fee, cet_or_refund_fee))
+    }
+
+    /// Returns the fees for the inputs and outputs of the party in the fund
+    /// transaction and the cet or refund transaction, including their share
+    /// of the base weight of these transactions.
+    fn get_fees(&self, fee_rate_per_vb: u64, cet_fee_rate_per_vb: u64) -> (u64, u64) {
         let inputs_weight: usize = self
             .inputs
             .iter()
@@ -262,18 +426,23 @@ impl PartyParams {
         // size of the payout script pubkey scaled by 4 from vBytes to weight units
         let output_spk_weight = self.payout_script_pubkey.len() * 4;
         let total_cet_weight = this_party_cet_base_weight + output_spk_weight;
-        let cet_or_refund_fee = util::weight_to_fee(total_cet_weight, fee_rate_per_vb);
+        let cet_or_refund_fee = util::weight_to_fee(total_cet_weight, cet_fee_rate_per_vb);
+
+        (fund_fee, cet_or_refund_fee)
+    }
+
+    /// Returns the change output of the party when paying the given fees,
+    /// or an error if the inputs do not cover them together with the collateral.
+    fn get_change_output(&self, fund_fee: u64, cet_or_refund_fee: u64) -> Result<TxOut, Error> {
         let required_input_funds = self.collateral + fund_fee + cet_or_refund_fee;
         if self.input_amount < required_input_funds {
             return Err(Error::InvalidArgument);
         }
 
-        let change_output = TxOut {
+        Ok(TxOut {
             value: self.input_amount - required_input_funds,
             script_pubkey: self.change_script_pubkey.clone(),
-        };
-
-        Ok((change_output, fund_fee, cet_or_refund_fee))
+        })
     }
 
     fn get_unsigned_tx_inputs_and_serial_ids(&self, sequence: u32) -> (Vec<TxIn>, Vec<u64>) {
@@ -306,6 +475,62 @@ pub fn create_dlc_transactions(
     cet_lock_time: u32,
     fund_output_serial_id: u64,
 ) -> Result<DlcTransactions, Error> {
+    create_dlc_transactions_with_params(
+        offer_params,
+        accept_params,
+        payouts,
+        &DlcTransactionsParams {
+            refund_lock_time,
+            fee_rate_per_vb,
+            cet_fee_rate_per_vb: fee_rate_per_vb,
+            fee_contribution: FeeContribution::Equal,
+            fund_lock_time,
+            cet_lock_time,
+            fund_output_serial_id,
+        },
+    )
+}
+
+/// The lock times, fees and fund output serial id used to create the
+/// transactions of a DLC contract, see [`create_dlc_transactions_with_params`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DlcTransactionsParams {
+    /// The lock time of the refund transaction.
+    pub refund_lock_time: u32,
+    /// The fee rate of the fund transaction.
+    pub fee_rate_per_vb: u64,
+    /// The fee rate of the CETs and refund transaction, which can be higher
+    /// than the one of the fund transaction to leave room for fee spikes
+    /// happening before they are broadcast.
+    pub cet_fee_rate_per_vb: u64,
+    /// How the fees of the transactions are split between the parties.
+    pub fee_contribution: FeeContribution,
+    /// The lock time of the fund transaction.
+    pub fund_lock_time: u32,
+    /// The lock time of the CETs.
+    pub cet_lock_time: u32,
+    /// The serial id of the fund output, used for output ordering.
+    pub fund_output_serial_id: u64,
+}
+
+/// Same as [`create_dlc_transactions`], using the given parameters, which
+/// allow for a distinct fee rate for the CETs and refund transaction and for
+/// an unequal split of the fees between the parties.
+pub fn create_dlc_transactions_with_params(
+    offer_params: &PartyParams,
+    accept_params: &PartyParams,
+    payouts: &[Payout],
+    params: &DlcTransactionsParams,
+) -> Result<DlcTransactions, Error> {
+    let DlcTransactionsParams {
+        refund_lock_time,
+        fee_rate_per_vb,
+        cet_fee_rate_per_vb,
+        fee_contribution,
+        fund_lock_time,
+        cet_lock_time,
+        fund_output_serial_id,
+    } = *params;
     let total_collateral = offer_params.collateral + accept_params.collateral;
 
     let has_proper_outcomes = payouts
@@ -316,10 +541,16 @@ pub fn create_dlc_transactions(
         return Err(Error::InvalidArgument);
     }
 
-    let (offer_change_output, offer_fund_fee, offer_cet_fee) =
-        offer_params.get_change_output_and_fees(fee_rate_per_vb)?;
-    let (accept_change_output, accept_fund_fee, accept_cet_fee) =
-        accept_params.get_change_output_and_fees(fee_rate_per_vb)?;
+    let (
+        (offer_change_output, offer_fund_fee, offer_cet_fee),
+        (accept_change_output, accept_fund_fee, accept_cet_fee),
+    ) = get_change_outputs_and_fees(
+        offer_params,
+        accept_params,
+        fee_rate_per_vb,
+        cet_fee_rate_per_vb,
+        fee_contribution,
+    )?;
 
     let fund_output_value = offer_params.input_amount + accept_params.input_amount
         - offer_change_output.value
@@ -483,6 +714,81 @@ pub fn create_cets(
     txs
 }
 
+/// Create the witness script of a CET output that can only be claimed using
+/// the given public key once `csv_delay` has elapsed since the CET
+/// confirmation. The delay is encoded as a BIP 68 relative lock time.
+pub fn make_csv_payout_script(pubkey: &PublicKey, csv_delay: u32) -> Script {
+    Builder::new()
+        .push_int(csv_delay as i64)
+        .push_opcode(opcodes::all::OP_CSV)
+        .push_opcode(opcodes::all::OP_DROP)
+        .push_slice(&pubkey.serialize())
+        .push_opcode(opcodes::all::OP_CHECKSIG)
+        .into_script()
+}
+
+/// The relative delays, in blocks, after which the parties can claim their
+/// CET outputs, see [`create_csv_gated_cets`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CetCsvDelays {
+    /// The delay of the output of the offer party.
+    pub offer: u32,
+    /// The delay of the output of the accept party.
+    pub accept: u32,
+}
+
+impl CetCsvDelays {
+    /// Returns an error if a delay is zero or cannot be encoded as a BIP 68
+    /// relative lock time in blocks, which is limited to 65535 blocks.
+    pub fn validate(&self) -> Result<(), Error> {
+        let is_valid = |delay: u32| delay > 0 && delay <= 0xffff;
+        if is_valid(self.offer) && is_valid(self.accept) {
+            Ok(())
+        } else {
+            Err(Error::InvalidArgument)
+        }
+    }
+
+    /// Returns the script pubkeys of the CET outputs of the offer and accept
+    /// parties, paying to their fund public keys once their delay elapsed.
+    pub fn get_payout_script_pubkeys(
+        &self,
+        offer_params: &PartyParams,
+        accept_params: &PartyParams,
+    ) -> (Script, Script) {
+        (
+            make_csv_payout_script(&offer_params.fund_pubkey, self.offer).to_v0_p2wsh(),
+            make_csv_payout_script(&accept_params.fund_pubkey, self.accept).to_v0_p2wsh(),
+        )
+    }
+}
+
+/// Create a set of contract execution transactions for each provided outcome,
+/// where the output of each party pays to its fund public key and is
+/// timelocked using its own relative delay so that neither party can sweep its
+/// payout immediately after broadcast.
+pub fn create_csv_gated_cets(
+    fund_tx_input: &TxIn,
+    offer_params: &PartyParams,
+    accept_params: &PartyParams,
+    csv_delays: &CetCsvDelays,
+    payouts: &[Payout],
+    lock_time: u32,
+) -> Vec<Transaction> {
+    let (offer_script_pubkey, accept_script_pubkey) =
+        csv_delays.get_payout_script_pubkeys(offer_params, accept_params);
+    create_cets(
+        fund_tx_input,
+        &offer_script_pubkey,
+        offer_params.payout_serial_id,
+        &accept_script_pubkey,
+        accept_params.payout_serial_id,
+        payouts,
+        lock_time,
+    )
+}
+
 /// Create a funding transaction
 pub fn create_funding_transaction(
     funding_script_pubkey: &Script,
@@ -577,14 +883,14 @@ fn get_oracle_sig_point<C: secp256k1_zkp::Verification>(
     oracle_info: &OracleInfo,
     msgs: &[Message],
 ) -> Result<PublicKey, Error> {
-    if oracle_info.nonces.len() < msgs.len() {
+    if oracle_info.nonces.len() < oracle_info.prefix_messages.len() + msgs.len() {
         return Err(Error::InvalidArgument);
     }
 
     let sig_points: Vec<PublicKey> = oracle_info
         .nonces
         .iter()
-        .zip(msgs.iter())
+        .zip(oracle_info.prefix_messages.iter().chain(msgs.iter()))
         .map(|(nonce, msg)| {
             secp_utils::schnorrsig_compute_sig_point(secp, &oracle_info.public_key, nonce, msg)
         })
@@ -618,7 +924,30 @@ pub fn create_cet_adaptor_sig_from_point<C: secp256k1_zkp::Signing>(
     funding_script_pubkey: &Script,
     fund_output_value: u64,
 ) -> Result<EcdsaAdaptorSignature, Error> {
-    let sig_hash = util::get_sig_hash_msg(cet, 0, funding_script_pubkey, fund_output_value);
+    create_cet_adaptor_sig_from_point_with_sig_hash_type(
+        secp,
+        cet,
+        adaptor_point,
+        funding_sk,
+        &SigHashParams {
+            script_pubkey: funding_script_pubkey,
+            value: fund_output_value,
+            sig_hash_type: SigHashType::All,
+        },
+    )
+}
+
+/// Same as [`create_cet_adaptor_sig_from_point`] using the given fund output
+/// information, whose sighash type must pass [`validate_cet_sig_hash_type`].
+pub fn create_cet_adaptor_sig_from_point_with_sig_hash_type<C: secp256k1_zkp::Signing>(
+    secp: &secp256k1_zkp::Secp256k1<C>,
+    cet: &Transaction,
+    adaptor_point: &PublicKey,
+    funding_sk: &SecretKey,
+    fund_output: &SigHashParams,
+) -> Result<EcdsaAdaptorSignature, Error> {
+    validate_cet_sig_hash_type(fund_output.sig_hash_type)?;
+    let sig_hash = fund_output.get_sig_hash_msg(cet, 0);
 
     Ok(secp256k1_zkp::EcdsaAdaptorSignature::encrypt(
         secp,
@@ -732,16 +1061,43 @@ pub fn sign_cet<C: secp256k1_zkp::Signing>(
     funding_script_pubkey: &Script,
     fund_output: u64,
 ) -> Result<(), Error> {
+    sign_cet_with_sig_hash_type(
+        secp,
+        cet,
+        adaptor_signature,
+        oracle_signatures,
+        funding_sk,
+        other_pk,
+        &SigHashParams {
+            script_pubkey: funding_script_pubkey,
+            value: fund_output,
+            sig_hash_type: SigHashType::All,
+        },
+    )
+}
+
+/// Same as [`sign_cet`] using the given fund output information, whose sighash
+/// type must match the one used to create the adaptor signature and pass
+/// [`validate_cet_sig_hash_type`].
+pub fn sign_cet_with_sig_hash_type<C: secp256k1_zkp::Signing>(
+    secp: &secp256k1_zkp::Secp256k1<C>,
+    cet: &mut Transaction,
+    adaptor_signature: &EcdsaAdaptorSignature,
+    oracle_signatures: &Vec<Vec<SchnorrSignature>>,
+    funding_sk: &SecretKey,
+    other_pk: &PublicKey,
+    fund_output: &SigHashParams,
+) -> Result<(), Error> {
+    validate_cet_sig_hash_type(fund_output.sig_hash_type)?;
     let adaptor_secret = signatures_to_secret(oracle_signatures)?;
     let adapted_sig = adaptor_signature.decrypt(&adaptor_secret)?;
 
-    util::sign_multi_sig_input(
+    util::sign_multi_sig_input_with_sig_hash_type(
         secp,
         cet,
         &adapted_sig,
         other_pk,
         funding_sk,
-        funding_script_pubkey,
         fund_output,
         0,
     );
@@ -749,6 +1105,29 @@ pub fn sign_cet<C: secp256k1_zkp::Signing>(
     Ok(())
 }
 
+/// Checks that the given sighash type can be used to sign a CET (or any other
+/// transaction spending the fund output). Only `ALL` and `ALL|ANYONECANPAY`
+/// are accepted: `NONE` and `SINGLE` do not commit to all the outputs, which
+/// would let the counter party redirect the payouts once it holds our
+/// signature.
+pub fn validate_cet_sig_hash_type(sig_hash_type: SigHashType) -> Result<(), Error> {
+    match sig_hash_type {
+        SigHashType::All | SigHashType::AllPlusAnyoneCanPay => Ok(()),
+        _ => Err(Error::InvalidArgument),
+    }
+}
+
+/// Checks that the given sighash type can be used to sign a funding input.
+/// Only `ALL` is accepted: any other type would let the inputs or outputs of
+/// the funding transaction be modified, changing its txid and invalidating the
+/// CETs and refund transaction that spend it.
+pub fn validate_funding_sig_hash_type(sig_hash_type: SigHashType) -> Result<(), Error> {
+    match sig_hash_type {
+        SigHashType::All => Ok(()),
+        _ => Err(Error::InvalidArgument),
+    }
+}
+
 /// Verify that a given adaptor signature for a given cet is valid with respect
 /// to an adaptor point.
 pub fn verify_cet_adaptor_sig_from_point(
@@ -760,7 +1139,32 @@ pub fn verify_cet_adaptor_sig_from_point(
     funding_script_pubkey: &Script,
     total_collateral: u64,
 ) -> Result<(), Error> {
-    let sig_hash = util::get_sig_hash_msg(cet, 0, funding_script_pubkey, total_collateral);
+    verify_cet_adaptor_sig_from_point_with_sig_hash_type(
+        secp,
+        adaptor_sig,
+        cet,
+        adaptor_point,
+        pubkey,
+        &SigHashParams {
+            script_pubkey: funding_script_pubkey,
+            value: total_collateral,
+            sig_hash_type: SigHashType::All,
+        },
+    )
+}
+
+/// Same as [`verify_cet_adaptor_sig_from_point`] using the given fund output
+/// information, whose sighash type must pass [`validate_cet_sig_hash_type`].
+pub fn verify_cet_adaptor_sig_from_point_with_sig_hash_type(
+    secp: &Secp256k1<secp256k1_zkp::All>,
+    adaptor_sig: &EcdsaAdaptorSignature,
+    cet: &Transaction,
+    adaptor_point: &PublicKey,
+    pubkey: &PublicKey,
+    fund_output: &SigHashParams,
+) -> Result<(), Error> {
+    validate_cet_sig_hash_type(fund_output.sig_hash_type)?;
+    let sig_hash = fund_output.get_sig_hash_msg(cet, 0);
     adaptor_sig.verify(secp, &sig_hash, &pubkey, &adaptor_point)?;
     Ok(())
 }
@@ -799,7 +1203,31 @@ pub fn verify_tx_input_sig<V: Verification>(
     value: u64,
     pk: &PublicKey,
 ) -> Result<(), Error> {
-    let sig_hash_msg = util::get_sig_hash_msg(tx, input_index, script_pubkey, value);
+    verify_tx_input_sig_with_sig_hash_type(
+        secp,
+        signature,
+        tx,
+        input_index,
+        &SigHashParams {
+            script_pubkey,
+            value,
+            sig_hash_type: SigHashType::All,
+        },
+        pk,
+    )
+}
+
+/// Verify a signature for a given transaction input computed using the given
+/// information about the spent output and sighash type.
+pub fn verify_tx_input_sig_with_sig_hash_type<V: Verification>(
+    secp: &Secp256k1<V>,
+    signature: &Signature,
+    tx: &Transaction,
+    input_index: usize,
+    sig_hash_params: &SigHashParams,
+    pk: &PublicKey,
+) -> Result<(), Error> {
+    let sig_hash_msg = sig_hash_params.get_sig_hash_msg(tx, input_index);
     secp.verify(&sig_hash_msg, signature, pk)?;
     Ok(())
 }
@@ -878,6 +1306,54 @@ mod tests {
         assert_eq!(3, refund_transaction.input[0].sequence);
     }
 
+    #[test]
+    fn create_csv_gated_cets_test() {
+        let (offer_params, _) = get_party_params(1000000, 100000, Some(0));
+        let (accept_params, _) = get_party_params(1000000, 100000, Some(1));
+        let offer_pubkey = offer_params.fund_pubkey;
+        let accept_pubkey = accept_params.fund_pubkey;
+        let (_, _, funding) = create_test_tx_io();
+        let payouts = vec![Payout {
+            offer: 100000,
+            accept: 200000,
+        }];
+        let csv_delays = CetCsvDelays {
+            offer: 144,
+            accept: 288,
+        };
+        csv_delays.validate().unwrap();
+
+        let cets = create_csv_gated_cets(
+            &funding,
+            &offer_params,
+            &accept_params,
+            &csv_delays,
+            &payouts,
+            0,
+        );
+
+        let get_csv_delay = |script: &Script| match script.instructions().next() {
+            Some(Ok(bitcoin::blockdata::script::Instruction::PushBytes(b))) => {
+                bitcoin::blockdata::script::read_scriptint(b).unwrap()
+            }
+            _ => panic!("Expected a push of the CSV delay"),
+        };
+        let offer_script = make_csv_payout_script(&offer_pubkey, 144);
+        let accept_script = make_csv_payout_script(&accept_pubkey, 288);
+        assert_eq!(144, get_csv_delay(&offer_script));
+        assert_eq!(288, get_csv_delay(&accept_script));
+
+        assert_eq!(1, cets.len());
+        assert_eq!(offer_script.to_v0_p2wsh(), cets[0].output[0].script_pubkey);
+        assert_eq!(100000, cets[0].output[0].value);
+        assert_eq!(accept_script.to_v0_p2wsh(), cets[0].output[1].script_pubkey);
+        assert_eq!(200000, cets[0].output[1].value);
+
+        for (offer, accept) in [(0, 288), (144, 0x10000)] {
+            assert!(CetCsvDelays { offer, accept }.validate().is_err());
+        }
+    }
+
     #[test]
     fn create_funding_transaction_test() {
         let (pk, pk1) = create_multi_party_pub_keys();
@@ -1160,6 +1636,67 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn fee_contribution_shifts_fees_between_parties() {
+        let (offer_params, _) = get_party_params(1000000, 100000, None);
+        let (accept_params, _) = get_party_params(100000, 100000, Some(2));
+
+        let ((_, offer_fund_fee, offer_cet_fee), (accept_change, accept_fund_fee, accept_cet_fee)) =
+            get_change_outputs_and_fees(
+                &offer_params,
+                &accept_params,
+                4,
+                4,
+                FeeContribution::OffererPays,
+            )
+            .unwrap();
+        let (_, equal_offer_fund_fee, equal_offer_cet_fee) =
+            offer_params.get_change_output_and_fees(4).unwrap();
+
+        assert_eq!(0, accept_change.value);
+        assert_eq!(0, accept_fund_fee + accept_cet_fee);
+        assert!(offer_fund_fee > equal_offer_fund_fee && offer_cet_fee > equal_offer_cet_fee);
+        assert!(get_change_outputs_and_fees(
+            &offer_params,
+            &accept_params,
+            4,
+            4,
+            FeeContribution::Equal
+        )
+        .is_err());
+        assert!(FeeContribution::Ratio(0, 0).split_fee(1, 1).is_err());
+        assert_eq!(
+            (25, 75),
+            FeeContribution::Ratio(1, 3).split_fee(60, 40).unwrap()
+        );
+    }
+
+    #[test]
+    fn get_change_output_and_fees_accounts_for_witness_size() {
+        // Arrange
+        let (p2wpkh_params, _) = get_party_params(100000, 10000, None);
+        let mut taproot_params = p2wpkh_params.clone();
+        // Script path spend of a 2-of-2 taproot leaf: item count, two signatures,
+        // the leaf script and the control block.
+        let taproot_witness_len = 1 + 65 + 65 + 69 + 34;
+        taproot_params.inputs[0].max_witness_len = taproot_witness_len;
+        let fee_rate = 4;
+
+        // Act
+        let (p2wpkh_change, p2wpkh_fee, _) =
+            p2wpkh_params.get_change_output_and_fees(fee_rate).unwrap();
+        let (taproot_change, taproot_fee, _) =
+            taproot_params.get_change_output_and_fees(fee_rate).unwrap();
+
+        // Assert
+        let extra_vbytes = ((taproot_witness_len - 108) / 4) as u64;
+        assert!(taproot_fee - p2wpkh_fee >= extra_vbytes * fee_rate);
+        assert_eq!(
+            taproot_fee - p2wpkh_fee,
+            p2wpkh_change.value - taproot_change.value
+        );
+    }
+
     #[test]
     fn create_dlc_transactions_no_error() {
         // Arrange
@@ -1253,10 +1790,7 @@ mod tests {
                 nonces.push(nonce);
                 sk_nonces.push(sk_nonce);
             }
-            oracle_infos.push(OracleInfo {
-                public_key: oracle_pubkey,
-                nonces,
-            });
+            oracle_infos.push(OracleInfo::new(oracle_pubkey, nonces));
             oracle_sk_nonce.push(sk_nonces);
             oracle_sks.push(oracle_kp);
         }
@@ -1321,6 +1855,149 @@ mod tests {
         .expect("Invalid decrypted adaptor signature");
     }
 
+    #[test]
+    fn sign_cet_with_anyone_can_pay_sig_hash_type_is_valid() {
+        // Arrange
+        let secp = Secp256k1::new();
+        let mut rng = secp256k1_zkp::rand::thread_rng();
+        let (offer_party_params, offer_fund_sk) = get_party_params(1000000000, 100000000, None);
+        let (accept_party_params, accept_fund_sk) = get_party_params(1000000000, 100000000, None);
+        let sig_hash_type = SigHashType::AllPlusAnyoneCanPay;
+
+        let dlc_txs = create_dlc_transactions(
+            &offer_party_params,
+            &accept_party_params,
+            &payouts(),
+            100,
+            4,
+            10,
+            10,
+            0,
+        )
+        .unwrap();
+
+        let mut cet = dlc_txs.cets[0].clone();
+        let msg = Message::from_hashed_data::<secp256k1_zkp::bitcoin_hashes::sha256::Hash>(&[1]);
+        let (oracle_kp, _) = secp.generate_schnorrsig_keypair(&mut rng);
+        let mut sk_nonce = [0u8; 32];
+        rng.fill_bytes(&mut sk_nonce);
+        let oracle_r_kp =
+            secp256k1_zkp::schnorrsig::KeyPair::from_seckey_slice(&secp, &sk_nonce).unwrap();
+        let oracle_info = OracleInfo::new(
+            SchnorrPublicKey::from_keypair(&secp, &oracle_kp),
+            vec![SchnorrPublicKey::from_keypair(&secp, &oracle_r_kp)],
+        );
+        let oracle_sigs = vec![vec![secp_utils::schnorrsig_sign_with_nonce(
+            &secp, &msg, &oracle_kp, &sk_nonce,
+        )]];
+        let adaptor_point =
+            get_adaptor_point_from_oracle_info(&secp, &[oracle_info], &vec![vec![msg]]).unwrap();
+
+        let funding_script_pubkey = make_funding_redeemscript(
+            &offer_party_params.fund_pubkey,
+            &accept_party_params.fund_pubkey,
+        );
+        let fund_output_value = dlc_txs.fund.output[0].value;
+        let fund_output = SigHashParams {
+            script_pubkey: &funding_script_pubkey,
+            value: fund_output_value,
+            sig_hash_type,
+        };
+
+        // Act
+        let adaptor_sig = create_cet_adaptor_sig_from_point_with_sig_hash_type(
+            &secp,
+            &cet,
+            &adaptor_point,
+            &offer_fund_sk,
+            &fund_output,
+        )
+        .unwrap();
+
+        sign_cet_with_sig_hash_type(
+            &secp,
+            &mut cet,
+            &adaptor_sig,
+            &oracle_sigs,
+            &accept_fund_sk,
+            &offer_party_params.fund_pubkey,
+            &fund_output,
+        )
+        .expect("Error signing CET");
+
+        let adaptor_secret = signatures_to_secret(&oracle_sigs).unwrap();
+        let adapted_sig = adaptor_sig.decrypt(&adaptor_secret).unwrap();
+
+        // Assert
+        verify_cet_adaptor_sig_from_point_with_sig_hash_type(
+            &secp,
+            &adaptor_sig,
+            &cet,
+            &adaptor_point,
+            &offer_party_params.fund_pubkey,
+            &fund_output,
+        )
+        .expect("Invalid adaptor signature");
+        verify_tx_input_sig_with_sig_hash_type(
+            &secp,
+            &adapted_sig,
+            &cet,
+            0,
+            &fund_output,
+            &offer_party_params.fund_pubkey,
+        )
+        .expect("Invalid decrypted adaptor signature");
+        let mut pubkeys = [
+            offer_party_params.fund_pubkey,
+            accept_party_params.fund_pubkey,
+        ];
+        pubkeys.sort();
+        for (witness_sig, pubkey) in cet.input[0].witness[1..3].iter().zip(pubkeys.iter()) {
+            let sig = Signature::from_der(&witness_sig[..witness_sig.len() - 1]).unwrap();
+            verify_tx_input_sig_with_sig_hash_type(&secp, &sig, &cet, 0, &fund_output, pubkey)
+                .expect("Invalid witness signature");
+            assert!(verify_tx_input_sig(
+                &secp,
+                &sig,
+                &cet,
+                0,
+                &funding_script_pubkey,
+                fund_output_value,
+                pubkey,
+            )
+            .is_err());
+        }
+        assert!(verify_tx_input_sig(
+            &secp,
+            &adapted_sig,
+            &cet,
+            0,
+            &funding_script_pubkey,
+            fund_output_value,
+            &offer_party_params.fund_pubkey,
+        )
+        .is_err());
+        assert!(cet.input[0].witness[1..3]
+            .iter()
+            .all(|sig| *sig.last().unwrap() == sig_hash_type.as_u32() as u8));
+    }
+
+    #[test]
+    fn unsafe_sig_hash_types_are_rejected() {
+        for sig_hash_type in [
+            SigHashType::None,
+            SigHashType::Single,
+            SigHashType::NonePlusAnyoneCanPay,
+            SigHashType::SinglePlusAnyoneCanPay,
+        ] {
+            assert!(validate_cet_sig_hash_type(sig_hash_type).is_err());
+            assert!(validate_funding_sig_hash_type(sig_hash_type).is_err());
+        }
+        assert!(validate_funding_sig_hash_type(SigHashType::AllPlusAnyoneCanPay).is_err());
+        assert!(validate_cet_sig_hash_type(SigHashType::AllPlusAnyoneCanPay).is_ok());
+        assert!(validate_funding_sig_hash_type(SigHashType::All).is_ok());
+    }
+
     #[test]
     fn input_output_ordering_test() {
         struct OrderingCase {
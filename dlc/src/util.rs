@@ -7,17 +7,30 @@ use bitcoin::{
 };
 use secp256k1_zkp::{Message, PublicKey, Secp256k1, SecretKey, Signature, Signing};
 
+use crate::SigHashParams;
+
 /// Get a BIP143 (https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki)
 /// signature hash with sighash all flag for a segwit transaction input as
 /// a Message instance
-pub(crate) fn get_sig_hash_msg(
+pub fn get_sig_hash_msg(
+    tx: &Transaction,
+    input_index: usize,
+    script_pubkey: &Script,
+    value: u64,
+) -> Message {
+    get_sig_hash_msg_with_sig_hash_type(tx, input_index, script_pubkey, value, SigHashType::All)
+}
+
+/// Same as [`get_sig_hash_msg`] using the given sighash type.
+pub fn get_sig_hash_msg_with_sig_hash_type(
     tx: &Transaction,
     input_index: usize,
     script_pubkey: &Script,
     value: u64,
+    sig_hash_type: SigHashType,
 ) -> Message {
     let sig_hash =
-        SigHashCache::new(tx).signature_hash(input_index, script_pubkey, value, SigHashType::All);
+        SigHashCache::new(tx).signature_hash(input_index, script_pubkey, value, sig_hash_type);
     Message::from_slice(&sig_hash).unwrap()
 }
 
@@ -40,7 +53,29 @@ pub fn get_raw_sig_for_tx_input<C: Signing>(
     value: u64,
     sk: &SecretKey,
 ) -> Signature {
-    let sig_hash_msg = get_sig_hash_msg(tx, input_index, script_pubkey, value);
+    get_raw_sig_for_tx_input_with_sig_hash_type(
+        secp,
+        tx,
+        input_index,
+        &SigHashParams {
+            script_pubkey,
+            value,
+            sig_hash_type: SigHashType::All,
+        },
+        sk,
+    )
+}
+
+/// Same as [`get_raw_sig_for_tx_input`] using the given information about the
+/// spent output and sighash type.
+pub fn get_raw_sig_for_tx_input_with_sig_hash_type<C: Signing>(
+    secp: &Secp256k1<C>,
+    tx: &Transaction,
+    input_index: usize,
+    sig_hash_params: &SigHashParams,
+    sk: &SecretKey,
+) -> Signature {
+    let sig_hash_msg = sig_hash_params.get_sig_hash_msg(tx, input_index);
     secp.sign_low_r(&sig_hash_msg, &sk)
 }
 
@@ -55,7 +90,17 @@ pub fn get_sig_for_tx_input<C: Signing>(
     sig_hash_type: SigHashType,
     sk: &SecretKey,
 ) -> Vec<u8> {
-    let sig = get_raw_sig_for_tx_input(secp, tx, input_index, script_pubkey, value, sk);
+    let sig = get_raw_sig_for_tx_input_with_sig_hash_type(
+        secp,
+        tx,
+        input_index,
+        &SigHashParams {
+            script_pubkey,
+            value,
+            sig_hash_type,
+        },
+        sk,
+    );
     finalize_sig(&sig, sig_hash_type)
 }
 
@@ -141,33 +186,60 @@ pub fn sign_multi_sig_input<C: Signing>(
     input_value: u64,
     input_index: usize,
 ) {
-    let own_sig = get_sig_for_tx_input(
+    sign_multi_sig_input_with_sig_hash_type(
         secp,
         transaction,
-        input_index,
-        script_pubkey,
-        input_value,
-        SigHashType::All,
+        other_sig,
+        other_pk,
         sk,
+        &SigHashParams {
+            script_pubkey,
+            value: input_value,
+            sig_hash_type: SigHashType::All,
+        },
+        input_index,
+    )
+}
+
+/// Same as [`sign_multi_sig_input`] using the given information about the
+/// spent output, both signatures using the given sighash type.
+pub fn sign_multi_sig_input_with_sig_hash_type<C: Signing>(
+    secp: &Secp256k1<C>,
+    transaction: &mut Transaction,
+    other_sig: &Signature,
+    other_pk: &PublicKey,
+    sk: &SecretKey,
+    sig_hash_params: &SigHashParams,
+    input_index: usize,
+) {
+    let own_sig = finalize_sig(
+        &get_raw_sig_for_tx_input_with_sig_hash_type(
+            secp,
+            transaction,
+            input_index,
+            sig_hash_params,
+            sk,
+        ),
+        sig_hash_params.sig_hash_type,
     );
 
     let own_pk = &PublicKey::from_secret_key(&secp, sk);
 
-    let other_finalized_sig = finalize_sig(other_sig, SigHashType::All);
+    let other_finalized_sig = finalize_sig(other_sig, sig_hash_params.sig_hash_type);
 
     transaction.input[input_index].witness = if own_pk < other_pk {
         vec![
             Vec::new(),
             own_sig,
             other_finalized_sig,
-            script_pubkey.to_bytes(),
+            sig_hash_params.script_pubkey.to_bytes(),
         ]
     } else {
         vec![
             Vec::new(),
             other_finalized_sig,
             own_sig,
-            script_pubkey.to_bytes(),
+            sig_hash_params.script_pubkey.to_bytes(),
         ]
     };
 }
@@ -11,7 +11,9 @@ use bitcoin::{OutPoint, Script, SigHashType};
 use bitcoin_test_utils::rpc_helpers::init_clients;
 use bitcoincore_rpc::{Client, RpcApi};
 use bitcoincore_rpc_json::AddressType;
-use dlc::{DlcTransactions, OracleInfo, PartyParams, Payout, RangePayout, TxInputInfo};
+use dlc::{
+    DlcTransactions, OracleInfo, PartyParams, Payout, RangePayout, SigHashParams, TxInputInfo,
+};
 use dlc_trie::digit_decomposition::{decompose_value, pad_range_payouts};
 use dlc_trie::multi_oracle_trie_with_diff::MultiOracleTrieWithDiff;
 use dlc_trie::DlcTrie;
@@ -137,10 +139,7 @@ fn get_oracle_infos<C: Signing, R: Rng + ?Sized>(
             sk_nonces.push(sk_nonce);
         }
         oracle_infos.push(OraclePrivInfo {
-            info: OracleInfo {
-                public_key: oracle_pubkey,
-                nonces,
-            },
+            info: OracleInfo::new(oracle_pubkey, nonces),
             priv_keypair: oracle_kp,
             priv_nonces: sk_nonces,
         });
@@ -313,6 +312,12 @@ fn integration_tests_decomposed_common(
     .1
     .value;
 
+    let fund_output = SigHashParams {
+        script_pubkey: &funding_script_pubkey,
+        value: fund_output_value,
+        sig_hash_type: SigHashType::All,
+    };
+
     let mut trie = MultiOracleTrieWithDiff::new(
         base,
         nb_oracles,
@@ -326,8 +331,7 @@ fn integration_tests_decomposed_common(
         .generate_sign(
             &secp,
             &offer_params.fund_priv_key,
-            &funding_script_pubkey,
-            fund_output_value,
+            &fund_output,
             &outcomes,
             &dlc_txs.cets,
             &oracle_infos,
@@ -339,8 +343,7 @@ fn integration_tests_decomposed_common(
         .sign(
             &secp,
             &accept_params.fund_priv_key,
-            &funding_script_pubkey,
-            fund_output_value,
+            &fund_output,
             &dlc_txs.cets,
             &oracle_infos,
         )
@@ -349,8 +352,7 @@ fn integration_tests_decomposed_common(
     trie.verify(
         &secp,
         &offer_params.params.fund_pubkey,
-        &funding_script_pubkey,
-        fund_output_value,
+        &fund_output,
         &adaptor_pairs_offer,
         &dlc_txs.cets,
         &oracle_infos,
@@ -360,8 +362,7 @@ fn integration_tests_decomposed_common(
     trie.verify(
         &secp,
         &accept_params.params.fund_pubkey,
-        &funding_script_pubkey,
-        fund_output_value,
+        &fund_output,
         &adaptor_pairs_accept,
         &dlc_txs.cets,
         &oracle_infos,
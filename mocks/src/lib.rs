@@ -1,3 +1,5 @@
 pub mod memory_storage_provider;
+pub mod mock_blockchain;
 pub mod mock_oracle_provider;
 pub mod mock_time;
+pub mod mock_wallet;
@@ -4,18 +4,34 @@ use dlc_manager::contract::{
     offered_contract::OfferedContract, signed_contract::SignedContract, Contract,
 };
 use dlc_manager::Storage;
-use dlc_manager::{error::Error as DaemonError, ContractId};
-use std::collections::HashMap;
-use std::sync::RwLock;
+use dlc_manager::{error::Error as DaemonError, ContractDataKind, ContractId};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+type ContractData = HashMap<(ContractDataKind, ContractId), Vec<u8>>;
 
 pub struct MemoryStorage {
-    contracts: RwLock<HashMap<ContractId, Contract>>,
+    contracts: Arc<RwLock<HashMap<ContractId, Contract>>>,
+    paused: Arc<RwLock<HashSet<ContractId>>>,
+    contract_data: Arc<RwLock<ContractData>>,
 }
 
 impl MemoryStorage {
     pub fn new() -> Self {
         MemoryStorage {
-            contracts: RwLock::new(HashMap::new()),
+            contracts: Arc::new(RwLock::new(HashMap::new())),
+            paused: Arc::new(RwLock::new(HashSet::new())),
+            contract_data: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Returns a storage backed by the same contracts as this one, to alter
+    /// the state of a manager owning this storage.
+    pub fn share(&self) -> Self {
+        MemoryStorage {
+            contracts: Arc::clone(&self.contracts),
+            paused: Arc::clone(&self.paused),
+            contract_data: Arc::clone(&self.contract_data),
         }
     }
 }
@@ -42,13 +58,13 @@ impl Storage for MemoryStorage {
 
     fn create_contract(&mut self, contract: &OfferedContract) -> Result<(), DaemonError> {
         let mut map = self.contracts.write().expect("Could not get write lock");
-        let res = map.insert(contract.id, Contract::Offered(contract.clone()));
-        match res {
-            None => Ok(()),
-            Some(_) => Err(DaemonError::StorageError(
+        if map.contains_key(&contract.id) {
+            return Err(DaemonError::StorageError(
                 "Contract already exists".to_string(),
-            )),
+            ));
         }
+        map.insert(contract.id, Contract::Offered(contract.clone()));
+        Ok(())
     }
 
     fn delete_contract(&mut self, id: &ContractId) -> Result<(), DaemonError> {
@@ -115,4 +131,63 @@ impl Storage for MemoryStorage {
 
         Ok(res)
     }
+
+    fn set_contract_paused(&mut self, id: &ContractId, paused: bool) -> Result<(), DaemonError> {
+        let mut paused_contracts = self.paused.write().expect("Could not get write lock");
+        if paused {
+            paused_contracts.insert(*id);
+        } else {
+            paused_contracts.remove(id);
+        }
+        Ok(())
+    }
+
+    fn get_paused_contracts(&self) -> Result<Vec<ContractId>, DaemonError> {
+        Ok(self
+            .paused
+            .read()
+            .expect("Could not get read lock")
+            .iter()
+            .cloned()
+            .collect())
+    }
+
+    fn upsert_contract_data(
+        &mut self,
+        kind: ContractDataKind,
+        id: &ContractId,
+        data: &[u8],
+    ) -> Result<(), DaemonError> {
+        self.contract_data
+            .write()
+            .expect("Could not get write lock")
+            .insert((kind, *id), data.to_vec());
+        Ok(())
+    }
+
+    fn delete_contract_data(
+        &mut self,
+        kind: ContractDataKind,
+        id: &ContractId,
+    ) -> Result<(), DaemonError> {
+        self.contract_data
+            .write()
+            .expect("Could not get write lock")
+            .remove(&(kind, *id));
+        Ok(())
+    }
+
+    fn get_contract_data(
+        &self,
+        kind: ContractDataKind,
+    ) -> Result<Vec<(ContractId, Vec<u8>)>, DaemonError> {
+        Ok(self
+            .contract_data
+            .read()
+            .expect("Could not get read lock")
+            .iter()
+            .filter(|((k, _), _)| *k == kind)
+            .map(|((_, id), data)| (*id, data.clone()))
+            .collect())
+    }
 }
@@ -0,0 +1,68 @@
+use bitcoin::{Network, OutPoint, Transaction};
+use dlc_manager::error::Error as DaemonError;
+use dlc_manager::{Blockchain, UtxoProof};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+pub struct MockBlockchain {
+    transactions: Mutex<Vec<Transaction>>,
+    utxo_proofs: Mutex<HashMap<OutPoint, UtxoProof>>,
+    spent_outputs: Mutex<HashSet<OutPoint>>,
+}
+
+impl Default for MockBlockchain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockBlockchain {
+    pub fn new() -> Self {
+        MockBlockchain {
+            transactions: Mutex::new(Vec::new()),
+            utxo_proofs: Mutex::new(HashMap::new()),
+            spent_outputs: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Sets the proof returned for the given outpoint. Outpoints without a
+    /// proof are reported as unsupported.
+    pub fn set_utxo_proof(&self, outpoint: OutPoint, proof: UtxoProof) {
+        self.utxo_proofs.lock().unwrap().insert(outpoint, proof);
+    }
+
+    /// Marks the given outpoint as spent. Other outpoints are reported as
+    /// unspent.
+    pub fn set_output_spent(&self, outpoint: OutPoint) {
+        self.spent_outputs.lock().unwrap().insert(outpoint);
+    }
+
+    /// Returns the transactions that were broadcast, in order.
+    pub fn get_transactions(&self) -> Vec<Transaction> {
+        self.transactions.lock().unwrap().clone()
+    }
+}
+
+impl Blockchain for MockBlockchain {
+    fn send_transaction(&self, transaction: &Transaction) -> Result<(), DaemonError> {
+        self.transactions.lock().unwrap().push(transaction.clone());
+        Ok(())
+    }
+
+    fn get_network(&self) -> Result<Network, DaemonError> {
+        Ok(Network::Regtest)
+    }
+
+    fn get_utxo_proof(&self, outpoint: &OutPoint) -> Result<UtxoProof, DaemonError> {
+        self.utxo_proofs
+            .lock()
+            .unwrap()
+            .get(outpoint)
+            .cloned()
+            .ok_or(DaemonError::Unsupported)
+    }
+
+    fn is_output_spent(&self, outpoint: &OutPoint) -> Result<bool, DaemonError> {
+        Ok(self.spent_outputs.lock().unwrap().contains(outpoint))
+    }
+}
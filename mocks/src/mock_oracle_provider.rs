@@ -1,7 +1,7 @@
 use dlc_manager::error::Error as DaemonError;
 use dlc_manager::Oracle;
 use dlc_messages::oracle_msgs::{
-    EventDescriptor, OracleAnnouncement, OracleAttestation, OracleEvent,
+    EventDescriptor, OracleAnnouncement, OracleAttestation, OracleEvent, OracleKeyDelegation,
 };
 use lightning::util::ser::Writeable;
 use secp256k1_zkp::key::SecretKey;
@@ -18,6 +18,7 @@ pub struct MockOracle {
     announcements: HashMap<String, OracleAnnouncement>,
     attestations: HashMap<String, OracleAttestation>,
     nonces: HashMap<String, Vec<SecretKey>>,
+    key_delegation: Option<OracleKeyDelegation>,
 }
 
 impl MockOracle {
@@ -31,6 +32,7 @@ impl MockOracle {
             announcements: HashMap::new(),
             attestations: HashMap::new(),
             nonces: HashMap::new(),
+            key_delegation: None,
         }
     }
 
@@ -44,13 +46,17 @@ impl MockOracle {
             announcements: HashMap::new(),
             attestations: HashMap::new(),
             nonces: HashMap::new(),
+            key_delegation: None,
         }
     }
 }
 
 impl Oracle for MockOracle {
     fn get_public_key(&self) -> PublicKey {
-        PublicKey::from_keypair(&self.secp, &self.key_pair)
+        match &self.key_delegation {
+            Some(delegation) => delegation.master_public_key,
+            None => self.get_signing_public_key(),
+        }
     }
 
     fn get_announcement(&self, event_id: &str) -> Result<OracleAnnouncement, DaemonError> {
@@ -72,9 +78,24 @@ impl Oracle for MockOracle {
             ))?;
         Ok(res.clone())
     }
+
+    fn get_key_delegation(&self) -> Option<OracleKeyDelegation> {
+        self.key_delegation.clone()
+    }
 }
 
 impl MockOracle {
+    /// Returns the public key used to sign announcements and attestations.
+    pub fn get_signing_public_key(&self) -> PublicKey {
+        PublicKey::from_keypair(&self.secp, &self.key_pair)
+    }
+
+    /// Makes the oracle report the given delegation, its public key becoming
+    /// the master key of the delegation.
+    pub fn set_key_delegation(&mut self, delegation: OracleKeyDelegation) {
+        self.key_delegation = Some(delegation);
+    }
+
     fn generate_nonces_for_event(
         &mut self,
         event_id: &str,
@@ -82,7 +103,9 @@ impl MockOracle {
     ) -> Vec<PublicKey> {
         let nb_nonces = match event_descriptor {
             EventDescriptor::EnumEvent(_) => 1,
-            EventDescriptor::DigitDecompositionEvent(d) => d.nb_digits,
+            EventDescriptor::DigitDecompositionEvent(d) => {
+                d.nb_digits as usize + d.get_prefix_outcomes().len()
+            }
         };
 
         let priv_nonces: Vec<_> = (0..nb_nonces)
@@ -119,7 +142,7 @@ impl MockOracle {
         let sig = self.secp.schnorrsig_sign(&msg, &self.key_pair);
         let announcement = OracleAnnouncement {
             oracle_event: oracle_event,
-            oracle_public_key: self.get_public_key(),
+            oracle_public_key: self.get_signing_public_key(),
             announcement_signature: sig,
         };
         self.announcements
@@ -144,7 +167,7 @@ impl MockOracle {
             })
             .collect();
         let attestation = OracleAttestation {
-            oracle_public_key: self.get_public_key(),
+            oracle_public_key: self.get_signing_public_key(),
             signatures,
             outcomes: outcomes.to_vec(),
         };
@@ -0,0 +1,288 @@
+use bitcoin::blockdata::{opcodes, script::Builder};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::{
+    Address, Network, OutPoint, PublicKey as BitcoinPublicKey, Script, Transaction, TxIn, TxOut,
+    Txid,
+};
+use dlc_manager::error::Error as DaemonError;
+use dlc_manager::{AddressType, Utxo, Wallet};
+use secp256k1_zkp::rand::thread_rng;
+use secp256k1_zkp::schnorrsig::{KeyPair, PublicKey as SchnorrPublicKey};
+use secp256k1_zkp::{All, PublicKey, Secp256k1, SecretKey};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub struct MockWallet {
+    secp: Secp256k1<All>,
+    network: Network,
+    keys: Mutex<HashMap<PublicKey, SecretKey>>,
+    address_keys: Mutex<HashMap<Script, SecretKey>>,
+    utxos: Mutex<Vec<Utxo>>,
+    reserved_utxos: Mutex<Vec<Utxo>>,
+    transactions: Mutex<HashMap<Txid, Transaction>>,
+    confirmations: Mutex<HashMap<Txid, u32>>,
+}
+
+impl Default for MockWallet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockWallet {
+    pub fn new() -> Self {
+        MockWallet {
+            secp: Secp256k1::new(),
+            network: Network::Regtest,
+            keys: Mutex::new(HashMap::new()),
+            address_keys: Mutex::new(HashMap::new()),
+            utxos: Mutex::new(Vec::new()),
+            reserved_utxos: Mutex::new(Vec::new()),
+            transactions: Mutex::new(HashMap::new()),
+            confirmations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a transaction paying the given value to a new address of the
+    /// wallet and adds the corresponding UTXO to the wallet.
+    pub fn fund(&self, value: u64) -> Utxo {
+        let address = self.get_new_address().unwrap();
+        let tx = Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::default(),
+                script_sig: Script::new(),
+                sequence: 0xffffffff,
+                witness: Vec::new(),
+            }],
+            output: vec![TxOut {
+                value,
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        let utxo = Utxo {
+            tx_out: tx.output[0].clone(),
+            outpoint: OutPoint {
+                txid: tx.txid(),
+                vout: 0,
+            },
+            address,
+            redeem_script: Script::new(),
+            max_witness_len: 107,
+        };
+        self.add_transaction(tx);
+        self.utxos.lock().unwrap().push(utxo.clone());
+        utxo
+    }
+
+    /// Returns the BIP 86 taproot output key for the given internal key.
+    fn get_taproot_output_key(&self, internal_key: &SchnorrPublicKey) -> SchnorrPublicKey {
+        let tag = sha256::Hash::hash(b"TapTweak");
+        let mut engine = sha256::Hash::engine();
+        engine.input(&tag[..]);
+        engine.input(&tag[..]);
+        engine.input(&internal_key.serialize());
+        let tweak = sha256::Hash::from_engine(engine);
+        let mut output_key = *internal_key;
+        output_key
+            .tweak_add_assign(&self.secp, &tweak[..])
+            .expect("Error tweaking internal key");
+        output_key
+    }
+
+    /// Returns the total value of the UTXOs available for selection.
+    pub fn get_available_balance(&self) -> u64 {
+        self.utxos
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| x.tx_out.value)
+            .sum()
+    }
+
+    pub fn add_transaction(&self, tx: Transaction) {
+        self.transactions.lock().unwrap().insert(tx.txid(), tx);
+    }
+
+    pub fn set_confirmations(&self, txid: &Txid, confirmations: u32) {
+        self.confirmations
+            .lock()
+            .unwrap()
+            .insert(*txid, confirmations);
+    }
+}
+
+impl Wallet for MockWallet {
+    fn get_new_address(&self) -> Result<Address, DaemonError> {
+        let sk = SecretKey::new(&mut thread_rng());
+        let pk = BitcoinPublicKey {
+            compressed: true,
+            key: PublicKey::from_secret_key(&self.secp, &sk),
+        };
+        let address = Address::p2wpkh(&pk, self.network).unwrap();
+        self.address_keys
+            .lock()
+            .unwrap()
+            .insert(address.script_pubkey(), sk);
+        Ok(address)
+    }
+
+    fn get_new_address_of_type(&self, address_type: AddressType) -> Result<Address, DaemonError> {
+        match address_type {
+            AddressType::P2wpkh => self.get_new_address(),
+            AddressType::P2tr => {
+                let sk = SecretKey::new(&mut thread_rng());
+                let key_pair = KeyPair::from_secret_key(&self.secp, sk);
+                let internal_key = SchnorrPublicKey::from_keypair(&self.secp, &key_pair);
+                let script_pubkey = Builder::new()
+                    .push_opcode(opcodes::all::OP_PUSHNUM_1)
+                    .push_slice(&self.get_taproot_output_key(&internal_key).serialize())
+                    .into_script();
+                let address = Address::from_script(&script_pubkey, self.network).unwrap();
+                self.address_keys.lock().unwrap().insert(script_pubkey, sk);
+                Ok(address)
+            }
+        }
+    }
+
+    fn get_new_secret_key(&self) -> Result<SecretKey, DaemonError> {
+        let sk = SecretKey::new(&mut thread_rng());
+        self.keys
+            .lock()
+            .unwrap()
+            .insert(PublicKey::from_secret_key(&self.secp, &sk), sk);
+        Ok(sk)
+    }
+
+    fn get_secret_key_for_pubkey(&self, pubkey: &PublicKey) -> Result<SecretKey, DaemonError> {
+        self.keys
+            .lock()
+            .unwrap()
+            .get(pubkey)
+            .cloned()
+            .ok_or(DaemonError::InvalidParameters(
+                "Unknown public key".to_string(),
+            ))
+    }
+
+    fn get_secret_key_for_address(&self, address: &Address) -> Result<SecretKey, DaemonError> {
+        self.address_keys
+            .lock()
+            .unwrap()
+            .get(&address.script_pubkey())
+            .cloned()
+            .ok_or(DaemonError::InvalidParameters(
+                "Unknown address".to_string(),
+            ))
+    }
+
+    fn get_utxos_for_amount(
+        &self,
+        amount: u64,
+        _fee_rate: Option<u64>,
+        lock_utxos: bool,
+    ) -> Result<Vec<Utxo>, DaemonError> {
+        let mut utxos = self.utxos.lock().unwrap();
+        let mut total = 0;
+        let mut nb_selected = 0;
+        for utxo in utxos.iter() {
+            if total >= amount {
+                break;
+            }
+            total += utxo.tx_out.value;
+            nb_selected += 1;
+        }
+
+        if total < amount {
+            return Err(DaemonError::InvalidParameters(
+                "Not enough funds".to_string(),
+            ));
+        }
+
+        if lock_utxos {
+            let selection: Vec<Utxo> = utxos.drain(..nb_selected).collect();
+            self.reserved_utxos
+                .lock()
+                .unwrap()
+                .extend(selection.iter().cloned());
+            Ok(selection)
+        } else {
+            Ok(utxos.iter().take(nb_selected).cloned().collect())
+        }
+    }
+
+    fn get_utxos_for_outpoints(
+        &self,
+        outpoints: &[OutPoint],
+        lock_utxos: bool,
+    ) -> Result<Vec<Utxo>, DaemonError> {
+        let mut utxos = self.utxos.lock().unwrap();
+        let selection = outpoints
+            .iter()
+            .map(|outpoint| {
+                utxos
+                    .iter()
+                    .find(|x| &x.outpoint == outpoint)
+                    .cloned()
+                    .ok_or(DaemonError::InvalidParameters(
+                        "Unknown or spent outpoint".to_string(),
+                    ))
+            })
+            .collect::<Result<Vec<Utxo>, DaemonError>>()?;
+
+        if lock_utxos {
+            utxos.retain(|x| !outpoints.contains(&x.outpoint));
+            self.reserved_utxos
+                .lock()
+                .unwrap()
+                .extend(selection.iter().cloned());
+        }
+
+        Ok(selection)
+    }
+
+    fn get_reserved_utxos(&self) -> Result<Vec<OutPoint>, DaemonError> {
+        Ok(self
+            .reserved_utxos
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| x.outpoint)
+            .collect())
+    }
+
+    fn unreserve_utxos(&self, outpoints: &[OutPoint]) -> Result<(), DaemonError> {
+        let mut reserved = self.reserved_utxos.lock().unwrap();
+        let mut utxos = self.utxos.lock().unwrap();
+        for outpoint in outpoints {
+            let index = reserved
+                .iter()
+                .position(|x| &x.outpoint == outpoint)
+                .ok_or(DaemonError::InvalidParameters(
+                    "Outpoint is not reserved".to_string(),
+                ))?;
+            utxos.push(reserved.remove(index));
+        }
+        Ok(())
+    }
+
+    fn import_address(&self, _address: &Address) -> Result<(), DaemonError> {
+        Ok(())
+    }
+
+    fn get_transaction(&self, tx_id: &Txid) -> Result<Transaction, DaemonError> {
+        self.transactions
+            .lock()
+            .unwrap()
+            .get(tx_id)
+            .cloned()
+            .ok_or(DaemonError::InvalidParameters(
+                "Unknown transaction".to_string(),
+            ))
+    }
+
+    fn get_transaction_confirmations(&self, tx_id: &Txid) -> Result<u32, DaemonError> {
+        Ok(*self.confirmations.lock().unwrap().get(tx_id).unwrap_or(&0))
+    }
+}
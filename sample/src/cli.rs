@@ -284,6 +284,9 @@ pub(crate) async fn poll_for_user_input(
 								Contract::Refunded(_) => {
 									println!("Refunded contract: {}", id);
 								}
+								Contract::CollaborativelyClosed(_) => {
+									println!("Collaboratively closed contract: {}", id);
+								}
 								_ => {
 									println!("Rejected contract: {}", id);
 								}
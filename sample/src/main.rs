@@ -83,6 +83,13 @@ impl CustomMessageReader for DlcMessageHandler {
             dlc_messages::OFFER_TYPE => DlcMessage::Offer(Readable::read(&mut buffer)?),
             dlc_messages::ACCEPT_TYPE => DlcMessage::Accept(Readable::read(&mut buffer)?),
             dlc_messages::SIGN_TYPE => DlcMessage::Sign(Readable::read(&mut buffer)?),
+            dlc_messages::REJECT_TYPE => DlcMessage::Reject(Readable::read(&mut buffer)?),
+            dlc_messages::CLOSE_OFFER_TYPE => {
+                DlcMessage::CollaborativeCloseOffer(Readable::read(&mut buffer)?)
+            }
+            dlc_messages::CLOSE_ACCEPT_TYPE => {
+                DlcMessage::CollaborativeCloseAccept(Readable::read(&mut buffer)?)
+            }
             _ => return Ok(None),
         };
 